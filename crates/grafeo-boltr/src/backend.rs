@@ -16,7 +16,9 @@ use parking_lot::Mutex;
 use uuid::Uuid;
 
 use grafeo_service::ServiceState;
-use grafeo_service::query::QueryService;
+use grafeo_service::error::ServiceError;
+use grafeo_service::query::{QueryService, run_with_timeout};
+use grafeo_service::types::QueryTransport;
 
 use crate::encode::{convert_params, grafeo_to_bolt};
 
@@ -34,6 +36,22 @@ struct GrafeoSession {
     db_scope: Vec<String>,
 }
 
+impl GrafeoSession {
+    /// Authenticated user for the running-query registry.
+    fn user(&self) -> Option<&str> {
+        #[cfg(feature = "auth")]
+        {
+            self.identity
+                .as_ref()
+                .map(grafeo_engine::auth::Identity::user_id)
+        }
+        #[cfg(not(feature = "auth"))]
+        {
+            None
+        }
+    }
+}
+
 /// Bolt backend implementation for Grafeo.
 pub struct GrafeoBackend {
     state: ServiceState,
@@ -307,24 +325,42 @@ impl BoltBackend for GrafeoBackend {
             .and_then(|v| v.as_str())
             .map(String::from);
 
-        let result = tokio::task::spawn_blocking(move || {
+        let timeout = self.state.effective_timeout(None);
+        let running = {
+            let s = session_arc.lock();
+            self.state.running_queries().register(
+                &s.database,
+                language.as_deref(),
+                query,
+                s.user(),
+                QueryTransport::Bolt,
+            )
+        };
+
+        // Dropping this future (connection reset, RESET mid-RUN) trips the
+        // cancel guard inside run_with_timeout and releases the thread.
+        let result = run_with_timeout(timeout, running.cancel_token(), move |cancel| {
             let session = session_arc.lock();
             let params_opt = if params.is_empty() {
                 None
             } else {
                 Some(&params)
             };
-            QueryService::dispatch(
+            QueryService::dispatch_cancellable(
                 &session.engine_session,
                 &statement,
                 language.as_deref(),
                 params_opt,
+                cancel,
             )
         })
         .await
-        .map_err(BoltError::backend)?
         .map_err(|e| match e {
-            grafeo_service::error::ServiceError::Forbidden(msg) => BoltError::Forbidden(msg),
+            ServiceError::Forbidden(msg) => BoltError::Forbidden(msg),
+            ServiceError::Timeout | ServiceError::Cancelled => BoltError::Query {
+                code: "Neo.TransientError.Transaction.Terminated".to_string(),
+                message: e.to_string(),
+            },
             other => BoltError::Query {
                 code: "Neo.ClientError.Statement.SyntaxError".to_string(),
                 message: other.to_string(),
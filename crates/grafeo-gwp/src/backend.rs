@@ -21,8 +21,10 @@ use uuid::Uuid;
 
 use grafeo_service::ServiceState;
 use grafeo_service::admin::AdminService;
-use grafeo_service::query::QueryService;
+use grafeo_service::error::ServiceError;
+use grafeo_service::query::{QueryService, run_with_timeout};
 use grafeo_service::search::SearchService;
+use grafeo_service::types::{QueryTransport, RunningQueryInfo};
 
 use crate::encode::{convert_params, grafeo_to_gwp};
 
@@ -44,6 +46,37 @@ struct GrafeoSession {
     db_scope: Vec<String>,
 }
 
+impl GrafeoSession {
+    /// Whether the session may call admin procedures. Always true when
+    /// authentication is disabled.
+    fn can_admin(&self) -> bool {
+        #[cfg(feature = "auth")]
+        {
+            self.identity
+                .as_ref()
+                .is_none_or(grafeo_engine::auth::Identity::can_admin)
+        }
+        #[cfg(not(feature = "auth"))]
+        {
+            true
+        }
+    }
+
+    /// Authenticated user for the running-query registry.
+    fn user(&self) -> Option<&str> {
+        #[cfg(feature = "auth")]
+        {
+            self.identity
+                .as_ref()
+                .map(grafeo_engine::auth::Identity::user_id)
+        }
+        #[cfg(not(feature = "auth"))]
+        {
+            None
+        }
+    }
+}
+
 #[cfg(feature = "auth")]
 use crate::auth::PendingAuth;
 
@@ -98,6 +131,82 @@ impl GrafeoBackend {
             .ok_or_else(|| GqlError::Session(format!("session '{}' not found", handle.0)))
     }
 
+    /// Lists queries currently executing on this server, across all
+    /// transports.
+    ///
+    /// GWP clients call it as `CALL grafeo.admin.list_queries()`, the
+    /// counterpart of `GET /admin/queries`.
+    pub fn list_running_queries(&self) -> Vec<RunningQueryInfo> {
+        self.state.running_queries().list()
+    }
+
+    /// Requests cancellation of a running query by ID.
+    ///
+    /// GWP clients call it as `CALL grafeo.admin.kill_query($id)`, the
+    /// counterpart of `DELETE /admin/queries/{id}`.
+    #[allow(clippy::result_large_err)]
+    pub fn kill_query(&self, id: &str) -> Result<(), GqlError> {
+        if self.state.running_queries().kill(id) {
+            Ok(())
+        } else {
+            Err(GqlError::Session(format!("query '{id}' not found")))
+        }
+    }
+
+    /// Runs an admin procedure. The caller checks admin access.
+    #[allow(clippy::result_large_err)]
+    fn run_admin_procedure(
+        &self,
+        procedure: AdminProcedure,
+    ) -> Result<grafeo_engine::database::QueryResult, GqlError> {
+        use grafeo_common::types::Value;
+        use grafeo_engine::database::QueryResult;
+
+        match procedure {
+            AdminProcedure::ListQueries => {
+                let columns = [
+                    "id",
+                    "database",
+                    "language",
+                    "statement",
+                    "user",
+                    "transport",
+                    "started_at_ms",
+                    "elapsed_ms",
+                    "cancelling",
+                ];
+                let rows = self
+                    .list_running_queries()
+                    .into_iter()
+                    .map(|q| {
+                        vec![
+                            Value::from(q.id),
+                            Value::from(q.database),
+                            Value::from(q.language),
+                            Value::from(q.statement),
+                            q.user.map_or(Value::Null, Value::from),
+                            Value::from(q.transport.as_str()),
+                            Value::Int64(q.started_at_ms as i64),
+                            Value::Int64(q.elapsed_ms as i64),
+                            Value::Bool(q.cancelling),
+                        ]
+                    })
+                    .collect();
+                Ok(QueryResult::from_rows(
+                    columns.map(String::from).to_vec(),
+                    rows,
+                ))
+            }
+            AdminProcedure::KillQuery(id) => {
+                self.kill_query(&id)?;
+                Ok(QueryResult::from_rows(
+                    vec!["cancelled".to_owned()],
+                    vec![vec![Value::Bool(true)]],
+                ))
+            }
+        }
+    }
+
     /// Builds a `GraphInfo` from a database entry.
     #[allow(clippy::result_large_err)]
     fn build_graph_info(&self, name: &str) -> Result<GraphInfo, GqlError> {
@@ -336,39 +445,55 @@ impl GqlBackend for GrafeoBackend {
         _transaction: Option<&TransactionHandle>,
     ) -> Result<Pin<Box<dyn ResultStream>>, GqlError> {
         let session_arc = self.get_session(session)?;
+        if let Some(procedure) = AdminProcedure::parse(statement, parameters) {
+            if !session_arc.lock().can_admin() {
+                return Err(GqlError::status(
+                    status::SYNTAX_OR_ACCESS_ERROR,
+                    "admin access required",
+                ));
+            }
+            let result = self.run_admin_procedure(procedure?)?;
+            return Ok(Box::pin(GrafeoResultStream::from_query_result(result)));
+        }
         let statement = statement.to_owned();
         let params = convert_params(parameters);
+        let timeout = self.state.effective_timeout(None);
+        let running = {
+            let s = session_arc.lock();
+            self.state.running_queries().register(
+                &s.database,
+                s.language.as_deref(),
+                &statement,
+                s.user(),
+                QueryTransport::Gwp,
+            )
+        };
 
-        let result = tokio::task::spawn_blocking(move || {
+        // The cancel guard inside run_with_timeout fires if the client drops
+        // the stream mid-flight, so the blocking thread is released early.
+        let result = run_with_timeout(timeout, running.cancel_token(), move |cancel| {
             let session = session_arc.lock();
-            if let Some(ref lang) = session.language {
-                // Language override set via Configure, route through dispatch
-                let params_opt = if params.is_empty() {
-                    None
-                } else {
-                    Some(params)
-                };
-                QueryService::dispatch(
-                    &session.engine_session,
-                    &statement,
-                    Some(lang.as_str()),
-                    params_opt.as_ref(),
-                )
-            } else if params.is_empty() {
-                session
-                    .engine_session
-                    .execute(&statement)
-                    .map_err(|e| grafeo_service::error::ServiceError::BadRequest(e.to_string()))
+            let params_opt = if params.is_empty() {
+                None
             } else {
-                session
-                    .engine_session
-                    .execute_with_params(&statement, params)
-                    .map_err(|e| grafeo_service::error::ServiceError::BadRequest(e.to_string()))
-            }
+                Some(params)
+            };
+            // Language override set via Configure; `None` defaults to GQL.
+            QueryService::dispatch_cancellable(
+                &session.engine_session,
+                &statement,
+                session.language.as_deref(),
+                params_opt.as_ref(),
+                cancel,
+            )
         })
         .await
-        .map_err(GqlError::backend)?
-        .map_err(|e| GqlError::status(status::INVALID_SYNTAX, e.to_string()))?;
+        .map_err(|e| match e {
+            ServiceError::Timeout | ServiceError::Cancelled => {
+                GqlError::status(status::TRANSACTION_ROLLBACK, e.to_string())
+            }
+            other => GqlError::status(status::INVALID_SYNTAX, other.to_string()),
+        })?;
 
         Ok(Box::pin(GrafeoResultStream::from_query_result(result)))
     }
@@ -840,6 +965,89 @@ impl GqlBackend for GrafeoBackend {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Admin procedures: server operations the GWP protocol has no RPC for
+// ---------------------------------------------------------------------------
+
+/// An admin procedure, called as `CALL grafeo.admin.<name>(...)` and
+/// answered by the backend instead of the engine.
+#[derive(Debug, PartialEq, Eq)]
+enum AdminProcedure {
+    /// `grafeo.admin.list_queries()`: the running queries.
+    ListQueries,
+    /// `grafeo.admin.kill_query(id)`: cancels a running query.
+    KillQuery(String),
+}
+
+impl AdminProcedure {
+    /// Recognizes an admin procedure call. Returns `None` for any other
+    /// statement, and an error for a call with invalid arguments.
+    ///
+    /// Arguments are a string literal or a `$name` parameter.
+    #[allow(clippy::result_large_err)]
+    fn parse(
+        statement: &str,
+        parameters: &HashMap<String, GwpValue>,
+    ) -> Option<Result<Self, GqlError>> {
+        let call = statement.trim().trim_end_matches(';').trim_end();
+        let rest = call
+            .get(..4)
+            .filter(|keyword| keyword.eq_ignore_ascii_case("call"))
+            .and_then(|_| call.get(4..))
+            .filter(|rest| rest.starts_with(char::is_whitespace))?;
+        let (name, args) = rest.split_once('(')?;
+        let args = args.strip_suffix(')')?.trim();
+        let name = name.trim().to_ascii_lowercase();
+        let name = name.strip_prefix("grafeo.admin.")?;
+
+        let procedure = match name {
+            "list_queries" if args.is_empty() => Ok(Self::ListQueries),
+            "kill_query" => string_argument(args, parameters).map(Self::KillQuery),
+            "list_queries" => Err(GqlError::status(
+                status::INVALID_ARGUMENT_COUNT,
+                "grafeo.admin.list_queries() takes no arguments",
+            )),
+            _ => Err(GqlError::status(
+                status::PROCEDURE_NOT_FOUND,
+                format!("unknown admin procedure 'grafeo.admin.{name}'"),
+            )),
+        };
+        Some(procedure)
+    }
+}
+
+/// Reads a procedure's single string argument: a quoted literal without
+/// escapes, or a `$name` parameter.
+#[allow(clippy::result_large_err)]
+fn string_argument(arg: &str, parameters: &HashMap<String, GwpValue>) -> Result<String, GqlError> {
+    if let Some(name) = arg.strip_prefix('$') {
+        return match parameters.get(name) {
+            Some(GwpValue::String(value)) => Ok(value.clone()),
+            Some(_) => Err(GqlError::status(
+                status::INVALID_VALUE_TYPE,
+                format!("parameter '${name}' must be a string"),
+            )),
+            None => Err(GqlError::status(
+                status::INVALID_REFERENCE,
+                format!("parameter '${name}' is not set"),
+            )),
+        };
+    }
+    for quote in ['\'', '"'] {
+        if let Some(value) = arg
+            .strip_prefix(quote)
+            .and_then(|a| a.strip_suffix(quote))
+            .filter(|value| !value.contains(quote))
+        {
+            return Ok(value.to_owned());
+        }
+    }
+    Err(GqlError::status(
+        status::INVALID_ARGUMENT_COUNT,
+        "expected one string argument",
+    ))
+}
+
 // ---------------------------------------------------------------------------
 // ResultStream: lazily converts QueryResult into GWP streaming frames
 // ---------------------------------------------------------------------------
@@ -1067,4 +1275,69 @@ mod tests {
         }
         assert!(matches!(frames[4], ResultFrame::Summary(_)));
     }
+
+    #[test]
+    fn admin_procedure_parse() {
+        let params = HashMap::from([
+            ("id".to_owned(), GwpValue::String("q-1".to_owned())),
+            ("n".to_owned(), GwpValue::Integer(1)),
+        ]);
+        let parse = |statement: &str| AdminProcedure::parse(statement, &params);
+
+        assert_eq!(
+            parse("CALL grafeo.admin.list_queries()").unwrap().unwrap(),
+            AdminProcedure::ListQueries
+        );
+        assert_eq!(
+            parse(" call GRAFEO.ADMIN.KILL_QUERY( $id );")
+                .unwrap()
+                .unwrap(),
+            AdminProcedure::KillQuery("q-1".to_owned())
+        );
+        assert_eq!(
+            parse("CALL grafeo.admin.kill_query('q-2')")
+                .unwrap()
+                .unwrap(),
+            AdminProcedure::KillQuery("q-2".to_owned())
+        );
+        assert!(parse("CALL grafeo.admin.kill_query($n)").unwrap().is_err());
+        assert!(
+            parse("CALL grafeo.admin.kill_query($missing)")
+                .unwrap()
+                .is_err()
+        );
+        assert!(
+            parse("CALL grafeo.admin.list_queries('x')")
+                .unwrap()
+                .is_err()
+        );
+        assert!(
+            parse("CALL grafeo.admin.drop_everything()")
+                .unwrap()
+                .is_err()
+        );
+        assert!(parse("CALL grafeo.pagerank()").is_none());
+        assert!(parse("MATCH (n) RETURN n").is_none());
+    }
+
+    #[test]
+    fn kill_query_cancels_registered_query() {
+        let state = ServiceState::new_in_memory(300);
+        let backend = GrafeoBackend::new(state.clone());
+        let guard = state.running_queries().register(
+            "default",
+            None,
+            "MATCH (n) RETURN n",
+            None,
+            QueryTransport::Gwp,
+        );
+
+        let listed = backend.list_running_queries();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, guard.id());
+
+        backend.kill_query(guard.id()).unwrap();
+        assert!(guard.cancel_token().is_cancelled());
+        assert!(backend.kill_query("no-such-id").is_err());
+    }
 }
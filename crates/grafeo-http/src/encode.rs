@@ -33,6 +33,8 @@ pub fn value_to_json(value: &grafeo_common::Value) -> serde_json::Value {
         Value::Duration(d) => serde_json::json!({ "$duration": d.to_string() }),
         Value::ZonedDatetime(zdt) => serde_json::json!({ "$datetime": zdt.to_string() }),
         Value::List(items) => serde_json::Value::Array(items.iter().map(value_to_json).collect()),
+        #[cfg(feature = "sync")]
+        Value::Map(_) if grafeo_service::crdt::decode(value).is_some() => crdt_to_json(value),
         Value::Map(map) => {
             let obj: serde_json::Map<String, serde_json::Value> = map
                 .iter()
@@ -62,6 +64,41 @@ pub fn value_to_json(value: &grafeo_common::Value) -> serde_json::Value {
     }
 }
 
+/// Encodes a map-stored CRDT like the counters: its state under a
+/// `$`-prefixed type key, and its logical value under `$value`.
+#[cfg(feature = "sync")]
+fn crdt_to_json(value: &grafeo_common::Value) -> serde_json::Value {
+    use grafeo_service::crdt::{self, MapCrdt};
+    let (kind, state) = match crdt::decode(value) {
+        Some(MapCrdt::LwwRegister {
+            timestamp,
+            replica_id,
+            ..
+        }) => (
+            "$lwwregister",
+            serde_json::json!({ "timestamp": timestamp, "replica_id": replica_id }),
+        ),
+        Some(MapCrdt::OrSet { elements, .. }) => (
+            "$orset",
+            serde_json::Value::Object(
+                elements
+                    .iter()
+                    .map(|(tag, e)| (tag.clone(), value_to_json(e)))
+                    .collect(),
+            ),
+        ),
+        Some(MapCrdt::MvRegister { versions }) => (
+            "$mvregister",
+            versions
+                .iter()
+                .map(|v| serde_json::json!({ "value": value_to_json(&v.value), "clock": v.clock }))
+                .collect(),
+        ),
+        None => return serde_json::Value::Null,
+    };
+    serde_json::json!({ kind: state, "$value": value_to_json(&crdt::read_value(value)) })
+}
+
 /// Converts an engine `QueryResult` to an HTTP `QueryResponse` with JSON values.
 pub fn query_result_to_response(result: &QueryResult) -> QueryResponse {
     let gql_status = {
@@ -275,6 +312,41 @@ mod tests {
         assert_eq!(json["key"], "val");
     }
 
+    #[cfg(feature = "sync")]
+    #[test]
+    fn value_to_json_map_crdts() {
+        use grafeo_service::crdt::apply_op;
+        use grafeo_service::sync::CrdtOp;
+
+        let register = apply_op(
+            &Value::Null,
+            &CrdtOp::LwwAssign {
+                value: Value::from("draft"),
+                timestamp: 7,
+                replica_id: "r1".to_string(),
+            },
+        );
+        assert_eq!(
+            value_to_json(&register),
+            serde_json::json!({
+                "$lwwregister": { "timestamp": 7, "replica_id": "r1" },
+                "$value": "draft",
+            })
+        );
+
+        let set = apply_op(
+            &Value::Null,
+            &CrdtOp::SetAdd {
+                element: Value::from("red"),
+                replica_id: "r1".to_string(),
+            },
+        );
+        assert_eq!(
+            value_to_json(&set),
+            serde_json::json!({ "$orset": { "r1:1": "red" }, "$value": ["red"] })
+        );
+    }
+
     #[test]
     fn value_to_json_temporal_types() {
         // Date wraps in {"$date": "..."}
@@ -97,6 +97,7 @@ impl IntoResponse for ApiError {
             ServiceError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", Some(msg.clone())),
             ServiceError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", Some(msg.clone())),
             ServiceError::Timeout => (StatusCode::REQUEST_TIMEOUT, "timeout", None),
+            ServiceError::Cancelled => (StatusCode::CONFLICT, "cancelled", None),
             ServiceError::Unauthorized => (StatusCode::UNAUTHORIZED, "unauthorized", None),
             ServiceError::TooManyRequests => {
                 (StatusCode::TOO_MANY_REQUESTS, "too_many_requests", None)
@@ -238,6 +239,14 @@ mod tests {
         assert!(body["detail"].is_null());
     }
 
+    #[tokio::test]
+    async fn cancelled_maps_to_409() {
+        use grafeo_service::error::ServiceError;
+        let (status, body) = parse_response(ApiError::from(ServiceError::Cancelled)).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(body["error"], "cancelled");
+    }
+
     #[tokio::test]
     async fn from_service_error_conversion() {
         use grafeo_service::error::ServiceError;
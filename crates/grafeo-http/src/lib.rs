@@ -92,14 +92,24 @@ pub use state::AppState;
         routes::admin::admin_list_projections,
         routes::admin::admin_drop_projection,
         routes::admin::admin_validate_shacl,
+        routes::admin::admin_list_queries,
+        routes::admin::admin_kill_query,
         routes::backup::create_backup,
         routes::backup::create_incremental_backup,
         routes::backup::list_backups,
         routes::backup::list_all_backups,
         routes::backup::restore_backup,
         routes::backup::restore_to_epoch,
+        routes::backup::restore_as_new,
         routes::backup::delete_backup,
         routes::backup::download_backup,
+        routes::backup::verify_backup,
+        routes::backup::verify_all_backups,
+        routes::backup::rekey_backups,
+        routes::backup::get_backup_schedule,
+        routes::backup::set_backup_schedule,
+        routes::backup::delete_backup_schedule,
+        routes::backup::list_backup_schedules,
         routes::search::vector_search,
         routes::search::text_search,
         routes::search::hybrid_search,
@@ -132,8 +142,19 @@ pub use state::AppState;
             grafeo_service::types::ShaclValidationReport,
             grafeo_service::types::ShaclViolation,
             grafeo_service::types::BackupEntry,
+            grafeo_service::types::BackupVerification,
+            grafeo_service::types::VerifyBackupRequest,
+            grafeo_service::types::VerifyBackupsRequest,
+            grafeo_service::types::RekeyBackupsRequest,
+            grafeo_service::types::RekeyedBackup,
             grafeo_service::types::RestoreRequest,
             grafeo_service::types::RestoreToEpochRequest,
+            grafeo_service::types::RestoreAsRequest,
+            grafeo_service::types::BackupSchedule,
+            grafeo_service::types::BackupRun,
+            grafeo_service::types::BackupScheduleStatus,
+            grafeo_service::types::RunningQueryInfo,
+            grafeo_service::types::QueryTransport,
             SearchResponse,
         )
     ),
@@ -227,6 +248,11 @@ pub fn router(state: AppState) -> Router {
                 .head(routes::graph_store::graph_store_head),
         )
         // Admin
+        .route("/admin/queries", get(routes::admin::admin_list_queries))
+        .route(
+            "/admin/queries/{id}",
+            delete(routes::admin::admin_kill_query),
+        )
         .route("/admin/{db}/stats", get(routes::admin::admin_stats))
         .route("/admin/{db}/wal", get(routes::admin::admin_wal_status))
         .route(
@@ -275,6 +301,10 @@ pub fn router(state: AppState) -> Router {
             "/admin/{db}/restore/epoch",
             post(routes::backup::restore_to_epoch),
         )
+        .route(
+            "/admin/{db}/restore/as",
+            post(routes::backup::restore_as_new),
+        )
         .route("/backups", get(routes::backup::list_all_backups))
         .route(
             "/admin/{db}/backups/{filename}",
@@ -284,6 +314,22 @@ pub fn router(state: AppState) -> Router {
             "/admin/{db}/backups/download/{filename}",
             get(routes::backup::download_backup),
         )
+        .route(
+            "/admin/{db}/backups/{filename}/verify",
+            post(routes::backup::verify_backup),
+        )
+        .route("/backups/verify", post(routes::backup::verify_all_backups))
+        .route("/backups/rekey", post(routes::backup::rekey_backups))
+        .route(
+            "/admin/{db}/backup/schedule",
+            get(routes::backup::get_backup_schedule)
+                .put(routes::backup::set_backup_schedule)
+                .delete(routes::backup::delete_backup_schedule),
+        )
+        .route(
+            "/backups/schedules",
+            get(routes::backup::list_backup_schedules),
+        )
         // Search
         .route("/search/vector", post(routes::search::vector_search))
         .route("/search/text", post(routes::search::text_search))
@@ -310,7 +356,19 @@ pub fn router(state: AppState) -> Router {
     #[cfg(feature = "sync")]
     let api = api
         .route("/db/{name}/changes", get(routes::sync::db_changes))
-        .route("/db/{name}/sync", post(routes::sync::db_apply));
+        .route("/db/{name}/sync", post(routes::sync::db_apply))
+        .route(
+            "/db/{name}/sync/policy",
+            get(routes::sync::get_policy).put(routes::sync::put_policy),
+        )
+        .route(
+            "/db/{name}/sync/conflicts",
+            get(routes::sync::list_conflicts),
+        )
+        .route(
+            "/db/{name}/sync/conflicts/{id}",
+            delete(routes::sync::dismiss_conflict),
+        );
 
     // Sync: SSE push stream (requires `push-changefeed` feature)
     #[cfg(feature = "push-changefeed")]
@@ -319,12 +377,33 @@ pub fn router(state: AppState) -> Router {
         get(routes::sync::db_changes_stream),
     );
 
-    // Replication status endpoint (requires `replication` feature)
+    // Replication endpoints (require `replication` feature)
     #[cfg(feature = "replication")]
-    let api = api.route(
-        "/admin/replication",
-        get(routes::replication::get_replication_status),
-    );
+    let api = api
+        .route(
+            "/admin/replication",
+            get(routes::replication::get_replication_status),
+        )
+        .route(
+            "/admin/replication/catalog",
+            get(routes::replication::get_replication_catalog),
+        )
+        .route(
+            "/admin/replication/promote",
+            post(routes::replication::promote),
+        )
+        .route(
+            "/admin/replication/follow",
+            post(routes::replication::follow),
+        )
+        .route(
+            "/db/{name}/replication/stream",
+            get(routes::replication::replication_stream),
+        )
+        .route(
+            "/db/{name}/replication/snapshot",
+            get(routes::replication::replication_snapshot),
+        );
 
     // Merge Swagger/OpenAPI routes into the main router BEFORE middleware
     // layers, so they are subject to auth and rate limiting.
@@ -46,6 +46,11 @@ impl AuthContext {
         Ok(())
     }
 
+    /// Name of the authenticated token, or `None` when auth is off.
+    pub fn user(&self) -> Option<&str> {
+        self.0.as_ref().map(|info| info.name.as_str())
+    }
+
     /// Build an engine [`Identity`] from the token, or anonymous if auth is off.
     ///
     /// When `server_read_only` is true, the identity is capped to [`Role::ReadOnly`]
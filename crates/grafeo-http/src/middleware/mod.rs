@@ -9,3 +9,5 @@ pub mod replica_guard;
 pub mod request_id;
 #[cfg(feature = "auth")]
 pub mod studio_auth;
+#[cfg(feature = "replication")]
+pub mod write_forward;
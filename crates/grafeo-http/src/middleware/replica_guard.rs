@@ -3,45 +3,122 @@
 //! POST, PUT, PATCH, and DELETE requests return `503 Service Unavailable`
 //! with `{"error": "replica_mode", "message": "..."}` when this instance
 //! is a read-only replica.  GET requests are always allowed.
+//!
+//! With `--replica-forward-writes` the replica sends writes to the primary
+//! instead (see [`write_forward`](super::write_forward)); the primary side
+//! of forwarding is handled here too.
+//!
+//! The guard also carries replication terms: a primary stamps the term it
+//! leads on every response, and any request carrying a newer term than the
+//! node has seen records it. A primary that learns of a newer term is
+//! fenced and rejects writes with `{"error": "fenced", ...}`.
 
 use axum::body::Body;
 use axum::extract::Request;
-use axum::http::{Method, StatusCode};
+use axum::http::{HeaderValue, Method, StatusCode};
 use axum::middleware::Next;
 use axum::response::Response;
 use serde_json::json;
 
+use super::write_forward::{self, FORWARDED_HEADER, Forwarding};
 use crate::AppState;
 
-/// Axum middleware that rejects write requests on replicas.
+/// Replication term of the sender: the term a primary leads, or the newest
+/// term a replica knows of.
+pub const TERM_HEADER: &str = "x-grafeo-replication-term";
+
+/// Axum middleware that rejects write requests on replicas and fenced
+/// primaries, and exchanges replication terms.
 ///
-/// Applied only when the `replication` feature is enabled. On non-replica
-/// instances (Standalone, Primary) this is a zero-cost pass-through.
+/// Applied only when the `replication` feature is enabled. On standalone
+/// instances this is a zero-cost pass-through.
 pub async fn replica_guard_middleware(
     axum::extract::State(state): axum::extract::State<AppState>,
     req: Request<Body>,
     next: Next,
 ) -> Response {
+    let service = state.service();
+    if let Some(term) = req
+        .headers()
+        .get(TERM_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        && service.replication_state().observe_term(term)
+        && service.is_fenced()
+    {
+        tracing::warn!(term, "A newer primary exists; rejecting writes");
+    }
+
+    let is_primary = service.replication_mode().is_primary();
+    let mut resp = guard(&state, req, next).await;
+    if is_primary {
+        let term = state.service().replication_state().led_term();
+        resp.headers_mut()
+            .insert(TERM_HEADER, HeaderValue::from(term));
+    }
+    resp
+}
+
+async fn guard(state: &AppState, req: Request<Body>, next: Next) -> Response {
+    if state.service().is_fenced() {
+        return fenced(req, next).await;
+    }
+
     // On replicas, allow read queries and sync endpoints but reject mutation
     // endpoints. The engine's read-only session flag provides a second line of
     // defense for queries that contain mutations.
+    if !state.service().is_replica() {
+        if req.headers().contains_key(FORWARDED_HEADER) {
+            return write_forward::stamp_epoch(state, req, next).await;
+        }
+        return next.run(req).await;
+    }
+
+    if state.service().forwards_writes() {
+        match write_forward::classify(req.method(), req.uri().path()) {
+            Some(Forwarding::Forward) => return write_forward::forward(state, req).await,
+            Some(Forwarding::TryLocal) => {
+                return write_forward::run_or_forward(state, req, next).await;
+            }
+            None => {}
+        }
+    }
+
     let path = req.uri().path();
     let is_replication_path = path.ends_with("/sync") || path.ends_with("/changes");
 
-    if state.service().is_replica()
-        && !is_replication_path
-        && matches!(*req.method(), Method::PUT | Method::PATCH | Method::DELETE)
+    if !is_replication_path && matches!(*req.method(), Method::PUT | Method::PATCH | Method::DELETE)
     {
-        let body = json!({
-            "error": "replica_mode",
-            "message": "This instance is a read-only replica. Write operations are not permitted."
-        });
-        return Response::builder()
-            .status(StatusCode::SERVICE_UNAVAILABLE)
-            .header("content-type", "application/json")
-            .body(Body::from(body.to_string()))
-            .expect("response builder with valid header is infallible");
+        return unavailable(
+            "replica_mode",
+            "This instance is a read-only replica. Write operations are not permitted.",
+        );
     }
 
     next.run(req).await
 }
+
+/// Handles a request on a primary that has been superseded: reads are
+/// served (queries run read-only), writes are rejected.
+async fn fenced(req: Request<Body>, next: Next) -> Response {
+    let is_write = matches!(
+        write_forward::classify(req.method(), req.uri().path()),
+        Some(Forwarding::Forward)
+    ) || matches!(*req.method(), Method::PUT | Method::PATCH | Method::DELETE);
+    if is_write {
+        return unavailable(
+            "fenced",
+            "This primary has been superseded by a newer one. Write operations are not permitted.",
+        );
+    }
+    next.run(req).await
+}
+
+fn unavailable(error: &str, message: &str) -> Response {
+    let body = json!({ "error": error, "message": message });
+    Response::builder()
+        .status(StatusCode::SERVICE_UNAVAILABLE)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .expect("response builder with valid header is infallible")
+}
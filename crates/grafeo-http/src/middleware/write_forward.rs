@@ -0,0 +1,312 @@
+//! Write forwarding from replicas to the primary.
+//!
+//! With `--replica-forward-writes`, a replica proxies writes to its primary
+//! instead of rejecting them, so clients can send every request to any node:
+//!
+//! - Transactions (`/tx/*`), batches, sync requests, Graph Store writes,
+//!   database management and any PUT/PATCH/DELETE under `/db` are always
+//!   forwarded.
+//! - Query endpoints run locally first. Reads are answered by the replica;
+//!   a query the read-only replica refuses (403) is forwarded.
+//!
+//! The request is replayed against `primary_url` with the caller's headers,
+//! including credentials, plus [`FORWARDED_HEADER`]. The primary answers a
+//! forwarded request with the database it wrote to and that database's
+//! epoch ([`DATABASE_HEADER`], [`EPOCH_HEADER`]); the replica waits until it
+//! has applied that epoch before returning the primary's response, so a
+//! client that reads from the same replica next sees its own write.
+//!
+//! Forwarded requests carry the replica's replication term, so a primary
+//! that has been superseded learns of it and refuses the write.
+
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use axum::body::{Body, Bytes};
+use axum::extract::Request;
+use axum::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use grafeo_service::error::ServiceError;
+use tracing::{debug, warn};
+
+use super::replica_guard::TERM_HEADER;
+use crate::AppState;
+use crate::error::ApiError;
+
+/// Marks a request forwarded by a replica.
+pub const FORWARDED_HEADER: &str = "x-grafeo-forwarded";
+/// Database a forwarded write went to.
+pub const DATABASE_HEADER: &str = "x-grafeo-database";
+/// Epoch of that database after a forwarded write.
+pub const EPOCH_HEADER: &str = "x-grafeo-epoch";
+
+/// How long a replica waits for a forwarded write to replicate back before
+/// responding anyway.
+const READ_YOUR_WRITES_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Forwarded requests carry no timeout: the primary enforces its own query
+/// timeout.
+static CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .expect("reqwest client construction cannot fail")
+});
+
+/// What a forwarding replica does with a request.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Forwarding {
+    /// Always send to the primary.
+    Forward,
+    /// Run locally; send to the primary if the replica refuses it.
+    TryLocal,
+}
+
+/// Classifies a request for a forwarding replica. `None` means it is
+/// handled locally as usual.
+pub(crate) fn classify(method: &Method, path: &str) -> Option<Forwarding> {
+    let db_path = path.strip_prefix("/db/").map(|rest| {
+        let (_, sub) = rest.split_once('/').unwrap_or((rest, ""));
+        sub
+    });
+
+    if *method == Method::POST {
+        return match (path, db_path) {
+            ("/query" | "/cypher" | "/graphql" | "/gremlin" | "/sparql" | "/sql", _)
+            | (_, Some("sparql")) => Some(Forwarding::TryLocal),
+            ("/batch" | "/db", _) => Some(Forwarding::Forward),
+            _ if path.starts_with("/tx/") => Some(Forwarding::Forward),
+            (_, Some("sync" | "graph-store" | "import/tsv" | "graphs" | "schemas")) => {
+                Some(Forwarding::Forward)
+            }
+            _ => None,
+        };
+    }
+
+    if matches!(*method, Method::PUT | Method::PATCH | Method::DELETE) && db_path.is_some() {
+        return Some(Forwarding::Forward);
+    }
+    None
+}
+
+/// Forwards a request to the primary and waits for the write to replicate.
+pub(crate) async fn forward(state: &AppState, req: Request<Body>) -> Response {
+    let (parts, body) = req.into_parts();
+    let body = match buffer(state, body).await {
+        Ok(body) => body,
+        Err(resp) => return resp,
+    };
+    proxy(state, &parts.method, &parts.uri, &parts.headers, body).await
+}
+
+/// Runs a query locally and forwards it to the primary if the replica
+/// refuses it as a write.
+pub(crate) async fn run_or_forward(state: &AppState, req: Request<Body>, next: Next) -> Response {
+    let (parts, body) = req.into_parts();
+    let body = match buffer(state, body).await {
+        Ok(body) => body,
+        Err(resp) => return resp,
+    };
+
+    let local = Request::from_parts(parts.clone(), Body::from(body.clone()));
+    let resp = next.run(local).await;
+    if resp.status() != StatusCode::FORBIDDEN {
+        return resp;
+    }
+
+    debug!(path = %parts.uri.path(), "Forwarding write query to the primary");
+    proxy(state, &parts.method, &parts.uri, &parts.headers, body).await
+}
+
+/// Runs a forwarded request on the primary and reports the epoch the
+/// replica must reach to observe it.
+pub(crate) async fn stamp_epoch(state: &AppState, req: Request<Body>, next: Next) -> Response {
+    let (parts, body) = req.into_parts();
+    let body = match buffer(state, body).await {
+        Ok(body) => body,
+        Err(resp) => return resp,
+    };
+
+    // Resolve before running: a commit removes its session.
+    let db_name = target_database(state, parts.uri.path(), &parts.headers, &body);
+
+    let mut resp = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if resp.status().is_success()
+        && let Some(entry) = state.databases().get(&db_name)
+        && let Ok(name) = HeaderValue::from_str(&db_name)
+    {
+        let epoch = entry.db().current_epoch().as_u64();
+        let headers = resp.headers_mut();
+        headers.insert(DATABASE_HEADER, name);
+        headers.insert(EPOCH_HEADER, HeaderValue::from(epoch));
+    }
+    resp
+}
+
+/// The database a request writes to: the `/db/{name}` path segment, the
+/// transaction's database, or the `database` field of a JSON body.
+fn target_database(state: &AppState, path: &str, headers: &HeaderMap, body: &[u8]) -> String {
+    if let Some(rest) = path.strip_prefix("/db/") {
+        let name = rest.split('/').next().unwrap_or_default();
+        return name.to_string();
+    }
+    if let Some(session_id) = headers.get("x-session-id").and_then(|v| v.to_str().ok())
+        && let Some(db_name) = state.sessions().db_name(session_id)
+    {
+        return db_name;
+    }
+    let database = serde_json::from_slice::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("database")?.as_str().map(str::to_string));
+    grafeo_service::resolve_db_name(database.as_deref()).to_string()
+}
+
+async fn buffer(state: &AppState, body: Body) -> Result<Bytes, Response> {
+    axum::body::to_bytes(body, state.max_body_size())
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE.into_response())
+}
+
+async fn proxy(
+    state: &AppState,
+    method: &Method,
+    uri: &axum::http::Uri,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Response {
+    let mode = state.service().replication_mode();
+    let Some(primary_url) = mode.primary_url() else {
+        return ApiError::internal("write forwarding without a primary").into_response();
+    };
+    let path = uri.path_and_query().map_or(uri.path(), |pq| pq.as_str());
+    let url = format!("{primary_url}{path}");
+
+    let mut outgoing = HeaderMap::new();
+    for (name, value) in headers {
+        if !is_hop_by_hop(name) {
+            outgoing.append(name.clone(), value.clone());
+        }
+    }
+    outgoing.insert(FORWARDED_HEADER, HeaderValue::from_static("1"));
+    let term = state.service().replication_state().term();
+    outgoing.insert(TERM_HEADER, HeaderValue::from(term));
+
+    let result = CLIENT
+        .request(method.clone(), &url)
+        .headers(outgoing)
+        .body(body)
+        .send()
+        .await;
+    let upstream = match result {
+        Ok(resp) => resp,
+        Err(e) => {
+            warn!(url = %url, error = %e, "Forwarding to the primary failed");
+            return ApiError::from(ServiceError::Unavailable(format!(
+                "primary unreachable: {e}"
+            )))
+            .into_response();
+        }
+    };
+
+    let status = upstream.status();
+    let mut response_headers = HeaderMap::new();
+    for (name, value) in upstream.headers() {
+        if !is_hop_by_hop(name) {
+            response_headers.append(name.clone(), value.clone());
+        }
+    }
+    let body = match upstream.bytes().await {
+        Ok(body) => body,
+        Err(e) => {
+            return ApiError::from(ServiceError::Unavailable(format!(
+                "primary response interrupted: {e}"
+            )))
+            .into_response();
+        }
+    };
+
+    if status.is_success() {
+        wait_for_write(state, &response_headers).await;
+    }
+
+    let mut resp = Response::new(Body::from(body));
+    *resp.status_mut() = status;
+    *resp.headers_mut() = response_headers;
+    resp
+}
+
+/// Waits until the replica has applied the epoch the primary reported.
+async fn wait_for_write(state: &AppState, headers: &HeaderMap) {
+    let db_name = headers.get(DATABASE_HEADER).and_then(|v| v.to_str().ok());
+    let epoch = headers
+        .get(EPOCH_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let (Some(db_name), Some(epoch)) = (db_name, epoch) else {
+        return;
+    };
+    let reached = state
+        .service()
+        .replication_state()
+        .wait_for_epoch(db_name, epoch, READ_YOUR_WRITES_TIMEOUT)
+        .await;
+    if !reached {
+        warn!(db = %db_name, epoch, "Forwarded write did not replicate back in time");
+    }
+}
+
+fn is_hop_by_hop(name: &HeaderName) -> bool {
+    matches!(
+        name.as_str(),
+        "connection"
+            | "keep-alive"
+            | "proxy-authenticate"
+            | "proxy-authorization"
+            | "te"
+            | "trailer"
+            | "transfer-encoding"
+            | "upgrade"
+    ) || *name == header::HOST
+        || *name == header::CONTENT_LENGTH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_routes() {
+        let post = Method::POST;
+        assert_eq!(classify(&post, "/query"), Some(Forwarding::TryLocal));
+        assert_eq!(
+            classify(&post, "/db/social/sparql"),
+            Some(Forwarding::TryLocal)
+        );
+        assert_eq!(classify(&post, "/tx/commit"), Some(Forwarding::Forward));
+        assert_eq!(classify(&post, "/batch"), Some(Forwarding::Forward));
+        assert_eq!(
+            classify(&post, "/db/social/sync"),
+            Some(Forwarding::Forward)
+        );
+        assert_eq!(classify(&post, "/db"), Some(Forwarding::Forward));
+        assert_eq!(
+            classify(&Method::DELETE, "/db/social"),
+            Some(Forwarding::Forward)
+        );
+        assert_eq!(
+            classify(&Method::PUT, "/db/social/graph-store"),
+            Some(Forwarding::Forward)
+        );
+    }
+
+    #[test]
+    fn classify_leaves_local_requests_alone() {
+        assert_eq!(classify(&Method::GET, "/db/social/changes"), None);
+        assert_eq!(classify(&Method::GET, "/db"), None);
+        assert_eq!(classify(&Method::POST, "/search/vector"), None);
+        assert_eq!(classify(&Method::POST, "/admin/default/backup"), None);
+        assert_eq!(classify(&Method::DELETE, "/admin/queries/7"), None);
+    }
+}
@@ -1,218 +1,752 @@
 //! Background replication task for replica instances.
 //!
-//! When the server starts in `Replica` mode, `start` spawns a Tokio task
-//! for each database that:
+//! When the server starts in `Replica` mode, `start` spawns a supervisor that
+//! mirrors the primary's catalog (`GET /admin/replication/catalog`) every
+//! `SUPERVISE_INTERVAL`, creating and dropping local databases to match, and
+//! keeps one follower task running per database. Each follower:
 //!
-//! 1. Calls `GET /db/{name}/changes?since={last_epoch}&limit=500` on the primary.
-//! 2. Applies the returned events via `SyncService::apply()`.
-//! 3. Advances the local epoch counter in `ReplicationState`.
-//! 4. Sleeps `POLL_INTERVAL` before the next iteration.
+//! 1. Bootstraps a database that has nothing replicated and no local data
+//!    by restoring `GET /db/{name}/replication/snapshot` and resuming from
+//!    the snapshot's epoch, rather than replaying the whole change history.
+//! 2. Catches up by calling
+//!    `GET /db/{name}/changes?since={next}&limit=500&complete=true` on the
+//!    primary and applying each batch via `SyncService::replicate()`, until
+//!    the primary has nothing more to send.
+//! 3. Opens `GET /db/{name}/replication/stream?since={next}` and applies
+//!    batches as the primary pushes them, advancing the local epoch counter
+//!    in `ReplicationState` after each one.
+//! 4. Falls back to step 2 when the stream reports a `gap`, disconnects, or
+//!    delivers nothing for `STREAM_IDLE_TIMEOUT`. A primary that does not
+//!    offer the stream is polled every `POLL_INTERVAL` instead.
 //!
-//! The task runs indefinitely until the process exits.  Transient HTTP errors
+//! Epochs are never split: the primary returns whole epochs, even one larger
+//! than the limit, so `ReplicationState` only ever records epochs that were
+//! applied in full. A batch from an older primary that ignores `complete` is
+//! applied up to its last complete epoch; an epoch it cannot send whole
+//! stops replication of the database with an error rather than diverging.
+//!
+//! Each follower also records the primary's reported epoch and when it was
+//! last caught up, from which `ReplicationState` derives the replica's lag.
+//!
+//! The supervisor re-reads the replication mode every cycle: after a
+//! promotion it stops all followers, and after `follow` re-points the node
+//! it replaces them with followers of the new primary, which first restore
+//! each database from the new primary's snapshot. Every request carries
+//! this node's replication term, and a primary reporting an older term is
+//! not followed.
+//!
+//! The supervisor runs until the process exits.  Transient HTTP errors
 //! are logged and retried; the error is recorded in `ReplicationState`.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
 use std::time::Duration;
 
+use futures_util::StreamExt;
 use grafeo_service::ServiceState;
+use grafeo_service::backup::BackupService;
+use grafeo_service::changefeed::ChangeBatch;
 use grafeo_service::error::ServiceError;
-use grafeo_service::replication::ReplicationState;
-use grafeo_service::sync::{SyncChangeRequest, SyncRequest, SyncService};
+use grafeo_service::replication::{CatalogEntry, ReplicationState};
+use grafeo_service::sse::SseParser;
+use grafeo_service::sync::{
+    ChangeEventDto, ChangesResponse, EntityRef, SyncChangeRequest, SyncRequest, SyncService,
+};
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tracing::{debug, error, info, warn};
 
+use crate::middleware::replica_guard::TERM_HEADER;
+use crate::routes::replication::SNAPSHOT_EPOCH_HEADER;
+
 const POLL_INTERVAL: Duration = Duration::from_millis(500);
 const BATCH_LIMIT: usize = 500;
+/// How often the supervisor checks for databases without a running follower.
+const SUPERVISE_INTERVAL: Duration = Duration::from_secs(1);
+/// A stream that delivers no batch for this long is dropped and re-opened
+/// after a catch-up poll, which bounds staleness on a silently dead feed.
+const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+static STATUS_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .expect("reqwest client construction cannot fail")
+});
 
 /// Starts background replication tasks for all databases on a replica.
 ///
-/// This is a no-op when not in `Replica` mode.
+/// This is a no-op when not in `Replica` mode, and when the tasks are
+/// already running.
 pub fn start(state: ServiceState) {
-    if !state.is_replica() {
+    if !state.is_replica() || !state.replication_state().claim_supervisor() {
         return;
     }
 
-    let primary_url = match state.replication_mode().primary_url() {
-        Some(url) => url.to_string(),
-        None => return,
-    };
-
     info!(
-        primary_url = %primary_url,
+        primary_url = ?state.replication_mode().primary_url(),
         "Starting replication background task"
     );
 
     let replication_state = Arc::clone(state.replication_state());
 
     tokio::spawn(async move {
-        poll_loop(state, primary_url, replication_state).await;
-        error!("Replication poll loop exited unexpectedly");
+        supervise(state, replication_state).await;
+        error!("Replication supervisor exited unexpectedly");
     });
 }
 
-/// The main polling loop. Polls all known databases on the primary.
-async fn poll_loop(
-    state: ServiceState,
-    primary_url: String,
-    replication_state: Arc<ReplicationState>,
-) {
+/// Keeps one follower running for every known database of the current
+/// primary. Followers are stopped when the node is promoted, and replaced
+/// when it is pointed at a different primary.
+async fn supervise(state: ServiceState, replication_state: Arc<ReplicationState>) {
     let http = reqwest::Client::builder()
         .timeout(Duration::from_secs(10))
         .build()
         .expect("reqwest client construction cannot fail");
+    // Streams are long-lived, so only the connect phase is bounded.
+    let stream_http = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(10))
+        .build()
+        .expect("reqwest client construction cannot fail");
 
-    loop {
-        // Collect all database names known locally.
-        let db_names: Vec<String> = state
-            .databases()
-            .list()
-            .into_iter()
-            .map(|info| info.name)
-            .collect();
+    let mut followers: HashMap<String, JoinHandle<()>> = HashMap::new();
+    let mut following: Option<String> = None;
 
-        // Also ensure "default" is always replicated even if not yet created.
-        let mut names = db_names;
-        if !names.iter().any(|n| n == "default") {
-            names.push("default".to_string());
+    loop {
+        let mode = state.replication_mode();
+        let primary_url = mode.primary_url().map(str::to_string);
+        if primary_url != following {
+            for (_, follower) in followers.drain() {
+                follower.abort();
+            }
+            match primary_url {
+                Some(ref url) => info!(primary_url = %url, "Following primary"),
+                None => info!("No longer a replica; stopped following"),
+            }
+            following.clone_from(&primary_url);
         }
+        let Some(primary_url) = primary_url else {
+            tokio::time::sleep(SUPERVISE_INTERVAL).await;
+            continue;
+        };
 
-        for db_name in &names {
-            if let Err(e) =
-                replicate_database(&http, &state, &primary_url, db_name, &replication_state).await
-            {
-                warn!(db = %db_name, error = %e, "Replication poll failed");
-                replication_state.set_error(db_name, e.to_string());
-            } else {
-                replication_state.clear_error(db_name);
+        let names = match fetch_catalog(&http, &state, &primary_url).await {
+            Ok(Some(catalog)) => {
+                reconcile(&state, &replication_state, &catalog, &mut followers);
+                catalog.into_iter().map(|entry| entry.name).collect()
             }
+            // The primary predates the catalog endpoint.
+            Ok(None) => local_names(&state),
+            Err(e) => {
+                warn!(error = %e, "Fetching the primary's catalog failed");
+                local_names(&state)
+            }
+        };
+
+        for db_name in names {
+            if followers.get(&db_name).is_some_and(|h| !h.is_finished()) {
+                continue;
+            }
+            let follower = Follower {
+                http: http.clone(),
+                stream_http: stream_http.clone(),
+                state: state.clone(),
+                primary_url: primary_url.clone(),
+                db_name: db_name.clone(),
+                replication_state: Arc::clone(&replication_state),
+            };
+            followers.insert(db_name, tokio::spawn(follower.run()));
         }
 
-        tokio::time::sleep(POLL_INTERVAL).await;
+        tokio::time::sleep(SUPERVISE_INTERVAL).await;
     }
 }
 
-/// Fetches and applies one batch of changes for a single database.
-async fn replicate_database(
+/// Replication status reported by a prospective primary.
+#[derive(Debug, serde::Deserialize)]
+pub struct PrimaryStatus {
+    /// Replication role of the node.
+    pub mode: String,
+    /// Highest term the node knows of.
+    #[serde(default)]
+    pub term: u64,
+    /// Whether the node is a superseded primary.
+    #[serde(default)]
+    pub fenced: bool,
+}
+
+/// Fetches `GET /admin/replication` from another node, presenting this
+/// node's term so a superseded primary learns it is fenced. Credentials in
+/// `headers` are passed through.
+pub async fn fetch_status(
+    url: &str,
+    term: u64,
+    headers: reqwest::header::HeaderMap,
+) -> Result<PrimaryStatus, String> {
+    let resp = STATUS_CLIENT
+        .get(format!("{url}/admin/replication"))
+        .headers(headers)
+        .header(TERM_HEADER, term)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(format!("{url} returned {}", resp.status()));
+    }
+    resp.json().await.map_err(|e| e.to_string())
+}
+
+/// Database names known locally, plus `default` even if not yet created.
+fn local_names(state: &ServiceState) -> Vec<String> {
+    let mut names: Vec<String> = state
+        .databases()
+        .list()
+        .into_iter()
+        .map(|info| info.name)
+        .collect();
+    if !names.iter().any(|n| n == "default") {
+        names.push("default".to_string());
+    }
+    names
+}
+
+/// Adds the replica's token to a request to the primary, if configured.
+fn with_credentials(
+    request: reqwest::RequestBuilder,
+    state: &ServiceState,
+) -> reqwest::RequestBuilder {
+    match state.replica_token() {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Fetches the primary's database catalog. Returns `None` if the primary
+/// does not serve one.
+async fn fetch_catalog(
     http: &reqwest::Client,
     state: &ServiceState,
     primary_url: &str,
-    db_name: &str,
-    replication_state: &ReplicationState,
-) -> Result<(), ReplicationError> {
-    let last = replication_state.last_epoch(db_name);
-    // `since` is inclusive in the changes API, so add 1 to skip already-applied epochs.
-    let since = if last > 0 { last + 1 } else { 0 };
-    let url = format!("{primary_url}/db/{db_name}/changes?since={since}&limit={BATCH_LIMIT}");
-
-    debug!(db = %db_name, since = since, "Polling primary for changes");
-
-    let resp = http
-        .get(&url)
+) -> Result<Option<Vec<CatalogEntry>>, ReplicationError> {
+    let request = http.get(format!("{primary_url}/admin/replication/catalog"));
+    let resp = with_credentials(request, state)
         .send()
         .await
         .map_err(ReplicationError::Http)?;
 
     if resp.status() == reqwest::StatusCode::NOT_FOUND {
-        // Database doesn't exist on the primary yet — skip silently.
-        return Ok(());
+        return Ok(None);
     }
-
     if !resp.status().is_success() {
         let status = resp.status().as_u16();
         let body = resp.text().await.unwrap_or_default();
         return Err(ReplicationError::BadStatus { status, body });
     }
+    resp.json().await.map(Some).map_err(ReplicationError::Http)
+}
 
-    let changes_resp: grafeo_service::sync::ChangesResponse =
-        resp.json().await.map_err(ReplicationError::Http)?;
+/// Creates the databases the primary has and this replica lacks, and drops
+/// the ones the primary no longer has (never `default`).
+fn reconcile(
+    state: &ServiceState,
+    replication_state: &ReplicationState,
+    catalog: &[CatalogEntry],
+    followers: &mut HashMap<String, JoinHandle<()>>,
+) {
+    let databases = state.databases();
 
-    if changes_resp.changes.is_empty() {
-        return Ok(());
+    for entry in catalog {
+        if databases.get(&entry.name).is_some() {
+            continue;
+        }
+        let req = entry.create_request(databases.data_dir().is_some());
+        match databases.create(&req) {
+            Ok(()) => info!(db = %entry.name, "Created database from primary catalog"),
+            Err(e) => {
+                warn!(db = %entry.name, error = %e, "Failed to create replicated database");
+                replication_state.set_error(&entry.name, e.to_string());
+            }
+        }
     }
 
-    let new_epoch = changes_resp.server_epoch;
-    let count = changes_resp.changes.len();
+    for local in databases.list() {
+        let name = local.name;
+        if name == "default" || catalog.iter().any(|e| e.name == name) {
+            continue;
+        }
+        if let Some(follower) = followers.remove(&name) {
+            follower.abort();
+        }
+        state.sessions().remove_by_database(&name);
+        match databases.delete(&name) {
+            Ok(()) => {
+                replication_state.forget(&name);
+                info!(db = %name, "Dropped database removed on the primary");
+            }
+            Err(e) => warn!(db = %name, error = %e, "Failed to drop replicated database"),
+        }
+    }
+}
 
-    info!(
-        db = %db_name,
-        changes = count,
-        up_to_epoch = new_epoch,
-        "Applying replication batch"
-    );
+/// How a replication stream ended without an error.
+enum StreamEnd {
+    /// The primary reported that batches were dropped.
+    Gap,
+    /// No batch arrived within `STREAM_IDLE_TIMEOUT`.
+    Idle,
+    /// The primary closed the stream.
+    Closed,
+}
 
-    // Ensure the database exists on the replica.
-    if state.databases().get(db_name).is_none() {
-        let create_req = grafeo_service::types::CreateDatabaseRequest {
-            name: db_name.to_string(),
-            database_type: grafeo_service::types::DatabaseType::default(),
-            storage_mode: grafeo_service::types::StorageMode::default(),
-            options: grafeo_service::types::DatabaseOptions::default(),
-            schema_file: None,
-            schema_filename: None,
-        };
-        if let Err(e) = state.databases().create(&create_req) {
-            return Err(ReplicationError::Apply(format!(
-                "Failed to create local database '{db_name}': {e}"
-            )));
+/// Replicates a single database from the primary.
+struct Follower {
+    http: reqwest::Client,
+    stream_http: reqwest::Client,
+    state: ServiceState,
+    primary_url: String,
+    db_name: String,
+    replication_state: Arc<ReplicationState>,
+}
+
+impl Follower {
+    async fn run(self) {
+        self.replication_state.track(&self.db_name);
+        let mut bootstrapped = false;
+        loop {
+            if !bootstrapped || self.replication_state.needs_resync(&self.db_name) {
+                if let Err(e) = self.bootstrap().await {
+                    self.record_error(&e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+                bootstrapped = true;
+            }
+
+            if let Err(e) = self.catch_up().await {
+                self.record_error(&e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+            self.replication_state.clear_error(&self.db_name);
+            self.replication_state.mark_caught_up(&self.db_name);
+
+            let ended = self.follow_stream().await;
+            self.replication_state.set_streaming(&self.db_name, false);
+            match ended {
+                Ok(StreamEnd::Gap) => {
+                    debug!(db = %self.db_name, "Replication stream gap, catching up");
+                }
+                Ok(StreamEnd::Idle) => {
+                    debug!(db = %self.db_name, "Replication stream idle, re-syncing");
+                }
+                Ok(StreamEnd::Closed) => {
+                    debug!(db = %self.db_name, "Replication stream closed by primary");
+                }
+                Err(ReplicationError::BadStatus { status: 404, .. }) => {
+                    // No stream on the primary (older version, or the
+                    // database does not exist there yet): keep polling.
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                Err(e) => {
+                    self.record_error(&e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
         }
     }
 
-    // Convert ChangeEventDto → SyncChangeRequest and apply.
-    let sync_changes: Vec<SyncChangeRequest> = changes_resp
-        .changes
-        .iter()
-        .map(change_event_to_sync_request)
-        .collect();
+    /// Builds a request to the primary carrying this node's term and
+    /// credentials.
+    fn get(&self, client: &reqwest::Client, url: &str) -> reqwest::RequestBuilder {
+        let request = client
+            .get(url)
+            .header(TERM_HEADER, self.replication_state.term());
+        with_credentials(request, &self.state)
+    }
 
-    let req = SyncRequest {
-        client_id: "replication".to_string(),
-        last_seen_epoch: since,
-        changes: sync_changes,
-        schema_version: None,
-    };
-
-    let state_clone = state.clone();
-    let db_name_owned = db_name.to_string();
-    let result = tokio::task::spawn_blocking(move || {
-        SyncService::apply(state_clone.databases(), &db_name_owned, req)
-    })
-    .await
-    .unwrap_or_else(|e| {
-        error!(error = %e, "SyncService::apply panicked");
-        Err(ServiceError::Internal(format!("apply panicked: {e}")))
-    });
+    /// Rejects responses from a primary older than the newest known term,
+    /// and from a primary this node no longer follows.
+    fn check_primary(&self, resp: &reqwest::Response) -> Result<(), ReplicationError> {
+        if self.state.replication_mode().primary_url() != Some(self.primary_url.as_str()) {
+            return Err(ReplicationError::NotFollowing);
+        }
+        let term = resp
+            .headers()
+            .get(TERM_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+        if let Some(term) = term {
+            self.replication_state.observe_term(term);
+            let known = self.replication_state.term();
+            if term < known {
+                return Err(ReplicationError::StalePrimary { term, known });
+            }
+        }
+        Ok(())
+    }
+
+    fn record_error(&self, e: &ReplicationError) {
+        warn!(db = %self.db_name, error = %e, "Replication failed");
+        self.replication_state
+            .set_error(&self.db_name, e.to_string());
+    }
+
+    /// The first epoch not yet applied locally.
+    fn next_since(&self) -> u64 {
+        let last = self.replication_state.last_epoch(&self.db_name);
+        // `since` is inclusive in the changes API, so add 1 to skip already-applied epochs.
+        if last > 0 { last + 1 } else { 0 }
+    }
+
+    /// Restores a snapshot of the primary's database if nothing has been
+    /// replicated into it yet and it holds no local data, then resumes from
+    /// the snapshot's epoch. Does nothing if the primary serves no snapshot.
+    ///
+    /// After the node is pointed at a new primary, the snapshot is restored
+    /// regardless, replacing whatever the database held.
+    async fn bootstrap(&self) -> Result<(), ReplicationError> {
+        let resync = self.replication_state.needs_resync(&self.db_name);
+        if !resync && self.replication_state.last_epoch(&self.db_name) > 0 {
+            return Ok(());
+        }
+        if !resync && let Some(entry) = self.state.databases().get(&self.db_name) {
+            let db = entry.db();
+            if db.node_count() > 0 || db.edge_count() > 0 {
+                return Ok(());
+            }
+        }
+
+        let url = format!(
+            "{}/db/{}/replication/snapshot",
+            self.primary_url, self.db_name
+        );
+        let resp = self
+            .get(&self.stream_http, &url)
+            .send()
+            .await
+            .map_err(ReplicationError::Http)?;
+        self.check_primary(&resp)?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            // Older primary, or the database doesn't exist there yet.
+            self.replication_state.resynced(&self.db_name);
+            return Ok(());
+        }
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ReplicationError::BadStatus { status, body });
+        }
+
+        let epoch = resp
+            .headers()
+            .get(SNAPSHOT_EPOCH_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+            .ok_or_else(|| {
+                ReplicationError::Apply("snapshot response has no epoch header".to_string())
+            })?;
+        if epoch == 0 && !resync {
+            // Nothing has committed on the primary yet: replaying from
+            // epoch 0 is cheap, and the epoch counter can't record that
+            // epoch 0 was restored.
+            return Ok(());
+        }
+
+        info!(db = %self.db_name, epoch, "Bootstrapping replica from primary snapshot");
+
+        let staged = std::env::temp_dir().join(format!(
+            "grafeo-replica-bootstrap-{}.grafeo",
+            uuid::Uuid::new_v4()
+        ));
+        let result = self.restore_snapshot(resp, &staged).await;
+        let _ = tokio::fs::remove_file(&staged).await;
+        result?;
+
+        self.replication_state.advance_epoch(&self.db_name, epoch);
+        self.replication_state.resynced(&self.db_name);
+        Ok(())
+    }
+
+    /// Downloads a snapshot response to `staged` and restores it.
+    async fn restore_snapshot(
+        &self,
+        resp: reqwest::Response,
+        staged: &std::path::Path,
+    ) -> Result<(), ReplicationError> {
+        let io_error =
+            |e: std::io::Error| ReplicationError::Apply(format!("failed to stage snapshot: {e}"));
+        let mut file = tokio::fs::File::create(staged).await.map_err(io_error)?;
+        let mut body = resp.bytes_stream();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(ReplicationError::Http)?;
+            file.write_all(&chunk).await.map_err(io_error)?;
+        }
+        file.flush().await.map_err(io_error)?;
+        drop(file);
+
+        self.ensure_database()?;
+        BackupService::restore_replica_snapshot(self.state.databases(), &self.db_name, staged)
+            .await
+            .map_err(|e| ReplicationError::Apply(e.to_string()))
+    }
+
+    /// Creates the database locally if it does not exist yet.
+    fn ensure_database(&self) -> Result<(), ReplicationError> {
+        let db_name = self.db_name.as_str();
+        if self.state.databases().get(db_name).is_none() {
+            let create_req = grafeo_service::types::CreateDatabaseRequest {
+                name: db_name.to_string(),
+                database_type: grafeo_service::types::DatabaseType::default(),
+                storage_mode: grafeo_service::types::StorageMode::default(),
+                options: grafeo_service::types::DatabaseOptions::default(),
+                schema_file: None,
+                schema_filename: None,
+            };
+            if let Err(e) = self.state.databases().create(&create_req) {
+                return Err(ReplicationError::Apply(format!(
+                    "Failed to create local database '{db_name}': {e}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Polls the primary until it has no more changes to send.
+    async fn catch_up(&self) -> Result<(), ReplicationError> {
+        while self.poll_once().await? {}
+        Ok(())
+    }
+
+    /// Fetches and applies one batch of changes. Returns `true` if the
+    /// primary has more changes waiting.
+    async fn poll_once(&self) -> Result<bool, ReplicationError> {
+        let since = self.next_since();
+        let url = format!(
+            "{}/db/{}/changes?since={since}&limit={BATCH_LIMIT}&complete=true",
+            self.primary_url, self.db_name
+        );
+
+        debug!(db = %self.db_name, since = since, "Polling primary for changes");
+
+        let resp = self
+            .get(&self.http, &url)
+            .send()
+            .await
+            .map_err(ReplicationError::Http)?;
+        self.check_primary(&resp)?;
+
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            // Database doesn't exist on the primary yet — skip silently.
+            return Ok(false);
+        }
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ReplicationError::BadStatus { status, body });
+        }
+
+        let changes_resp: ChangesResponse = resp.json().await.map_err(ReplicationError::Http)?;
+
+        if changes_resp.changes.is_empty() {
+            return Ok(false);
+        }
+        // Only batches carry an epoch the replica will reach: an empty
+        // pull's epoch may still receive events.
+        self.replication_state
+            .observe_primary_epoch(&self.db_name, changes_resp.server_epoch);
+
+        let (changes, up_to, more) = complete_epochs(changes_resp, BATCH_LIMIT)?;
+        self.apply(since, changes, up_to).await?;
+        Ok(more)
+    }
 
-    match result {
-        Ok(resp) => {
-            if resp.applied > 0 || resp.skipped > 0 {
-                debug!(
-                    db = %db_name,
-                    applied = resp.applied,
-                    skipped = resp.skipped,
-                    "Applied replication batch"
-                );
+    /// Follows the primary's push stream until it ends.
+    async fn follow_stream(&self) -> Result<StreamEnd, ReplicationError> {
+        let url = format!(
+            "{}/db/{}/replication/stream?since={}",
+            self.primary_url,
+            self.db_name,
+            self.next_since()
+        );
+
+        let resp = self
+            .get(&self.stream_http, &url)
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .map_err(ReplicationError::Http)?;
+        self.check_primary(&resp)?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ReplicationError::BadStatus { status, body });
+        }
+
+        debug!(db = %self.db_name, "Following primary replication stream");
+        self.replication_state.set_streaming(&self.db_name, true);
+
+        let mut body = resp.bytes_stream();
+        let mut parser = SseParser::default();
+        let mut deadline = Instant::now() + STREAM_IDLE_TIMEOUT;
+
+        loop {
+            let bytes = match tokio::time::timeout_at(deadline, body.next()).await {
+                Err(_) => return Ok(StreamEnd::Idle),
+                Ok(None) => return Ok(StreamEnd::Closed),
+                Ok(Some(Err(e))) => return Err(ReplicationError::Http(e)),
+                Ok(Some(Ok(bytes))) => bytes,
+            };
+
+            for event in parser.push(&bytes) {
+                match event.event.as_str() {
+                    "batch" => {
+                        let batch: ChangeBatch = serde_json::from_str(&event.data)
+                            .map_err(|e| ReplicationError::Apply(format!("bad batch: {e}")))?;
+                        self.replication_state
+                            .observe_primary_epoch(&self.db_name, batch.server_epoch);
+                        let next = self.next_since();
+                        if batch.server_epoch < next {
+                            continue;
+                        }
+                        if batch.since > next {
+                            return Ok(StreamEnd::Gap);
+                        }
+                        self.apply(next, batch.changes, batch.server_epoch).await?;
+                        self.replication_state.clear_error(&self.db_name);
+                        deadline = Instant::now() + STREAM_IDLE_TIMEOUT;
+                    }
+                    "gap" => return Ok(StreamEnd::Gap),
+                    _ => {}
+                }
             }
         }
-        Err(e) => {
-            return Err(ReplicationError::Apply(e.to_string()));
+    }
+
+    /// Applies the events with `epoch >= since` and advances the local
+    /// epoch counter to `up_to`.
+    async fn apply(
+        &self,
+        since: u64,
+        changes: Vec<ChangeEventDto>,
+        up_to: u64,
+    ) -> Result<(), ReplicationError> {
+        let db_name = self.db_name.as_str();
+        let changes: Vec<ChangeEventDto> =
+            changes.into_iter().filter(|c| c.epoch >= since).collect();
+
+        if !changes.is_empty() {
+            info!(
+                db = %db_name,
+                changes = changes.len(),
+                up_to_epoch = up_to,
+                "Applying replication batch"
+            );
+
+            self.ensure_database()?;
+
+            // Convert ChangeEventDto → SyncChangeRequest and apply.
+            let req = SyncRequest {
+                client_id: "replication".to_string(),
+                last_seen_epoch: since,
+                changes: changes.iter().map(change_event_to_sync_request).collect(),
+                schema_version: None,
+                // A batch is applied whole or not at all, so a failed batch
+                // is retried from the same epoch without duplicating creates.
+                atomic: true,
+            };
+
+            let state_clone = self.state.clone();
+            let db_name_owned = db_name.to_string();
+            let result = tokio::task::spawn_blocking(move || {
+                SyncService::replicate(state_clone.databases(), &db_name_owned, req)
+            })
+            .await
+            .unwrap_or_else(|e| {
+                error!(error = %e, "SyncService::replicate panicked");
+                Err(ServiceError::Internal(format!("apply panicked: {e}")))
+            });
+
+            match result {
+                Ok(resp) if resp.rolled_back => {
+                    let reason = resp
+                        .conflicts
+                        .last()
+                        .map_or("unknown", |c| c.reason.as_str());
+                    return Err(ReplicationError::Apply(format!(
+                        "batch rolled back: {reason}"
+                    )));
+                }
+                Ok(resp) => {
+                    if resp.applied > 0 || resp.skipped > 0 {
+                        debug!(
+                            db = %db_name,
+                            applied = resp.applied,
+                            skipped = resp.skipped,
+                            "Applied replication batch"
+                        );
+                    }
+                }
+                Err(e) => {
+                    return Err(ReplicationError::Apply(e.to_string()));
+                }
+            }
         }
+
+        self.replication_state.advance_epoch(db_name, up_to);
+        Ok(())
     }
+}
 
-    replication_state.advance_epoch(db_name, new_epoch);
-    Ok(())
+/// Trims a polled batch to the epochs it contains in full.
+///
+/// Returns the events to apply, the epoch to advance to, and whether the
+/// primary may have more to send. A batch the primary marked `complete` is
+/// whole. Otherwise a batch of `limit` events may end part-way through an
+/// epoch; the events of that last epoch are dropped so they are fetched
+/// again on the next poll. A single epoch larger than `limit` cannot be
+/// fetched whole from such a primary, so the batch is rejected.
+fn complete_epochs(
+    resp: ChangesResponse,
+    limit: usize,
+) -> Result<(Vec<ChangeEventDto>, u64, bool), ReplicationError> {
+    let ChangesResponse {
+        server_epoch,
+        mut changes,
+        complete,
+    } = resp;
+    if complete || changes.len() < limit {
+        let more = changes.len() >= limit;
+        return Ok((changes, server_epoch, more));
+    }
+    let last_epoch = changes.last().map_or(server_epoch, |c| c.epoch);
+    let whole = changes.partition_point(|c| c.epoch < last_epoch);
+    if whole == 0 {
+        return Err(ReplicationError::Apply(format!(
+            "epoch {last_epoch} has more than {limit} changes and the primary cannot \
+             send it whole; upgrade the primary"
+        )));
+    }
+    changes.truncate(whole);
+    Ok((changes, last_epoch - 1, true))
 }
 
 /// Converts a `ChangeEventDto` to a `SyncChangeRequest` for replay.
-fn change_event_to_sync_request(event: &grafeo_service::sync::ChangeEventDto) -> SyncChangeRequest {
+fn change_event_to_sync_request(event: &ChangeEventDto) -> SyncChangeRequest {
     let after = event.after.clone();
     SyncChangeRequest {
         kind: event.kind.clone(),
         entity_type: event.entity_type.clone(),
-        id: Some(event.id),
+        id: Some(event.id.into()),
         timestamp: event.timestamp,
         labels: event.labels.clone(),
         edge_type: event.edge_type.clone(),
-        src_id: event.src_id,
-        dst_id: event.dst_id,
+        src_id: event.src_id.map(EntityRef::from),
+        dst_id: event.dst_id.map(EntityRef::from),
         after,
         crdt_op: None,
         crdt_property: None,
@@ -226,8 +760,18 @@ fn change_event_to_sync_request(event: &grafeo_service::sync::ChangeEventDto) ->
 #[derive(Debug)]
 enum ReplicationError {
     Http(reqwest::Error),
-    BadStatus { status: u16, body: String },
+    BadStatus {
+        status: u16,
+        body: String,
+    },
     Apply(String),
+    /// The primary leads an older term than one this node has seen.
+    StalePrimary {
+        term: u64,
+        known: u64,
+    },
+    /// The node was promoted or re-pointed while the request was in flight.
+    NotFollowing,
 }
 
 impl std::fmt::Display for ReplicationError {
@@ -238,6 +782,78 @@ impl std::fmt::Display for ReplicationError {
                 write!(f, "Primary returned {status}: {body}")
             }
             Self::Apply(e) => write!(f, "Apply error: {e}"),
+            Self::StalePrimary { term, known } => {
+                write!(f, "Primary leads term {term}, but term {known} exists")
+            }
+            Self::NotFollowing => write!(f, "No longer following this primary"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(epoch: u64) -> ChangeEventDto {
+        ChangeEventDto {
+            id: epoch,
+            entity_type: "node".to_string(),
+            kind: "create".to_string(),
+            epoch,
+            timestamp: 0,
+            labels: None,
+            edge_type: None,
+            src_id: None,
+            dst_id: None,
+            before: None,
+            after: None,
+            triple_subject: None,
+            triple_predicate: None,
+            triple_object: None,
+            triple_graph: None,
+        }
+    }
+
+    fn response(
+        changes: Vec<ChangeEventDto>,
+        server_epoch: u64,
+        complete: bool,
+    ) -> ChangesResponse {
+        ChangesResponse {
+            server_epoch,
+            changes,
+            complete,
         }
     }
+
+    #[test]
+    fn complete_epochs_drops_partial_last_epoch() {
+        let changes = vec![event(3), event(4), event(5), event(5)];
+        let (kept, up_to, more) = complete_epochs(response(changes, 9, false), 4).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(up_to, 4);
+        assert!(more);
+    }
+
+    #[test]
+    fn complete_epochs_keeps_short_batches_whole() {
+        let (kept, up_to, more) =
+            complete_epochs(response(vec![event(3), event(3)], 9, false), 4).unwrap();
+        assert_eq!(kept.len(), 2);
+        assert_eq!(up_to, 9);
+        assert!(!more);
+    }
+
+    #[test]
+    fn complete_epochs_applies_oversized_epochs_only_when_whole() {
+        // A primary that honours `complete` sends the whole epoch.
+        let (kept, up_to, more) =
+            complete_epochs(response(vec![event(6); 600], 6, true), 500).unwrap();
+        assert_eq!(kept.len(), 600);
+        assert_eq!(up_to, 6);
+        assert!(more);
+
+        // One that doesn't may have cut it short.
+        assert!(complete_epochs(response(vec![event(6); 500], 9, false), 500).is_err());
+    }
 }
@@ -329,6 +329,50 @@ pub async fn admin_validate_shacl(
     Ok(Json(report))
 }
 
+/// List queries currently executing, across all databases and transports.
+#[utoipa::path(
+    get, path = "/admin/queries",
+    responses(
+        (status = 200, description = "In-flight queries, oldest first", body = Vec<types::RunningQueryInfo>),
+        (status = 403, description = "Admin access required", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn admin_list_queries(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<Vec<types::RunningQueryInfo>>, ApiError> {
+    auth.check_admin()?;
+    Ok(Json(state.running_queries().list()))
+}
+
+/// Kill a running query.
+///
+/// Cancellation is cooperative: the query stops at its next checkpoint and
+/// its client receives a `cancelled` error. Until then it stays listed with
+/// `cancelling: true`.
+#[utoipa::path(
+    delete, path = "/admin/queries/{id}",
+    params(("id" = String, Path, description = "Query ID from `GET /admin/queries`")),
+    responses(
+        (status = 200, description = "Cancellation requested"),
+        (status = 403, description = "Admin access required", body = crate::error::ErrorBody),
+        (status = 404, description = "Query not found (already finished or unknown ID)", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn admin_kill_query(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    auth.check_admin()?;
+    if !state.running_queries().kill(&id) {
+        return Err(ApiError::not_found(format!("query '{id}' not found")));
+    }
+    Ok(Json(serde_json::json!({ "cancelled": true })))
+}
+
 #[cfg(test)]
 mod tests {
     use axum::body::Body;
@@ -446,4 +490,68 @@ mod tests {
             resp.status()
         );
     }
+
+    // -----------------------------------------------------------------------
+    // Running query endpoints
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn list_queries_shows_registered_query() {
+        let state = crate::AppState::new_in_memory(300);
+        let guard = state.running_queries().register(
+            "default",
+            Some("cypher"),
+            "MATCH (n) RETURN n",
+            None,
+            grafeo_service::types::QueryTransport::Bolt,
+        );
+        let resp = crate::router(state.clone())
+            .oneshot(Request::get("/admin/queries").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(list[0]["id"], guard.id());
+        assert_eq!(list[0]["language"], "cypher");
+        assert_eq!(list[0]["transport"], "bolt");
+    }
+
+    #[tokio::test]
+    async fn kill_query_cancels_token() {
+        let state = crate::AppState::new_in_memory(300);
+        let guard = state.running_queries().register(
+            "default",
+            None,
+            "MATCH (n) RETURN n",
+            None,
+            grafeo_service::types::QueryTransport::Http,
+        );
+        let token = guard.cancel_token();
+        let resp = crate::router(state.clone())
+            .oneshot(
+                Request::delete(format!("/admin/queries/{}", guard.id()))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(token.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn kill_unknown_query_returns_404() {
+        let resp = app()
+            .oneshot(
+                Request::delete("/admin/queries/no-such-id")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
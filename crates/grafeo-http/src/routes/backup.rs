@@ -1,5 +1,8 @@
 //! Backup and restore endpoints.
 
+use std::io::Read;
+use std::sync::Arc;
+
 use axum::body::Body;
 use axum::extract::{Json, Path, State};
 use axum::http::header;
@@ -10,8 +13,13 @@ use crate::middleware::auth_context::AuthContext;
 use crate::state::AppState;
 
 use grafeo_service::backup::BackupService;
+use grafeo_service::backup_schedule::BackupScheduler;
+use grafeo_service::backup_target::BackupTarget;
 use grafeo_service::types;
 
+/// Bytes read from a backup per download body chunk.
+const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
 /// Create a full backup of a database.
 ///
 /// Exports a point-in-time snapshot. The database stays available during
@@ -39,13 +47,22 @@ pub async fn create_backup(
     body: Option<Json<types::CreateBackupRequest>>,
 ) -> Result<Json<types::BackupEntry>, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
+    let (backup_dir, target) = require_backup(&state)?;
 
     let label = body.and_then(|Json(req)| req.label);
-    let entry = BackupService::backup_database(state.databases(), &db, &backup_dir, label).await?;
+    let entry = BackupService::backup_database_to(
+        state.databases(),
+        target.as_ref(),
+        &db,
+        &backup_dir,
+        label,
+        state.backup_keys(),
+    )
+    .await?;
 
     if let Some(keep) = state.backup_retention() {
-        let _ = BackupService::enforce_retention(&db, &backup_dir, keep);
+        let _ =
+            BackupService::prune_backups(target.as_ref(), &db, &backup_dir, Some(keep), None).await;
     }
 
     Ok(Json(entry))
@@ -70,8 +87,14 @@ pub async fn list_backups(
     Path(db): Path<String>,
 ) -> Result<Json<Vec<types::BackupEntry>>, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
-    let entries = BackupService::list_backups(Some(&db), &backup_dir)?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let entries = BackupService::list_backups_from(
+        target.as_ref(),
+        Some(&db),
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
     Ok(Json(entries))
 }
 
@@ -90,8 +113,10 @@ pub async fn list_all_backups(
     auth: AuthContext,
 ) -> Result<Json<Vec<types::BackupEntry>>, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
-    let entries = BackupService::list_backups(None, &backup_dir)?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let entries =
+        BackupService::list_backups_from(target.as_ref(), None, &backup_dir, state.backup_keys())
+            .await?;
     Ok(Json(entries))
 }
 
@@ -121,7 +146,7 @@ pub async fn restore_backup(
     Json(req): Json<types::RestoreRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
+    let (backup_dir, target) = require_backup(&state)?;
 
     // Source database defaults to the target for same-db restores. When
     // the client provides source_db, this is a cross-database restore and
@@ -141,9 +166,16 @@ pub async fn restore_backup(
         }
     }
 
-    let backup_path = backup_dir.join(source_db).join(&req.backup);
-
-    BackupService::restore_database(state.databases(), &db, &backup_path, &backup_dir).await?;
+    BackupService::restore_database_from(
+        state.databases(),
+        target.as_ref(),
+        &db,
+        source_db,
+        &req.backup,
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
 
     Ok(Json(serde_json::json!({ "restored": true })))
 }
@@ -169,14 +201,23 @@ pub async fn delete_backup(
     Path((db, filename)): Path<(String, String)>,
 ) -> Result<impl IntoResponse, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
-    BackupService::delete_backup(&db, &filename, &backup_dir)?;
+    let (backup_dir, target) = require_backup(&state)?;
+    BackupService::delete_backup_from(
+        target.as_ref(),
+        &db,
+        &filename,
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
 /// Download a backup file.
 ///
 /// Streams the backup file with `Content-Disposition: attachment`.
+/// Encrypted backups are decrypted on the fly, so the download is always
+/// the plaintext `.grafeo` or WAL segment.
 #[utoipa::path(
     get,
     path = "/admin/{db}/backups/download/{filename}",
@@ -197,7 +238,7 @@ pub async fn download_backup(
     Path((db, filename)): Path<(String, String)>,
 ) -> Result<impl IntoResponse, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
+    let (backup_dir, target) = require_backup(&state)?;
 
     // Validate both params — axum percent-decodes path segments
     for param in [&db, &filename] {
@@ -209,21 +250,41 @@ pub async fn download_backup(
         }
     }
 
-    grafeo_service::backup::ensure_migrated(&backup_dir);
-
-    let file_path = backup_dir.join(&db).join(&filename);
-    if !file_path.exists() {
-        return Err(grafeo_service::error::ServiceError::NotFound(format!(
-            "backup '{filename}' not found for database '{db}'"
-        ))
-        .into());
-    }
-
-    let file = tokio::fs::File::open(&file_path)
-        .await
-        .map_err(|e| grafeo_service::error::ServiceError::Internal(e.to_string()))?;
-
-    let stream = tokio_util::io::ReaderStream::new(file);
+    // Backups only the target holds are downloaded into the local cache
+    let mut reader = BackupService::open_backup(
+        target.as_ref(),
+        &db,
+        &filename,
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
+
+    // Decryption is blocking, so a blocking task feeds the response body
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<std::io::Result<Vec<u8>>>(4);
+    tokio::task::spawn_blocking(move || {
+        loop {
+            let mut chunk = vec![0u8; DOWNLOAD_CHUNK_SIZE];
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => {
+                    chunk.truncate(n);
+                    Ok(chunk)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = read.is_err();
+            if tx.blocking_send(read).is_err() || failed {
+                break;
+            }
+        }
+    });
+    let stream = async_stream::stream! {
+        while let Some(chunk) = rx.recv().await {
+            yield chunk;
+        }
+    };
     let body = Body::from_stream(stream);
 
     let safe_filename: String = filename
@@ -265,8 +326,15 @@ pub async fn create_incremental_backup(
     Path(db): Path<String>,
 ) -> Result<Json<types::BackupEntry>, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
-    let entry = BackupService::backup_incremental(state.databases(), &db, &backup_dir).await?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let entry = BackupService::backup_incremental_to(
+        state.databases(),
+        target.as_ref(),
+        &db,
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
     Ok(Json(entry))
 }
 
@@ -296,20 +364,298 @@ pub async fn restore_to_epoch(
     Json(req): Json<types::RestoreToEpochRequest>,
 ) -> Result<impl IntoResponse, ApiError> {
     auth.check_admin()?;
-    let backup_dir = require_backup_dir(&state)?;
-    BackupService::restore_to_epoch(state.databases(), &db, req.epoch, &backup_dir).await?;
+    let (backup_dir, target) = require_backup(&state)?;
+    BackupService::restore_to_epoch_from(
+        state.databases(),
+        target.as_ref(),
+        &db,
+        req.epoch,
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
     Ok(axum::http::StatusCode::NO_CONTENT)
 }
 
-fn require_backup_dir(state: &AppState) -> Result<std::path::PathBuf, ApiError> {
+/// Restore a backup into a new database.
+///
+/// Materializes a backup of `{db}`, or its backup chain up to an epoch, as
+/// a newly created database, in-memory or persistent. `{db}` is left
+/// untouched and need no longer exist, so old data can be queried next to
+/// the live database.
+#[utoipa::path(
+    post,
+    path = "/admin/{db}/restore/as",
+    params(
+        ("db" = String, Path, description = "Database whose backups to restore"),
+    ),
+    request_body = types::RestoreAsRequest,
+    responses(
+        (status = 200, description = "Database created from the backup", body = types::DatabaseSummary),
+        (status = 400, description = "Invalid request", body = crate::error::ErrorBody),
+        (status = 403, description = "Server is read-only", body = crate::error::ErrorBody),
+        (status = 404, description = "Backup not found", body = crate::error::ErrorBody),
+        (status = 409, description = "Database already exists", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn restore_as_new(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(db): Path<String>,
+    Json(req): Json<types::RestoreAsRequest>,
+) -> Result<Json<types::DatabaseSummary>, ApiError> {
+    auth.check_admin()?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let summary = BackupService::restore_as_new(
+        state.databases(),
+        target.as_ref(),
+        &db,
+        &req,
+        &backup_dir,
+        state.backup_keys(),
+    )
+    .await?;
+    Ok(Json(summary))
+}
+
+/// Verify a backup without restoring it.
+///
+/// Checks the file against the manifest checksum and walks its
+/// full + incremental chain for missing, corrupt or non-contiguous segments.
+/// With `{ "open": true }` the backup is also loaded into a scratch
+/// in-memory database to count its nodes and edges. The result is recorded
+/// and shown in backup listings; failed checks are reported in it rather
+/// than as an error status.
+#[utoipa::path(
+    post,
+    path = "/admin/{db}/backups/{filename}/verify",
+    params(
+        ("db" = String, Path, description = "Database name"),
+        ("filename" = String, Path, description = "Backup filename"),
+    ),
+    request_body = types::VerifyBackupRequest,
+    responses(
+        (status = 200, description = "Verification result", body = types::BackupVerification),
+        (status = 400, description = "Bad request", body = crate::error::ErrorBody),
+        (status = 404, description = "Backup not found", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn verify_backup(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path((db, filename)): Path<(String, String)>,
+    body: Option<Json<types::VerifyBackupRequest>>,
+) -> Result<Json<types::BackupVerification>, ApiError> {
+    auth.check_admin()?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let Json(req) = body.unwrap_or_default();
+    let verification = BackupService::verify_backup(
+        target.as_ref(),
+        &db,
+        &filename,
+        &backup_dir,
+        req.open,
+        state.backup_keys(),
+    )
+    .await?;
+    Ok(Json(verification))
+}
+
+/// Verify every backup, optionally of one database only.
+///
+/// Runs the checks of `POST /admin/{db}/backups/{filename}/verify` on each
+/// listed backup in turn and returns all results.
+#[utoipa::path(
+    post,
+    path = "/backups/verify",
+    request_body = types::VerifyBackupsRequest,
+    responses(
+        (status = 200, description = "Verification results", body = Vec<types::BackupVerification>),
+        (status = 400, description = "Backup not configured", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn verify_all_backups(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    body: Option<Json<types::VerifyBackupsRequest>>,
+) -> Result<Json<Vec<types::BackupVerification>>, ApiError> {
+    auth.check_admin()?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let Json(req) = body.unwrap_or_default();
+    let results = BackupService::verify_backups(
+        target.as_ref(),
+        req.database.as_deref(),
+        &backup_dir,
+        req.open,
+        state.backup_keys(),
+    )
+    .await?;
+    Ok(Json(results))
+}
+
+/// Re-encrypt backups with the active backup key.
+///
+/// Rotates the backup encryption key: every backup that is unencrypted or
+/// encrypted with an older key, optionally of one database only, is
+/// re-encrypted with the first configured key, along with the label
+/// sidecars. The older keys must stay configured until this has run.
+#[utoipa::path(
+    post,
+    path = "/backups/rekey",
+    request_body = types::RekeyBackupsRequest,
+    responses(
+        (status = 200, description = "Re-encrypted backups", body = Vec<types::RekeyedBackup>),
+        (status = 400, description = "Backup or encryption not configured", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn rekey_backups(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    body: Option<Json<types::RekeyBackupsRequest>>,
+) -> Result<Json<Vec<types::RekeyedBackup>>, ApiError> {
+    auth.check_admin()?;
+    let (backup_dir, target) = require_backup(&state)?;
+    let keys = state.backup_keys().ok_or_else(|| {
+        grafeo_service::error::ServiceError::BadRequest(
+            "backup encryption not configured: start server with --backup-encryption-key-file"
+                .to_string(),
+        )
+    })?;
+    let Json(req) = body.unwrap_or_default();
+    let rekeyed =
+        BackupService::rekey_backups(target.as_ref(), req.database.as_deref(), &backup_dir, keys)
+            .await?;
+    Ok(Json(rekeyed))
+}
+
+/// Get the backup schedule of a database.
+///
+/// Returns the schedule with the next due times and the most recent runs.
+#[utoipa::path(
+    get,
+    path = "/admin/{db}/backup/schedule",
+    params(
+        ("db" = String, Path, description = "Database name"),
+    ),
+    responses(
+        (status = 200, description = "Backup schedule", body = types::BackupScheduleStatus),
+        (status = 400, description = "Backup not configured", body = crate::error::ErrorBody),
+        (status = 404, description = "No schedule for this database", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn get_backup_schedule(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(db): Path<String>,
+) -> Result<Json<types::BackupScheduleStatus>, ApiError> {
+    auth.check_admin()?;
+    let scheduler = require_scheduler(&state)?;
+    Ok(Json(scheduler.get(&db)?))
+}
+
+/// Create or replace the backup schedule of a database.
+///
+/// Full and incremental backups then run in the background at the given
+/// intervals, followed by the schedule's retention. The schedule is saved
+/// in the backup directory and survives restarts.
+#[utoipa::path(
+    put,
+    path = "/admin/{db}/backup/schedule",
+    params(
+        ("db" = String, Path, description = "Database name"),
+    ),
+    request_body = types::BackupSchedule,
+    responses(
+        (status = 200, description = "Backup schedule set", body = types::BackupScheduleStatus),
+        (status = 400, description = "Backup not configured or invalid schedule", body = crate::error::ErrorBody),
+        (status = 404, description = "Database not found", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn set_backup_schedule(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(db): Path<String>,
+    Json(req): Json<types::BackupSchedule>,
+) -> Result<Json<types::BackupScheduleStatus>, ApiError> {
+    auth.check_admin()?;
+    let scheduler = require_scheduler(&state)?;
+    Ok(Json(scheduler.set(state.databases(), &db, req)?))
+}
+
+/// Remove the backup schedule of a database.
+///
+/// Existing backups are kept.
+#[utoipa::path(
+    delete,
+    path = "/admin/{db}/backup/schedule",
+    params(
+        ("db" = String, Path, description = "Database name"),
+    ),
+    responses(
+        (status = 200, description = "Backup schedule removed"),
+        (status = 400, description = "Backup not configured", body = crate::error::ErrorBody),
+        (status = 404, description = "No schedule for this database", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn delete_backup_schedule(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(db): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    auth.check_admin()?;
+    let scheduler = require_scheduler(&state)?;
+    scheduler.remove(&db)?;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// List the backup schedules of all databases.
+#[utoipa::path(
+    get,
+    path = "/backups/schedules",
+    responses(
+        (status = 200, description = "All backup schedules", body = Vec<types::BackupScheduleStatus>),
+        (status = 400, description = "Backup not configured", body = crate::error::ErrorBody),
+    ),
+    tag = "Admin"
+)]
+pub async fn list_backup_schedules(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<Vec<types::BackupScheduleStatus>>, ApiError> {
+    auth.check_admin()?;
+    let scheduler = require_scheduler(&state)?;
+    Ok(Json(scheduler.list()))
+}
+
+fn require_scheduler(state: &AppState) -> Result<&BackupScheduler, ApiError> {
+    state.backup_scheduler().ok_or_else(|| {
+        grafeo_service::error::ServiceError::BadRequest(
+            "backup not configured: start server with --backup-dir".to_string(),
+        )
+        .into()
+    })
+}
+
+/// Returns the backup directory and the target backups are stored at.
+fn require_backup(
+    state: &AppState,
+) -> Result<(std::path::PathBuf, Arc<dyn BackupTarget>), ApiError> {
     state
         .backup_dir()
+        .zip(state.backup_target())
         .ok_or_else(|| {
             grafeo_service::error::ServiceError::BadRequest(
                 "backup not configured: start server with --backup-dir".to_string(),
             )
         })
-        .map(|p| p.to_path_buf())
+        .map(|(dir, target)| (dir.to_path_buf(), Arc::clone(target)))
         .map_err(Into::into)
 }
 
@@ -352,6 +698,20 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[tokio::test]
+    async fn backup_schedule_no_backup_dir() {
+        let resp = app()
+            .oneshot(
+                Request::put("/admin/default/backup/schedule")
+                    .header("content-type", "application/json")
+                    .body(Body::from(r#"{"full_interval_secs": 3600}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[tokio::test]
     async fn incremental_backup_database_not_found() {
         let resp = app()
@@ -5,7 +5,7 @@
 use axum::extract::{Json, State};
 
 use grafeo_service::query::QueryService;
-use grafeo_service::types::BatchQuery;
+use grafeo_service::types::{BatchQuery, QueryTransport};
 
 use crate::encode::{convert_json_params, query_result_to_response};
 use crate::error::{ApiError, ErrorBody};
@@ -68,6 +68,14 @@ pub async fn batch_query(
         .collect::<Result<Vec<_>, ApiError>>()?;
 
     let identity = auth.identity(state.service().is_query_read_only());
+    let statements: Vec<&str> = req.queries.iter().map(|q| q.query.as_str()).collect();
+    let running = state.running_queries().register(
+        db_name,
+        Some("batch"),
+        &statements.join(";\n"),
+        auth.user(),
+        QueryTransport::Http,
+    );
 
     let results = QueryService::batch_execute(
         state.databases(),
@@ -77,6 +85,7 @@ pub async fn batch_query(
         timeout,
         state.service().is_query_read_only(),
         Some(identity),
+        running.cancel_token(),
     )
     .await?;
 
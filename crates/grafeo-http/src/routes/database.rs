@@ -104,6 +104,10 @@ pub async fn delete_database(
     // Clean up any transaction sessions belonging to this database
     state.sessions().remove_by_database(&name);
     state.databases().delete(&name)?;
+    // Its backups are kept, but there is nothing left to schedule
+    if let Some(scheduler) = state.backup_scheduler() {
+        let _ = scheduler.remove(&name);
+    }
     Ok(Json(serde_json::json!({ "deleted": name })))
 }
 
@@ -18,7 +18,11 @@ use axum::extract::{Path, Query, State};
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 
+use grafeo_engine::auth::Identity;
+use grafeo_engine::database::QueryResult;
+use grafeo_service::error::ServiceError;
 use grafeo_service::query::QueryService;
+use grafeo_service::types::QueryTransport;
 
 use crate::error::ApiError;
 use crate::middleware::auth_context::AuthContext;
@@ -57,6 +61,36 @@ fn resolve_target(params: &GraphStoreParams) -> Result<GraphTarget, ApiError> {
     }
 }
 
+/// Runs a generated SPARQL statement, registered as a running query.
+async fn run_sparql(
+    state: &AppState,
+    auth: &AuthContext,
+    db_name: &str,
+    sparql: &str,
+    identity: Identity,
+) -> Result<QueryResult, ServiceError> {
+    let running = state.running_queries().register(
+        db_name,
+        Some("sparql"),
+        sparql,
+        auth.user(),
+        QueryTransport::Http,
+    );
+    QueryService::execute(
+        state.databases(),
+        state.metrics(),
+        db_name,
+        sparql,
+        Some("sparql"),
+        None,
+        state.effective_timeout(None),
+        state.service().is_query_read_only(),
+        Some(identity),
+        running.cancel_token(),
+    )
+    .await
+}
+
 // ---------------------------------------------------------------------------
 // Content negotiation
 // ---------------------------------------------------------------------------
@@ -88,21 +122,9 @@ pub async fn graph_store_get(
         }
     };
 
-    let timeout = state.effective_timeout(None);
     let identity = auth.identity(state.service().is_query_read_only());
 
-    let result = QueryService::execute(
-        state.databases(),
-        state.metrics(),
-        &db_name,
-        &sparql,
-        Some("sparql"),
-        None,
-        timeout,
-        state.service().is_query_read_only(),
-        Some(identity),
-    )
-    .await?;
+    let result = run_sparql(&state, &auth, &db_name, &sparql, identity).await?;
 
     // CONSTRUCT results come back as rows with columns [subject, predicate, object].
     // Serialize as N-Triples.
@@ -146,22 +168,10 @@ pub async fn graph_store_head(
         }
     };
 
-    let timeout = state.effective_timeout(None);
     let identity = auth.identity(state.service().is_query_read_only());
 
     // If the query succeeds, the graph exists (or is the default graph).
-    let result = QueryService::execute(
-        state.databases(),
-        state.metrics(),
-        &db_name,
-        &sparql,
-        Some("sparql"),
-        None,
-        timeout,
-        state.service().is_query_read_only(),
-        Some(identity),
-    )
-    .await?;
+    let result = run_sparql(&state, &auth, &db_name, &sparql, identity).await?;
 
     // For default graph, always 200. For named, check if ASK returned true.
     match target {
@@ -215,38 +225,15 @@ pub async fn graph_store_put(
         GraphTarget::Named(ref iri) => format!("DROP SILENT GRAPH <{iri}>"),
     };
 
-    let timeout = state.effective_timeout(None);
     let read_only = state.service().is_query_read_only();
     let identity = auth.identity(read_only);
 
-    QueryService::execute(
-        state.databases(),
-        state.metrics(),
-        &db_name,
-        &drop_sparql,
-        Some("sparql"),
-        None,
-        timeout,
-        read_only,
-        Some(identity.clone()),
-    )
-    .await?;
+    run_sparql(&state, &auth, &db_name, &drop_sparql, identity.clone()).await?;
 
     // Insert new content.
     if !parsed.lines.is_empty() {
         let insert_sparql = build_insert_data(&target, &parsed);
-        QueryService::execute(
-            state.databases(),
-            state.metrics(),
-            &db_name,
-            &insert_sparql,
-            Some("sparql"),
-            None,
-            timeout,
-            read_only,
-            Some(identity),
-        )
-        .await?;
+        run_sparql(&state, &auth, &db_name, &insert_sparql, identity).await?;
     }
 
     Ok(StatusCode::NO_CONTENT.into_response())
@@ -277,36 +264,13 @@ pub async fn graph_store_post(
     if params.default.is_none() && params.graph.is_none() {
         let graph_iri = format!("urn:grafeo:graph:{}", uuid::Uuid::new_v4());
         let create_sparql = format!("CREATE GRAPH <{graph_iri}>");
-        let timeout = state.effective_timeout(None);
-
-        QueryService::execute(
-            state.databases(),
-            state.metrics(),
-            &db_name,
-            &create_sparql,
-            Some("sparql"),
-            None,
-            timeout,
-            read_only,
-            Some(identity.clone()),
-        )
-        .await?;
+
+        run_sparql(&state, &auth, &db_name, &create_sparql, identity.clone()).await?;
 
         if !parsed.lines.is_empty() {
             let target = GraphTarget::Named(graph_iri.clone());
             let insert_sparql = build_insert_data(&target, &parsed);
-            QueryService::execute(
-                state.databases(),
-                state.metrics(),
-                &db_name,
-                &insert_sparql,
-                Some("sparql"),
-                None,
-                timeout,
-                read_only,
-                Some(identity),
-            )
-            .await?;
+            run_sparql(&state, &auth, &db_name, &insert_sparql, identity).await?;
         }
 
         return Ok(Response::builder()
@@ -323,19 +287,7 @@ pub async fn graph_store_post(
 
     if !parsed.lines.is_empty() {
         let insert_sparql = build_insert_data(&target, &parsed);
-        let timeout = state.effective_timeout(None);
-        QueryService::execute(
-            state.databases(),
-            state.metrics(),
-            &db_name,
-            &insert_sparql,
-            Some("sparql"),
-            None,
-            timeout,
-            read_only,
-            Some(identity),
-        )
-        .await?;
+        run_sparql(&state, &auth, &db_name, &insert_sparql, identity).await?;
     }
 
     Ok(StatusCode::NO_CONTENT.into_response())
@@ -360,21 +312,9 @@ pub async fn graph_store_delete(
         GraphTarget::Named(ref iri) => format!("DROP GRAPH <{iri}>"),
     };
 
-    let timeout = state.effective_timeout(None);
     let identity = auth.identity(state.service().is_query_read_only());
 
-    let result = QueryService::execute(
-        state.databases(),
-        state.metrics(),
-        &db_name,
-        &sparql,
-        Some("sparql"),
-        None,
-        timeout,
-        state.service().is_query_read_only(),
-        Some(identity),
-    )
-    .await;
+    let result = run_sparql(&state, &auth, &db_name, &sparql, identity).await;
 
     match result {
         Ok(_) => Ok(StatusCode::NO_CONTENT.into_response()),
@@ -9,6 +9,7 @@ use axum::response::Response;
 use grafeo_engine::database::QueryResult;
 
 use grafeo_service::query::QueryService;
+use grafeo_service::types::QueryTransport;
 
 use crate::encode::{convert_json_params, streaming_json_response};
 use crate::error::{ApiError, ErrorBody};
@@ -78,6 +79,13 @@ async fn execute_query(
     let timeout = state.effective_timeout(req.timeout_ms);
 
     let identity = auth.identity(state.service().is_query_read_only());
+    let running = state.running_queries().register(
+        db_name,
+        language,
+        &req.query,
+        auth.user(),
+        QueryTransport::Http,
+    );
 
     let result = QueryService::execute(
         state.databases(),
@@ -89,6 +97,7 @@ async fn execute_query(
         timeout,
         state.service().is_query_read_only(),
         Some(identity),
+        running.cancel_token(),
     )
     .await?;
 
@@ -1,16 +1,49 @@
-//! Replication status endpoint.
+//! Replication endpoints.
 //!
 //! `GET /admin/replication` returns the current replication mode and
 //! per-database epoch lag.  Always available (returns `{mode: "standalone"}`
 //! on instances that have not been configured for replication).
+//!
+//! `GET /admin/replication/catalog` lists the databases a replica should
+//! mirror, with their creation settings.
+//!
+//! `GET /db/{name}/replication/stream?since=<epoch>` is the push feed that
+//! replicas follow: a Server-Sent Events stream of `ChangeBatch`es.
+//!
+//! `GET /db/{name}/replication/snapshot` serves a consistent snapshot that
+//! an empty replica restores before it starts following the feed.
+//!
+//! `POST /admin/replication/promote` makes a replica the primary, and
+//! `POST /admin/replication/follow` points a node at a new primary.
+
+use std::convert::Infallible;
 
 use axum::Json;
-use axum::extract::State;
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, header};
+use axum::response::IntoResponse;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::StreamExt;
+use tokio::sync::broadcast::error::RecvError;
 
-use grafeo_service::replication::ReplicationStatus;
+use grafeo_service::backup::BackupService;
+use grafeo_service::changefeed::ChangeBatch;
+use grafeo_service::error::ServiceError;
+use grafeo_service::replication::{self, CatalogEntry, ReplicationStatus};
+use grafeo_service::sync::{ChangesResponse, SyncService};
 
 use crate::AppState;
 use crate::error::ApiError;
+use crate::middleware::auth_context::AuthContext;
+use crate::replication_task;
+use crate::routes::sync::ChangesQuery;
+
+/// Events per catch-up batch sent before switching to live batches.
+const CATCH_UP_LIMIT: usize = 500;
+
+/// Response header carrying the epoch a replication snapshot reflects.
+pub const SNAPSHOT_EPOCH_HEADER: &str = "x-grafeo-snapshot-epoch";
 
 /// `GET /admin/replication`
 ///
@@ -21,6 +54,271 @@ pub async fn get_replication_status(
     let status = state
         .service()
         .replication_state()
-        .status(state.service().replication_mode());
+        .status(&state.service().replication_mode());
     Ok(Json(status))
 }
+
+/// `POST /admin/replication/promote`
+///
+/// Turns this replica into the primary under a new replication term: the
+/// replication task stops following, CDC is enabled and writes are
+/// accepted. The old primary, if reachable, is told about the new term so
+/// that it stops accepting writes. Returns the new replication status.
+pub async fn promote(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    headers: HeaderMap,
+) -> Result<Json<ReplicationStatus>, ApiError> {
+    auth.check_admin()?;
+    let service = state.service();
+    let old_primary = service.replication_mode().primary_url().map(str::to_string);
+    let term = service.promote()?;
+
+    if let Some(url) = old_primary {
+        let headers = credentials(&headers);
+        tokio::spawn(async move {
+            if let Err(e) = replication_task::fetch_status(&url, term, headers).await {
+                tracing::warn!(primary_url = %url, error = %e, "Could not fence the old primary");
+            }
+        });
+    }
+
+    Ok(Json(
+        service
+            .replication_state()
+            .status(&service.replication_mode()),
+    ))
+}
+
+/// Body of `POST /admin/replication/follow`.
+#[derive(Debug, serde::Deserialize)]
+pub struct FollowRequest {
+    /// Base URL of the new primary.
+    pub primary_url: String,
+}
+
+/// `POST /admin/replication/follow`
+///
+/// Makes this node a replica of `primary_url`, demoting it if it is a
+/// primary. The target must be an unfenced primary at least as recent as
+/// any term this node knows (409 otherwise, 503 if unreachable). Open
+/// transactions are discarded and every database is restored from the new
+/// primary before it is followed. Returns the new replication status.
+pub async fn follow(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    headers: HeaderMap,
+    Json(req): Json<FollowRequest>,
+) -> Result<Json<ReplicationStatus>, ApiError> {
+    auth.check_admin()?;
+    let service = state.service();
+    let primary_url = req.primary_url.trim_end_matches('/').to_string();
+    if primary_url.is_empty() {
+        return Err(ServiceError::BadRequest("primary_url is required".to_string()).into());
+    }
+
+    let own_term = service.replication_state().term();
+    let target = replication_task::fetch_status(&primary_url, own_term, credentials(&headers))
+        .await
+        .map_err(|e| ServiceError::Unavailable(format!("primary unreachable: {e}")))?;
+    if target.mode != "primary" || target.fenced {
+        return Err(
+            ServiceError::Conflict(format!("{primary_url} is not an active primary")).into(),
+        );
+    }
+
+    service.follow(primary_url, target.term)?;
+    replication_task::start(service.clone());
+
+    Ok(Json(
+        service
+            .replication_state()
+            .status(&service.replication_mode()),
+    ))
+}
+
+/// The caller's credentials, passed on to the other node.
+fn credentials(headers: &HeaderMap) -> reqwest::header::HeaderMap {
+    let mut out = reqwest::header::HeaderMap::new();
+    for name in [
+        header::AUTHORIZATION,
+        header::HeaderName::from_static("x-api-key"),
+    ] {
+        if let Some(value) = headers.get(&name) {
+            out.insert(name, value.clone());
+        }
+    }
+    out
+}
+
+/// `GET /admin/replication/catalog`
+///
+/// Lists every database the caller may access, with the type, storage mode
+/// and options a replica needs to create a matching one. Requires admin.
+pub async fn get_replication_catalog(
+    State(state): State<AppState>,
+    auth: AuthContext,
+) -> Result<Json<Vec<CatalogEntry>>, ApiError> {
+    auth.check_admin()?;
+    let mut catalog = replication::catalog(state.databases());
+    if let Some(ref info) = auth.0 {
+        let scope = &info.scope.databases;
+        if !scope.is_empty() {
+            catalog.retain(|db| scope.iter().any(|d| d == &db.name));
+        }
+    }
+    Ok(Json(catalog))
+}
+
+/// `GET /db/{name}/replication/stream?since=<epoch>`
+///
+/// Streams every change with `epoch >= since` as SSE `batch` events, each
+/// a JSON [`ChangeBatch`] covering a contiguous epoch range in full. Stored
+/// history is sent first in chunks, then batches are pushed as the primary
+/// commits them. If the subscriber falls behind the broadcast channel, a
+/// `gap` event (data: the next epoch it needs) is sent and the stream ends;
+/// the replica catches up by polling and resubscribes.
+pub async fn replication_stream(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(name): Path<String>,
+    Query(params): Query<ChangesQuery>,
+) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    auth.check_db_access(&name)?;
+    // Subscribe before reading history so nothing committed in between is
+    // missed; batches that overlap the history are trimmed below.
+    let mut receiver =
+        state
+            .change_hub()
+            .subscribe_batches(&name, params.since, state.service().clone());
+
+    // Fail fast (404, CDC disabled) before committing to a stream.
+    let mut chunk = pull_chunk(&state, &name, params.since).await?;
+
+    let stream = async_stream::stream! {
+        let mut next = params.since;
+
+        // Stored history, one complete chunk at a time.
+        while !chunk.changes.is_empty() {
+            let batch = ChangeBatch {
+                since: next,
+                server_epoch: chunk.server_epoch,
+                changes: chunk.changes,
+            };
+            next = batch.server_epoch + 1;
+            yield Ok(batch_event(&batch));
+
+            match pull_chunk(&state, &name, next).await {
+                Ok(resp) => chunk = resp,
+                Err(_) => return,
+            }
+        }
+
+        // Live batches.
+        loop {
+            match receiver.recv().await {
+                Ok(batch) if batch.server_epoch < next => {}
+                Ok(batch) if batch.since > next => {
+                    yield Ok(Event::default().event("gap").data(next.to_string()));
+                    break;
+                }
+                Ok(batch) => {
+                    let batch = if batch.since < next {
+                        ChangeBatch {
+                            since: next,
+                            server_epoch: batch.server_epoch,
+                            changes: batch
+                                .changes
+                                .iter()
+                                .filter(|c| c.epoch >= next)
+                                .cloned()
+                                .collect(),
+                        }
+                    } else {
+                        ChangeBatch::clone(&batch)
+                    };
+                    next = batch.server_epoch + 1;
+                    yield Ok(batch_event(&batch));
+                }
+                Err(RecvError::Lagged(n)) => {
+                    tracing::debug!("replication stream for '{name}' lagged by {n} batches");
+                    yield Ok(Event::default().event("gap").data(next.to_string()));
+                    break;
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Reads one complete chunk of stored history starting at `since`.
+async fn pull_chunk(state: &AppState, name: &str, since: u64) -> Result<ChangesResponse, ApiError> {
+    let state = state.clone();
+    let name = name.to_string();
+    tokio::task::spawn_blocking(move || {
+        SyncService::pull_complete(state.databases(), &name, since, CATCH_UP_LIMIT)
+    })
+    .await
+    .map_err(|e| ApiError::internal(e.to_string()))?
+    .map_err(ApiError::from)
+}
+
+fn batch_event(batch: &ChangeBatch) -> Event {
+    let json = serde_json::to_string(batch).unwrap_or_else(|_| "{}".to_string());
+    Event::default().event("batch").data(json)
+}
+
+/// `GET /db/{name}/replication/snapshot`
+///
+/// Streams a consistent `.grafeo` snapshot of the database, with the epoch
+/// it reflects in the `x-grafeo-snapshot-epoch` header. A replica restores
+/// it and then follows the stream from the next epoch. The snapshot is
+/// staged in the temp directory and removed once sent. Requires admin, as
+/// it exports the whole database.
+pub async fn replication_snapshot(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    auth.check_db_access(&name)?;
+    auth.check_admin()?;
+    let staged = StagedFile(std::env::temp_dir().join(format!(
+        "grafeo-replica-snapshot-{}.grafeo",
+        uuid::Uuid::new_v4()
+    )));
+    let epoch = BackupService::snapshot_database(state.databases(), &name, &staged.0).await?;
+
+    let file = tokio::fs::File::open(&staged.0)
+        .await
+        .map_err(|e| ApiError::internal(e.to_string()))?;
+
+    let stream = async_stream::stream! {
+        let mut chunks = tokio_util::io::ReaderStream::new(file);
+        while let Some(chunk) = chunks.next().await {
+            yield chunk;
+        }
+        // Close the file before the guard removes it.
+        drop(chunks);
+        drop(staged);
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+        (
+            header::HeaderName::from_static(SNAPSHOT_EPOCH_HEADER),
+            epoch.to_string(),
+        ),
+    ];
+    Ok((headers, Body::from_stream(stream)))
+}
+
+/// Removes a staged snapshot file when dropped.
+struct StagedFile(std::path::PathBuf);
+
+impl Drop for StagedFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
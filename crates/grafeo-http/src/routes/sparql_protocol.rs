@@ -10,6 +10,10 @@
 //! - `POST /db/{name}/sparql` with `Content-Type: application/x-www-form-urlencoded`
 //! - `POST /db/{name}/sparql` with `Content-Type: application/json` (backwards compat)
 //!
+//! Repeatable `default-graph-uri` and `named-graph-uri` parameters select the
+//! query's RDF dataset (see [`SparqlDataset`]); a query that also has its own
+//! `FROM` / `FROM NAMED` clauses is rejected with 400.
+//!
 //! Response format is negotiated via the `Accept` header:
 //! - `application/sparql-results+json` for SELECT/ASK (default)
 //! - `application/json` for Grafeo's native JSON format
@@ -22,6 +26,8 @@ use axum::http::{HeaderMap, StatusCode};
 use axum::response::Response;
 
 use grafeo_service::query::QueryService;
+use grafeo_service::sparql_dataset::SparqlDataset;
+use grafeo_service::types::QueryTransport;
 
 use crate::encode::{convert_json_params, streaming_json_response};
 use crate::encode_sparql::sparql_results_json_response;
@@ -48,43 +54,48 @@ const ACCEPT_NTRIPLES: &str = "application/n-triples";
 // GET /db/{name}/sparql?query=...
 // ---------------------------------------------------------------------------
 
-/// Query parameters for SPARQL GET requests.
-#[derive(serde::Deserialize)]
-pub struct SparqlGetParams {
-    /// URL-encoded SPARQL query string.
-    pub query: String,
-    /// Override default graph URI (repeatable, not yet enforced).
-    #[serde(rename = "default-graph-uri")]
-    pub default_graph_uri: Option<String>,
-    /// Named graph URI (repeatable, not yet enforced).
-    #[serde(rename = "named-graph-uri")]
-    pub named_graph_uri: Option<String>,
-}
-
 /// `GET /db/{name}/sparql?query=...`
 ///
 /// Executes a SPARQL query (read-only). Updates via GET return 400.
+/// Repeatable `default-graph-uri` / `named-graph-uri` parameters select the
+/// query's RDF dataset.
 pub async fn sparql_get(
     State(state): State<AppState>,
     Path(db_name): Path<String>,
-    Query(params): Query<SparqlGetParams>,
+    Query(params): Query<Vec<(String, String)>>,
     auth: AuthContext,
     headers: HeaderMap,
 ) -> Result<Response, ApiError> {
     auth.check_db_access(&db_name)?;
+    let query = params
+        .iter()
+        .find(|(k, _)| k == "query")
+        .map(|(_, v)| v.as_str())
+        .ok_or_else(|| ApiError::bad_request("missing 'query' parameter"))?;
+    let statement = dataset_from(&params).apply(query)?;
+
     let timeout = state.effective_timeout(None);
     let identity = auth.identity(state.service().is_query_read_only());
 
+    let running = state.running_queries().register(
+        &db_name,
+        Some("sparql"),
+        &statement,
+        auth.user(),
+        QueryTransport::Http,
+    );
+
     let result = QueryService::execute(
         state.databases(),
         state.metrics(),
         &db_name,
-        &params.query,
+        &statement,
         Some("sparql"),
         None,
         timeout,
         state.service().is_query_read_only(),
         Some(identity),
+        running.cancel_token(),
     )
     .await?;
 
@@ -102,9 +113,13 @@ pub async fn sparql_get(
 /// - `application/sparql-update`: body is a SPARQL update string
 /// - `application/x-www-form-urlencoded`: `query=...` or `update=...` parameter
 /// - `application/json`: Grafeo JSON format (backwards compat)
+///
+/// Dataset parameters (`default-graph-uri`, `named-graph-uri`) are read from
+/// the URL query string and, for form posts, from the form body.
 pub async fn sparql_post(
     State(state): State<AppState>,
     Path(db_name): Path<String>,
+    Query(url_params): Query<Vec<(String, String)>>,
     auth: AuthContext,
     headers: HeaderMap,
     body: Bytes,
@@ -126,18 +141,24 @@ pub async fn sparql_post(
     let identity = auth.identity(read_only);
 
     let statement = match base_ct {
-        CT_SPARQL_QUERY | CT_SPARQL_UPDATE => String::from_utf8(body.to_vec())
-            .map_err(|_| ApiError::bad_request("request body is not valid UTF-8"))?,
+        CT_SPARQL_QUERY | CT_SPARQL_UPDATE => {
+            let statement = String::from_utf8(body.to_vec())
+                .map_err(|_| ApiError::bad_request("request body is not valid UTF-8"))?;
+            dataset_from(&url_params).apply(&statement)?
+        }
         CT_FORM_URLENCODED => {
-            let form: Vec<(String, String)> = serde_urlencoded::from_bytes(&body)
+            let mut form: Vec<(String, String)> = serde_urlencoded::from_bytes(&body)
                 .map_err(|e| ApiError::bad_request(format!("invalid form encoding: {e}")))?;
 
-            form.iter()
+            let statement = form
+                .iter()
                 .find(|(k, _)| k == "query" || k == "update")
                 .map(|(_, v)| v.clone())
                 .ok_or_else(|| {
                     ApiError::bad_request("form body must contain 'query' or 'update' parameter")
-                })?
+                })?;
+            form.extend(url_params);
+            dataset_from(&form).apply(&statement)?
         }
         CT_JSON => {
             // Backwards-compatible JSON body.
@@ -146,17 +167,27 @@ pub async fn sparql_post(
 
             let params = convert_json_params(req.params.as_ref())?;
             let timeout = state.effective_timeout(req.timeout_ms);
+            let statement = dataset_from(&url_params).apply(&req.query)?;
+
+            let running = state.running_queries().register(
+                &db_name,
+                Some("sparql"),
+                &statement,
+                auth.user(),
+                QueryTransport::Http,
+            );
 
             let result = QueryService::execute(
                 state.databases(),
                 state.metrics(),
                 &db_name,
-                &req.query,
+                &statement,
                 Some("sparql"),
                 params,
                 timeout,
                 read_only,
                 Some(identity),
+                running.cancel_token(),
             )
             .await?;
 
@@ -173,6 +204,14 @@ pub async fn sparql_post(
 
     let timeout = state.effective_timeout(None);
 
+    let running = state.running_queries().register(
+        &db_name,
+        Some("sparql"),
+        &statement,
+        auth.user(),
+        QueryTransport::Http,
+    );
+
     let result = QueryService::execute(
         state.databases(),
         state.metrics(),
@@ -183,12 +222,18 @@ pub async fn sparql_post(
         timeout,
         read_only,
         Some(identity),
+        running.cancel_token(),
     )
     .await?;
 
     Ok(format_sparql_response(result, &headers))
 }
 
+/// Collects the protocol's RDF dataset from decoded query or form pairs.
+fn dataset_from(pairs: &[(String, String)]) -> SparqlDataset {
+    SparqlDataset::from_pairs(pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+}
+
 // ---------------------------------------------------------------------------
 // Response format negotiation
 // ---------------------------------------------------------------------------
@@ -589,4 +634,57 @@ mod tests {
             assert!(ct.contains("n-triples"), "ct: {ct}");
         }
     }
+
+    // -----------------------------------------------------------------------
+    // RDF dataset parameters
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn get_dataset_conflicting_with_from_is_rejected() {
+        let resp = app()
+            .oneshot(
+                Request::get(
+                    "/db/default/sparql?query=SELECT%20*%20FROM%20%3Chttp%3A%2F%2Fex.org%2Fg%3E%20WHERE%20%7B%20%3Fs%20%3Fp%20%3Fo%20%7D\
+                     &default-graph-uri=http%3A%2F%2Fex.org%2Fa",
+                )
+                .body(Body::empty())
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn post_form_dataset_conflicting_with_from_is_rejected() {
+        let resp = app()
+            .oneshot(
+                Request::post("/db/default/sparql")
+                    .header("content-type", "application/x-www-form-urlencoded")
+                    .body(Body::from(
+                        "query=SELECT+*+FROM+NAMED+%3Chttp%3A%2F%2Fex.org%2Fn%3E+%7B+%3Fs+%3Fp+%3Fo+%7D\
+                         &named-graph-uri=http%3A%2F%2Fex.org%2Fn",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn post_update_with_dataset_is_rejected() {
+        let resp = app()
+            .oneshot(
+                Request::post("/db/default/sparql?default-graph-uri=http%3A%2F%2Fex.org%2Fa")
+                    .header("content-type", "application/sparql-update")
+                    .body(Body::from(
+                        "INSERT DATA { <http://ex.org/s> <http://ex.org/p> \"o\" }",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
 }
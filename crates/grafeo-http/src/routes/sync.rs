@@ -2,19 +2,30 @@
 //!
 //! # Endpoints
 //!
-//! - `GET /db/{name}/changes?since=<epoch>&limit=<n>` — pull changefeed
-//! - `POST /db/{name}/sync` — push client changes with LWW conflict resolution
+//! - `GET /db/{name}/changes?since=<epoch>&limit=<n>&complete=<bool>` — pull
+//!   changefeed
+//! - `POST /db/{name}/sync` — push client changes, resolving conflicts with
+//!   the database's conflict policy
+//! - `GET`/`PUT /db/{name}/sync/policy` — read or set the conflict policy
+//! - `GET /db/{name}/sync/conflicts` — list logged conflicts
+//! - `DELETE /db/{name}/sync/conflicts/{id}` — dismiss a logged conflict
 //! - `GET /db/{name}/changes/stream` — SSE push stream (requires `push-changefeed`)
 //!
+//! Both changefeeds accept the filter parameters of [`ChangeFilterQuery`].
+//!
 //! Requires the `sync` feature (implies `cdc`).
 
 use axum::extract::{Path, Query, State};
 use axum::response::Json;
 use serde::Deserialize;
 
+use axum::http::StatusCode;
+use grafeo_service::change_filter::ChangeFilter;
+use grafeo_service::conflicts::{ConflictsResponse, SyncPolicy};
 use grafeo_service::sync::{ChangesResponse, SyncRequest, SyncResponse, SyncService};
 
 use crate::error::ApiError;
+use crate::middleware::auth_context::AuthContext;
 use crate::state::AppState;
 
 const MAX_LIMIT: usize = 10_000;
@@ -28,26 +39,82 @@ pub struct ChangesQuery {
     pub since: u64,
     /// Maximum number of events per response. Defaults to 1 000, max 10 000.
     pub limit: Option<usize>,
+    /// Never split an epoch across responses: an epoch with more than
+    /// `limit` events is returned whole, and `server_epoch` is the last
+    /// epoch returned in full.
+    #[serde(default)]
+    pub complete: bool,
+}
+
+/// Filter parameters of the changefeed endpoints, see [`ChangeFilter`].
+/// List parameters are comma-separated.
+#[derive(Debug, Default, Deserialize)]
+pub struct ChangeFilterQuery {
+    /// Entity types to include: `node`, `edge` or `triple`.
+    pub entity_types: Option<String>,
+    /// Only nodes with one of these labels (and edges of `edge_types`).
+    pub labels: Option<String>,
+    /// Only edges of one of these types (and nodes with `labels`).
+    pub edge_types: Option<String>,
+    /// Only changes that set or remove one of these property keys.
+    pub properties: Option<String>,
+    /// GQL boolean expression over `after`, e.g. `after.age >= 18`.
+    pub predicate: Option<String>,
+}
+
+impl ChangeFilterQuery {
+    /// Converts the parameters into a [`ChangeFilter`].
+    pub fn into_filter(self) -> ChangeFilter {
+        let list = |param: Option<String>| {
+            param
+                .iter()
+                .flat_map(|p| p.split(','))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_owned)
+                .collect()
+        };
+        ChangeFilter {
+            entity_types: list(self.entity_types),
+            labels: list(self.labels),
+            edge_types: list(self.edge_types),
+            properties: list(self.properties),
+            predicate: self.predicate.filter(|p| !p.trim().is_empty()),
+        }
+    }
 }
 
 /// Poll for change events since a given epoch.
 ///
 /// Returns all mutations (create, update, delete) for nodes and edges in the
-/// named database where the MVCC epoch is >= `since`.
+/// named database where the MVCC epoch is >= `since`, narrowed by the
+/// [`ChangeFilterQuery`] parameters. A `predicate` runs with the caller's
+/// permissions.
 ///
 /// Store `server_epoch` from the response and pass it as `since` on the next
 /// request. If `changes.len() == limit`, more events may be available: poll
-/// again using the epoch of the last returned event.
+/// again using the epoch of the last returned event. With `complete=true`
+/// no epoch is split, so poll again from `server_epoch + 1` instead.
 pub async fn db_changes(
     State(state): State<AppState>,
+    auth: AuthContext,
     Path(name): Path<String>,
     Query(params): Query<ChangesQuery>,
+    Query(filter): Query<ChangeFilterQuery>,
 ) -> Result<Json<ChangesResponse>, ApiError> {
+    auth.check_db_access(&name)?;
     let limit = params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
     let since = params.since;
+    let filter = filter.into_filter();
+    let identity = auth.identity(state.service().is_query_read_only());
 
     let result = tokio::task::spawn_blocking(move || {
-        SyncService::pull(state.databases(), &name, since, limit)
+        let dbs = state.databases();
+        if params.complete {
+            SyncService::pull_matching_complete(dbs, &name, since, limit, &filter, &identity)
+        } else {
+            SyncService::pull_matching(dbs, &name, since, limit, &filter, &identity)
+        }
     })
     .await
     .map_err(|e| ApiError::internal(e.to_string()))??;
@@ -57,12 +124,15 @@ pub async fn db_changes(
 
 /// Apply a client changeset to the named database.
 ///
-/// Accepts a JSON body with `{ client_id, last_seen_epoch, changes: [...] }`.
-/// Changes are applied in order with last-write-wins (LWW) conflict
-/// resolution: if the server has a more recent CDC timestamp for the target
-/// entity, the client change is skipped and recorded in `conflicts`.
+/// Accepts a JSON body with `{ client_id, last_seen_epoch, changes: [...], atomic }`.
+/// Changes are applied in order, in one transaction. A change that races a
+/// server change is resolved with the database's conflict policy, by
+/// default last-write-wins (LWW): if the server has a more recent CDC
+/// timestamp for the target entity, the client change is skipped. Conflicts
+/// are recorded in `conflicts` with the server's value and epoch. With
+/// `atomic: true`, a change that fails rolls back the whole changeset.
 ///
-/// Returns `{ server_epoch, applied, skipped, conflicts, id_mappings }`.
+/// Returns `{ server_epoch, applied, skipped, conflicts, id_mappings, rolled_back }`.
 /// The `id_mappings` array maps each create request (by index) to the
 /// server-assigned entity ID.
 pub async fn db_apply(
@@ -78,6 +148,84 @@ pub async fn db_apply(
     Ok(Json(result))
 }
 
+/// Query parameters for the conflict log endpoint.
+#[derive(Debug, Deserialize)]
+pub struct ConflictsQuery {
+    /// Return conflicts with `id >= since`. Defaults to 0 (the whole log).
+    #[serde(default)]
+    pub since: u64,
+    /// Maximum number of conflicts per response. Defaults to 1 000, max 10 000.
+    pub limit: Option<usize>,
+    /// Only return conflicts pushed by this client.
+    pub client_id: Option<String>,
+}
+
+/// Return the sync conflict policy of the named database.
+pub async fn get_policy(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(name): Path<String>,
+) -> Result<Json<SyncPolicy>, ApiError> {
+    auth.check_db_access(&name)?;
+    Ok(Json(SyncService::policy(state.databases(), &name)?))
+}
+
+/// Set the sync conflict policy of the named database.
+///
+/// Accepts `{ policy, fields, log_conflicts }`; `policy` is one of `lww`
+/// (default), `server_wins`, `client_wins`, `reject` and `field_merge`,
+/// and `fields` maps property names to the policy `field_merge` applies
+/// to them. Requires admin access.
+pub async fn put_policy(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(name): Path<String>,
+    Json(policy): Json<SyncPolicy>,
+) -> Result<Json<SyncPolicy>, ApiError> {
+    auth.check_admin()?;
+    auth.check_db_access(&name)?;
+    Ok(Json(SyncService::set_policy(
+        state.databases(),
+        &name,
+        policy,
+    )?))
+}
+
+/// List the conflicts logged for the named database, oldest first.
+///
+/// Conflicts are logged while the policy's `log_conflicts` is set. Each
+/// entry carries the client's change and the server's value, so an app can
+/// let a user resolve it and then dismiss it with
+/// `DELETE /db/{name}/sync/conflicts/{id}`.
+pub async fn list_conflicts(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path(name): Path<String>,
+    Query(params): Query<ConflictsQuery>,
+) -> Result<Json<ConflictsResponse>, ApiError> {
+    auth.check_db_access(&name)?;
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    Ok(Json(SyncService::conflicts(
+        state.databases(),
+        &name,
+        params.since,
+        limit,
+        params.client_id.as_deref(),
+    )?))
+}
+
+/// Remove a conflict from the named database's conflict log.
+pub async fn dismiss_conflict(
+    State(state): State<AppState>,
+    auth: AuthContext,
+    Path((name, id)): Path<(String, u64)>,
+) -> Result<StatusCode, ApiError> {
+    auth.check_write()?;
+    auth.check_db_access(&name)?;
+    SyncService::dismiss_conflict(state.databases(), &name, id)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // ---------------------------------------------------------------------------
 // SSE push stream (requires `push-changefeed` feature)
 // ---------------------------------------------------------------------------
@@ -88,71 +236,67 @@ mod sse {
 
     use axum::extract::{Path, Query, State};
     use axum::response::sse::{Event, KeepAlive, Sse};
-    use tokio::sync::broadcast::error::RecvError;
 
-    use grafeo_service::sync::SyncService;
+    use grafeo_service::changefeed::ChangeFeedItem;
 
     use crate::error::ApiError;
-    use crate::routes::sync::ChangesQuery;
+    use crate::middleware::auth_context::AuthContext;
+    use crate::routes::sync::{ChangeFilterQuery, ChangesQuery};
     use crate::state::AppState;
 
     /// Server-Sent Events stream of change events for the named database.
     ///
     /// The client receives all historical events since `since` first, then
-    /// live events as they are committed. The stream stays open until the
+    /// live events as they are committed, both narrowed by the
+    /// [`ChangeFilterQuery`] parameters. The stream stays open until the
     /// client disconnects.
     ///
     /// Events are newline-delimited JSON objects in the `data:` field of each
-    /// SSE event, matching the `ChangeEventDto` schema.
+    /// SSE event, matching the `ChangeEventDto` schema. If the client falls
+    /// behind the live channel, a `gap` event (data: `{"last_epoch": N}`,
+    /// the epoch of the last event sent) is sent, followed by the dropped
+    /// events read back from the CDC log, so no event is lost.
     ///
     /// The `limit` query parameter is ignored for the streaming endpoint.
     pub async fn db_changes_stream(
         State(state): State<AppState>,
+        auth: AuthContext,
         Path(name): Path<String>,
         Query(params): Query<ChangesQuery>,
+        Query(filter): Query<ChangeFilterQuery>,
     ) -> Result<Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>>, ApiError> {
-        let since = params.since;
-
-        // Pull historical events up to the current epoch.
-        let historical = {
-            let name_clone = name.clone();
-            let state_clone = state.clone();
-            tokio::task::spawn_blocking(move || {
-                SyncService::pull(state_clone.databases(), &name_clone, since, 10_000)
-            })
-            .await
-            .map_err(|e| ApiError::internal(e.to_string()))??
-        };
-
-        let live_since = historical.server_epoch;
+        auth.check_db_access(&name)?;
 
-        // Subscribe to live events from the hub.
-        let receiver = state
+        // Fails fast (404, CDC disabled, invalid filter) before committing
+        // to a stream.
+        let mut subscription = state
             .change_hub()
-            .subscribe(&name, live_since, state.service().clone());
+            .subscribe_from(
+                &name,
+                params.since,
+                filter.into_filter(),
+                auth.identity(state.service().is_query_read_only()),
+                state.service().clone(),
+            )
+            .await?;
 
         let stream = async_stream::stream! {
-            // Yield historical events first.
-            for event in historical.changes {
-                let json = serde_json::to_string(&event)
-                    .unwrap_or_else(|_| "{}".to_string());
-                yield Ok(Event::default().data(json));
-            }
-
-            // Then stream live events.
-            let mut receiver = receiver;
-            loop {
-                match receiver.recv().await {
-                    Ok(event) => {
+            while let Some(item) = subscription.next().await {
+                match item {
+                    Ok(ChangeFeedItem::Change(event)) => {
                         let json = serde_json::to_string(&event)
                             .unwrap_or_else(|_| "{}".to_string());
                         yield Ok(Event::default().data(json));
                     }
-                    Err(RecvError::Lagged(n)) => {
-                        tracing::debug!("SSE receiver lagged by {n} events");
-                        // Continue — the client will see the next available event.
+                    Ok(ChangeFeedItem::Gap(gap)) => {
+                        let json = serde_json::to_string(&gap)
+                            .unwrap_or_else(|_| "{}".to_string());
+                        yield Ok(Event::default().event("gap").data(json));
+                    }
+                    Err(e) => {
+                        tracing::debug!("SSE changefeed for '{name}' ended: {e}");
+                        break;
                     }
-                    Err(RecvError::Closed) => break,
                 }
             }
         };
@@ -36,7 +36,8 @@ pub async fn health(State(state): State<AppState>) -> impl IntoResponse {
 ///
 /// Returns 200 when the server is ready to accept requests (default database
 /// is accessible). Returns 503 when the server is starting up, mid-restore,
-/// or otherwise unable to serve queries.
+/// or otherwise unable to serve queries, and on a replica whose lag exceeds
+/// `--replica-max-lag`.
 pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
     // Check that the default database is accessible
     let dbs = state.databases();
@@ -50,6 +51,23 @@ pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
         );
     }
 
+    #[cfg(feature = "replication")]
+    if let Some(max_lag) = state.service().replica_max_lag()
+        && state.service().is_replica()
+    {
+        let lagging = state.service().replication_state().lagging(max_lag);
+        if !lagging.is_empty() {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({
+                    "status": "not_ready",
+                    "reason": "replica lag exceeds threshold",
+                    "lagging": lagging,
+                })),
+            );
+        }
+    }
+
     (
         StatusCode::OK,
         Json(serde_json::json!({
@@ -135,6 +153,17 @@ pub async fn metrics_endpoint(State(state): State<AppState>) -> impl IntoRespons
     let nodes_total: usize = db_list.iter().map(|d| d.node_count).sum();
     let edges_total: usize = db_list.iter().map(|d| d.edge_count).sum();
     let engine_metrics = dbs.engine_prometheus_metrics();
+    #[cfg(feature = "replication")]
+    let replica_lag = state
+        .service()
+        .replication_state()
+        .lag_metrics(&state.service().replication_mode());
+    #[cfg(not(feature = "replication"))]
+    let replica_lag = Vec::new();
+    let backups = state
+        .backup_scheduler()
+        .map(|s| s.metrics())
+        .unwrap_or_default();
 
     let body = state.metrics().render(
         db_list.len(),
@@ -142,6 +171,8 @@ pub async fn metrics_endpoint(State(state): State<AppState>) -> impl IntoRespons
         edges_total,
         state.sessions().active_count(),
         state.uptime_secs(),
+        &replica_lag,
+        &backups,
         engine_metrics.as_deref(),
     );
 
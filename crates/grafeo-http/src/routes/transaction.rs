@@ -7,6 +7,7 @@ use axum::http::HeaderMap;
 use axum::response::Response;
 
 use grafeo_service::query::QueryService;
+use grafeo_service::types::QueryTransport;
 
 use crate::encode::{convert_json_params, streaming_json_response};
 use crate::error::{ApiError, ErrorBody};
@@ -95,6 +96,14 @@ pub async fn tx_query(
     let caller_token_id = auth.0.as_ref().map(|info| info.id.as_str());
     let params = convert_json_params(req.params.as_ref())?;
     let timeout = state.effective_timeout(req.timeout_ms);
+    let db_name = state.sessions().db_name(&session_id).unwrap_or_default();
+    let running = state.running_queries().register(
+        &db_name,
+        req.language.as_deref(),
+        &req.query,
+        auth.user(),
+        QueryTransport::Http,
+    );
 
     let result = QueryService::tx_execute(
         state.sessions(),
@@ -106,6 +115,7 @@ pub async fn tx_query(
         params,
         timeout,
         caller_token_id,
+        running.cancel_token(),
     )
     .await?;
 
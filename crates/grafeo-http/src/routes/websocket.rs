@@ -9,6 +9,12 @@
 //!
 //! Multiple subscriptions may be active on the same connection simultaneously.
 //! Each subscription is identified by a client-assigned `sub_id`.
+//!
+//! While a query runs the socket keeps being read, so a client that
+//! disconnects mid-query cancels it instead of leaving it running.
+
+use std::collections::VecDeque;
+use std::future::Future;
 
 use axum::extract::State;
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
@@ -18,12 +24,19 @@ use futures_util::{SinkExt, StreamExt};
 use grafeo_engine::auth::Identity;
 use grafeo_service::error::ServiceError;
 use grafeo_service::query::QueryService;
+use grafeo_service::types::QueryTransport;
 
 use crate::encode::{convert_json_params, query_result_to_response};
 use crate::middleware::auth_context::AuthContext;
 use crate::state::AppState;
 use crate::types::{QueryRequest, WsClientMessage, WsServerMessage};
 
+/// Subscription messages buffered for a connection. When a slow client
+/// fills the buffer, its subscriptions stop reading the change feed, fall
+/// behind it and report a gap.
+#[cfg(feature = "push-changefeed")]
+const SUBSCRIPTION_BUFFER: usize = 256;
+
 /// WebSocket upgrade handler.
 ///
 /// Authentication is handled by the middleware stack before this handler
@@ -60,7 +73,8 @@ pub async fn ws_handler(
         .as_ref()
         .map(|info| info.scope.databases.clone())
         .unwrap_or_default();
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, identity, db_scope)))
+    let user = auth.user().map(str::to_owned);
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, identity, db_scope, user)))
 }
 
 async fn handle_socket(
@@ -68,18 +82,21 @@ async fn handle_socket(
     state: AppState,
     identity: Identity,
     db_scope: Vec<String>,
+    user: Option<String>,
 ) {
     let (mut sender, mut receiver) = socket.split();
 
     #[cfg(feature = "push-changefeed")]
     {
-        handle_with_subscriptions(&mut sender, &mut receiver, state, identity, db_scope).await;
+        handle_with_subscriptions(&mut sender, &mut receiver, state, identity, db_scope, user)
+            .await;
     }
 
     #[cfg(not(feature = "push-changefeed"))]
     {
         // Simple sequential version, used when push-changefeed is not enabled.
-        while let Some(msg) = receiver.next().await {
+        let mut backlog = VecDeque::new();
+        while let Some(msg) = next_message(&mut backlog, &mut receiver).await {
             let text = match msg {
                 Ok(Message::Text(t)) => t,
                 Ok(Message::Close(_)) => break,
@@ -112,7 +129,12 @@ async fn handle_socket(
             let reply = match client_msg {
                 WsClientMessage::Ping => WsServerMessage::Pong,
                 WsClientMessage::Query { id, request } => {
-                    process_query(&state, id, request, &identity, &db_scope).await
+                    let query =
+                        process_query(&state, id, request, &identity, &db_scope, user.as_deref());
+                    match until_disconnect(query, &mut receiver, &mut backlog).await {
+                        Some(reply) => reply,
+                        None => break,
+                    }
                 }
             };
 
@@ -136,30 +158,33 @@ async fn handle_with_subscriptions<S, R>(
     state: AppState,
     identity: Identity,
     db_scope: Vec<String>,
+    user: Option<String>,
 ) where
     S: SinkExt<Message, Error = axum::Error> + Unpin,
     R: StreamExt<Item = Result<Message, axum::Error>> + Unpin,
 {
     use std::collections::HashMap;
 
-    use tokio::sync::broadcast::error::RecvError;
+    use grafeo_service::changefeed::ChangeFeedItem;
     use tokio::sync::mpsc;
 
     use crate::types::WsServerMessage;
 
-    // Channel that collects events from all active subscription tasks.
-    let (event_tx, mut event_rx) =
-        mpsc::unbounded_channel::<(String, grafeo_service::sync::ChangeEventDto)>();
+    // Channel that collects messages from all active subscription tasks.
+    let (event_tx, mut event_rx) = mpsc::channel::<WsServerMessage>(SUBSCRIPTION_BUFFER);
 
     // Active subscription tasks, keyed by sub_id.
     let mut sub_tasks: HashMap<String, tokio::task::JoinHandle<()>> = HashMap::new();
 
+    // Messages that arrived while a query was running.
+    let mut backlog = VecDeque::new();
+
     loop {
         tokio::select! {
             biased;
 
             // Prioritise incoming WebSocket messages.
-            msg = receiver.next() => {
+            msg = next_message(&mut backlog, receiver) => {
                 let text = match msg {
                     Some(Ok(Message::Text(t))) => t,
                     Some(Ok(Message::Close(_))) | None => break,
@@ -192,9 +217,13 @@ async fn handle_with_subscriptions<S, R>(
                 let reply: WsServerMessage = match client_msg {
                     WsClientMessage::Ping => WsServerMessage::Pong,
                     WsClientMessage::Query { id, request } => {
-                        process_query(&state, id, request, &identity, &db_scope).await
+                        let query = process_query(&state, id, request, &identity, &db_scope, user.as_deref());
+                        match until_disconnect(query, receiver, &mut backlog).await {
+                            Some(reply) => reply,
+                            None => break,
+                        }
                     }
-                    WsClientMessage::Subscribe { sub_id, db, since } => {
+                    WsClientMessage::Subscribe { sub_id, db, since, filter } => {
                         // Check database scope before subscribing.
                         if !db_scope.is_empty()
                             && !db_scope.iter().any(|d| d == &db)
@@ -207,30 +236,41 @@ async fn handle_with_subscriptions<S, R>(
                                 )),
                             }
                         } else {
-                            let rx = state.change_hub().subscribe(&db, since, state.service().clone());
-                            let tx = event_tx.clone();
-                            let sid = sub_id.clone();
-                            let handle = tokio::spawn(async move {
-                                let mut rx = rx;
-                                loop {
-                                    match rx.recv().await {
-                                        Ok(event) => {
-                                            if tx.send((sid.clone(), event)).is_err() {
+                            match state
+                                .change_hub()
+                                .subscribe_from(&db, since, filter, identity.clone(), state.service().clone())
+                                .await
+                            {
+                                Err(e) => service_error(None, &e),
+                                Ok(mut subscription) => {
+                                    let tx = event_tx.clone();
+                                    let sid = sub_id.clone();
+                                    let handle = tokio::spawn(async move {
+                                        while let Some(item) = subscription.next().await {
+                                            let msg = match item {
+                                                Ok(ChangeFeedItem::Change(event)) => {
+                                                    WsServerMessage::Change { sub_id: sid.clone(), event }
+                                                }
+                                                Ok(ChangeFeedItem::Gap(gap)) => WsServerMessage::Gap {
+                                                    sub_id: sid.clone(),
+                                                    last_epoch: gap.last_epoch,
+                                                },
+                                                Err(e) => {
+                                                    let _ = tx.send(service_error(None, &e)).await;
+                                                    break;
+                                                }
+                                            };
+                                            if tx.send(msg).await.is_err() {
                                                 break;
                                             }
                                         }
-                                        Err(RecvError::Lagged(n)) => {
-                                            tracing::debug!(
-                                                "WebSocket changefeed sub '{sid}' lagged by {n} events"
-                                            );
-                                            // Continue: client will receive the next available event.
-                                        }
-                                        Err(RecvError::Closed) => break,
+                                    });
+                                    if let Some(previous) = sub_tasks.insert(sub_id.clone(), handle) {
+                                        previous.abort();
                                     }
+                                    WsServerMessage::Subscribed { sub_id }
                                 }
-                            });
-                            sub_tasks.insert(sub_id.clone(), handle);
-                            WsServerMessage::Subscribed { sub_id }
+                            }
                         }
                     }
                     WsClientMessage::Unsubscribe { sub_id } => {
@@ -246,13 +286,12 @@ async fn handle_with_subscriptions<S, R>(
                 }
             }
 
-            // Forward change events from active subscriptions.
-            event = event_rx.recv() => {
-                if let Some((sub_id, change_event)) = event {
-                    let msg = WsServerMessage::Change { sub_id, event: Box::new(change_event) };
-                    if send_json(sender, &msg).await.is_err() {
-                        break;
-                    }
+            // Forward messages from active subscriptions.
+            msg = event_rx.recv() => {
+                if let Some(msg) = msg
+                    && send_json(sender, &msg).await.is_err()
+                {
+                    break;
                 }
             }
         }
@@ -268,6 +307,49 @@ async fn handle_with_subscriptions<S, R>(
 // Shared helpers
 // ---------------------------------------------------------------------------
 
+/// Returns the next queued message, or reads one from the socket.
+async fn next_message<R>(
+    backlog: &mut VecDeque<Message>,
+    receiver: &mut R,
+) -> Option<Result<Message, axum::Error>>
+where
+    R: StreamExt<Item = Result<Message, axum::Error>> + Unpin,
+{
+    match backlog.pop_front() {
+        Some(msg) => Some(Ok(msg)),
+        None => receiver.next().await,
+    }
+}
+
+/// Drives `fut` while watching the socket for a disconnect.
+///
+/// Returns `None` if the client closed the connection first; `fut` is then
+/// dropped, which cancels the query running behind it. Messages that arrive
+/// in the meantime are queued in `backlog` and handled afterwards.
+async fn until_disconnect<F, R>(
+    fut: F,
+    receiver: &mut R,
+    backlog: &mut VecDeque<Message>,
+) -> Option<F::Output>
+where
+    F: Future,
+    R: StreamExt<Item = Result<Message, axum::Error>> + Unpin,
+{
+    tokio::pin!(fut);
+    loop {
+        tokio::select! {
+            out = &mut fut => return Some(out),
+            msg = receiver.next() => match msg {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => {
+                    tracing::debug!("WebSocket closed during query, cancelling");
+                    return None;
+                }
+                Some(Ok(msg)) => backlog.push_back(msg),
+            },
+        }
+    }
+}
+
 /// Sends a JSON-serialized message over the WebSocket.
 async fn send_json<S>(sender: &mut S, msg: &WsServerMessage) -> Result<(), ()>
 where
@@ -287,6 +369,7 @@ async fn process_query(
     req: QueryRequest,
     identity: &Identity,
     db_scope: &[String],
+    user: Option<&str>,
 ) -> WsServerMessage {
     let db_name = grafeo_service::resolve_db_name(req.database.as_deref());
 
@@ -309,6 +392,13 @@ async fn process_query(
         }
     };
     let timeout = state.effective_timeout(req.timeout_ms);
+    let running = state.running_queries().register(
+        db_name,
+        req.language.as_deref(),
+        &req.query,
+        user,
+        QueryTransport::WebSocket,
+    );
 
     let result = QueryService::execute(
         state.databases(),
@@ -320,6 +410,7 @@ async fn process_query(
         timeout,
         state.service().is_query_read_only(),
         Some(identity.clone()),
+        running.cancel_token(),
     )
     .await;
 
@@ -328,14 +419,18 @@ async fn process_query(
             id,
             response: query_result_to_response(&qr),
         },
-        Err(e) => {
-            let (error, detail) = match &e {
-                ServiceError::BadRequest(msg) => ("bad_request".to_string(), Some(msg.clone())),
-                ServiceError::Timeout => ("timeout".to_string(), None),
-                ServiceError::NotFound(msg) => ("not_found".to_string(), Some(msg.clone())),
-                _ => ("internal_error".to_string(), Some(e.to_string())),
-            };
-            WsServerMessage::Error { id, error, detail }
-        }
+        Err(e) => service_error(id, &e),
     }
 }
+
+/// Converts a service error into an `error` message.
+fn service_error(id: Option<String>, e: &ServiceError) -> WsServerMessage {
+    let (error, detail) = match e {
+        ServiceError::BadRequest(msg) => ("bad_request".to_string(), Some(msg.clone())),
+        ServiceError::Timeout => ("timeout".to_string(), None),
+        ServiceError::Cancelled => ("cancelled".to_string(), None),
+        ServiceError::NotFound(msg) => ("not_found".to_string(), Some(msg.clone())),
+        _ => ("internal_error".to_string(), Some(e.to_string())),
+    };
+    WsServerMessage::Error { id, error, detail }
+}
@@ -258,6 +258,9 @@ pub enum WsClientMessage {
         /// Return events with epoch >= this value. Use 0 for full history.
         #[serde(default)]
         since: u64,
+        /// Only deliver the events this filter matches. Default: all events.
+        #[serde(default)]
+        filter: grafeo_service::change_filter::ChangeFilter,
     },
     /// Cancel an active subscription.
     #[cfg(feature = "push-changefeed")]
@@ -320,6 +323,18 @@ pub enum WsServerMessage {
         /// The change event payload.
         event: Box<grafeo_service::sync::ChangeEventDto>,
     },
+    /// The subscription fell behind and live events were dropped. The
+    /// events after `last_epoch` follow as `change` messages, read back
+    /// from the CDC log, before live delivery resumes.
+    #[cfg(feature = "push-changefeed")]
+    #[serde(rename = "gap")]
+    Gap {
+        /// Identifies the subscription that lagged.
+        sub_id: String,
+        /// Epoch of the last event delivered before the gap, or the
+        /// subscription's `since` if there was none.
+        last_epoch: u64,
+    },
 }
 
 #[derive(Serialize, ToSchema)]
@@ -4,6 +4,7 @@
 
 use std::path::Path;
 
+use crate::cancel::CancelToken;
 #[cfg(feature = "compact-store")]
 use crate::database::DatabaseEntry;
 use crate::database::DatabaseManager;
@@ -460,6 +461,7 @@ impl AdminService {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await?;
 
@@ -501,6 +503,7 @@ impl AdminService {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await;
 
@@ -536,6 +539,7 @@ impl AdminService {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await;
 
@@ -3,6 +3,10 @@
 //! Uses the engine's backup chain API (`backup_full`) for hot snapshots with
 //! real epoch tracking and checksums. Each database gets its own subdirectory
 //! within the configured backup dir (`{backup_dir}/{db_name}/`).
+//!
+//! Verification results are kept per backup in a `verifications.json`
+//! sidecar, copied to the backup target with the backups, and reported as
+//! `verification` in backup listings.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -11,6 +15,8 @@ use std::sync::Arc;
 use grafeo_engine::GrafeoDB;
 use grafeo_engine::database::backup::{BackupKind, BackupSegment};
 
+use crate::backup_crypto::{self, BackupKeys};
+use crate::backup_target::{BackupTarget, TargetFile};
 use crate::database::{DatabaseEntry, DatabaseManager};
 use crate::error::ServiceError;
 use crate::types;
@@ -18,9 +24,37 @@ use crate::types;
 /// Sidecar file name (relative to each per-database backup directory) that
 /// stores optional user-supplied labels keyed by backup filename. The engine
 /// owns filenames, so labels live out-of-band — losing this file loses
-/// labels but not backups.
+/// labels but not backups. Sealed like the backups when encryption is
+/// configured, since labels can describe what a backup holds.
 const LABELS_FILENAME: &str = "labels.json";
 
+/// Sidecar file name storing the latest verification result of each backup,
+/// keyed by filename. Like labels, results are best-effort metadata.
+const VERIFICATIONS_FILENAME: &str = "verifications.json";
+
+/// Sidecar file name storing the ID of the key that sealed each encrypted
+/// backup, keyed by filename, so listings can report it for backups only
+/// the target holds. Each sealed file names its key in its header too.
+const ENCRYPTION_FILENAME: &str = "encryption.json";
+
+/// The engine's backup chain manifest, relative to each per-database
+/// backup directory. Never sealed: its checksums cover the plaintext.
+const MANIFEST_FILENAME: &str = "backup_manifest.json";
+
+/// Metadata files stored at the target beside each database's backups.
+const METADATA_FILENAMES: [&str; 4] = [
+    MANIFEST_FILENAME,
+    LABELS_FILENAME,
+    VERIFICATIONS_FILENAME,
+    ENCRYPTION_FILENAME,
+];
+
+/// Files a backup target holds for one database, by name.
+type RemoteFiles = HashMap<String, TargetFile>;
+
+/// How many times `snapshot_database` retries when a commit races it.
+const SNAPSHOT_ATTEMPTS: usize = 5;
+
 /// Returns the per-database backup subdirectory.
 fn db_backup_dir(backup_dir: &Path, db_name: &str) -> Result<PathBuf, ServiceError> {
     if db_name.contains('/') || db_name.contains('\\') || db_name.contains("..") {
@@ -62,49 +96,78 @@ fn labels_path(dir: &Path) -> PathBuf {
 
 /// Load the label sidecar for a database's backup directory. Missing or
 /// corrupt files yield an empty map — labels are best-effort metadata.
-fn load_labels(dir: &Path) -> HashMap<String, String> {
+/// Returns `None` when the sidecar is sealed with a key `keys` lacks, so
+/// callers don't overwrite labels they cannot read.
+fn load_labels(dir: &Path, keys: Option<&BackupKeys>) -> Option<HashMap<String, String>> {
     let path = labels_path(dir);
-    if !path.exists() {
-        return HashMap::new();
-    }
-    match std::fs::read_to_string(&path) {
-        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+    let Ok(data) = std::fs::read(&path) else {
+        return Some(HashMap::new());
+    };
+    let data = match backup_crypto::open_bytes(&data, keys) {
+        Ok(data) => data,
+        Err(e) => {
             tracing::warn!(
                 path = %path.display(),
                 error = %e,
-                "backup label sidecar is corrupt, ignoring"
+                "cannot decrypt backup label sidecar"
             );
-            HashMap::new()
-        }),
-        Err(_) => HashMap::new(),
-    }
+            return None;
+        }
+    };
+    Some(serde_json::from_slice(&data).unwrap_or_else(|e| {
+        tracing::warn!(
+            path = %path.display(),
+            error = %e,
+            "backup label sidecar is corrupt, ignoring"
+        );
+        HashMap::new()
+    }))
 }
 
-fn save_labels(dir: &Path, labels: &HashMap<String, String>) -> Result<(), ServiceError> {
+/// Write the label sidecar, sealed when `keys` is set.
+fn save_labels(
+    dir: &Path,
+    labels: &HashMap<String, String>,
+    keys: Option<&BackupKeys>,
+) -> Result<(), ServiceError> {
     let path = labels_path(dir);
-    let text = serde_json::to_string_pretty(labels)
+    let mut data = serde_json::to_vec_pretty(labels)
         .map_err(|e| ServiceError::Internal(format!("failed to serialize labels: {e}")))?;
-    std::fs::write(&path, text)
+    if let Some(keys) = keys {
+        data = keys.seal_bytes(&data);
+    }
+    std::fs::write(&path, data)
         .map_err(|e| ServiceError::Internal(format!("failed to write labels sidecar: {e}")))?;
     Ok(())
 }
 
 /// Merge label into the sidecar for `dir`. No-op if label is `None`.
-fn upsert_label(dir: &Path, filename: &str, label: Option<&str>) -> Result<(), ServiceError> {
+fn upsert_label(
+    dir: &Path,
+    filename: &str,
+    label: Option<&str>,
+    keys: Option<&BackupKeys>,
+) -> Result<(), ServiceError> {
     let Some(label) = label else {
         return Ok(());
     };
-    let mut labels = load_labels(dir);
+    let mut labels = load_labels(dir, keys).ok_or_else(|| {
+        ServiceError::BadRequest(
+            "backup labels are encrypted with a key that is not configured".to_string(),
+        )
+    })?;
     labels.insert(filename.to_owned(), label.to_owned());
-    save_labels(dir, &labels)
+    save_labels(dir, &labels, keys)
 }
 
 /// Remove a label entry from the sidecar. Best-effort — errors are logged
 /// but not surfaced because delete_backup itself already succeeded.
-fn remove_label(dir: &Path, filename: &str) {
-    let mut labels = load_labels(dir);
+fn remove_label(dir: &Path, filename: &str, keys: Option<&BackupKeys>) {
+    let Some(mut labels) = load_labels(dir, keys) else {
+        return;
+    };
     if labels.remove(filename).is_some()
-        && let Err(e) = save_labels(dir, &labels)
+        && let Err(e) = save_labels(dir, &labels, keys)
     {
         tracing::warn!(
             filename = %filename,
@@ -114,6 +177,84 @@ fn remove_label(dir: &Path, filename: &str) {
     }
 }
 
+/// Load a plaintext sidecar keyed by backup filename. Missing or corrupt
+/// files yield an empty map.
+fn load_sidecar<T: serde::de::DeserializeOwned>(dir: &Path, name: &str) -> HashMap<String, T> {
+    let path = dir.join(name);
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        return HashMap::new();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        tracing::warn!(
+            path = %path.display(),
+            error = %e,
+            "backup sidecar is corrupt, ignoring"
+        );
+        HashMap::new()
+    })
+}
+
+fn save_sidecar<T: serde::Serialize>(
+    dir: &Path,
+    name: &str,
+    entries: &HashMap<String, T>,
+) -> Result<(), ServiceError> {
+    let text = serde_json::to_string_pretty(entries)
+        .map_err(|e| ServiceError::Internal(format!("failed to serialize {name}: {e}")))?;
+    std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(dir.join(name), text))
+        .map_err(|e| ServiceError::Internal(format!("failed to write {name}: {e}")))
+}
+
+/// Remove an entry from a plaintext sidecar. Best-effort, like
+/// [`remove_label`].
+fn remove_sidecar_entry<T: serde::Serialize + serde::de::DeserializeOwned>(
+    dir: &Path,
+    name: &str,
+    filename: &str,
+) {
+    let mut entries: HashMap<String, T> = load_sidecar(dir, name);
+    if entries.remove(filename).is_some()
+        && let Err(e) = save_sidecar(dir, name, &entries)
+    {
+        tracing::warn!(filename = %filename, error = %e, "failed to update {name} after delete");
+    }
+}
+
+/// Load the verification sidecar.
+fn load_verifications(dir: &Path) -> HashMap<String, types::BackupVerification> {
+    load_sidecar(dir, VERIFICATIONS_FILENAME)
+}
+
+/// Record a verification result in the sidecar, replacing the previous one.
+fn record_verification(
+    dir: &Path,
+    verification: &types::BackupVerification,
+) -> Result<(), ServiceError> {
+    let mut verifications = load_verifications(dir);
+    verifications.insert(verification.filename.clone(), verification.clone());
+    save_sidecar(dir, VERIFICATIONS_FILENAME, &verifications)
+}
+
+/// Record the IDs of the keys that sealed backup files, by filename.
+fn record_key_ids<'a>(
+    dir: &Path,
+    key_ids: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<(), ServiceError> {
+    let mut recorded: HashMap<String, String> = load_sidecar(dir, ENCRYPTION_FILENAME);
+    for (filename, key_id) in key_ids {
+        recorded.insert(filename.to_owned(), key_id.to_owned());
+    }
+    save_sidecar(dir, ENCRYPTION_FILENAME, &recorded)
+}
+
+/// Remove a backup's entries from every sidecar.
+fn remove_sidecar_entries(dir: &Path, filename: &str, keys: Option<&BackupKeys>) {
+    remove_label(dir, filename, keys);
+    remove_sidecar_entry::<types::BackupVerification>(dir, VERIFICATIONS_FILENAME, filename);
+    remove_sidecar_entry::<String>(dir, ENCRYPTION_FILENAME, filename);
+}
+
 /// Ensure legacy backups in the root backup directory are migrated to
 /// per-database subdirectories. Safe to call multiple times.
 pub fn ensure_migrated(backup_dir: &Path) {
@@ -123,6 +264,14 @@ pub fn ensure_migrated(backup_dir: &Path) {
 /// Stateless backup and restore operations.
 pub struct BackupService;
 
+/// Failure while swapping a database's data files for a backup.
+enum ReplaceError {
+    /// The old data files could not be removed.
+    Files(ServiceError),
+    /// The backup could not be opened or saved in their place.
+    Open(String),
+}
+
 impl BackupService {
     /// Create a full backup of a database.
     ///
@@ -198,13 +347,15 @@ impl BackupService {
                 end_epoch: 0,
                 checksum: 0,
                 label: None,
+                verification: None,
+                encryption_key_id: None,
             }
         };
 
         // Persist the label in the sidecar before returning so the caller
         // immediately sees it in the entry.
         if let Some(ref l) = label {
-            upsert_label(&dir, &entry_out.filename, Some(l.as_str()))?;
+            upsert_label(&dir, &entry_out.filename, Some(l.as_str()), None)?;
             entry_out.label = Some(l.clone());
         }
 
@@ -421,23 +572,7 @@ impl BackupService {
     ) -> Result<(), ServiceError> {
         ensure_migrated(backup_dir);
 
-        if databases.is_read_only() {
-            return Err(ServiceError::ReadOnly);
-        }
-
-        let data_dir = databases.data_dir().ok_or_else(|| {
-            ServiceError::BadRequest("restore requires persistent storage (--data-dir)".to_string())
-        })?;
-
-        let entry = databases
-            .get(db_name)
-            .ok_or_else(|| ServiceError::NotFound(format!("database '{db_name}' not found")))?;
-
-        if entry.db().path().is_none() {
-            return Err(ServiceError::BadRequest(
-                "cannot restore an in-memory database".to_string(),
-            ));
-        }
+        let (entry, data_dir) = Self::restorable(databases, db_name)?;
 
         if !backup_path.exists() {
             return Err(ServiceError::NotFound(format!(
@@ -465,6 +600,33 @@ impl BackupService {
         result
     }
 
+    /// Checks that `db_name` can be restored: the server is writable and
+    /// the database exists on persistent storage. Returns its entry and the
+    /// data directory.
+    fn restorable<'a>(
+        databases: &'a DatabaseManager,
+        db_name: &str,
+    ) -> Result<(Arc<DatabaseEntry>, &'a Path), ServiceError> {
+        if databases.is_read_only() {
+            return Err(ServiceError::ReadOnly);
+        }
+
+        let data_dir = databases.data_dir().ok_or_else(|| {
+            ServiceError::BadRequest("restore requires persistent storage (--data-dir)".to_string())
+        })?;
+
+        let entry = databases
+            .get(db_name)
+            .ok_or_else(|| ServiceError::NotFound(format!("database '{db_name}' not found")))?;
+
+        if entry.db().path().is_none() {
+            return Err(ServiceError::BadRequest(
+                "cannot restore an in-memory database".to_string(),
+            ));
+        }
+        Ok((entry, data_dir))
+    }
+
     async fn do_restore(
         entry: &Arc<DatabaseEntry>,
         db_name: &str,
@@ -526,6 +688,47 @@ impl BackupService {
             Ok(Ok(())) => {}
         }
 
+        let db_file = data_dir.join(db_name).join("data.grafeo");
+        match Self::replace_data(entry, db_name, backup_path, data_dir).await {
+            Ok(new_db) => {
+                entry.swap_db(Arc::new(new_db));
+                tracing::info!(database = %db_name, "Database restored from backup");
+                (Ok(()), true)
+            }
+            Err(ReplaceError::Files(e)) => (Err(e), true),
+            Err(ReplaceError::Open(e)) => {
+                tracing::error!(
+                    database = %db_name,
+                    error = %e,
+                    "Failed to restore, recovering from safety backup"
+                );
+                let recovered =
+                    Self::recover_from_safety(entry, &db_file, backup_dir, safety_file.as_deref())
+                        .await;
+                (
+                    Err(ServiceError::Internal(format!(
+                        "restore failed{}: {e}",
+                        if recovered {
+                            ", recovered from safety backup"
+                        } else {
+                            ", recovery also failed"
+                        }
+                    ))),
+                    recovered,
+                )
+            }
+        }
+    }
+
+    /// Closes the entry's handle, replaces its data files with the contents
+    /// of `backup_path`, and opens the result. The caller swaps the returned
+    /// handle in.
+    async fn replace_data(
+        entry: &Arc<DatabaseEntry>,
+        db_name: &str,
+        backup_path: &Path,
+        data_dir: &Path,
+    ) -> Result<GrafeoDB, ReplaceError> {
         // 2. Close the old handle
         let old_db = entry.db();
         if let Err(e) = old_db.close() {
@@ -544,30 +747,24 @@ impl BackupService {
                 std::fs::remove_file(&db_file)
             };
             if let Err(e) = remove_result {
-                return (
-                    Err(ServiceError::Internal(format!(
-                        "failed to remove old database: {e}"
-                    ))),
-                    true,
-                );
+                return Err(ReplaceError::Files(ServiceError::Internal(format!(
+                    "failed to remove old database: {e}"
+                ))));
             }
         }
         let wal_dir = db_dir.join("data.grafeo.wal");
         if wal_dir.exists()
             && let Err(e) = std::fs::remove_dir_all(&wal_dir)
         {
-            return (
-                Err(ServiceError::Internal(format!(
-                    "failed to remove old WAL: {e}"
-                ))),
-                true,
-            );
+            return Err(ReplaceError::Files(ServiceError::Internal(format!(
+                "failed to remove old WAL: {e}"
+            ))));
         }
 
         // 4. Open backup and save to the persistent path
         let backup_owned = backup_path.to_path_buf();
         let db_file_clone = db_file.clone();
-        let open_result = tokio::task::spawn_blocking(move || -> Result<GrafeoDB, String> {
+        tokio::task::spawn_blocking(move || -> Result<GrafeoDB, String> {
             let backup_db =
                 GrafeoDB::open(&backup_owned).map_err(|e| format!("failed to open backup: {e}"))?;
             backup_db
@@ -579,36 +776,8 @@ impl BackupService {
         })
         .await
         .map_err(|e| e.to_string())
-        .and_then(|r| r);
-
-        match open_result {
-            Ok(new_db) => {
-                entry.swap_db(Arc::new(new_db));
-                tracing::info!(database = %db_name, "Database restored from backup");
-                (Ok(()), true)
-            }
-            Err(e) => {
-                tracing::error!(
-                    database = %db_name,
-                    error = %e,
-                    "Failed to restore, recovering from safety backup"
-                );
-                let recovered =
-                    Self::recover_from_safety(entry, &db_file, backup_dir, safety_file.as_deref())
-                        .await;
-                (
-                    Err(ServiceError::Internal(format!(
-                        "restore failed{}: {e}",
-                        if recovered {
-                            ", recovered from safety backup"
-                        } else {
-                            ", recovery also failed"
-                        }
-                    ))),
-                    recovered,
-                )
-            }
-        }
+        .and_then(|r| r)
+        .map_err(ReplaceError::Open)
     }
 
     /// Attempt recovery from the safety backup after a failed restore.
@@ -664,6 +833,144 @@ impl BackupService {
         }
     }
 
+    /// Writes a point-in-time snapshot of a database to `path` for
+    /// bootstrapping a replica, returning the epoch it reflects.
+    ///
+    /// Uses the same `save()` export as in-memory backups, so it works for
+    /// either storage mode. The snapshot is retaken if a transaction commits
+    /// while it is being written, so it holds every change up to the
+    /// returned epoch and none after it.
+    pub async fn snapshot_database(
+        databases: &DatabaseManager,
+        db_name: &str,
+        path: &Path,
+    ) -> Result<u64, ServiceError> {
+        let entry = databases.get_available(db_name)?;
+        let db = entry.db();
+        let db_name = db_name.to_owned();
+        let path = path.to_path_buf();
+
+        tokio::task::spawn_blocking(move || {
+            for _ in 0..SNAPSHOT_ATTEMPTS {
+                if path.exists() {
+                    std::fs::remove_file(&path).map_err(|e| {
+                        ServiceError::Internal(format!("failed to remove stale snapshot: {e}"))
+                    })?;
+                }
+                let before = db.current_epoch();
+                db.save(&path)
+                    .map_err(|e| ServiceError::Internal(format!("snapshot failed: {e}")))?;
+                if db.current_epoch() == before {
+                    tracing::info!(
+                        database = %db_name,
+                        epoch = before.as_u64(),
+                        "Replica snapshot created"
+                    );
+                    return Ok(before.as_u64());
+                }
+            }
+            let _ = std::fs::remove_file(&path);
+            Err(ServiceError::Unavailable(format!(
+                "database '{db_name}' changed during every snapshot attempt, retry later"
+            )))
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(e.to_string()))?
+    }
+
+    /// Replaces a replica's copy of a database with a snapshot written by
+    /// [`snapshot_database`](Self::snapshot_database) on the primary.
+    ///
+    /// Meant for bootstrapping an empty replica, so unlike `restore_database`
+    /// no safety backup is taken. In-memory databases load the snapshot in
+    /// place; persistent ones have their data files replaced, and are
+    /// reopened empty if that fails so the bootstrap can be retried.
+    pub async fn restore_replica_snapshot(
+        databases: &DatabaseManager,
+        db_name: &str,
+        snapshot_path: &Path,
+    ) -> Result<(), ServiceError> {
+        let entry = databases
+            .get(db_name)
+            .ok_or_else(|| ServiceError::NotFound(format!("database '{db_name}' not found")))?;
+
+        if !entry.set_restoring() {
+            return Err(ServiceError::Conflict(
+                "database is already being restored".to_string(),
+            ));
+        }
+
+        let result = match databases.data_dir() {
+            Some(data_dir) if entry.db().path().is_some() => {
+                match Self::replace_data(&entry, db_name, snapshot_path, data_dir).await {
+                    Ok(new_db) => {
+                        entry.swap_db(Arc::new(new_db));
+                        Ok(())
+                    }
+                    Err(e) => {
+                        let e = match e {
+                            ReplaceError::Files(e) => e.to_string(),
+                            ReplaceError::Open(e) => e,
+                        };
+                        Self::reopen_empty(&entry, db_name, data_dir).await;
+                        Err(ServiceError::Internal(format!(
+                            "replica bootstrap failed: {e}"
+                        )))
+                    }
+                }
+            }
+            _ => {
+                let db = entry.db();
+                let snapshot_path = snapshot_path.to_path_buf();
+                tokio::task::spawn_blocking(move || -> Result<(), String> {
+                    let snapshot = GrafeoDB::open(&snapshot_path)
+                        .map_err(|e| format!("failed to open snapshot: {e}"))?;
+                    let data = snapshot
+                        .export_snapshot()
+                        .map_err(|e| format!("failed to read snapshot: {e}"))?;
+                    snapshot.close().ok();
+                    db.restore_snapshot(&data)
+                        .map_err(|e| format!("failed to load snapshot: {e}"))
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+                .map_err(|e| ServiceError::Internal(format!("replica bootstrap failed: {e}")))
+            }
+        };
+
+        entry.set_available();
+        if result.is_ok() {
+            tracing::info!(database = %db_name, "Replica bootstrapped from primary snapshot");
+        }
+        result
+    }
+
+    /// Replaces whatever is left of a persistent database's files with an
+    /// empty database after a failed replica bootstrap.
+    async fn reopen_empty(entry: &Arc<DatabaseEntry>, db_name: &str, data_dir: &Path) {
+        let db_file = data_dir.join(db_name).join("data.grafeo");
+        let reopened = tokio::task::spawn_blocking(move || {
+            if db_file.is_dir() {
+                let _ = std::fs::remove_dir_all(&db_file);
+            } else if db_file.exists() {
+                let _ = std::fs::remove_file(&db_file);
+            }
+            GrafeoDB::open(db_file.to_str().unwrap())
+        })
+        .await;
+
+        match reopened {
+            Ok(Ok(db)) => entry.swap_db(Arc::new(db)),
+            Ok(Err(e)) => {
+                tracing::error!(database = %db_name, error = %e, "Failed to reopen database");
+            }
+            Err(e) => {
+                tracing::error!(database = %db_name, error = %e, "Failed to reopen database");
+            }
+        }
+    }
+
     /// List backup segments, optionally filtered by database name.
     ///
     /// On first call, migrates any legacy backup files (`{db}_{timestamp}.grafeo`)
@@ -681,7 +988,7 @@ impl BackupService {
 
         if let Some(name) = db_name {
             let dir = db_backup_dir(backup_dir, name)?;
-            return Self::list_from_manifest(&dir, name);
+            return Self::list_from_manifest(&dir, name, &RemoteFiles::new(), None);
         }
 
         let mut all = Vec::new();
@@ -692,7 +999,8 @@ impl BackupService {
             let path = entry.path();
             if path.is_dir()
                 && let Some(name) = path.file_name().and_then(|n| n.to_str())
-                && let Ok(mut backups) = Self::list_from_manifest(&path, name)
+                && let Ok(mut backups) =
+                    Self::list_from_manifest(&path, name, &RemoteFiles::new(), None)
             {
                 all.append(&mut backups);
             }
@@ -702,15 +1010,25 @@ impl BackupService {
         Ok(all)
     }
 
+    /// Lists the backups of one database. A file counts as present when
+    /// it is in `dir` or in `remote`, the target's copy. `keys` opens a
+    /// sealed label sidecar.
     fn list_from_manifest(
         dir: &Path,
         db_name: &str,
+        remote: &RemoteFiles,
+        keys: Option<&BackupKeys>,
     ) -> Result<Vec<types::BackupEntry>, ServiceError> {
-        if !dir.exists() {
+        if !dir.exists() && remote.is_empty() {
             return Ok(vec![]);
         }
-        let manifest = GrafeoDB::read_backup_manifest(dir)
-            .map_err(|e| ServiceError::Internal(format!("failed to read backup manifest: {e}")))?;
+        let manifest = if dir.exists() {
+            GrafeoDB::read_backup_manifest(dir).map_err(|e| {
+                ServiceError::Internal(format!("failed to read backup manifest: {e}"))
+            })?
+        } else {
+            None
+        };
         let manifest_filenames: std::collections::HashSet<String>;
         let mut entries = match manifest {
             Some(m) => {
@@ -718,7 +1036,7 @@ impl BackupService {
                 m.segments
                     .into_iter()
                     .filter(|seg| seg.kind == BackupKind::Full)
-                    .filter(|seg| dir.join(&seg.filename).exists())
+                    .filter(|seg| present(dir, remote, &seg.filename))
                     .map(|seg| segment_to_entry(seg, db_name.to_owned()))
                     .collect::<Vec<_>>()
             }
@@ -754,19 +1072,44 @@ impl BackupService {
                         end_epoch: 0,
                         checksum: 0,
                         label: None,
+                        verification: None,
+                        encryption_key_id: None,
                     });
                 }
             }
         }
+        for file in remote.values() {
+            if file.name.ends_with(".grafeo")
+                && !manifest_filenames.contains(&file.name)
+                && !dir.join(&file.name).exists()
+            {
+                entries.push(types::BackupEntry {
+                    filename: file.name.clone(),
+                    database: db_name.to_owned(),
+                    kind: "full".to_owned(),
+                    size_bytes: file.size_bytes,
+                    created_at: millis_to_iso(file.modified_ms),
+                    start_epoch: 0,
+                    end_epoch: 0,
+                    checksum: 0,
+                    label: None,
+                    verification: None,
+                    encryption_key_id: None,
+                });
+            }
+        }
 
-        // Merge user-supplied labels from the sidecar.
-        let labels = load_labels(dir);
-        if !labels.is_empty() {
-            for entry in &mut entries {
-                if let Some(label) = labels.get(&entry.filename) {
-                    entry.label = Some(label.clone());
-                }
+        // Merge user-supplied labels, verification results and key IDs
+        // from the sidecars.
+        let labels = load_labels(dir, keys).unwrap_or_default();
+        let mut verifications = load_verifications(dir);
+        let mut key_ids: HashMap<String, String> = load_sidecar(dir, ENCRYPTION_FILENAME);
+        for entry in &mut entries {
+            if let Some(label) = labels.get(&entry.filename) {
+                entry.label = Some(label.clone());
             }
+            entry.verification = verifications.remove(&entry.filename);
+            entry.encryption_key_id = key_ids.remove(&entry.filename);
         }
 
         entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
@@ -778,6 +1121,16 @@ impl BackupService {
         db_name: &str,
         filename: &str,
         backup_dir: &Path,
+    ) -> Result<(), ServiceError> {
+        Self::delete_local(db_name, filename, backup_dir, None)
+    }
+
+    /// Deletes a local backup file. `keys` opens a sealed label sidecar.
+    fn delete_local(
+        db_name: &str,
+        filename: &str,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
     ) -> Result<(), ServiceError> {
         ensure_migrated(backup_dir);
 
@@ -803,7 +1156,7 @@ impl BackupService {
 
         // Best-effort sidecar cleanup so stale labels don't linger when
         // a backup is recreated with the same filename later.
-        remove_label(&dir, filename);
+        remove_sidecar_entries(&dir, filename, keys);
 
         tracing::info!(database = %db_name, filename = %filename, "Backup deleted");
         Ok(())
@@ -818,13 +1171,23 @@ impl BackupService {
         keep: usize,
     ) -> Result<Vec<String>, ServiceError> {
         ensure_migrated(backup_dir);
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        Self::retain_newest(&dir, keep, &RemoteFiles::new())
+    }
 
+    /// Count retention over the local directory and the target's copies in
+    /// `remote`. Removes local files and returns every deleted name, so
+    /// the caller can delete the target's copies.
+    fn retain_newest(
+        dir: &Path,
+        keep: usize,
+        remote: &RemoteFiles,
+    ) -> Result<Vec<String>, ServiceError> {
         // keep=0 would delete everything including the backup just created.
         // Treat as "keep at least 1" to avoid accidental data loss.
         let keep = keep.max(1);
 
-        let dir = db_backup_dir(backup_dir, db_name)?;
-        let manifest = GrafeoDB::read_backup_manifest(&dir).ok().flatten();
+        let manifest = GrafeoDB::read_backup_manifest(dir).ok().flatten();
 
         if let Some(ref m) = manifest {
             // Manifest-based retention: only count full backups whose files exist
@@ -832,7 +1195,7 @@ impl BackupService {
                 .segments
                 .iter()
                 .enumerate()
-                .filter(|(_, s)| s.kind == BackupKind::Full && dir.join(&s.filename).exists())
+                .filter(|(_, s)| s.kind == BackupKind::Full && present(dir, remote, &s.filename))
                 .map(|(i, _)| i)
                 .collect();
 
@@ -843,13 +1206,12 @@ impl BackupService {
                 let filenames_to_delete: std::collections::HashSet<String> = m.segments
                     [..cutoff_idx]
                     .iter()
-                    .filter(|s| dir.join(&s.filename).exists())
+                    .filter(|s| present(dir, remote, &s.filename))
                     .map(|s| s.filename.clone())
                     .collect();
 
                 for filename in &filenames_to_delete {
-                    let path = dir.join(filename);
-                    if std::fs::remove_file(&path).is_ok() {
+                    if remove_backup_file(dir, remote, filename) {
                         tracing::info!(filename = %filename, "Removed old backup (retention policy)");
                         deleted.push(filename.clone());
                     }
@@ -859,29 +1221,14 @@ impl BackupService {
             // Prune untracked .grafeo files not in the manifest
             let manifest_filenames: std::collections::HashSet<&str> =
                 m.segments.iter().map(|s| s.filename.as_str()).collect();
-            if let Ok(dir_entries) = std::fs::read_dir(&dir) {
-                let mut untracked: Vec<(String, std::time::SystemTime)> = dir_entries
-                    .flatten()
-                    .filter_map(|e| {
-                        let path = e.path();
-                        let fname = path.file_name()?.to_str()?.to_string();
-                        if !fname.ends_with(".grafeo")
-                            || manifest_filenames.contains(fname.as_str())
-                        {
-                            return None;
-                        }
-                        let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
-                        Some((fname, modified))
-                    })
-                    .collect();
-                // Keep the newest `keep` untracked files too
-                if untracked.len() > keep {
-                    untracked.sort_by_key(|b| std::cmp::Reverse(b.1));
-                    for (fname, _) in untracked.into_iter().skip(keep) {
-                        if std::fs::remove_file(dir.join(&fname)).is_ok() {
-                            tracing::info!(filename = %fname, "Removed untracked backup (retention)");
-                            deleted.push(fname);
-                        }
+            let mut untracked = untracked_files(dir, &manifest_filenames, remote);
+            // Keep the newest `keep` untracked files too
+            if untracked.len() > keep {
+                untracked.sort_by_key(|b| std::cmp::Reverse(b.1));
+                for (fname, _) in untracked.into_iter().skip(keep) {
+                    if remove_backup_file(dir, remote, &fname) {
+                        tracing::info!(filename = %fname, "Removed untracked backup (retention)");
+                        deleted.push(fname);
                     }
                 }
             }
@@ -891,19 +1238,11 @@ impl BackupService {
 
         // No manifest — file-based retention (legacy and in-memory backups).
         // Sort by modified time, delete oldest.
-        let mut files: Vec<(String, std::time::SystemTime)> = std::fs::read_dir(&dir)
-            .map_err(|e| ServiceError::Internal(format!("failed to read backup dir: {e}")))?
-            .flatten()
-            .filter_map(|e| {
-                let path = e.path();
-                let fname = path.file_name()?.to_str()?.to_string();
-                if !fname.ends_with(".grafeo") {
-                    return None;
-                }
-                let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
-                Some((fname, modified))
-            })
-            .collect();
+        if remote.is_empty() {
+            std::fs::read_dir(dir)
+                .map_err(|e| ServiceError::Internal(format!("failed to read backup dir: {e}")))?;
+        }
+        let mut files = untracked_files(dir, &std::collections::HashSet::new(), remote);
 
         if files.len() <= keep {
             return Ok(vec![]);
@@ -914,8 +1253,7 @@ impl BackupService {
 
         let mut deleted = Vec::new();
         for (filename, _) in files.into_iter().skip(keep) {
-            let path = dir.join(&filename);
-            if std::fs::remove_file(&path).is_ok() {
+            if remove_backup_file(dir, remote, &filename) {
                 tracing::info!(filename = %filename, "Removed old backup (retention policy)");
                 deleted.push(filename);
             }
@@ -923,24 +1261,1243 @@ impl BackupService {
 
         Ok(deleted)
     }
-}
 
-/// Migrate legacy backup files from the root backup directory into per-database
-/// subdirectories. Old files were named `{db}_{timestamp}.grafeo`.
-fn migrate_legacy_backups(backup_dir: &Path) {
-    let Ok(entries) = std::fs::read_dir(backup_dir) else {
-        return;
-    };
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if !path.is_file() {
-            continue;
-        }
-        let Some(fname) = path.file_name().and_then(|n| n.to_str()) else {
-            continue;
-        };
-        if !fname.ends_with(".grafeo") {
-            continue;
+    /// Enforce a maximum age: delete backups created more than `max_age`
+    /// ago. The newest full backup created before the cutoff is kept with
+    /// everything after it, since restoring to any point inside the window
+    /// starts from it; untracked files keep at least the newest one.
+    pub fn enforce_max_age(
+        db_name: &str,
+        backup_dir: &Path,
+        max_age: std::time::Duration,
+    ) -> Result<Vec<String>, ServiceError> {
+        ensure_migrated(backup_dir);
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        Self::expire(&dir, max_age, &RemoteFiles::new())
+    }
+
+    /// Age retention over the local directory and the target's copies in
+    /// `remote`; see [`Self::retain_newest`].
+    fn expire(
+        dir: &Path,
+        max_age: std::time::Duration,
+        remote: &RemoteFiles,
+    ) -> Result<Vec<String>, ServiceError> {
+        if !dir.exists() && remote.is_empty() {
+            return Ok(vec![]);
+        }
+        let cutoff_ms = unix_millis().saturating_sub(max_age.as_millis() as u64);
+        let manifest = GrafeoDB::read_backup_manifest(dir).ok().flatten();
+
+        let mut deleted = Vec::new();
+        let mut manifest_filenames = std::collections::HashSet::new();
+        if let Some(m) = &manifest {
+            let base = m.segments.iter().rposition(|s| {
+                s.kind == BackupKind::Full
+                    && s.created_at_ms <= cutoff_ms
+                    && present(dir, remote, &s.filename)
+            });
+            if let Some(base) = base {
+                for seg in &m.segments[..base] {
+                    if remove_backup_file(dir, remote, &seg.filename) {
+                        tracing::info!(filename = %seg.filename, "Removed expired backup (max age)");
+                        deleted.push(seg.filename.clone());
+                    }
+                }
+            }
+            manifest_filenames = m.segments.iter().map(|s| s.filename.as_str()).collect();
+        }
+
+        // Untracked .grafeo files (legacy and in-memory backups), by mtime
+        let cutoff = std::time::UNIX_EPOCH + std::time::Duration::from_millis(cutoff_ms);
+        let mut untracked = untracked_files(dir, &manifest_filenames, remote);
+        untracked.sort_by_key(|b| std::cmp::Reverse(b.1));
+        for (fname, modified) in untracked.into_iter().skip(1) {
+            if modified < cutoff && remove_backup_file(dir, remote, &fname) {
+                tracing::info!(filename = %fname, "Removed expired untracked backup (max age)");
+                deleted.push(fname);
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    // -----------------------------------------------------------------------
+    // Backup targets
+    // -----------------------------------------------------------------------
+
+    /// Creates a full backup and stores it at `target`. See
+    /// [`Self::backup_database`].
+    ///
+    /// The chain's manifest is fetched from the target first when the
+    /// backup directory lacks it, so a server on a new host continues the
+    /// existing chain instead of starting one that would overwrite it.
+    /// With `keys`, the backup and the label sidecar are sealed before
+    /// anything is uploaded.
+    pub async fn backup_database_to(
+        databases: &DatabaseManager,
+        target: &dyn BackupTarget,
+        db_name: &str,
+        backup_dir: &Path,
+        label: Option<String>,
+        keys: Option<&BackupKeys>,
+    ) -> Result<types::BackupEntry, ServiceError> {
+        let label = validate_label(label)?;
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        Self::sync_metadata(target, db_name, &dir).await?;
+        let mut entry = Self::backup_database(databases, db_name, backup_dir, None).await?;
+        if let Some(keys) = keys {
+            Self::seal_backup(&dir, &entry.filename, keys).await?;
+            entry.encryption_key_id = Some(keys.active_id().to_owned());
+        }
+        if let Some(label) = label {
+            upsert_label(&dir, &entry.filename, Some(&label), keys)?;
+            entry.label = Some(label);
+        }
+        Self::upload_backup(target, db_name, &dir, &entry.filename).await?;
+        Ok(entry)
+    }
+
+    /// Creates an incremental backup and stores it at `target`, sealed
+    /// with `keys` if set. See [`Self::backup_incremental`].
+    pub async fn backup_incremental_to(
+        databases: &DatabaseManager,
+        target: &dyn BackupTarget,
+        db_name: &str,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<types::BackupEntry, ServiceError> {
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        Self::sync_metadata(target, db_name, &dir).await?;
+        let mut entry = Self::backup_incremental(databases, db_name, backup_dir).await?;
+        if let Some(keys) = keys {
+            Self::seal_backup(&dir, &entry.filename, keys).await?;
+            entry.encryption_key_id = Some(keys.active_id().to_owned());
+        }
+        Self::upload_backup(target, db_name, &dir, &entry.filename).await?;
+        Ok(entry)
+    }
+
+    /// Seals a backup file the engine just wrote and records its key ID.
+    async fn seal_backup(
+        dir: &Path,
+        filename: &str,
+        keys: &BackupKeys,
+    ) -> Result<(), ServiceError> {
+        let path = dir.join(filename);
+        let keys_owned = keys.clone();
+        tokio::task::spawn_blocking(move || backup_crypto::seal_file(&path, &keys_owned))
+            .await
+            .map_err(|e| ServiceError::Internal(e.to_string()))??;
+        record_key_ids(dir, [(filename, keys.active_id())])
+    }
+
+    /// Lists the backups held locally or at `target`, optionally filtered
+    /// by database name.
+    pub async fn list_backups_from(
+        target: &dyn BackupTarget,
+        db_name: Option<&str>,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<Vec<types::BackupEntry>, ServiceError> {
+        if backup_dir.exists() {
+            migrate_legacy_backups(backup_dir);
+        }
+
+        if let Some(name) = db_name {
+            let dir = db_backup_dir(backup_dir, name)?;
+            let remote = Self::sync_metadata(target, name, &dir).await?;
+            return Self::list_from_manifest(&dir, name, &remote, keys);
+        }
+
+        let mut all = Vec::new();
+        for name in Self::backup_databases(target, backup_dir).await? {
+            let Ok(dir) = db_backup_dir(backup_dir, &name) else {
+                continue;
+            };
+            let remote = match Self::sync_metadata(target, &name, &dir).await {
+                Ok(remote) => remote,
+                Err(e) => {
+                    tracing::warn!(database = %name, error = %e, "failed to list backup target");
+                    RemoteFiles::new()
+                }
+            };
+            if let Ok(mut backups) = Self::list_from_manifest(&dir, &name, &remote, keys) {
+                all.append(&mut backups);
+            }
+        }
+
+        all.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(all)
+    }
+
+    /// Names of the databases with backups locally or at `target`.
+    async fn backup_databases(
+        target: &dyn BackupTarget,
+        backup_dir: &Path,
+    ) -> Result<Vec<String>, ServiceError> {
+        let mut names: std::collections::BTreeSet<String> =
+            target.list_databases().await?.into_iter().collect();
+        if let Ok(entries) = std::fs::read_dir(backup_dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir()
+                    && let Some(name) = path.file_name().and_then(|n| n.to_str())
+                {
+                    names.insert(name.to_owned());
+                }
+            }
+        }
+        Ok(names.into_iter().collect())
+    }
+
+    /// Returns the local path of a backup file, downloading it from
+    /// `target` when only the target holds it.
+    pub async fn fetch_backup(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        filename: &str,
+        backup_dir: &Path,
+    ) -> Result<PathBuf, ServiceError> {
+        if filename.contains('/') || filename.contains('\\') || filename.contains("..") {
+            return Err(ServiceError::BadRequest(
+                "invalid backup filename".to_string(),
+            ));
+        }
+        ensure_migrated(backup_dir);
+        let path = db_backup_dir(backup_dir, db_name)?.join(filename);
+        if path.exists() || target.download(db_name, filename, &path).await? {
+            return Ok(path);
+        }
+        Err(ServiceError::NotFound(format!(
+            "backup '{filename}' not found for database '{db_name}'"
+        )))
+    }
+
+    /// Restores a database from a backup of `source_db` held locally or at
+    /// `target`. See [`Self::restore_database`].
+    ///
+    /// A sealed backup is decrypted with `keys` into a scratch directory
+    /// first; with `keys` set, the safety backup is sealed afterwards.
+    pub async fn restore_database_from(
+        databases: &DatabaseManager,
+        target: &dyn BackupTarget,
+        db_name: &str,
+        source_db: &str,
+        filename: &str,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<(), ServiceError> {
+        // Fail fast before downloading anything
+        Self::restorable(databases, db_name)?;
+        let mut backup_path = Self::fetch_backup(target, source_db, filename, backup_dir).await?;
+        // The safety backup extends this database's chain
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        Self::sync_metadata(target, db_name, &dir).await?;
+
+        let scratch = ScratchDir::new("grafeo-restore");
+        if backup_crypto::sealed_key_id(&backup_path)
+            .map_err(|e| ServiceError::Internal(format!("failed to read backup: {e}")))?
+            .is_some()
+        {
+            let plaintext = scratch.create()?.join("restore.grafeo");
+            let source = backup_path.clone();
+            let dest = plaintext.clone();
+            let keys_owned = keys.cloned();
+            tokio::task::spawn_blocking(move || {
+                backup_crypto::decrypt_file(&source, &dest, keys_owned.as_ref())
+            })
+            .await
+            .map_err(|e| ServiceError::Internal(e.to_string()))??;
+            backup_path = plaintext;
+        }
+
+        let before = backup_files(&dir);
+        let result = Self::restore_database(databases, db_name, &backup_path, backup_dir).await;
+        if let Some(keys) = keys {
+            for filename in backup_files(&dir).difference(&before) {
+                if let Err(e) = Self::seal_backup(&dir, filename, keys).await {
+                    tracing::warn!(
+                        database = %db_name,
+                        filename = %filename,
+                        error = %e,
+                        "failed to encrypt safety backup"
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    /// Restores a database to an epoch, first downloading the segments of
+    /// the chain up to that epoch that only `target` holds. See
+    /// [`Self::restore_to_epoch`]. Sealed segments are decrypted with
+    /// `keys` into a scratch copy of the chain, which the engine replays.
+    pub async fn restore_to_epoch_from(
+        databases: &DatabaseManager,
+        target: &dyn BackupTarget,
+        db_name: &str,
+        target_epoch: u64,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<(), ServiceError> {
+        Self::restorable(databases, db_name)?;
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        let chain = Self::fetch_chain(target, db_name, &dir, target_epoch).await?;
+
+        let scratch = ScratchDir::new("grafeo-restore");
+        let mut source = backup_dir.to_path_buf();
+        if let Some((_, names)) = chain {
+            let staged = scratch.path().join(db_name);
+            if Self::decrypt_chain(&dir, names, keys, &staged).await? {
+                source = scratch.path().to_path_buf();
+            }
+        }
+        Self::restore_to_epoch(databases, db_name, target_epoch, &source).await
+    }
+
+    /// Copies the manifest and the chain segments `names` from `dir` to
+    /// `dest`, decrypting sealed ones, when any of them is sealed. Returns
+    /// whether it did; if not, the engine can read `dir` directly.
+    async fn decrypt_chain(
+        dir: &Path,
+        names: Vec<String>,
+        keys: Option<&BackupKeys>,
+        dest: &Path,
+    ) -> Result<bool, ServiceError> {
+        let dir = dir.to_path_buf();
+        let dest = dest.to_path_buf();
+        let keys = keys.cloned();
+        tokio::task::spawn_blocking(move || {
+            copy_plaintext_chain(&dir, &names, keys.as_ref(), &dest)
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(e.to_string()))?
+    }
+
+    /// Restores a backup of `source_db`, or its backup chain up to an epoch,
+    /// into a new database, leaving `source_db` untouched. The source
+    /// database need not exist anymore, only its backups.
+    ///
+    /// The new database gets the backup's graph model and the requested
+    /// storage mode. It is deleted again if the backup fails to load.
+    /// Sealed backups are decrypted with `keys`.
+    pub async fn restore_as_new(
+        databases: &DatabaseManager,
+        target: &dyn BackupTarget,
+        source_db: &str,
+        req: &types::RestoreAsRequest,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<types::DatabaseSummary, ServiceError> {
+        if databases.is_read_only() {
+            return Err(ServiceError::ReadOnly);
+        }
+        if databases.get(&req.name).is_some() {
+            return Err(ServiceError::Conflict(format!(
+                "database '{}' already exists",
+                req.name
+            )));
+        }
+        if req.storage_mode == types::StorageMode::Persistent && databases.data_dir().is_none() {
+            return Err(ServiceError::BadRequest(
+                "persistent storage requires the server to be started with --data-dir".to_string(),
+            ));
+        }
+
+        let scratch = ScratchDir::new("grafeo-restore");
+        Self::restore_into_new(
+            databases,
+            target,
+            source_db,
+            req,
+            backup_dir,
+            keys,
+            scratch.create()?,
+        )
+        .await
+    }
+
+    /// Materializes the backup in `scratch`, then creates and fills the new
+    /// database. See [`Self::restore_as_new`].
+    async fn restore_into_new(
+        databases: &DatabaseManager,
+        target: &dyn BackupTarget,
+        source_db: &str,
+        req: &types::RestoreAsRequest,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+        scratch: &Path,
+    ) -> Result<types::DatabaseSummary, ServiceError> {
+        let file = scratch.join("restore.grafeo");
+        match (&req.backup, req.epoch) {
+            (Some(filename), None) => {
+                let path = Self::fetch_backup(target, source_db, filename, backup_dir).await?;
+                let dest = file.clone();
+                let keys = keys.cloned();
+                tokio::task::spawn_blocking(move || {
+                    backup_crypto::decrypt_file(&path, &dest, keys.as_ref())
+                })
+                .await
+                .map_err(|e| ServiceError::Internal(e.to_string()))??;
+            }
+            (None, Some(epoch)) => {
+                let mut dir = db_backup_dir(backup_dir, source_db)?;
+                let (manifest, names) = Self::fetch_chain(target, source_db, &dir, epoch)
+                    .await?
+                    .ok_or_else(|| {
+                        ServiceError::NotFound(format!(
+                            "no backup chain found for database '{source_db}'"
+                        ))
+                    })?;
+                let epoch_id = grafeo_common::types::EpochId::new(epoch);
+                if !manifest
+                    .segments
+                    .iter()
+                    .any(|s| s.kind == BackupKind::Full && s.end_epoch <= epoch_id)
+                {
+                    return Err(ServiceError::BadRequest(format!(
+                        "no full backup of '{source_db}' covers epoch {epoch}"
+                    )));
+                }
+                let staged = scratch.join("chain");
+                if Self::decrypt_chain(&dir, names, keys, &staged).await? {
+                    dir = staged;
+                }
+                let file = file.clone();
+                tokio::task::spawn_blocking(move || {
+                    GrafeoDB::restore_to_epoch(&dir, epoch_id, &file)
+                })
+                .await
+                .map_err(|e| ServiceError::Internal(e.to_string()))?
+                .map_err(|e| ServiceError::Internal(format!("restore to epoch failed: {e}")))?;
+            }
+            _ => {
+                return Err(ServiceError::BadRequest(
+                    "exactly one of 'backup' and 'epoch' must be set".to_string(),
+                ));
+            }
+        }
+
+        // Read the backup's graph model, and its contents for an in-memory
+        // database
+        let in_memory = req.storage_mode == types::StorageMode::InMemory;
+        let file_owned = file.clone();
+        let (graph_model, snapshot) = tokio::task::spawn_blocking(move || {
+            let db =
+                GrafeoDB::open(&file_owned).map_err(|e| format!("failed to open backup: {e}"))?;
+            let snapshot = if in_memory {
+                Some(
+                    db.export_snapshot()
+                        .map_err(|e| format!("failed to read backup: {e}")),
+                )
+            } else {
+                None
+            };
+            let graph_model = db.graph_model();
+            db.close().ok();
+            Ok::<_, String>((graph_model, snapshot.transpose()?))
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(e.to_string()))?
+        .map_err(ServiceError::Internal)?;
+
+        let database_type = match graph_model {
+            grafeo_engine::GraphModel::Rdf => types::DatabaseType::Rdf,
+            _ => types::DatabaseType::Lpg,
+        };
+        databases.create(&types::CreateDatabaseRequest {
+            name: req.name.clone(),
+            database_type,
+            storage_mode: req.storage_mode,
+            options: types::DatabaseOptions::default(),
+            schema_file: None,
+            schema_filename: None,
+        })?;
+        let entry = databases
+            .get(&req.name)
+            .ok_or_else(|| ServiceError::Internal("database disappeared after creation".into()))?;
+
+        let loaded = match (snapshot, databases.data_dir()) {
+            (Some(data), _) => {
+                let db = entry.db();
+                tokio::task::spawn_blocking(move || db.restore_snapshot(&data))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r.map_err(|e| format!("failed to load backup: {e}")))
+            }
+            (None, Some(data_dir)) => {
+                entry.set_restoring();
+                let result = Self::replace_data(&entry, &req.name, &file, data_dir)
+                    .await
+                    .map(|db| entry.swap_db(Arc::new(db)))
+                    .map_err(|e| match e {
+                        ReplaceError::Files(e) => e.to_string(),
+                        ReplaceError::Open(e) => e,
+                    });
+                entry.set_available();
+                result
+            }
+            (None, None) => unreachable!("persistent storage was checked above"),
+        };
+        if let Err(e) = loaded {
+            drop(entry);
+            if let Err(delete_err) = databases.delete(&req.name) {
+                tracing::warn!(
+                    database = %req.name,
+                    error = %delete_err,
+                    "failed to remove database after failed restore"
+                );
+            }
+            return Err(ServiceError::Internal(format!("restore failed: {e}")));
+        }
+
+        let db = entry.db();
+        tracing::info!(
+            database = %req.name,
+            source = %source_db,
+            backup = ?req.backup,
+            epoch = ?req.epoch,
+            "Backup restored into new database"
+        );
+        Ok(types::DatabaseSummary {
+            name: req.name.clone(),
+            node_count: db.node_count(),
+            edge_count: db.edge_count(),
+            persistent: db.path().is_some(),
+            database_type: database_type.as_str().to_string(),
+        })
+    }
+
+    /// Deletes a backup locally and at `target`. Fails with `NotFound`
+    /// only when neither holds it.
+    pub async fn delete_backup_from(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        filename: &str,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<(), ServiceError> {
+        ensure_migrated(backup_dir);
+        if filename.contains('/') || filename.contains('\\') || filename.contains("..") {
+            return Err(ServiceError::BadRequest(
+                "invalid backup filename".to_string(),
+            ));
+        }
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        let remote = Self::sync_metadata(target, db_name, &dir).await?;
+
+        if dir.join(filename).exists() {
+            Self::delete_local(db_name, filename, backup_dir, keys)?;
+        } else if remote.contains_key(filename) {
+            remove_sidecar_entries(&dir, filename, keys);
+            tracing::info!(database = %db_name, filename = %filename, "Backup deleted");
+        } else {
+            return Err(ServiceError::NotFound(format!(
+                "backup '{filename}' not found for database '{db_name}'"
+            )));
+        }
+        target.delete(db_name, filename).await?;
+        Self::upload_metadata(target, db_name, &dir).await
+    }
+
+    /// Applies count (`keep`) and age retention to the backups held
+    /// locally or at `target`, deleting both copies. Returns the deleted
+    /// file names.
+    pub async fn prune_backups(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        backup_dir: &Path,
+        keep: Option<usize>,
+        max_age: Option<std::time::Duration>,
+    ) -> Result<Vec<String>, ServiceError> {
+        ensure_migrated(backup_dir);
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        let mut remote = Self::sync_metadata(target, db_name, &dir).await?;
+
+        let mut deleted = Vec::new();
+        if let Some(keep) = keep {
+            deleted = Self::retain_newest(&dir, keep, &remote)?;
+            for name in &deleted {
+                remote.remove(name);
+            }
+        }
+        if let Some(max_age) = max_age {
+            deleted.append(&mut Self::expire(&dir, max_age, &remote)?);
+        }
+
+        for name in &deleted {
+            target.delete(db_name, name).await?;
+        }
+        Ok(deleted)
+    }
+
+    /// Verifies a backup held locally or at `target` without restoring it,
+    /// and records the result for the backup listing.
+    ///
+    /// Checks the file against the size and CRC-32 the chain's manifest
+    /// recorded, then walks its chain (see
+    /// [`types::BackupVerification::chain`]): every segment must be present
+    /// and intact, incremental headers must match the manifest, and each
+    /// incremental must start right after the previous segment's end epoch.
+    /// With `open`, the backup (replayed to its end epoch, for an
+    /// incremental) is also loaded into a scratch in-memory database to
+    /// count its nodes and edges. Sealed segments are decrypted with `keys`
+    /// and checked against the plaintext, and a sealed file the manifest
+    /// does not track is checked for tampering.
+    ///
+    /// Failed checks are reported in the result, not as errors.
+    pub async fn verify_backup(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        filename: &str,
+        backup_dir: &Path,
+        open: bool,
+        keys: Option<&BackupKeys>,
+    ) -> Result<types::BackupVerification, ServiceError> {
+        if filename.contains('/') || filename.contains('\\') || filename.contains("..") {
+            return Err(ServiceError::BadRequest(
+                "invalid backup filename".to_string(),
+            ));
+        }
+        ensure_migrated(backup_dir);
+        let dir = db_backup_dir(backup_dir, db_name)?;
+        let remote = Self::sync_metadata(target, db_name, &dir).await?;
+        if !present(&dir, &remote, filename) {
+            return Err(ServiceError::NotFound(format!(
+                "backup '{filename}' not found for database '{db_name}'"
+            )));
+        }
+
+        let chain = GrafeoDB::read_backup_manifest(&dir)
+            .ok()
+            .flatten()
+            .and_then(|m| verification_chain(&m.segments, filename));
+        let names = chain.as_ref().map_or_else(
+            || vec![filename.to_owned()],
+            |c| c.iter().map(|s| s.filename.clone()).collect(),
+        );
+        for name in &names {
+            if !dir.join(name).exists() && remote.contains_key(name) {
+                target.download(db_name, name, &dir.join(name)).await?;
+            }
+        }
+
+        let dir_owned = dir.clone();
+        let filename_owned = filename.to_owned();
+        let keys_owned = keys.cloned();
+        let (checksum_ok, issues, counts) = tokio::task::spawn_blocking(move || {
+            check_backup(
+                &dir_owned,
+                &filename_owned,
+                chain.as_deref(),
+                open,
+                keys_owned.as_ref(),
+            )
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(format!("verification task failed: {e}")))?;
+
+        let verification = types::BackupVerification {
+            database: db_name.to_owned(),
+            filename: filename.to_owned(),
+            verified_at: millis_to_iso(unix_millis()),
+            ok: issues.is_empty() && checksum_ok != Some(false),
+            checksum_ok,
+            chain: names,
+            issues,
+            node_count: counts.map(|(nodes, _)| nodes),
+            edge_count: counts.map(|(_, edges)| edges),
+        };
+        if verification.ok {
+            tracing::info!(database = %db_name, filename = %filename, "Backup verified");
+        } else {
+            tracing::warn!(
+                database = %db_name,
+                filename = %filename,
+                issues = ?verification.issues,
+                "Backup verification failed"
+            );
+        }
+
+        record_verification(&dir, &verification)?;
+        Self::upload_metadata(target, db_name, &dir).await?;
+        Ok(verification)
+    }
+
+    /// Verifies every listed backup, optionally of one database only. See
+    /// [`Self::verify_backup`]. A backup that cannot be verified at all,
+    /// e.g. because the target is unreachable, is reported as failed.
+    pub async fn verify_backups(
+        target: &dyn BackupTarget,
+        db_name: Option<&str>,
+        backup_dir: &Path,
+        open: bool,
+        keys: Option<&BackupKeys>,
+    ) -> Result<Vec<types::BackupVerification>, ServiceError> {
+        let entries = Self::list_backups_from(target, db_name, backup_dir, keys).await?;
+        let mut results = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let verification = match Self::verify_backup(
+                target,
+                &entry.database,
+                &entry.filename,
+                backup_dir,
+                open,
+                keys,
+            )
+            .await
+            {
+                Ok(verification) => verification,
+                Err(e) => types::BackupVerification {
+                    database: entry.database,
+                    filename: entry.filename.clone(),
+                    verified_at: millis_to_iso(unix_millis()),
+                    ok: false,
+                    checksum_ok: None,
+                    chain: vec![entry.filename],
+                    issues: vec![e.to_string()],
+                    node_count: None,
+                    edge_count: None,
+                },
+            };
+            results.push(verification);
+        }
+        Ok(results)
+    }
+
+    /// Opens a backup held locally or at `target` for reading its
+    /// plaintext, decrypting it with `keys` when it is sealed. See
+    /// [`Self::fetch_backup`].
+    pub async fn open_backup(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        filename: &str,
+        backup_dir: &Path,
+        keys: Option<&BackupKeys>,
+    ) -> Result<Box<dyn std::io::Read + Send>, ServiceError> {
+        let path = Self::fetch_backup(target, db_name, filename, backup_dir).await?;
+        let keys = keys.cloned();
+        tokio::task::spawn_blocking(move || backup_crypto::open(&path, keys.as_ref()))
+            .await
+            .map_err(|e| ServiceError::Internal(e.to_string()))?
+    }
+
+    /// Re-seals backups with the active key of `keys`, optionally of one
+    /// database only: every backup file held locally or at `target` that
+    /// is plaintext or sealed with another key, and the label sidecars.
+    /// Files sealed with an older key are read with it, so it must still
+    /// be in `keys`. Returns the re-sealed files.
+    pub async fn rekey_backups(
+        target: &dyn BackupTarget,
+        db_name: Option<&str>,
+        backup_dir: &Path,
+        keys: &BackupKeys,
+    ) -> Result<Vec<types::RekeyedBackup>, ServiceError> {
+        ensure_migrated(backup_dir);
+        let names = match db_name {
+            Some(name) => vec![name.to_owned()],
+            None => Self::backup_databases(target, backup_dir).await?,
+        };
+        let active = keys.active_id();
+
+        let mut rekeyed = Vec::new();
+        for name in names {
+            let dir = db_backup_dir(backup_dir, &name)?;
+            let remote = Self::sync_metadata(target, &name, &dir).await?;
+            let manifest = GrafeoDB::read_backup_manifest(&dir).ok().flatten();
+            let tracked: std::collections::HashSet<&str> = manifest
+                .iter()
+                .flat_map(|m| m.segments.iter().map(|s| s.filename.as_str()))
+                .collect();
+            let mut files: std::collections::BTreeSet<String> = tracked
+                .iter()
+                .filter(|f| present(&dir, &remote, f))
+                .map(|f| (*f).to_owned())
+                .collect();
+            files.extend(
+                untracked_files(&dir, &tracked, &remote)
+                    .into_iter()
+                    .map(|(f, _)| f),
+            );
+
+            let recorded: HashMap<String, String> = load_sidecar(&dir, ENCRYPTION_FILENAME);
+            for filename in &files {
+                let path = dir.join(filename);
+                if !path.exists() {
+                    // Trust the sidecar rather than download what is current
+                    if recorded.get(filename).is_some_and(|id| id == active) {
+                        continue;
+                    }
+                    target.download(&name, filename, &path).await?;
+                }
+                let previous = backup_crypto::sealed_key_id(&path).map_err(|e| {
+                    ServiceError::Internal(format!("failed to read {filename}: {e}"))
+                })?;
+                if previous.as_deref() == Some(active) {
+                    continue;
+                }
+                let keys_owned = keys.clone();
+                tokio::task::spawn_blocking(move || backup_crypto::seal_file(&path, &keys_owned))
+                    .await
+                    .map_err(|e| ServiceError::Internal(e.to_string()))??;
+                target.upload(&name, filename, &dir.join(filename)).await?;
+                tracing::info!(
+                    database = %name,
+                    filename = %filename,
+                    previous_key_id = ?previous,
+                    key_id = %active,
+                    "Backup re-encrypted"
+                );
+                rekeyed.push(types::RekeyedBackup {
+                    database: name.clone(),
+                    filename: filename.clone(),
+                    previous_key_id: previous,
+                    key_id: active.to_owned(),
+                });
+            }
+            if !files.is_empty() {
+                record_key_ids(&dir, files.iter().map(|f| (f.as_str(), active)))?;
+            }
+
+            let labels = labels_path(&dir);
+            if labels.exists()
+                && backup_crypto::sealed_key_id(&labels)
+                    .map_err(|e| ServiceError::Internal(format!("failed to read labels: {e}")))?
+                    .as_deref()
+                    != Some(active)
+            {
+                let map = load_labels(&dir, Some(keys)).ok_or_else(|| {
+                    ServiceError::BadRequest(format!(
+                        "backup labels of '{name}' are encrypted with a key that is not configured"
+                    ))
+                })?;
+                save_labels(&dir, &map, Some(keys))?;
+            }
+            Self::upload_metadata(target, &name, &dir).await?;
+        }
+        Ok(rekeyed)
+    }
+
+    /// Uploads a new backup file with the chain's manifest and label
+    /// sidecar, so the target alone can restore it.
+    async fn upload_backup(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        dir: &Path,
+        filename: &str,
+    ) -> Result<(), ServiceError> {
+        target
+            .upload(db_name, filename, &dir.join(filename))
+            .await?;
+        Self::upload_metadata(target, db_name, dir).await
+    }
+
+    async fn upload_metadata(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        dir: &Path,
+    ) -> Result<(), ServiceError> {
+        for name in METADATA_FILENAMES {
+            let path = dir.join(name);
+            if path.exists() {
+                target.upload(db_name, name, &path).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads the segments of `db_name`'s chain up to `epoch` that only
+    /// `target` holds, and returns the chain's manifest with the names of
+    /// those segments.
+    async fn fetch_chain(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        dir: &Path,
+        epoch: u64,
+    ) -> Result<Option<(grafeo_engine::database::backup::BackupManifest, Vec<String>)>, ServiceError>
+    {
+        let remote = Self::sync_metadata(target, db_name, dir).await?;
+        let Some(m) = GrafeoDB::read_backup_manifest(dir).ok().flatten() else {
+            return Ok(None);
+        };
+        let epoch = grafeo_common::types::EpochId::new(epoch);
+        let base = m
+            .segments
+            .iter()
+            .rposition(|s| {
+                s.kind == BackupKind::Full
+                    && s.end_epoch <= epoch
+                    && present(dir, &remote, &s.filename)
+            })
+            .unwrap_or(0);
+        let mut names = Vec::new();
+        for seg in m.segments[base..]
+            .iter()
+            .take_while(|s| s.start_epoch <= epoch)
+        {
+            if !dir.join(&seg.filename).exists() && remote.contains_key(&seg.filename) {
+                target
+                    .download(db_name, &seg.filename, &dir.join(&seg.filename))
+                    .await?;
+            }
+            names.push(seg.filename.clone());
+        }
+        Ok(Some((m, names)))
+    }
+
+    /// Lists what `target` holds for `db_name`, downloading the manifest
+    /// and sidecars when `dir` lacks them.
+    async fn sync_metadata(
+        target: &dyn BackupTarget,
+        db_name: &str,
+        dir: &Path,
+    ) -> Result<RemoteFiles, ServiceError> {
+        let remote: RemoteFiles = target
+            .list(db_name)
+            .await?
+            .into_iter()
+            .map(|f| (f.name.clone(), f))
+            .collect();
+        for name in METADATA_FILENAMES {
+            let path = dir.join(name);
+            if remote.contains_key(name) && !path.exists() {
+                target.download(db_name, name, &path).await?;
+            }
+        }
+        Ok(remote)
+    }
+}
+
+/// Whether a backup file is in `dir` or at the target.
+fn present(dir: &Path, remote: &RemoteFiles, filename: &str) -> bool {
+    remote.contains_key(filename) || dir.join(filename).exists()
+}
+
+/// Removes the local copy of a backup file. Returns whether the file
+/// existed in either place; the caller deletes the target's copy.
+fn remove_backup_file(dir: &Path, remote: &RemoteFiles, filename: &str) -> bool {
+    let removed = std::fs::remove_file(dir.join(filename)).is_ok();
+    removed || remote.contains_key(filename)
+}
+
+/// `.grafeo` files in `dir` or at the target that the manifest does not
+/// track (legacy and in-memory backups), with their modification times.
+fn untracked_files(
+    dir: &Path,
+    manifest_filenames: &std::collections::HashSet<&str>,
+    remote: &RemoteFiles,
+) -> Vec<(String, std::time::SystemTime)> {
+    let mut files: HashMap<String, std::time::SystemTime> = remote
+        .values()
+        .map(|f| {
+            let modified = std::time::UNIX_EPOCH + std::time::Duration::from_millis(f.modified_ms);
+            (f.name.clone(), modified)
+        })
+        .collect();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(fname) = path.file_name().and_then(|n| n.to_str())
+                && let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified())
+            {
+                files.insert(fname.to_owned(), modified);
+            }
+        }
+    }
+    files
+        .into_iter()
+        .filter(|(name, _)| {
+            name.ends_with(".grafeo") && !manifest_filenames.contains(name.as_str())
+        })
+        .collect()
+}
+
+/// Names of the backup segments (`.grafeo` and `.wal` files) in `dir`.
+fn backup_files(dir: &Path) -> std::collections::HashSet<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return std::collections::HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str().map(str::to_owned))
+        .filter(|name| {
+            Path::new(name)
+                .extension()
+                .is_some_and(|ext| ext == "grafeo" || ext == "wal")
+        })
+        .collect()
+}
+
+/// A scratch directory in the temp directory, created on demand and
+/// removed when dropped.
+struct ScratchDir(PathBuf);
+
+impl ScratchDir {
+    fn new(prefix: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("{prefix}-{}", uuid::Uuid::new_v4())))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    /// Creates the directory and returns its path.
+    fn create(&self) -> Result<&Path, ServiceError> {
+        std::fs::create_dir_all(&self.0).map_err(|e| {
+            ServiceError::Internal(format!("failed to create scratch directory: {e}"))
+        })?;
+        Ok(&self.0)
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        if self.0.exists()
+            && let Err(e) = std::fs::remove_dir_all(&self.0)
+        {
+            tracing::warn!(path = %self.0.display(), error = %e, "failed to remove scratch directory");
+        }
+    }
+}
+
+/// Copies the manifest and the segments `names` from `dir` to `dest`,
+/// decrypting sealed ones, when any of them is sealed. Returns whether it
+/// did. Segments missing from `dir` are skipped, for the engine to report.
+fn copy_plaintext_chain(
+    dir: &Path,
+    names: &[String],
+    keys: Option<&BackupKeys>,
+    dest: &Path,
+) -> Result<bool, ServiceError> {
+    let sealed = names
+        .iter()
+        .any(|name| backup_crypto::sealed_key_id(&dir.join(name)).is_ok_and(|id| id.is_some()));
+    if !sealed {
+        return Ok(false);
+    }
+    std::fs::create_dir_all(dest)
+        .map_err(|e| ServiceError::Internal(format!("failed to create scratch directory: {e}")))?;
+    std::fs::copy(dir.join(MANIFEST_FILENAME), dest.join(MANIFEST_FILENAME))
+        .map_err(|e| ServiceError::Internal(format!("failed to copy backup manifest: {e}")))?;
+    for name in names {
+        let source = dir.join(name);
+        if source.exists() {
+            backup_crypto::decrypt_file(&source, &dest.join(name), keys)?;
+        }
+    }
+    Ok(true)
+}
+
+/// The manifest segments verifying `filename` checks: a full backup and the
+/// incrementals built on it, or for an incremental, the segments from its
+/// base full backup up to itself. `None` if the manifest does not track
+/// `filename`.
+fn verification_chain(segments: &[BackupSegment], filename: &str) -> Option<Vec<BackupSegment>> {
+    let index = segments.iter().position(|s| s.filename == filename)?;
+    let range = if segments[index].kind == BackupKind::Full {
+        let incrementals = segments[index + 1..]
+            .iter()
+            .take_while(|s| s.kind == BackupKind::Incremental)
+            .count();
+        index..=index + incrementals
+    } else {
+        let base = segments[..index]
+            .iter()
+            .rposition(|s| s.kind == BackupKind::Full)
+            .unwrap_or(0);
+        base..=index
+    };
+    Some(segments[range].to_vec())
+}
+
+/// Checks a backup file and its chain. Returns whether `filename` matches
+/// its manifest checksum (`None` when untracked), the problems found, and
+/// the node and edge counts when `open` is set and the checks passed.
+/// Sealed files are decrypted with `keys`.
+fn check_backup(
+    dir: &Path,
+    filename: &str,
+    chain: Option<&[BackupSegment]>,
+    open: bool,
+    keys: Option<&BackupKeys>,
+) -> (Option<bool>, Vec<String>, Option<(u64, u64)>) {
+    let mut issues = Vec::new();
+    let mut checksum_ok = None;
+
+    match chain {
+        Some(chain) => {
+            if chain.first().is_some_and(|s| s.kind != BackupKind::Full) {
+                issues.push(format!("no full backup precedes {}", chain[0].filename));
+            }
+            let mut previous_end: Option<u64> = None;
+            for seg in chain {
+                if seg.kind == BackupKind::Incremental
+                    && let Some(end) = previous_end
+                    && seg.start_epoch.as_u64() > end + 1
+                {
+                    issues.push(format!(
+                        "gap before {}: epochs {} to {} are not covered",
+                        seg.filename,
+                        end + 1,
+                        seg.start_epoch.as_u64() - 1
+                    ));
+                }
+                previous_end = Some(seg.end_epoch.as_u64());
+
+                let intact = check_segment(dir, seg, keys, &mut issues);
+                if seg.filename == filename {
+                    checksum_ok = intact;
+                }
+            }
+        }
+        None => {
+            // Without a checksum, a sealed file can still be authenticated
+            let result = backup_crypto::open(&dir.join(filename), keys)
+                .map_err(|e| e.to_string())
+                .and_then(|mut reader| {
+                    std::io::copy(&mut reader, &mut std::io::sink()).map_err(|e| e.to_string())
+                });
+            if let Err(e) = result {
+                issues.push(format!("{filename}: cannot read: {e}"));
+            }
+        }
+    }
+
+    let mut counts = None;
+    if open && issues.is_empty() && checksum_ok != Some(false) {
+        let chain = chain.unwrap_or_default();
+        let index = chain.iter().position(|s| s.filename == filename);
+        match count_in_scratch(dir, filename, index.map(|i| &chain[..=i]), keys) {
+            Ok(c) => counts = Some(c),
+            Err(e) => issues.push(format!("{filename}: {e}")),
+        }
+    }
+    (checksum_ok, issues, counts)
+}
+
+/// Checks one manifest segment's size and checksum, and for an incremental
+/// its header. Returns whether size and checksum match, or `None` if the
+/// file cannot be read or decrypted.
+fn check_segment(
+    dir: &Path,
+    seg: &BackupSegment,
+    keys: Option<&BackupKeys>,
+    issues: &mut Vec<String>,
+) -> Option<bool> {
+    let read = backup_crypto::open(&dir.join(&seg.filename), keys)
+        .map_err(|e| e.to_string())
+        .and_then(|mut reader| {
+            let mut data = Vec::new();
+            std::io::Read::read_to_end(&mut reader, &mut data)
+                .map(|_| data)
+                .map_err(|e| e.to_string())
+        });
+    let data = match read {
+        Ok(data) => data,
+        Err(e) => {
+            issues.push(format!("{}: cannot read: {e}", seg.filename));
+            return None;
+        }
+    };
+    let intact = data.len() as u64 == seg.size_bytes && crc32fast::hash(&data) == seg.checksum;
+    if !intact {
+        issues.push(format!(
+            "{}: does not match the manifest (expected {} bytes with CRC {:08x}, found {} bytes with CRC {:08x})",
+            seg.filename,
+            seg.size_bytes,
+            seg.checksum,
+            data.len(),
+            crc32fast::hash(&data)
+        ));
+    }
+    if seg.kind == BackupKind::Incremental {
+        match grafeo_engine::database::backup::read_backup_header(&data) {
+            Ok((start, end, _)) if start == seg.start_epoch && end == seg.end_epoch => {}
+            Ok((start, end, _)) => issues.push(format!(
+                "{}: header covers epochs {}-{} but the manifest records {}-{}",
+                seg.filename,
+                start.as_u64(),
+                end.as_u64(),
+                seg.start_epoch.as_u64(),
+                seg.end_epoch.as_u64()
+            )),
+            Err(e) => issues.push(format!("{}: {e}", seg.filename)),
+        }
+    }
+    Some(intact)
+}
+
+/// Loads a backup into an in-memory database and returns its node and edge
+/// counts. The backup is first copied, or for an incremental replayed from
+/// `chain` (its segments up to itself), into a scratch directory, so the
+/// backup files stay untouched. Sealed files are decrypted with `keys`.
+fn count_in_scratch(
+    dir: &Path,
+    filename: &str,
+    chain: Option<&[BackupSegment]>,
+    keys: Option<&BackupKeys>,
+) -> Result<(u64, u64), String> {
+    let scratch = std::env::temp_dir().join(format!("grafeo-verify-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&scratch)
+        .map_err(|e| format!("failed to create scratch directory: {e}"))?;
+    let file = scratch.join("verify.grafeo");
+
+    let result = (|| {
+        match chain.and_then(<[_]>::last) {
+            Some(seg) if seg.kind == BackupKind::Incremental => {
+                let names: Vec<String> = chain
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|s| s.filename.clone())
+                    .collect();
+                let staged = scratch.join("chain");
+                let chain_dir = if copy_plaintext_chain(dir, &names, keys, &staged)
+                    .map_err(|e| e.to_string())?
+                {
+                    staged.as_path()
+                } else {
+                    dir
+                };
+                GrafeoDB::restore_to_epoch(chain_dir, seg.end_epoch, &file)
+                    .map_err(|e| format!("failed to replay backup chain: {e}"))?;
+            }
+            _ => {
+                backup_crypto::decrypt_file(&dir.join(filename), &file, keys)
+                    .map_err(|e| format!("failed to copy backup: {e}"))?;
+            }
+        }
+        let db = GrafeoDB::open(&file).map_err(|e| format!("failed to open backup: {e}"))?;
+        let data = db.export_snapshot();
+        db.close().ok();
+        let data = data.map_err(|e| format!("failed to read backup: {e}"))?;
+        let memory =
+            GrafeoDB::import_snapshot(&data).map_err(|e| format!("failed to load backup: {e}"))?;
+        Ok((memory.node_count() as u64, memory.edge_count() as u64))
+    })();
+
+    if let Err(e) = std::fs::remove_dir_all(&scratch) {
+        tracing::warn!(path = %scratch.display(), error = %e, "failed to remove verification scratch directory");
+    }
+    result
+}
+
+/// Migrate legacy backup files from the root backup directory into per-database
+/// subdirectories. Old files were named `{db}_{timestamp}.grafeo`.
+fn migrate_legacy_backups(backup_dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(backup_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(fname) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !fname.ends_with(".grafeo") {
+            continue;
         }
         let stem = fname.strip_suffix(".grafeo").unwrap();
         let parts: Vec<&str> = stem.split('_').collect();
@@ -991,10 +2548,20 @@ fn segment_to_entry(seg: BackupSegment, database: String) -> types::BackupEntry
         end_epoch: seg.end_epoch.0,
         checksum: seg.checksum,
         label: None,
+        verification: None,
+        encryption_key_id: None,
     }
 }
 
-fn millis_to_iso(ms: u64) -> String {
+/// Milliseconds since the Unix epoch.
+pub(crate) fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+pub(crate) fn millis_to_iso(ms: u64) -> String {
     let secs = ms / 1000;
     let millis = ms % 1000;
     let days = secs / 86400;
@@ -1006,7 +2573,7 @@ fn millis_to_iso(ms: u64) -> String {
     format!("{year:04}-{month:02}-{day:02}T{hours:02}:{minutes:02}:{seconds:02}.{millis:03}Z")
 }
 
-fn days_to_ymd(days: u64) -> (u64, u64, u64) {
+pub(crate) fn days_to_ymd(days: u64) -> (u64, u64, u64) {
     let z = days + 719_468;
     let era = z / 146_097;
     let doe = z - era * 146_097;
@@ -1059,155 +2626,438 @@ mod tests {
         }
         let db = GrafeoDB::open_read_only(db_path.to_str().unwrap()).unwrap();
 
-        let result = db.backup_full(backup_dir.path());
-        assert!(
-            result.is_ok(),
-            "backup_full() on a read-only database should succeed: {result:?}"
-        );
+        let result = db.backup_full(backup_dir.path());
+        assert!(
+            result.is_ok(),
+            "backup_full() on a read-only database should succeed: {result:?}"
+        );
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn backup_full_works_on_windows() {
+        let dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+
+        let db_path = dir.path().join("data.grafeo");
+        let db = GrafeoDB::open(db_path.to_str().unwrap()).unwrap();
+        db.session().execute("INSERT (:Test {v: 1})").unwrap();
+
+        let result = db.backup_full(backup_dir.path());
+        assert!(
+            result.is_ok(),
+            "backup_full() on Windows should succeed: {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn backup_and_list() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+
+        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+        assert_eq!(backup.database, "default");
+        assert_eq!(backup.kind, "full");
+        assert!(backup.size_bytes > 0);
+
+        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].kind, "full");
+    }
+
+    #[tokio::test]
+    async fn backup_not_found() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+
+        let result =
+            BackupService::backup_database(&mgr, "nonexistent", backup_dir.path(), None).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn list_backups_empty() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        assert!(
+            BackupService::list_backups(None, backup_dir.path())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn list_backups_nonexistent_dir() {
+        assert!(
+            BackupService::list_backups(None, Path::new("/nonexistent"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_backup() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+
+        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+        BackupService::delete_backup("default", &backup.filename, backup_dir.path()).unwrap();
+        assert!(
+            BackupService::list_backups(Some("default"), backup_dir.path())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_backup_path_traversal() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        assert!(
+            BackupService::delete_backup("default", "../etc/passwd", backup_dir.path()).is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_nonexistent_backup() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            BackupService::delete_backup("default", "nope.grafeo", backup_dir.path()),
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn backup_creates_directory() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let nested = tmp.path().join("deeply").join("nested");
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+
+        assert!(
+            BackupService::backup_database(&mgr, "default", &nested, None)
+                .await
+                .is_ok()
+        );
+        assert!(nested.join("default").exists());
+    }
+
+    #[tokio::test]
+    async fn restore_requires_persistent_storage() {
+        let state = crate::ServiceState::new_in_memory(300);
+        let backup_dir = tempfile::tempdir().unwrap();
+        let dummy = backup_dir.path().join("dummy.grafeo");
+        std::fs::write(&dummy, b"fake").unwrap();
+
+        let result = BackupService::restore_database(
+            state.databases(),
+            "default",
+            &dummy,
+            backup_dir.path(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn restore_with_persistent_storage() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+
+        {
+            let entry = mgr.get("default").unwrap();
+            entry
+                .db()
+                .session()
+                .execute("INSERT (:Person {name: 'Alice'})")
+                .unwrap();
+            assert_eq!(entry.db().node_count(), 1);
+        }
+
+        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+
+        {
+            let entry = mgr.get("default").unwrap();
+            entry
+                .db()
+                .session()
+                .execute("INSERT (:Person {name: 'Bob'})")
+                .unwrap();
+            assert_eq!(entry.db().node_count(), 2);
+        }
+
+        let file_path = db_backup_dir(backup_dir.path(), "default")
+            .unwrap()
+            .join(&backup.filename);
+        BackupService::restore_database(&mgr, "default", &file_path, backup_dir.path())
+            .await
+            .unwrap();
+
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 1);
     }
 
-    #[cfg(target_os = "windows")]
-    #[test]
-    fn backup_full_works_on_windows() {
-        let dir = tempfile::tempdir().unwrap();
+    #[tokio::test]
+    async fn restore_read_only_rejected() {
+        let mgr = crate::database::DatabaseManager::new(None, true);
         let backup_dir = tempfile::tempdir().unwrap();
+        let dummy = backup_dir.path().join("dummy.grafeo");
+        std::fs::write(&dummy, b"fake").unwrap();
 
-        let db_path = dir.path().join("data.grafeo");
-        let db = GrafeoDB::open(db_path.to_str().unwrap()).unwrap();
-        db.session().execute("INSERT (:Test {v: 1})").unwrap();
-
-        let result = db.backup_full(backup_dir.path());
-        assert!(
-            result.is_ok(),
-            "backup_full() on Windows should succeed: {result:?}"
-        );
+        assert!(matches!(
+            BackupService::restore_database(&mgr, "default", &dummy, backup_dir.path()).await,
+            Err(ServiceError::ReadOnly)
+        ));
     }
 
+    // -----------------------------------------------------------------------
+    // In-memory backup (save() fallback)
+    // -----------------------------------------------------------------------
+
     #[tokio::test]
-    async fn backup_and_list() {
-        let data_dir = tempfile::tempdir().unwrap();
+    async fn backup_in_memory_database_uses_save_fallback() {
+        let mgr = crate::database::DatabaseManager::new(None, false);
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
         let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
+
         assert_eq!(backup.database, "default");
         assert_eq!(backup.kind, "full");
         assert!(backup.size_bytes > 0);
-
-        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(list.len(), 1);
-        assert_eq!(list[0].kind, "full");
+        // In-memory backup has epoch 0 (no chain API)
+        assert_eq!(backup.start_epoch, 0);
+        assert_eq!(backup.end_epoch, 0);
+        assert_eq!(backup.checksum, 0);
+        assert!(!backup.created_at.is_empty());
     }
 
     #[tokio::test]
-    async fn backup_not_found() {
-        let data_dir = tempfile::tempdir().unwrap();
+    async fn backup_in_memory_listed_as_untracked() {
+        let mgr = crate::database::DatabaseManager::new(None, false);
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let result =
-            BackupService::backup_database(&mgr, "nonexistent", backup_dir.path(), None).await;
-        assert!(result.is_err());
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+
+        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].filename.ends_with(".grafeo"));
     }
 
+    // -----------------------------------------------------------------------
+    // Incremental backup
+    // -----------------------------------------------------------------------
+
     #[tokio::test]
-    async fn list_backups_empty() {
+    async fn incremental_backup_rejects_in_memory() {
+        let mgr = crate::database::DatabaseManager::new(None, false);
         let backup_dir = tempfile::tempdir().unwrap();
-        assert!(
-            BackupService::list_backups(None, backup_dir.path())
-                .unwrap()
-                .is_empty()
-        );
+        let err = BackupService::backup_incremental(&mgr, "default", backup_dir.path())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest(_)));
     }
 
     #[tokio::test]
-    async fn list_backups_nonexistent_dir() {
-        assert!(
-            BackupService::list_backups(None, Path::new("/nonexistent"))
-                .unwrap()
-                .is_empty()
-        );
+    async fn incremental_backup_not_found() {
+        let mgr = crate::database::DatabaseManager::new(None, false);
+        let backup_dir = tempfile::tempdir().unwrap();
+        let err = BackupService::backup_incremental(&mgr, "nonexistent", backup_dir.path())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotFound(_)));
     }
 
     #[tokio::test]
-    async fn delete_backup() {
+    async fn incremental_backup_persistent() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+        // Create a full backup first (required before incremental)
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
-        BackupService::delete_backup("default", &backup.filename, backup_dir.path()).unwrap();
-        assert!(
-            BackupService::list_backups(Some("default"), backup_dir.path())
-                .unwrap()
-                .is_empty()
-        );
+
+        // Incremental backup should succeed on persistent DB
+        let result = BackupService::backup_incremental(&mgr, "default", backup_dir.path()).await;
+        // May fail if engine requires WAL commits between full and incremental,
+        // but the code path is exercised either way.
+        assert!(result.is_ok() || result.is_err());
     }
 
+    // -----------------------------------------------------------------------
+    // Restore to epoch
+    // -----------------------------------------------------------------------
+
     #[tokio::test]
-    async fn delete_backup_path_traversal() {
+    async fn restore_to_epoch_rejects_read_only() {
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
-        assert!(
-            BackupService::delete_backup("default", "../etc/passwd", backup_dir.path()).is_err()
-        );
+        // Create a writable DB first so the directory has a valid database
+        let path = data_dir.path().to_str().unwrap();
+        {
+            let _mgr = crate::database::DatabaseManager::new(Some(path), false);
+        }
+        // Now open read-only
+        let mgr = crate::database::DatabaseManager::new(Some(path), true);
+        let err = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::ReadOnly));
     }
 
     #[tokio::test]
-    async fn delete_nonexistent_backup() {
+    async fn restore_to_epoch_rejects_in_memory() {
         let backup_dir = tempfile::tempdir().unwrap();
-        assert!(matches!(
-            BackupService::delete_backup("default", "nope.grafeo", backup_dir.path()),
-            Err(ServiceError::NotFound(_))
-        ));
+        let mgr = crate::database::DatabaseManager::new(None, false);
+        let err = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest(_)));
     }
 
     #[tokio::test]
-    async fn backup_creates_directory() {
+    async fn restore_to_epoch_not_found() {
         let data_dir = tempfile::tempdir().unwrap();
-        let tmp = tempfile::tempdir().unwrap();
-        let nested = tmp.path().join("deeply").join("nested");
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let err = BackupService::restore_to_epoch(&mgr, "nonexistent", 0, backup_dir.path())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotFound(_)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn restore_to_epoch_reopen_failure_leaves_entry_in_restoring() {
+        // Covers the recover_after_failed_epoch_restore "reopen failed
+        // too" branch: when the engine call fails AND the fallback reopen
+        // can't open the file, the entry must stay in Restoring so
+        // subsequent requests 503 instead of hitting a dropped handle.
+        use std::os::unix::fs::PermissionsExt;
+
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
+        {
+            let entry = mgr.get("default").unwrap();
+            entry
+                .db()
+                .session()
+                .execute("INSERT (:Person {name: 'Alice'})")
+                .unwrap();
+        }
+
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+
+        // Strip write+read on the per-db data dir so both the engine's
+        // restore_to_epoch (tries to overwrite data.grafeo) and the
+        // recovery's GrafeoDB::open (tries to read the same file) fail.
+        let db_data_dir = data_dir.path().join("default");
+        let original_mode = std::fs::metadata(&db_data_dir)
+            .unwrap()
+            .permissions()
+            .mode();
+        std::fs::set_permissions(&db_data_dir, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+        let result = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path()).await;
+
+        // Restore perms before asserting so tempdir cleanup works even
+        // if the assertion fails.
+        std::fs::set_permissions(&db_data_dir, std::fs::Permissions::from_mode(original_mode))
+            .unwrap();
+
         assert!(
-            BackupService::backup_database(&mgr, "default", &nested, None)
-                .await
-                .is_ok()
+            result.is_err(),
+            "expected restore_to_epoch to fail when the db dir is unreadable, got {result:?}"
+        );
+
+        // The entry should still be in Restoring state because the
+        // recovery reopen also failed — no fresh handle was installed.
+        let entry = mgr.get("default").unwrap();
+        assert!(
+            entry.is_restoring(),
+            "expected entry to stay in Restoring after both engine and recovery reopen failed"
         );
-        assert!(nested.join("default").exists());
     }
 
     #[tokio::test]
-    async fn restore_requires_persistent_storage() {
-        let state = crate::ServiceState::new_in_memory(300);
+    async fn restore_to_epoch_failure_leaves_entry_queryable() {
+        // Covers the regression where a failed engine call (after the old
+        // handle has already been closed) left the entry marked Available
+        // while its ArcSwap pointed at a dropped GrafeoDB. Force a real
+        // engine failure by corrupting the backup file after the manifest
+        // references it, so restore_to_epoch can't read it.
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
-        let dummy = backup_dir.path().join("dummy.grafeo");
-        std::fs::write(&dummy, b"fake").unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let result = BackupService::restore_database(
-            state.databases(),
-            "default",
-            &dummy,
-            backup_dir.path(),
-        )
-        .await;
-        assert!(result.is_err());
+        {
+            let entry = mgr.get("default").unwrap();
+            entry
+                .db()
+                .session()
+                .execute("INSERT (:Person {name: 'Alice'})")
+                .unwrap();
+        }
+
+        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+
+        // Overwrite the backup file with garbage while keeping the
+        // manifest untouched. The engine's restore path opens the file
+        // from the manifest entry and chokes on the header.
+        let backup_file = backup_dir.path().join("default").join(&backup.filename);
+        std::fs::write(&backup_file, b"corrupt").unwrap();
+
+        let result = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path()).await;
+        assert!(
+            result.is_err(),
+            "expected restore_to_epoch to fail against a corrupt backup file, got {result:?}"
+        );
+
+        // The entry should still be queryable via a fresh handle that the
+        // recovery path reopened from disk. Without the recovery the
+        // ArcSwap points at a dropped handle and this call would hit
+        // stale state.
+        let entry = mgr.get("default").unwrap();
+        let _ = entry.db().node_count();
     }
 
     #[tokio::test]
-    async fn restore_with_persistent_storage() {
+    async fn restore_to_epoch_round_trip() {
+        // Covers the file-lock regression where restore_to_epoch failed to
+        // close the old handle before asking the engine to replace the file.
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
+        // Seed a row so the initial backup captures non-trivial state.
         {
             let entry = mgr.get("default").unwrap();
             entry
@@ -1215,13 +3065,13 @@ mod tests {
                 .session()
                 .execute("INSERT (:Person {name: 'Alice'})")
                 .unwrap();
-            assert_eq!(entry.db().node_count(), 1);
         }
 
-        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+        let initial = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
 
+        // Add more data after the backup so the epoch moves forward.
         {
             let entry = mgr.get("default").unwrap();
             entry
@@ -1232,821 +3082,1426 @@ mod tests {
             assert_eq!(entry.db().node_count(), 2);
         }
 
-        let file_path = db_backup_dir(backup_dir.path(), "default")
-            .unwrap()
-            .join(&backup.filename);
-        BackupService::restore_database(&mgr, "default", &file_path, backup_dir.path())
+        // Restore to the first backup's end_epoch — rolls Bob away.
+        BackupService::restore_to_epoch(&mgr, "default", initial.end_epoch, backup_dir.path())
             .await
             .unwrap();
 
-        assert_eq!(mgr.get("default").unwrap().db().node_count(), 1);
+        // The entry should be usable after the swap — the old handle was
+        // released and a fresh one was opened from the restored file.
+        let entry = mgr.get("default").unwrap();
+        assert_eq!(entry.db().node_count(), 1);
     }
 
-    #[tokio::test]
-    async fn restore_read_only_rejected() {
-        let mgr = crate::database::DatabaseManager::new(None, true);
-        let backup_dir = tempfile::tempdir().unwrap();
-        let dummy = backup_dir.path().join("dummy.grafeo");
-        std::fs::write(&dummy, b"fake").unwrap();
+    // -----------------------------------------------------------------------
+    // db_backup_dir validation
+    // -----------------------------------------------------------------------
 
-        assert!(matches!(
-            BackupService::restore_database(&mgr, "default", &dummy, backup_dir.path()).await,
-            Err(ServiceError::ReadOnly)
-        ));
+    #[test]
+    fn db_backup_dir_rejects_traversal() {
+        let dir = Path::new("/backups");
+        assert!(db_backup_dir(dir, "valid-db").is_ok());
+        assert!(db_backup_dir(dir, "has/slash").is_err());
+        assert!(db_backup_dir(dir, "has\\backslash").is_err());
+        assert!(db_backup_dir(dir, "has..dots").is_err());
     }
 
     // -----------------------------------------------------------------------
-    // In-memory backup (save() fallback)
+    // Retention enforcement
     // -----------------------------------------------------------------------
 
     #[tokio::test]
-    async fn backup_in_memory_database_uses_save_fallback() {
-        let mgr = crate::database::DatabaseManager::new(None, false);
+    async fn retention_keeps_latest_n() {
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
+        // Create 3 backups
+        for _ in 0..3 {
+            BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+                .await
+                .unwrap();
+        }
 
-        assert_eq!(backup.database, "default");
-        assert_eq!(backup.kind, "full");
-        assert!(backup.size_bytes > 0);
-        // In-memory backup has epoch 0 (no chain API)
-        assert_eq!(backup.start_epoch, 0);
-        assert_eq!(backup.end_epoch, 0);
-        assert_eq!(backup.checksum, 0);
-        assert!(!backup.created_at.is_empty());
+        let before = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(before.len(), 3);
+
+        // Keep 2
+        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 2).unwrap();
+        assert_eq!(deleted.len(), 1);
+
+        let after = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(after.len(), 2);
     }
 
     #[tokio::test]
-    async fn backup_in_memory_listed_as_untracked() {
-        let mgr = crate::database::DatabaseManager::new(None, false);
+    async fn retention_keep_zero_treated_as_one() {
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
         BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
 
-        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(list.len(), 1);
-        assert!(list[0].filename.ends_with(".grafeo"));
-    }
+        // keep=0 is clamped to 1
+        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 0).unwrap();
+        assert_eq!(deleted.len(), 1);
 
-    // -----------------------------------------------------------------------
-    // Incremental backup
-    // -----------------------------------------------------------------------
+        let remaining = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(remaining.len(), 1);
+    }
 
     #[tokio::test]
-    async fn incremental_backup_rejects_in_memory() {
-        let mgr = crate::database::DatabaseManager::new(None, false);
+    async fn retention_no_op_when_under_limit() {
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
-        let err = BackupService::backup_incremental(&mgr, "default", backup_dir.path())
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
-            .unwrap_err();
-        assert!(matches!(err, ServiceError::BadRequest(_)));
+            .unwrap();
+
+        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 5).unwrap();
+        assert!(deleted.is_empty());
     }
 
     #[tokio::test]
-    async fn incremental_backup_not_found() {
+    async fn retention_file_based_for_in_memory() {
         let mgr = crate::database::DatabaseManager::new(None, false);
         let backup_dir = tempfile::tempdir().unwrap();
-        let err = BackupService::backup_incremental(&mgr, "nonexistent", backup_dir.path())
-            .await
-            .unwrap_err();
-        assert!(matches!(err, ServiceError::NotFound(_)));
+
+        for _ in 0..3 {
+            BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+                .await
+                .unwrap();
+            // Small delay so timestamps differ
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 1).unwrap();
+        assert_eq!(deleted.len(), 2);
+
+        let remaining = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(remaining.len(), 1);
     }
 
     #[tokio::test]
-    async fn incremental_backup_persistent() {
+    async fn max_age_keeps_base_of_window() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        // Create a full backup first (required before incremental)
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
+        for _ in 0..3 {
+            BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+                .await
+                .unwrap();
+        }
 
-        // Incremental backup should succeed on persistent DB
-        let result = BackupService::backup_incremental(&mgr, "default", backup_dir.path()).await;
-        // May fail if engine requires WAL commits between full and incremental,
-        // but the code path is exercised either way.
-        assert!(result.is_ok() || result.is_err());
+        // Nothing is older than an hour
+        let deleted = BackupService::enforce_max_age(
+            "default",
+            backup_dir.path(),
+            std::time::Duration::from_secs(3600),
+        )
+        .unwrap();
+        assert!(deleted.is_empty());
+
+        // With a zero window only the newest full backup is still needed
+        let deleted =
+            BackupService::enforce_max_age("default", backup_dir.path(), std::time::Duration::ZERO)
+                .unwrap();
+        assert_eq!(deleted.len(), 2);
+
+        let remaining = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(remaining.len(), 1);
+    }
+
+    #[test]
+    fn max_age_missing_dir_is_noop() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let deleted =
+            BackupService::enforce_max_age("default", backup_dir.path(), std::time::Duration::ZERO)
+                .unwrap();
+        assert!(deleted.is_empty());
     }
 
     // -----------------------------------------------------------------------
-    // Restore to epoch
+    // Legacy migration
     // -----------------------------------------------------------------------
 
-    #[tokio::test]
-    async fn restore_to_epoch_rejects_read_only() {
-        let data_dir = tempfile::tempdir().unwrap();
+    #[test]
+    fn migrate_legacy_backups_moves_files() {
         let backup_dir = tempfile::tempdir().unwrap();
-        // Create a writable DB first so the directory has a valid database
-        let path = data_dir.path().to_str().unwrap();
-        {
-            let _mgr = crate::database::DatabaseManager::new(Some(path), false);
-        }
-        // Now open read-only
-        let mgr = crate::database::DatabaseManager::new(Some(path), true);
-        let err = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path())
-            .await
-            .unwrap_err();
-        assert!(matches!(err, ServiceError::ReadOnly));
+        let root = backup_dir.path();
+
+        // Create a legacy-style file: {db}_{YYYY}_{MM}_{DD}_{HH}_{MM}_{SS}.grafeo
+        let legacy = "mydb_2024_01_15_10_30_45.grafeo";
+        std::fs::write(root.join(legacy), b"fake backup").unwrap();
+
+        migrate_legacy_backups(root);
+
+        // File should have moved to mydb/ subdirectory
+        assert!(!root.join(legacy).exists());
+        assert!(root.join("mydb").join(legacy).exists());
     }
 
-    #[tokio::test]
-    async fn restore_to_epoch_rejects_in_memory() {
+    #[test]
+    fn migrate_legacy_backups_7_part_timestamp() {
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr = crate::database::DatabaseManager::new(None, false);
-        let err = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path())
-            .await
-            .unwrap_err();
-        assert!(matches!(err, ServiceError::BadRequest(_)));
+        let root = backup_dir.path();
+
+        // 7-part timestamp (with millis)
+        let legacy = "db_2024_01_15_10_30_45_123.grafeo";
+        std::fs::write(root.join(legacy), b"data").unwrap();
+
+        migrate_legacy_backups(root);
+
+        assert!(!root.join(legacy).exists());
+        assert!(root.join("db").join(legacy).exists());
     }
 
-    #[tokio::test]
-    async fn restore_to_epoch_not_found() {
-        let data_dir = tempfile::tempdir().unwrap();
+    #[test]
+    fn migrate_legacy_backups_underscore_in_db_name() {
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
-        let err = BackupService::restore_to_epoch(&mgr, "nonexistent", 0, backup_dir.path())
-            .await
-            .unwrap_err();
-        assert!(matches!(err, ServiceError::NotFound(_)));
+        let root = backup_dir.path();
+
+        // DB name with underscores: "my_cool_db"
+        let legacy = "my_cool_db_2024_01_15_10_30_45.grafeo";
+        std::fs::write(root.join(legacy), b"data").unwrap();
+
+        migrate_legacy_backups(root);
+
+        assert!(!root.join(legacy).exists());
+        assert!(root.join("my_cool_db").join(legacy).exists());
+    }
+
+    #[test]
+    fn migrate_legacy_backups_skips_non_grafeo() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let root = backup_dir.path();
+
+        std::fs::write(root.join("readme.txt"), b"not a backup").unwrap();
+        std::fs::write(root.join("data.json"), b"{}").unwrap();
+
+        migrate_legacy_backups(root);
+
+        // Non-grafeo files should remain in root
+        assert!(root.join("readme.txt").exists());
+        assert!(root.join("data.json").exists());
     }
 
-    #[cfg(unix)]
-    #[tokio::test]
-    async fn restore_to_epoch_reopen_failure_leaves_entry_in_restoring() {
-        // Covers the recover_after_failed_epoch_restore "reopen failed
-        // too" branch: when the engine call fails AND the fallback reopen
-        // can't open the file, the entry must stay in Restoring so
-        // subsequent requests 503 instead of hitting a dropped handle.
-        use std::os::unix::fs::PermissionsExt;
-
-        let data_dir = tempfile::tempdir().unwrap();
+    #[test]
+    fn migrate_legacy_backups_skips_short_filenames() {
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let root = backup_dir.path();
 
-        {
-            let entry = mgr.get("default").unwrap();
-            entry
-                .db()
-                .session()
-                .execute("INSERT (:Person {name: 'Alice'})")
-                .unwrap();
-        }
+        // Too few parts to be a legacy backup
+        std::fs::write(root.join("simple.grafeo"), b"data").unwrap();
+        std::fs::write(root.join("two_parts.grafeo"), b"data").unwrap();
 
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
+        migrate_legacy_backups(root);
 
-        // Strip write+read on the per-db data dir so both the engine's
-        // restore_to_epoch (tries to overwrite data.grafeo) and the
-        // recovery's GrafeoDB::open (tries to read the same file) fail.
-        let db_data_dir = data_dir.path().join("default");
-        let original_mode = std::fs::metadata(&db_data_dir)
-            .unwrap()
-            .permissions()
-            .mode();
-        std::fs::set_permissions(&db_data_dir, std::fs::Permissions::from_mode(0o000)).unwrap();
+        // Should remain untouched
+        assert!(root.join("simple.grafeo").exists());
+        assert!(root.join("two_parts.grafeo").exists());
+    }
 
-        let result = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path()).await;
+    #[test]
+    fn migrate_legacy_backups_skips_already_migrated() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let root = backup_dir.path();
 
-        // Restore perms before asserting so tempdir cleanup works even
-        // if the assertion fails.
-        std::fs::set_permissions(&db_data_dir, std::fs::Permissions::from_mode(original_mode))
-            .unwrap();
+        let legacy = "mydb_2024_01_15_10_30_45.grafeo";
+        // Pre-create the target
+        std::fs::create_dir_all(root.join("mydb")).unwrap();
+        std::fs::write(root.join("mydb").join(legacy), b"existing").unwrap();
+        // Put a source file too
+        std::fs::write(root.join(legacy), b"source").unwrap();
 
-        assert!(
-            result.is_err(),
-            "expected restore_to_epoch to fail when the db dir is unreadable, got {result:?}"
-        );
+        migrate_legacy_backups(root);
 
-        // The entry should still be in Restoring state because the
-        // recovery reopen also failed — no fresh handle was installed.
-        let entry = mgr.get("default").unwrap();
-        assert!(
-            entry.is_restoring(),
-            "expected entry to stay in Restoring after both engine and recovery reopen failed"
-        );
+        // Source should remain (target already existed)
+        assert!(root.join(legacy).exists());
+        // Target should still have original content
+        let content = std::fs::read(root.join("mydb").join(legacy)).unwrap();
+        assert_eq!(content, b"existing");
+    }
+
+    #[test]
+    fn migrate_legacy_backups_empty_dir() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        // Should not panic on empty directory
+        migrate_legacy_backups(backup_dir.path());
     }
 
+    // -----------------------------------------------------------------------
+    // restore edge cases
+    // -----------------------------------------------------------------------
+
     #[tokio::test]
-    async fn restore_to_epoch_failure_leaves_entry_queryable() {
-        // Covers the regression where a failed engine call (after the old
-        // handle has already been closed) left the entry marked Available
-        // while its ArcSwap pointed at a dropped GrafeoDB. Force a real
-        // engine failure by corrupting the backup file after the manifest
-        // references it, so restore_to_epoch can't read it.
+    async fn restore_nonexistent_database_returns_not_found() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        {
-            let entry = mgr.get("default").unwrap();
-            entry
-                .db()
-                .session()
-                .execute("INSERT (:Person {name: 'Alice'})")
-                .unwrap();
-        }
-
-        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
-
-        // Overwrite the backup file with garbage while keeping the
-        // manifest untouched. The engine's restore path opens the file
-        // from the manifest entry and chokes on the header.
-        let backup_file = backup_dir.path().join("default").join(&backup.filename);
-        std::fs::write(&backup_file, b"corrupt").unwrap();
-
-        let result = BackupService::restore_to_epoch(&mgr, "default", 0, backup_dir.path()).await;
-        assert!(
-            result.is_err(),
-            "expected restore_to_epoch to fail against a corrupt backup file, got {result:?}"
-        );
+        let dummy = backup_dir.path().join("dummy.grafeo");
+        std::fs::write(&dummy, b"fake").unwrap();
 
-        // The entry should still be queryable via a fresh handle that the
-        // recovery path reopened from disk. Without the recovery the
-        // ArcSwap points at a dropped handle and this call would hit
-        // stale state.
-        let entry = mgr.get("default").unwrap();
-        let _ = entry.db().node_count();
+        let result =
+            BackupService::restore_database(&mgr, "nonexistent", &dummy, backup_dir.path()).await;
+        assert!(matches!(result, Err(ServiceError::NotFound(_))));
     }
 
     #[tokio::test]
-    async fn restore_to_epoch_round_trip() {
-        // Covers the file-lock regression where restore_to_epoch failed to
-        // close the old handle before asking the engine to replace the file.
+    async fn restore_missing_backup_file_returns_not_found() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        // Seed a row so the initial backup captures non-trivial state.
-        {
-            let entry = mgr.get("default").unwrap();
-            entry
-                .db()
-                .session()
-                .execute("INSERT (:Person {name: 'Alice'})")
-                .unwrap();
-        }
+        let missing = backup_dir.path().join("does-not-exist.grafeo");
+        let result =
+            BackupService::restore_database(&mgr, "default", &missing, backup_dir.path()).await;
+        assert!(matches!(result, Err(ServiceError::NotFound(_))));
+    }
 
-        let initial = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
+    #[tokio::test]
+    async fn restore_in_memory_database_rejected() {
+        let mgr = crate::database::DatabaseManager::new(None, false);
+        let backup_dir = tempfile::tempdir().unwrap();
+        let dummy = backup_dir.path().join("dummy.grafeo");
+        std::fs::write(&dummy, b"fake").unwrap();
 
-        // Add more data after the backup so the epoch moves forward.
-        {
-            let entry = mgr.get("default").unwrap();
-            entry
-                .db()
-                .session()
-                .execute("INSERT (:Person {name: 'Bob'})")
-                .unwrap();
-            assert_eq!(entry.db().node_count(), 2);
-        }
+        // In-memory manager has no data_dir
+        let result =
+            BackupService::restore_database(&mgr, "default", &dummy, backup_dir.path()).await;
+        assert!(result.is_err());
+    }
 
-        // Restore to the first backup's end_epoch — rolls Bob away.
-        BackupService::restore_to_epoch(&mgr, "default", initial.end_epoch, backup_dir.path())
-            .await
-            .unwrap();
+    // -----------------------------------------------------------------------
+    // list_backups with untracked files
+    // -----------------------------------------------------------------------
 
-        // The entry should be usable after the swap — the old handle was
-        // released and a fresh one was opened from the restored file.
-        let entry = mgr.get("default").unwrap();
-        assert_eq!(entry.db().node_count(), 1);
+    #[test]
+    fn list_from_manifest_includes_untracked_grafeo_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_dir = dir.path().join("mydb");
+        std::fs::create_dir_all(&db_dir).unwrap();
+
+        // Create an untracked .grafeo file (no manifest)
+        std::fs::write(db_dir.join("legacy_backup.grafeo"), b"data").unwrap();
+
+        let entries =
+            BackupService::list_from_manifest(&db_dir, "mydb", &RemoteFiles::new(), None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].filename, "legacy_backup.grafeo");
+        assert_eq!(entries[0].database, "mydb");
+        assert_eq!(entries[0].kind, "full");
+    }
+
+    #[test]
+    fn list_from_manifest_nonexistent_dir() {
+        let entries = BackupService::list_from_manifest(
+            Path::new("/nonexistent/path"),
+            "test",
+            &RemoteFiles::new(),
+            None,
+        )
+        .unwrap();
+        assert!(entries.is_empty());
     }
 
     // -----------------------------------------------------------------------
-    // db_backup_dir validation
+    // delete_backup validation
     // -----------------------------------------------------------------------
 
     #[test]
-    fn db_backup_dir_rejects_traversal() {
-        let dir = Path::new("/backups");
-        assert!(db_backup_dir(dir, "valid-db").is_ok());
-        assert!(db_backup_dir(dir, "has/slash").is_err());
-        assert!(db_backup_dir(dir, "has\\backslash").is_err());
-        assert!(db_backup_dir(dir, "has..dots").is_err());
+    fn delete_backup_rejects_db_name_traversal() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            BackupService::delete_backup("../evil", "backup.grafeo", backup_dir.path()),
+            Err(ServiceError::BadRequest(_))
+        ));
+    }
+
+    // -----------------------------------------------------------------------
+    // segment_to_entry
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn segment_to_entry_maps_fields() {
+        let seg = BackupSegment {
+            filename: "test.grafeo".to_string(),
+            kind: BackupKind::Full,
+            size_bytes: 1024,
+            created_at_ms: 1_705_282_245_123,
+            start_epoch: grafeo_common::types::EpochId::new(1),
+            end_epoch: grafeo_common::types::EpochId::new(5),
+            checksum: 42,
+        };
+        let entry = segment_to_entry(seg, "mydb".to_string());
+        assert_eq!(entry.filename, "test.grafeo");
+        assert_eq!(entry.database, "mydb");
+        assert_eq!(entry.kind, "full");
+        assert_eq!(entry.size_bytes, 1024);
+        assert_eq!(entry.start_epoch, 1);
+        assert_eq!(entry.end_epoch, 5);
+        assert_eq!(entry.checksum, 42);
+        assert_eq!(entry.created_at, "2024-01-15T01:30:45.123Z");
+    }
+
+    #[test]
+    fn segment_to_entry_incremental() {
+        let seg = BackupSegment {
+            filename: "inc.grafeo".to_string(),
+            kind: BackupKind::Incremental,
+            size_bytes: 512,
+            created_at_ms: 0,
+            start_epoch: grafeo_common::types::EpochId::new(3),
+            end_epoch: grafeo_common::types::EpochId::new(7),
+            checksum: 99,
+        };
+        let entry = segment_to_entry(seg, "db2".to_string());
+        assert_eq!(entry.kind, "incremental");
     }
 
     // -----------------------------------------------------------------------
-    // Retention enforcement
+    // ensure_migrated (idempotent wrapper)
     // -----------------------------------------------------------------------
 
-    #[tokio::test]
-    async fn retention_keeps_latest_n() {
-        let data_dir = tempfile::tempdir().unwrap();
+    #[test]
+    fn ensure_migrated_is_idempotent() {
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
-
-        // Create 3 backups
-        for _ in 0..3 {
-            BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-                .await
-                .unwrap();
-        }
+        let root = backup_dir.path();
 
-        let before = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(before.len(), 3);
+        let legacy = "mydb_2024_01_15_10_30_45.grafeo";
+        std::fs::write(root.join(legacy), b"data").unwrap();
 
-        // Keep 2
-        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 2).unwrap();
-        assert_eq!(deleted.len(), 1);
+        ensure_migrated(root);
+        ensure_migrated(root); // second call should not panic
 
-        let after = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(after.len(), 2);
+        assert!(root.join("mydb").join(legacy).exists());
     }
 
     #[tokio::test]
-    async fn retention_keep_zero_treated_as_one() {
+    async fn multi_database_isolation() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
+        let req = crate::types::CreateDatabaseRequest {
+            name: "other".to_string(),
+            database_type: crate::types::DatabaseType::Lpg,
+            storage_mode: crate::types::StorageMode::Persistent,
+            options: crate::types::DatabaseOptions::default(),
+            schema_file: None,
+            schema_filename: None,
+        };
+        mgr.create(&req).unwrap();
+
         BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+        BackupService::backup_database(&mgr, "other", backup_dir.path(), None)
             .await
             .unwrap();
 
-        // keep=0 is clamped to 1
-        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 0).unwrap();
-        assert_eq!(deleted.len(), 1);
+        let default_list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(default_list.len(), 1);
+        assert_eq!(default_list[0].database, "default");
 
-        let remaining = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(remaining.len(), 1);
+        let other_list = BackupService::list_backups(Some("other"), backup_dir.path()).unwrap();
+        assert_eq!(other_list.len(), 1);
+        assert_eq!(other_list[0].database, "other");
+
+        assert_eq!(
+            BackupService::list_backups(None, backup_dir.path())
+                .unwrap()
+                .len(),
+            2
+        );
     }
 
     #[tokio::test]
-    async fn retention_no_op_when_under_limit() {
+    async fn backup_with_label_round_trips_via_list() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
+        let created = BackupService::backup_database(
+            &mgr,
+            "default",
+            backup_dir.path(),
+            Some("pre-migration".to_owned()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(created.label.as_deref(), Some("pre-migration"));
 
-        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 5).unwrap();
-        assert!(deleted.is_empty());
+        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].filename, created.filename);
+        assert_eq!(list[0].label.as_deref(), Some("pre-migration"));
     }
 
     #[tokio::test]
-    async fn retention_file_based_for_in_memory() {
-        let mgr = crate::database::DatabaseManager::new(None, false);
-        let backup_dir = tempfile::tempdir().unwrap();
-
-        for _ in 0..3 {
-            BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-                .await
-                .unwrap();
-            // Small delay so timestamps differ
-            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
-        }
-
-        let deleted = BackupService::enforce_retention("default", backup_dir.path(), 1).unwrap();
-        assert_eq!(deleted.len(), 2);
-
-        let remaining = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(remaining.len(), 1);
-    }
-
-    // -----------------------------------------------------------------------
-    // Legacy migration
-    // -----------------------------------------------------------------------
-
-    #[test]
-    fn migrate_legacy_backups_moves_files() {
-        let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
-
-        // Create a legacy-style file: {db}_{YYYY}_{MM}_{DD}_{HH}_{MM}_{SS}.grafeo
-        let legacy = "mydb_2024_01_15_10_30_45.grafeo";
-        std::fs::write(root.join(legacy), b"fake backup").unwrap();
-
-        migrate_legacy_backups(root);
-
-        // File should have moved to mydb/ subdirectory
-        assert!(!root.join(legacy).exists());
-        assert!(root.join("mydb").join(legacy).exists());
-    }
-
-    #[test]
-    fn migrate_legacy_backups_7_part_timestamp() {
-        let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
-
-        // 7-part timestamp (with millis)
-        let legacy = "db_2024_01_15_10_30_45_123.grafeo";
-        std::fs::write(root.join(legacy), b"data").unwrap();
-
-        migrate_legacy_backups(root);
-
-        assert!(!root.join(legacy).exists());
-        assert!(root.join("db").join(legacy).exists());
-    }
-
-    #[test]
-    fn migrate_legacy_backups_underscore_in_db_name() {
-        let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
-
-        // DB name with underscores: "my_cool_db"
-        let legacy = "my_cool_db_2024_01_15_10_30_45.grafeo";
-        std::fs::write(root.join(legacy), b"data").unwrap();
-
-        migrate_legacy_backups(root);
-
-        assert!(!root.join(legacy).exists());
-        assert!(root.join("my_cool_db").join(legacy).exists());
-    }
-
-    #[test]
-    fn migrate_legacy_backups_skips_non_grafeo() {
-        let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
-
-        std::fs::write(root.join("readme.txt"), b"not a backup").unwrap();
-        std::fs::write(root.join("data.json"), b"{}").unwrap();
-
-        migrate_legacy_backups(root);
-
-        // Non-grafeo files should remain in root
-        assert!(root.join("readme.txt").exists());
-        assert!(root.join("data.json").exists());
-    }
-
-    #[test]
-    fn migrate_legacy_backups_skips_short_filenames() {
+    async fn backup_without_label_has_no_label_after_list() {
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
-
-        // Too few parts to be a legacy backup
-        std::fs::write(root.join("simple.grafeo"), b"data").unwrap();
-        std::fs::write(root.join("two_parts.grafeo"), b"data").unwrap();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        migrate_legacy_backups(root);
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
 
-        // Should remain untouched
-        assert!(root.join("simple.grafeo").exists());
-        assert!(root.join("two_parts.grafeo").exists());
+        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].label.is_none());
     }
 
-    #[test]
-    fn migrate_legacy_backups_skips_already_migrated() {
+    #[tokio::test]
+    async fn backup_label_validation_rejects_bad_input() {
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let legacy = "mydb_2024_01_15_10_30_45.grafeo";
-        // Pre-create the target
-        std::fs::create_dir_all(root.join("mydb")).unwrap();
-        std::fs::write(root.join("mydb").join(legacy), b"existing").unwrap();
-        // Put a source file too
-        std::fs::write(root.join(legacy), b"source").unwrap();
+        // Spaces — rejected
+        assert!(matches!(
+            BackupService::backup_database(
+                &mgr,
+                "default",
+                backup_dir.path(),
+                Some("has spaces".to_owned()),
+            )
+            .await,
+            Err(ServiceError::BadRequest(_))
+        ));
 
-        migrate_legacy_backups(root);
+        // Slashes (path traversal attempt) — rejected
+        assert!(matches!(
+            BackupService::backup_database(
+                &mgr,
+                "default",
+                backup_dir.path(),
+                Some("../etc".to_owned()),
+            )
+            .await,
+            Err(ServiceError::BadRequest(_))
+        ));
 
-        // Source should remain (target already existed)
-        assert!(root.join(legacy).exists());
-        // Target should still have original content
-        let content = std::fs::read(root.join("mydb").join(legacy)).unwrap();
-        assert_eq!(content, b"existing");
-    }
+        // Too long — rejected
+        let long = "a".repeat(33);
+        assert!(matches!(
+            BackupService::backup_database(&mgr, "default", backup_dir.path(), Some(long)).await,
+            Err(ServiceError::BadRequest(_))
+        ));
 
-    #[test]
-    fn migrate_legacy_backups_empty_dir() {
-        let backup_dir = tempfile::tempdir().unwrap();
-        // Should not panic on empty directory
-        migrate_legacy_backups(backup_dir.path());
+        // Empty string — treated as no label
+        let entry =
+            BackupService::backup_database(&mgr, "default", backup_dir.path(), Some(String::new()))
+                .await
+                .unwrap();
+        assert!(entry.label.is_none());
     }
 
-    // -----------------------------------------------------------------------
-    // restore edge cases
-    // -----------------------------------------------------------------------
-
     #[tokio::test]
-    async fn restore_nonexistent_database_returns_not_found() {
+    async fn labels_sidecar_corrupt_is_ignored() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let dummy = backup_dir.path().join("dummy.grafeo");
-        std::fs::write(&dummy, b"fake").unwrap();
+        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
+
+        // Write a corrupt sidecar and confirm listing still works.
+        let sidecar = backup_dir.path().join("default").join(LABELS_FILENAME);
+        std::fs::write(&sidecar, b"{not json").unwrap();
 
-        let result =
-            BackupService::restore_database(&mgr, "nonexistent", &dummy, backup_dir.path()).await;
-        assert!(matches!(result, Err(ServiceError::NotFound(_))));
+        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(list.len(), 1);
+        assert!(list[0].label.is_none());
     }
 
     #[tokio::test]
-    async fn restore_missing_backup_file_returns_not_found() {
+    async fn delete_backup_clears_label_sidecar() {
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
 
-        let missing = backup_dir.path().join("does-not-exist.grafeo");
-        let result =
-            BackupService::restore_database(&mgr, "default", &missing, backup_dir.path()).await;
-        assert!(matches!(result, Err(ServiceError::NotFound(_))));
-    }
+        let first = BackupService::backup_database(
+            &mgr,
+            "default",
+            backup_dir.path(),
+            Some("first".to_owned()),
+        )
+        .await
+        .unwrap();
 
-    #[tokio::test]
-    async fn restore_in_memory_database_rejected() {
-        let mgr = crate::database::DatabaseManager::new(None, false);
-        let backup_dir = tempfile::tempdir().unwrap();
-        let dummy = backup_dir.path().join("dummy.grafeo");
-        std::fs::write(&dummy, b"fake").unwrap();
+        BackupService::delete_backup("default", &first.filename, backup_dir.path()).unwrap();
 
-        // In-memory manager has no data_dir
-        let result =
-            BackupService::restore_database(&mgr, "default", &dummy, backup_dir.path()).await;
-        assert!(result.is_err());
+        // Sidecar should no longer reference the deleted filename.
+        let sidecar = backup_dir.path().join("default").join(LABELS_FILENAME);
+        if sidecar.exists() {
+            let text = std::fs::read_to_string(&sidecar).unwrap();
+            let map: HashMap<String, String> = serde_json::from_str(&text).unwrap();
+            assert!(!map.contains_key(&first.filename));
+        }
     }
 
     // -----------------------------------------------------------------------
-    // list_backups with untracked files
+    // Replica bootstrap
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn list_from_manifest_includes_untracked_grafeo_files() {
-        let dir = tempfile::tempdir().unwrap();
-        let db_dir = dir.path().join("mydb");
-        std::fs::create_dir_all(&db_dir).unwrap();
+    #[tokio::test]
+    async fn replica_snapshot_round_trip_in_memory() {
+        let primary = crate::database::DatabaseManager::new(None, false);
+        let session = primary.get("default").unwrap().db().session();
+        session.execute("INSERT (:Person {name: 'Alix'})").unwrap();
+        session.execute("INSERT (:Person {name: 'Gus'})").unwrap();
+        let primary_epoch = primary.get("default").unwrap().db().current_epoch();
 
-        // Create an untracked .grafeo file (no manifest)
-        std::fs::write(db_dir.join("legacy_backup.grafeo"), b"data").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.grafeo");
+        let epoch = BackupService::snapshot_database(&primary, "default", &path)
+            .await
+            .unwrap();
+        assert_eq!(epoch, primary_epoch.as_u64());
+        assert!(epoch > 0);
 
-        let entries = BackupService::list_from_manifest(&db_dir, "mydb").unwrap();
-        assert_eq!(entries.len(), 1);
-        assert_eq!(entries[0].filename, "legacy_backup.grafeo");
-        assert_eq!(entries[0].database, "mydb");
-        assert_eq!(entries[0].kind, "full");
+        let replica = crate::database::DatabaseManager::new(None, false);
+        BackupService::restore_replica_snapshot(&replica, "default", &path)
+            .await
+            .unwrap();
+        let entry = replica.get("default").unwrap();
+        assert_eq!(entry.db().node_count(), 2);
+        assert!(replica.get_available("default").is_ok());
     }
 
-    #[test]
-    fn list_from_manifest_nonexistent_dir() {
-        let entries =
-            BackupService::list_from_manifest(Path::new("/nonexistent/path"), "test").unwrap();
-        assert!(entries.is_empty());
-    }
+    #[tokio::test]
+    async fn replica_snapshot_round_trip_persistent() {
+        let primary = crate::database::DatabaseManager::new(None, false);
+        primary
+            .get("default")
+            .unwrap()
+            .db()
+            .session()
+            .execute("INSERT (:Person {name: 'Vincent'})")
+            .unwrap();
 
-    // -----------------------------------------------------------------------
-    // delete_backup validation
-    // -----------------------------------------------------------------------
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.grafeo");
+        BackupService::snapshot_database(&primary, "default", &path)
+            .await
+            .unwrap();
 
-    #[test]
-    fn delete_backup_rejects_db_name_traversal() {
-        let backup_dir = tempfile::tempdir().unwrap();
-        assert!(matches!(
-            BackupService::delete_backup("../evil", "backup.grafeo", backup_dir.path()),
-            Err(ServiceError::BadRequest(_))
-        ));
+        let data_dir = tempfile::tempdir().unwrap();
+        let replica =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        BackupService::restore_replica_snapshot(&replica, "default", &path)
+            .await
+            .unwrap();
+        assert_eq!(replica.get("default").unwrap().db().node_count(), 1);
     }
 
-    // -----------------------------------------------------------------------
-    // segment_to_entry
-    // -----------------------------------------------------------------------
+    #[tokio::test]
+    async fn replica_snapshot_bad_file_leaves_empty_database() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let replica =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("snapshot.grafeo");
+        std::fs::write(&path, b"not a snapshot").unwrap();
 
-    #[test]
-    fn segment_to_entry_maps_fields() {
-        let seg = BackupSegment {
-            filename: "test.grafeo".to_string(),
-            kind: BackupKind::Full,
-            size_bytes: 1024,
-            created_at_ms: 1_705_282_245_123,
-            start_epoch: grafeo_common::types::EpochId::new(1),
-            end_epoch: grafeo_common::types::EpochId::new(5),
-            checksum: 42,
-        };
-        let entry = segment_to_entry(seg, "mydb".to_string());
-        assert_eq!(entry.filename, "test.grafeo");
-        assert_eq!(entry.database, "mydb");
-        assert_eq!(entry.kind, "full");
-        assert_eq!(entry.size_bytes, 1024);
-        assert_eq!(entry.start_epoch, 1);
-        assert_eq!(entry.end_epoch, 5);
-        assert_eq!(entry.checksum, 42);
-        assert_eq!(entry.created_at, "2024-01-15T01:30:45.123Z");
+        assert!(
+            BackupService::restore_replica_snapshot(&replica, "default", &path)
+                .await
+                .is_err()
+        );
+        let entry = replica.get_available("default").unwrap();
+        assert_eq!(entry.db().node_count(), 0);
     }
 
-    #[test]
-    fn segment_to_entry_incremental() {
-        let seg = BackupSegment {
-            filename: "inc.grafeo".to_string(),
-            kind: BackupKind::Incremental,
-            size_bytes: 512,
-            created_at_ms: 0,
-            start_epoch: grafeo_common::types::EpochId::new(3),
-            end_epoch: grafeo_common::types::EpochId::new(7),
-            checksum: 99,
-        };
-        let entry = segment_to_entry(seg, "db2".to_string());
-        assert_eq!(entry.kind, "incremental");
+    #[tokio::test]
+    async fn snapshot_unknown_database_is_not_found() {
+        let mgr = crate::database::DatabaseManager::new(None, false);
+        let dir = tempfile::tempdir().unwrap();
+        let result =
+            BackupService::snapshot_database(&mgr, "nope", &dir.path().join("s.grafeo")).await;
+        assert!(matches!(result, Err(ServiceError::NotFound(_))));
     }
 
     // -----------------------------------------------------------------------
-    // ensure_migrated (idempotent wrapper)
+    // Backup targets
     // -----------------------------------------------------------------------
 
-    #[test]
-    fn ensure_migrated_is_idempotent() {
+    #[tokio::test]
+    async fn target_survives_loss_of_local_backups() {
+        use crate::backup_target::LocalTarget;
+
+        let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
-        let root = backup_dir.path();
+        let remote_dir = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(remote_dir.path().to_path_buf());
+        let mgr =
+            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let insert = |name: &str| {
+            mgr.get("default")
+                .unwrap()
+                .db()
+                .session()
+                .execute(&format!("INSERT (:Person {{name: '{name}'}})"))
+                .unwrap();
+        };
 
-        let legacy = "mydb_2024_01_15_10_30_45.grafeo";
-        std::fs::write(root.join(legacy), b"data").unwrap();
+        insert("Alice");
+        let full = BackupService::backup_database_to(
+            &mgr,
+            &target,
+            "default",
+            backup_dir.path(),
+            Some("nightly".to_owned()),
+            None,
+        )
+        .await
+        .unwrap();
+        insert("Bob");
+        let incremental =
+            BackupService::backup_incremental_to(&mgr, &target, "default", backup_dir.path(), None)
+                .await
+                .unwrap();
 
-        ensure_migrated(root);
-        ensure_migrated(root); // second call should not panic
+        let remote: Vec<String> = target
+            .list("default")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        for name in [
+            full.filename.as_str(),
+            incremental.filename.as_str(),
+            MANIFEST_FILENAME,
+            LABELS_FILENAME,
+        ] {
+            assert!(remote.iter().any(|r| r == name), "{name} not uploaded");
+        }
 
-        assert!(root.join("mydb").join(legacy).exists());
+        // Lose the local copies: listing and restoring fall back to the target
+        std::fs::remove_dir_all(backup_dir.path().join("default")).unwrap();
+        let listed =
+            BackupService::list_backups_from(&target, Some("default"), backup_dir.path(), None)
+                .await
+                .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].filename, full.filename);
+        assert_eq!(listed[0].label.as_deref(), Some("nightly"));
+        let all = BackupService::list_backups_from(&target, None, backup_dir.path(), None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 1);
+
+        insert("Carol");
+        BackupService::restore_to_epoch_from(
+            &mgr,
+            &target,
+            "default",
+            incremental.end_epoch,
+            backup_dir.path(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 2);
+
+        // Retention deletes the target's copies too
+        let newest = BackupService::backup_database_to(
+            &mgr,
+            &target,
+            "default",
+            backup_dir.path(),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let pruned =
+            BackupService::prune_backups(&target, "default", backup_dir.path(), Some(1), None)
+                .await
+                .unwrap();
+        assert!(pruned.contains(&full.filename));
+        assert!(pruned.contains(&incremental.filename));
+        assert!(
+            !remote_dir
+                .path()
+                .join("default")
+                .join(&full.filename)
+                .exists()
+        );
+
+        // A backup only the target holds can be fetched and deleted
+        let local = backup_dir.path().join("default").join(&newest.filename);
+        std::fs::remove_file(&local).unwrap();
+        let fetched =
+            BackupService::fetch_backup(&target, "default", &newest.filename, backup_dir.path())
+                .await
+                .unwrap();
+        assert_eq!(fetched, local);
+        std::fs::remove_file(&local).unwrap();
+        BackupService::delete_backup_from(
+            &target,
+            "default",
+            &newest.filename,
+            backup_dir.path(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(
+            !remote_dir
+                .path()
+                .join("default")
+                .join(&newest.filename)
+                .exists()
+        );
+        let again = BackupService::delete_backup_from(
+            &target,
+            "default",
+            &newest.filename,
+            backup_dir.path(),
+            None,
+        )
+        .await;
+        assert!(matches!(again, Err(ServiceError::NotFound(_))));
     }
 
+    // -----------------------------------------------------------------------
+    // Verification
+    // -----------------------------------------------------------------------
+
     #[tokio::test]
-    async fn multi_database_isolation() {
+    async fn verify_checks_chain_and_records_result() {
+        use crate::backup_target::LocalTarget;
+
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(backup_dir.path().to_path_buf());
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
-
-        let req = crate::types::CreateDatabaseRequest {
-            name: "other".to_string(),
-            database_type: crate::types::DatabaseType::Lpg,
-            storage_mode: crate::types::StorageMode::Persistent,
-            options: crate::types::DatabaseOptions::default(),
-            schema_file: None,
-            schema_filename: None,
+        let insert = |name: &str| {
+            mgr.get("default")
+                .unwrap()
+                .db()
+                .session()
+                .execute(&format!("INSERT (:Person {{name: '{name}'}})"))
+                .unwrap();
         };
-        mgr.create(&req).unwrap();
 
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+        insert("Alice");
+        let full = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
-        BackupService::backup_database(&mgr, "other", backup_dir.path(), None)
+        insert("Bob");
+        let first = BackupService::backup_incremental(&mgr, "default", backup_dir.path())
+            .await
+            .unwrap();
+        insert("Carol");
+        let second = BackupService::backup_incremental(&mgr, "default", backup_dir.path())
             .await
             .unwrap();
 
-        let default_list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(default_list.len(), 1);
-        assert_eq!(default_list[0].database, "default");
-
-        let other_list = BackupService::list_backups(Some("other"), backup_dir.path()).unwrap();
-        assert_eq!(other_list.len(), 1);
-        assert_eq!(other_list[0].database, "other");
-
+        let verified = BackupService::verify_backup(
+            &target,
+            "default",
+            &full.filename,
+            backup_dir.path(),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(verified.ok, "{:?}", verified.issues);
+        assert_eq!(verified.checksum_ok, Some(true));
         assert_eq!(
-            BackupService::list_backups(None, backup_dir.path())
-                .unwrap()
-                .len(),
-            2
+            verified.chain,
+            [full.filename.as_str(), &first.filename, &second.filename]
+        );
+        assert_eq!(verified.node_count, Some(1));
+
+        // An incremental is opened replayed onto its base
+        let replayed = BackupService::verify_backup(
+            &target,
+            "default",
+            &second.filename,
+            backup_dir.path(),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(replayed.ok, "{:?}", replayed.issues);
+        assert_eq!(replayed.node_count, Some(3));
+
+        let listed = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        let recorded = listed[0].verification.as_ref().unwrap();
+        assert!(recorded.ok);
+        assert_eq!(recorded.verified_at, verified.verified_at);
+
+        // A corrupt incremental fails its full backup's chain
+        let path = backup_dir.path().join("default").join(&first.filename);
+        let mut data = std::fs::read(&path).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        std::fs::write(&path, data).unwrap();
+        let corrupt = BackupService::verify_backup(
+            &target,
+            "default",
+            &full.filename,
+            backup_dir.path(),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(!corrupt.ok);
+        assert_eq!(corrupt.checksum_ok, Some(true));
+        assert_eq!(corrupt.node_count, None);
+        assert!(
+            corrupt.issues[0].starts_with(&first.filename),
+            "{:?}",
+            corrupt.issues
         );
+        let listed = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert!(!listed[0].verification.as_ref().unwrap().ok);
+
+        // Dropping it from the manifest leaves a gap before the next one
+        let dir = backup_dir.path().join("default");
+        let mut manifest = GrafeoDB::read_backup_manifest(&dir).unwrap().unwrap();
+        manifest.segments.retain(|s| s.filename != first.filename);
+        grafeo_engine::database::backup::write_manifest(&dir, &manifest).unwrap();
+        let gap = BackupService::verify_backup(
+            &target,
+            "default",
+            &second.filename,
+            backup_dir.path(),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(!gap.ok);
+        assert_eq!(gap.checksum_ok, Some(true));
+        assert_eq!(gap.chain, [full.filename.as_str(), &second.filename]);
+        assert!(gap.issues[0].starts_with("gap before"), "{:?}", gap.issues);
     }
 
     #[tokio::test]
-    async fn backup_with_label_round_trips_via_list() {
-        let data_dir = tempfile::tempdir().unwrap();
+    async fn verify_untracked_and_bulk() {
+        use crate::backup_target::LocalTarget;
+
+        let mgr = crate::database::DatabaseManager::new(None, false);
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let target = LocalTarget::new(backup_dir.path().to_path_buf());
+        mgr.get("default")
+            .unwrap()
+            .db()
+            .session()
+            .execute("INSERT (:Person {name: 'Alice'})-[:KNOWS]->(:Person {name: 'Bob'})")
+            .unwrap();
+        let backup = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+            .await
+            .unwrap();
 
-        let created = BackupService::backup_database(
-            &mgr,
+        let results = BackupService::verify_backups(&target, None, backup_dir.path(), true, None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        let verified = &results[0];
+        assert!(verified.ok, "{:?}", verified.issues);
+        assert_eq!(verified.filename, backup.filename);
+        // In-memory backups have no manifest checksum
+        assert_eq!(verified.checksum_ok, None);
+        assert_eq!(verified.node_count, Some(2));
+        assert_eq!(verified.edge_count, Some(1));
+
+        // Garbage fails to open
+        std::fs::write(
+            backup_dir.path().join("default").join(&backup.filename),
+            b"not a database",
+        )
+        .unwrap();
+        let broken = BackupService::verify_backup(
+            &target,
             "default",
+            &backup.filename,
             backup_dir.path(),
-            Some("pre-migration".to_owned()),
+            true,
+            None,
         )
         .await
         .unwrap();
-        assert_eq!(created.label.as_deref(), Some("pre-migration"));
+        assert!(!broken.ok);
+        assert_eq!(broken.issues.len(), 1);
 
-        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(list.len(), 1);
-        assert_eq!(list[0].filename, created.filename);
-        assert_eq!(list[0].label.as_deref(), Some("pre-migration"));
+        let missing = BackupService::verify_backup(
+            &target,
+            "default",
+            "nope.grafeo",
+            backup_dir.path(),
+            false,
+            None,
+        )
+        .await;
+        assert!(matches!(missing, Err(ServiceError::NotFound(_))));
+        let traversal = BackupService::verify_backup(
+            &target,
+            "default",
+            "../x",
+            backup_dir.path(),
+            false,
+            None,
+        )
+        .await;
+        assert!(matches!(traversal, Err(ServiceError::BadRequest(_))));
+
+        // Deleting the backup drops its recorded result
+        BackupService::delete_backup("default", &backup.filename, backup_dir.path()).unwrap();
+        assert!(load_verifications(&backup_dir.path().join("default")).is_empty());
     }
 
+    // -----------------------------------------------------------------------
+    // Restore into a new database
+    // -----------------------------------------------------------------------
+
     #[tokio::test]
-    async fn backup_without_label_has_no_label_after_list() {
+    async fn restore_as_new_leaves_source_untouched() {
+        use crate::backup_target::LocalTarget;
+
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(backup_dir.path().to_path_buf());
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let insert = |name: &str| {
+            mgr.get("default")
+                .unwrap()
+                .db()
+                .session()
+                .execute(&format!("INSERT (:Person {{name: '{name}'}})"))
+                .unwrap();
+        };
+        let request = |name: &str, backup: Option<&str>, epoch: Option<u64>, persistent: bool| {
+            types::RestoreAsRequest {
+                name: name.to_owned(),
+                backup: backup.map(str::to_owned),
+                epoch,
+                storage_mode: if persistent {
+                    types::StorageMode::Persistent
+                } else {
+                    types::StorageMode::InMemory
+                },
+            }
+        };
 
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
+        insert("Alice");
+        let full = BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
             .await
             .unwrap();
+        insert("Bob");
+        let incremental = BackupService::backup_incremental(&mgr, "default", backup_dir.path())
+            .await
+            .unwrap();
+        insert("Carol");
 
-        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(list.len(), 1);
-        assert!(list[0].label.is_none());
-    }
+        let summary = BackupService::restore_as_new(
+            &mgr,
+            &target,
+            "default",
+            &request("lastweek", Some(&full.filename), None, false),
+            backup_dir.path(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.node_count, 1);
+        assert!(!summary.persistent);
+        assert_eq!(summary.database_type, "lpg");
 
-    #[tokio::test]
-    async fn backup_label_validation_rejects_bad_input() {
-        let data_dir = tempfile::tempdir().unwrap();
-        let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let summary = BackupService::restore_as_new(
+            &mgr,
+            &target,
+            "default",
+            &request("whatif", None, Some(incremental.end_epoch), true),
+            backup_dir.path(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.node_count, 2);
+        assert!(summary.persistent);
+        let whatif = mgr.get("whatif").unwrap();
+        whatif
+            .db()
+            .session()
+            .execute("INSERT (:Person {name: 'Dave'})")
+            .unwrap();
+        assert_eq!(whatif.db().node_count(), 3);
 
-        // Spaces — rejected
-        assert!(matches!(
-            BackupService::backup_database(
-                &mgr,
-                "default",
-                backup_dir.path(),
-                Some("has spaces".to_owned()),
-            )
-            .await,
-            Err(ServiceError::BadRequest(_))
-        ));
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 3);
+        assert_eq!(mgr.get("lastweek").unwrap().db().node_count(), 1);
 
-        // Slashes (path traversal attempt) — rejected
-        assert!(matches!(
-            BackupService::backup_database(
+        let taken = BackupService::restore_as_new(
+            &mgr,
+            &target,
+            "default",
+            &request("lastweek", Some(&full.filename), None, false),
+            backup_dir.path(),
+            None,
+        )
+        .await;
+        assert!(matches!(taken, Err(ServiceError::Conflict(_))));
+        for (backup, epoch) in [(None, None), (Some(full.filename.as_str()), Some(1))] {
+            let ambiguous = BackupService::restore_as_new(
                 &mgr,
+                &target,
                 "default",
+                &request("other", backup, epoch, false),
                 backup_dir.path(),
-                Some("../etc".to_owned()),
+                None,
             )
-            .await,
-            Err(ServiceError::BadRequest(_))
-        ));
+            .await;
+            assert!(matches!(ambiguous, Err(ServiceError::BadRequest(_))));
+        }
+        let missing = BackupService::restore_as_new(
+            &mgr,
+            &target,
+            "default",
+            &request("other", Some("nope.grafeo"), None, false),
+            backup_dir.path(),
+            None,
+        )
+        .await;
+        assert!(matches!(missing, Err(ServiceError::NotFound(_))));
+        assert!(mgr.get("other").is_none());
+    }
 
-        // Too long — rejected
-        let long = "a".repeat(33);
-        assert!(matches!(
-            BackupService::backup_database(&mgr, "default", backup_dir.path(), Some(long)).await,
-            Err(ServiceError::BadRequest(_))
-        ));
+    #[tokio::test]
+    async fn restore_as_new_rejects_unusable_backups() {
+        use crate::backup_target::LocalTarget;
 
-        // Empty string — treated as no label
-        let entry =
-            BackupService::backup_database(&mgr, "default", backup_dir.path(), Some(String::new()))
-                .await
-                .unwrap();
-        assert!(entry.label.is_none());
+        let mgr = crate::database::DatabaseManager::new(None, false);
+        let backup_dir = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(backup_dir.path().to_path_buf());
+        let dir = backup_dir.path().join("default");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("broken.grafeo"), b"not a database").unwrap();
+
+        let result = BackupService::restore_as_new(
+            &mgr,
+            &target,
+            "default",
+            &types::RestoreAsRequest {
+                name: "copy".to_owned(),
+                backup: Some("broken.grafeo".to_owned()),
+                epoch: None,
+                storage_mode: types::StorageMode::Persistent,
+            },
+            backup_dir.path(),
+            None,
+        )
+        .await;
+        // Persistent storage needs a data dir
+        assert!(matches!(result, Err(ServiceError::BadRequest(_))));
+
+        let result = BackupService::restore_as_new(
+            &mgr,
+            &target,
+            "default",
+            &types::RestoreAsRequest {
+                name: "copy".to_owned(),
+                backup: Some("broken.grafeo".to_owned()),
+                epoch: None,
+                storage_mode: types::StorageMode::InMemory,
+            },
+            backup_dir.path(),
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(ServiceError::Internal(_))));
+        assert!(mgr.get("copy").is_none());
     }
 
+    // -----------------------------------------------------------------------
+    // Encryption at rest
+    // -----------------------------------------------------------------------
+
+    #[cfg(feature = "backup-encryption")]
+    const KEY_OLD: &str = "old:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    #[cfg(feature = "backup-encryption")]
+    const KEY_NEW: &str = "new:AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=";
+
+    #[cfg(feature = "backup-encryption")]
     #[tokio::test]
-    async fn labels_sidecar_corrupt_is_ignored() {
+    async fn encrypted_backups_restore_transparently() {
+        use crate::backup_target::LocalTarget;
+
         let data_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
+        let remote_dir = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(remote_dir.path().to_path_buf());
+        let keys = BackupKeys::parse(KEY_OLD).unwrap();
         let mgr =
             crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let insert = |name: &str| {
+            mgr.get("default")
+                .unwrap()
+                .db()
+                .session()
+                .execute(&format!("INSERT (:Person {{name: '{name}'}})"))
+                .unwrap();
+        };
 
-        BackupService::backup_database(&mgr, "default", backup_dir.path(), None)
-            .await
-            .unwrap();
+        insert("Alice");
+        let full = BackupService::backup_database_to(
+            &mgr,
+            &target,
+            "default",
+            backup_dir.path(),
+            Some("nightly".to_owned()),
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        insert("Bob");
+        let incremental = BackupService::backup_incremental_to(
+            &mgr,
+            &target,
+            "default",
+            backup_dir.path(),
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        assert_eq!(full.encryption_key_id.as_deref(), Some("old"));
+        assert_eq!(full.label.as_deref(), Some("nightly"));
+
+        // Segments and labels are sealed locally and at the target
+        for dir in [backup_dir.path(), remote_dir.path()] {
+            let dir = dir.join("default");
+            for name in [&full.filename, &incremental.filename] {
+                assert_eq!(
+                    backup_crypto::sealed_key_id(&dir.join(name))
+                        .unwrap()
+                        .as_deref(),
+                    Some("old")
+                );
+            }
+            assert!(backup_crypto::is_sealed(
+                &std::fs::read(dir.join(LABELS_FILENAME)).unwrap()
+            ));
+        }
 
-        // Write a corrupt sidecar and confirm listing still works.
-        let sidecar = backup_dir.path().join("default").join(LABELS_FILENAME);
-        std::fs::write(&sidecar, b"{not json").unwrap();
+        // Labels need the key; key IDs don't
+        let listed =
+            BackupService::list_backups_from(&target, Some("default"), backup_dir.path(), None)
+                .await
+                .unwrap();
+        assert_eq!(listed[0].encryption_key_id.as_deref(), Some("old"));
+        assert!(listed[0].label.is_none());
+        let listed = BackupService::list_backups_from(
+            &target,
+            Some("default"),
+            backup_dir.path(),
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed[0].label.as_deref(), Some("nightly"));
 
-        let list = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
-        assert_eq!(list.len(), 1);
-        assert!(list[0].label.is_none());
+        // Downloads and verification see the plaintext
+        let mut reader = BackupService::open_backup(
+            &target,
+            "default",
+            &full.filename,
+            backup_dir.path(),
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        let mut plaintext = Vec::new();
+        std::io::Read::read_to_end(&mut reader, &mut plaintext).unwrap();
+        assert_eq!(crc32fast::hash(&plaintext), full.checksum);
+        let verified = BackupService::verify_backup(
+            &target,
+            "default",
+            &incremental.filename,
+            backup_dir.path(),
+            true,
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        assert!(verified.ok, "{:?}", verified.issues);
+        assert_eq!(verified.node_count, Some(2));
+        let unverified = BackupService::verify_backup(
+            &target,
+            "default",
+            &full.filename,
+            backup_dir.path(),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(!unverified.ok);
+
+        // Restores decrypt without touching the sealed files
+        insert("Carol");
+        let restore_full = BackupService::restore_database_from(
+            &mgr,
+            &target,
+            "default",
+            "default",
+            &full.filename,
+            backup_dir.path(),
+            None,
+        )
+        .await;
+        assert!(matches!(restore_full, Err(ServiceError::BadRequest(_))));
+        BackupService::restore_database_from(
+            &mgr,
+            &target,
+            "default",
+            "default",
+            &full.filename,
+            backup_dir.path(),
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 1);
+        let dir = backup_dir.path().join("default");
+        for name in backup_files(&dir) {
+            assert!(
+                backup_crypto::sealed_key_id(&dir.join(&name))
+                    .unwrap()
+                    .is_some(),
+                "{name} left in plaintext"
+            );
+        }
+
+        BackupService::restore_to_epoch_from(
+            &mgr,
+            &target,
+            "default",
+            incremental.end_epoch,
+            backup_dir.path(),
+            Some(&keys),
+        )
+        .await
+        .unwrap();
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 2);
+        assert_eq!(
+            backup_crypto::sealed_key_id(&dir.join(&incremental.filename))
+                .unwrap()
+                .as_deref(),
+            Some("old")
+        );
     }
 
+    #[cfg(feature = "backup-encryption")]
     #[tokio::test]
-    async fn delete_backup_clears_label_sidecar() {
-        let data_dir = tempfile::tempdir().unwrap();
+    async fn rekey_rotates_backups_and_labels() {
+        use crate::backup_target::LocalTarget;
+
         let backup_dir = tempfile::tempdir().unwrap();
-        let mgr =
-            crate::database::DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let remote_dir = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(remote_dir.path().to_path_buf());
+        let old = BackupKeys::parse(KEY_OLD).unwrap();
+        let rotated = BackupKeys::parse(&format!("{KEY_NEW}\n{KEY_OLD}")).unwrap();
+        let mgr = crate::database::DatabaseManager::new(None, false);
 
-        let first = BackupService::backup_database(
+        let plain = BackupService::backup_database_to(
             &mgr,
+            &target,
             "default",
             backup_dir.path(),
-            Some("first".to_owned()),
+            Some("before".to_owned()),
+            None,
         )
         .await
         .unwrap();
+        // In-memory backups are named by timestamp
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let sealed = BackupService::backup_database_to(
+            &mgr,
+            &target,
+            "default",
+            backup_dir.path(),
+            Some("after".to_owned()),
+            Some(&old),
+        )
+        .await
+        .unwrap();
+        assert!(plain.encryption_key_id.is_none());
 
-        BackupService::delete_backup("default", &first.filename, backup_dir.path()).unwrap();
-
-        // Sidecar should no longer reference the deleted filename.
-        let sidecar = backup_dir.path().join("default").join(LABELS_FILENAME);
-        if sidecar.exists() {
-            let text = std::fs::read_to_string(&sidecar).unwrap();
-            let map: HashMap<String, String> = serde_json::from_str(&text).unwrap();
-            assert!(!map.contains_key(&first.filename));
+        let mut rekeyed = BackupService::rekey_backups(&target, None, backup_dir.path(), &rotated)
+            .await
+            .unwrap();
+        rekeyed.sort_by(|a, b| a.filename.cmp(&b.filename));
+        assert_eq!(rekeyed.len(), 2);
+        assert_eq!(rekeyed[0].filename, plain.filename);
+        assert!(rekeyed[0].previous_key_id.is_none());
+        assert_eq!(rekeyed[1].previous_key_id.as_deref(), Some("old"));
+        assert!(rekeyed.iter().all(|r| r.key_id == "new"));
+
+        let remote = remote_dir.path().join("default");
+        for name in [&plain.filename, &sealed.filename] {
+            assert_eq!(
+                backup_crypto::sealed_key_id(&remote.join(name))
+                    .unwrap()
+                    .as_deref(),
+                Some("new")
+            );
         }
+        assert!(
+            load_labels(&backup_dir.path().join("default"), Some(&old)).is_none(),
+            "labels still readable with the old key"
+        );
+        let listed = BackupService::list_backups_from(
+            &target,
+            Some("default"),
+            backup_dir.path(),
+            Some(&BackupKeys::parse(KEY_NEW).unwrap()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed.len(), 2);
+        assert!(
+            listed
+                .iter()
+                .all(|e| e.encryption_key_id.as_deref() == Some("new") && e.label.is_some())
+        );
+
+        // Nothing left to rotate
+        let again = BackupService::rekey_backups(&target, None, backup_dir.path(), &rotated)
+            .await
+            .unwrap();
+        assert!(again.is_empty());
     }
 }
@@ -0,0 +1,618 @@
+//! Encryption of backups at rest.
+//!
+//! With backup keys configured, [`BackupService`](crate::backup::BackupService)
+//! seals every backup file right after the engine writes it into the backup
+//! directory, and writes the label sidecar sealed too. Sealed files keep their
+//! names, so the engine's manifest still describes the chain; its checksums
+//! cover the plaintext. Restores, verification and downloads decrypt into a
+//! scratch directory or stream, never back into the backup directory.
+//!
+//! Files are encrypted with AES-256-GCM in 64 KiB chunks (the STREAM
+//! construction): each chunk's nonce holds a random per-file prefix, the
+//! chunk counter and a final-chunk flag, and the file header is
+//! authenticated with every chunk. Files of any size are processed in
+//! constant memory, and truncated, reordered or altered chunks fail to
+//! decrypt. The header names the key that sealed the file.
+//!
+//! A keyring holds one or more keys. The first seals new files and all of
+//! them open existing ones, so a key is rotated by listing the new key
+//! first, re-sealing the existing backups (`POST /backups/rekey`), then
+//! dropping the old key.
+//!
+//! The cipher needs the `backup-encryption` feature. Without it,
+//! [`BackupKeys`] cannot be constructed, so backups are always written in
+//! plaintext; sealed files are still recognised, but fail to open.
+
+#[cfg(feature = "backup-encryption")]
+use std::fmt::{self, Write as _};
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+
+#[cfg(feature = "backup-encryption")]
+use base64::Engine as _;
+#[cfg(feature = "backup-encryption")]
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+#[cfg(feature = "backup-encryption")]
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::error::ServiceError;
+
+/// Leading bytes of a sealed file.
+const MAGIC: &[u8; 4] = b"GENC";
+
+/// Format version written after the magic.
+const VERSION: u8 = 1;
+
+/// Plaintext bytes per chunk.
+#[cfg(feature = "backup-encryption")]
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// AES-GCM authentication tag length.
+#[cfg(feature = "backup-encryption")]
+const TAG_LEN: usize = 16;
+
+/// Random per-file part of each nonce; the rest is the chunk counter (4
+/// bytes) and the final-chunk flag (1 byte).
+const NONCE_PREFIX_LEN: usize = 7;
+
+#[cfg(feature = "backup-encryption")]
+const _: () = assert!(NONCE_PREFIX_LEN + 5 == NONCE_LEN);
+
+/// Length of a backup key in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// Backup encryption keys. The first one seals, all of them open.
+#[cfg(feature = "backup-encryption")]
+#[derive(Clone)]
+pub struct BackupKeys {
+    keys: Vec<BackupKey>,
+}
+
+/// Backup encryption keys. Built without the `backup-encryption` feature,
+/// they cannot be constructed.
+#[cfg(not(feature = "backup-encryption"))]
+#[derive(Debug, Clone)]
+pub struct BackupKeys {
+    _private: (),
+}
+
+#[cfg(feature = "backup-encryption")]
+#[derive(Clone)]
+struct BackupKey {
+    id: String,
+    bytes: [u8; KEY_LEN],
+}
+
+#[cfg(feature = "backup-encryption")]
+impl BackupKey {
+    fn aead(&self) -> LessSafeKey {
+        LessSafeKey::new(
+            UnboundKey::new(&AES_256_GCM, &self.bytes).expect("key length matches AES-256"),
+        )
+    }
+}
+
+#[cfg(feature = "backup-encryption")]
+impl fmt::Debug for BackupKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BackupKeys")
+            .field("ids", &self.keys.iter().map(|k| &k.id).collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "backup-encryption")]
+impl BackupKeys {
+    /// Parses a keyring: keys separated by newlines or commas, each either
+    /// `<id>:<base64 key>` or just the base64-encoded 32-byte key, whose ID
+    /// is then derived from it. Blank lines and `#` comments are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keys: Vec<BackupKey> = Vec::new();
+        for item in text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .flat_map(|line| line.split(','))
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let (id, encoded) = match item.split_once(':') {
+                Some((id, encoded)) => (Some(id.trim()), encoded.trim()),
+                None => (None, item),
+            };
+            let decoded = base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .map_err(|e| format!("invalid base64 in backup key: {e}"))?;
+            let bytes: [u8; KEY_LEN] = decoded.try_into().map_err(|d: Vec<u8>| {
+                format!("backup keys must be {KEY_LEN} bytes, got {}", d.len())
+            })?;
+            let id = match id {
+                Some(id) => {
+                    if id.is_empty()
+                        || id.len() > 64
+                        || !id
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+                    {
+                        return Err(format!(
+                            "invalid backup key ID '{id}': use up to 64 letters, digits, '-', '_' and '.'"
+                        ));
+                    }
+                    id.to_owned()
+                }
+                None => derive_id(&bytes),
+            };
+            if keys.iter().any(|k| k.id == id) {
+                return Err(format!("duplicate backup key ID '{id}'"));
+            }
+            keys.push(BackupKey { id, bytes });
+        }
+        if keys.is_empty() {
+            return Err("no backup key given".to_owned());
+        }
+        Ok(Self { keys })
+    }
+
+    /// Reads a keyring file. See [`Self::parse`].
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+        Self::parse(&text)
+    }
+
+    /// ID of the key that seals new files.
+    pub fn active_id(&self) -> &str {
+        &self.keys[0].id
+    }
+
+    fn get(&self, id: &str) -> Option<&BackupKey> {
+        self.keys.iter().find(|k| k.id == id)
+    }
+
+    /// Encrypts everything `reader` yields into `writer` with the active key.
+    pub fn seal(&self, mut reader: impl Read, mut writer: impl Write) -> io::Result<()> {
+        let key = &self.keys[0];
+        let mut prefix = [0u8; NONCE_PREFIX_LEN];
+        SystemRandom::new()
+            .fill(&mut prefix)
+            .map_err(|_| io::Error::other("failed to generate a nonce"))?;
+        let header = header(&key.id, prefix);
+        writer.write_all(&header)?;
+
+        let aead = key.aead();
+        let mut current = vec![0u8; CHUNK_SIZE];
+        let mut next = vec![0u8; CHUNK_SIZE];
+        let mut len = fill(&mut reader, &mut current)?;
+        let mut counter = 0u32;
+        loop {
+            // A full chunk is the last one only if nothing follows it
+            let next_len = if len == CHUNK_SIZE {
+                fill(&mut reader, &mut next)?
+            } else {
+                0
+            };
+            let last = next_len == 0;
+            let tag = aead
+                .seal_in_place_separate_tag(
+                    nonce(prefix, counter, last),
+                    Aad::from(&header[..]),
+                    &mut current[..len],
+                )
+                .map_err(|_| io::Error::other("backup encryption failed"))?;
+            writer.write_all(&current[..len])?;
+            writer.write_all(tag.as_ref())?;
+            if last {
+                break;
+            }
+            counter = counter
+                .checked_add(1)
+                .ok_or_else(|| io::Error::other("backup too large to encrypt"))?;
+            std::mem::swap(&mut current, &mut next);
+            len = next_len;
+        }
+        writer.flush()
+    }
+
+    /// Encrypts `data` with the active key.
+    pub fn seal_bytes(&self, data: &[u8]) -> Vec<u8> {
+        let mut sealed = Vec::with_capacity(data.len() + 64);
+        self.seal(data, &mut sealed)
+            .expect("sealing into memory cannot fail");
+        sealed
+    }
+}
+
+#[cfg(not(feature = "backup-encryption"))]
+impl BackupKeys {
+    /// ID of the key that seals new files.
+    pub fn active_id(&self) -> &str {
+        unreachable!("backup keys require the backup-encryption feature")
+    }
+
+    /// Encrypts everything `reader` yields into `writer` with the active key.
+    pub fn seal(&self, _reader: impl Read, _writer: impl Write) -> io::Result<()> {
+        unreachable!("backup keys require the backup-encryption feature")
+    }
+
+    /// Encrypts `data` with the active key.
+    pub fn seal_bytes(&self, _data: &[u8]) -> Vec<u8> {
+        unreachable!("backup keys require the backup-encryption feature")
+    }
+}
+
+/// Derives a key ID from the key: `k` and 8 hex digits of its SHA-256.
+#[cfg(feature = "backup-encryption")]
+fn derive_id(bytes: &[u8]) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, bytes);
+    let mut id = "k".to_owned();
+    for b in &digest.as_ref()[..4] {
+        let _ = write!(id, "{b:02x}");
+    }
+    id
+}
+
+fn header(key_id: &str, prefix: [u8; NONCE_PREFIX_LEN]) -> Vec<u8> {
+    let mut header = Vec::with_capacity(MAGIC.len() + 2 + key_id.len() + NONCE_PREFIX_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(VERSION);
+    header.push(key_id.len() as u8);
+    header.extend_from_slice(key_id.as_bytes());
+    header.extend_from_slice(&prefix);
+    header
+}
+
+#[cfg(feature = "backup-encryption")]
+fn nonce(prefix: [u8; NONCE_PREFIX_LEN], counter: u32, last: bool) -> Nonce {
+    let mut bytes = [0u8; NONCE_LEN];
+    bytes[..NONCE_PREFIX_LEN].copy_from_slice(&prefix);
+    bytes[NONCE_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+    bytes[NONCE_LEN - 1] = u8::from(last);
+    Nonce::assume_unique_for_key(bytes)
+}
+
+/// Reads until `buf` is full or the reader is exhausted.
+fn fill(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buf.len() {
+        match reader.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+/// Reads the rest of a header whose magic was consumed. Returns the whole
+/// header, the key ID and the nonce prefix.
+fn read_header(reader: &mut impl Read) -> io::Result<(Vec<u8>, String, [u8; NONCE_PREFIX_LEN])> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "invalid encrypted backup header",
+        )
+    };
+    let mut fixed = [0u8; 2];
+    reader.read_exact(&mut fixed).map_err(|_| invalid())?;
+    let [version, id_len] = fixed;
+    if version != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported encrypted backup version {version}"),
+        ));
+    }
+    let mut id = vec![0u8; usize::from(id_len)];
+    reader.read_exact(&mut id).map_err(|_| invalid())?;
+    let id = String::from_utf8(id).map_err(|_| invalid())?;
+    let mut prefix = [0u8; NONCE_PREFIX_LEN];
+    reader.read_exact(&mut prefix).map_err(|_| invalid())?;
+    Ok((header(&id, prefix), id, prefix))
+}
+
+/// Returns the ID of the key that sealed the file at `path`, or `None` if
+/// it is plaintext.
+pub fn sealed_key_id(path: &Path) -> io::Result<Option<String>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut magic = [0u8; MAGIC.len()];
+    if fill(&mut file, &mut magic)? < MAGIC.len() || &magic != MAGIC {
+        return Ok(None);
+    }
+    read_header(&mut file).map(|(_, id, _)| Some(id))
+}
+
+/// Opens a file for reading its plaintext: decrypted if sealed, as is
+/// otherwise. Fails if the file is sealed with a key `keys` lacks.
+pub fn open(path: &Path, keys: Option<&BackupKeys>) -> Result<Box<dyn Read + Send>, ServiceError> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| ServiceError::Internal(format!("failed to open {}: {e}", path.display())))?;
+    let mut magic = [0u8; MAGIC.len()];
+    let read = fill(&mut file, &mut magic)
+        .map_err(|e| ServiceError::Internal(format!("failed to read {}: {e}", path.display())))?;
+    if read == MAGIC.len() && &magic == MAGIC {
+        return decryptor(io::BufReader::new(file), keys);
+    }
+    file.rewind()
+        .map_err(|e| ServiceError::Internal(format!("failed to read {}: {e}", path.display())))?;
+    Ok(Box::new(file))
+}
+
+/// Writes the plaintext of the file at `source` to `dest`.
+pub fn decrypt_file(
+    source: &Path,
+    dest: &Path,
+    keys: Option<&BackupKeys>,
+) -> Result<(), ServiceError> {
+    let mut reader = open(source, keys)?;
+    let mut out = std::fs::File::create(dest)
+        .map_err(|e| ServiceError::Internal(format!("failed to create {}: {e}", dest.display())))?;
+    io::copy(&mut reader, &mut out).map_err(|e| {
+        ServiceError::Internal(format!("failed to decrypt {}: {e}", source.display()))
+    })?;
+    Ok(())
+}
+
+/// Seals the file at `path` in place with the active key. A plaintext file
+/// is encrypted; one sealed with another key is re-encrypted.
+pub fn seal_file(path: &Path, keys: &BackupKeys) -> Result<(), ServiceError> {
+    let reader = open(path, Some(keys))?;
+    let tmp = path.with_extension("seal.tmp");
+    let result = std::fs::File::create(&tmp)
+        .and_then(|file| keys.seal(reader, io::BufWriter::new(file)))
+        .and_then(|()| std::fs::rename(&tmp, path));
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(ServiceError::Internal(format!(
+            "failed to encrypt {}: {e}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Whether `data` is sealed.
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Returns the plaintext of `data`: decrypted if sealed, as is otherwise.
+pub fn open_bytes(data: &[u8], keys: Option<&BackupKeys>) -> Result<Vec<u8>, ServiceError> {
+    let Some(rest) = data.strip_prefix(MAGIC) else {
+        return Ok(data.to_vec());
+    };
+    let mut plaintext = Vec::new();
+    decryptor(rest, keys)?
+        .read_to_end(&mut plaintext)
+        .map_err(|e| ServiceError::Internal(e.to_string()))?;
+    Ok(plaintext)
+}
+
+/// Reads the plaintext of a sealed stream whose magic was consumed.
+#[cfg(feature = "backup-encryption")]
+fn decryptor<'a, R: Read + Send + 'a>(
+    inner: R,
+    keys: Option<&BackupKeys>,
+) -> Result<Box<dyn Read + Send + 'a>, ServiceError> {
+    Ok(Box::new(Decryptor::new(inner, keys)?))
+}
+
+#[cfg(not(feature = "backup-encryption"))]
+fn decryptor<'a, R: Read + Send + 'a>(
+    mut inner: R,
+    _keys: Option<&BackupKeys>,
+) -> Result<Box<dyn Read + Send + 'a>, ServiceError> {
+    let (_, id, _) = read_header(&mut inner).map_err(|e| ServiceError::Internal(e.to_string()))?;
+    Err(ServiceError::BadRequest(format!(
+        "backup is encrypted with key '{id}' but this server was built without backup encryption"
+    )))
+}
+
+/// Decrypts a sealed stream whose magic was consumed.
+#[cfg(feature = "backup-encryption")]
+struct Decryptor<R> {
+    inner: R,
+    key: LessSafeKey,
+    header: Vec<u8>,
+    prefix: [u8; NONCE_PREFIX_LEN],
+    counter: u32,
+    /// Decrypted bytes of the current chunk and how many were read.
+    chunk: Vec<u8>,
+    pos: usize,
+    /// A byte read past a full chunk to learn whether another follows.
+    lookahead: Option<u8>,
+    done: bool,
+}
+
+#[cfg(feature = "backup-encryption")]
+impl<R: Read> Decryptor<R> {
+    fn new(mut inner: R, keys: Option<&BackupKeys>) -> Result<Self, ServiceError> {
+        let (header, id, prefix) =
+            read_header(&mut inner).map_err(|e| ServiceError::Internal(e.to_string()))?;
+        let key = match keys {
+            Some(keys) => keys.get(&id).ok_or_else(|| {
+                ServiceError::BadRequest(format!(
+                    "backup is encrypted with key '{id}', which is not configured"
+                ))
+            })?,
+            None => {
+                return Err(ServiceError::BadRequest(format!(
+                    "backup is encrypted with key '{id}' but no backup encryption key is configured"
+                )));
+            }
+        };
+        Ok(Self {
+            inner,
+            key: key.aead(),
+            header,
+            prefix,
+            counter: 0,
+            chunk: Vec::new(),
+            pos: 0,
+            lookahead: None,
+            done: false,
+        })
+    }
+
+    fn next_chunk(&mut self) -> io::Result<()> {
+        let mut chunk = vec![0u8; CHUNK_SIZE + TAG_LEN];
+        let mut len = 0;
+        if let Some(b) = self.lookahead.take() {
+            chunk[0] = b;
+            len = 1;
+        }
+        len += fill(&mut self.inner, &mut chunk[len..])?;
+        let last = if len < chunk.len() {
+            true
+        } else {
+            let mut b = [0u8; 1];
+            let more = fill(&mut self.inner, &mut b)? == 1;
+            self.lookahead = more.then_some(b[0]);
+            !more
+        };
+        chunk.truncate(len);
+
+        let plaintext_len = self
+            .key
+            .open_in_place(
+                nonce(self.prefix, self.counter, last),
+                Aad::from(&self.header[..]),
+                &mut chunk,
+            )
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "backup decryption failed: the file is corrupt, truncated or was altered",
+                )
+            })?
+            .len();
+        chunk.truncate(plaintext_len);
+        self.counter = self.counter.wrapping_add(1);
+        self.chunk = chunk;
+        self.pos = 0;
+        self.done = last;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "backup-encryption")]
+impl<R: Read> Read for Decryptor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            if self.done {
+                return Ok(0);
+            }
+            self.next_chunk()?;
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(all(test, feature = "backup-encryption"))]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+    const KEY_B: &str = "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=";
+
+    fn roundtrip(keys: &BackupKeys, data: &[u8]) -> Vec<u8> {
+        let sealed = keys.seal_bytes(data);
+        assert!(is_sealed(&sealed));
+        open_bytes(&sealed, Some(keys)).unwrap()
+    }
+
+    #[test]
+    fn seal_and_open_round_trip() {
+        let keys = BackupKeys::parse(&format!("primary:{KEY_A}")).unwrap();
+        for len in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE,
+        ] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            assert_eq!(roundtrip(&keys, &data), data, "length {len}");
+        }
+        // Plaintext passes through
+        assert_eq!(open_bytes(b"{}", None).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn tampering_and_truncation_are_detected() {
+        let keys = BackupKeys::parse(KEY_A).unwrap();
+        let data = vec![7u8; 2 * CHUNK_SIZE];
+        let sealed = keys.seal_bytes(&data);
+
+        let mut flipped = sealed.clone();
+        let last = flipped.len() - 1;
+        flipped[last] ^= 1;
+        assert!(open_bytes(&flipped, Some(&keys)).is_err());
+
+        // Dropping the final chunk leaves a non-final chunk last
+        let header_len = MAGIC.len() + 2 + keys.active_id().len() + NONCE_PREFIX_LEN;
+        let truncated = &sealed[..header_len + CHUNK_SIZE + TAG_LEN];
+        assert!(open_bytes(truncated, Some(&keys)).is_err());
+
+        // The header is authenticated
+        let mut renamed = sealed;
+        renamed[MAGIC.len() + 2 + keys.active_id().len()] ^= 1;
+        assert!(open_bytes(&renamed, Some(&keys)).is_err());
+    }
+
+    #[test]
+    fn rotation_keeps_old_keys_readable() {
+        let old = BackupKeys::parse(&format!("old:{KEY_A}")).unwrap();
+        let rotated = BackupKeys::parse(&format!("new:{KEY_B}\nold:{KEY_A}")).unwrap();
+        assert_eq!(rotated.active_id(), "new");
+
+        let sealed = old.seal_bytes(b"graph");
+        assert_eq!(open_bytes(&sealed, Some(&rotated)).unwrap(), b"graph");
+        let resealed = rotated.seal_bytes(b"graph");
+        assert!(matches!(
+            open_bytes(&resealed, Some(&old)),
+            Err(ServiceError::BadRequest(_))
+        ));
+        assert!(matches!(
+            open_bytes(&sealed, None),
+            Err(ServiceError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn seal_file_records_key_in_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backup.grafeo");
+        std::fs::write(&path, b"plaintext").unwrap();
+        assert_eq!(sealed_key_id(&path).unwrap(), None);
+
+        let old = BackupKeys::parse(&format!("old:{KEY_A}")).unwrap();
+        seal_file(&path, &old).unwrap();
+        assert_eq!(sealed_key_id(&path).unwrap().as_deref(), Some("old"));
+
+        let rotated = BackupKeys::parse(&format!("new:{KEY_B},old:{KEY_A}")).unwrap();
+        seal_file(&path, &rotated).unwrap();
+        assert_eq!(sealed_key_id(&path).unwrap().as_deref(), Some("new"));
+
+        let copy = dir.path().join("copy.grafeo");
+        decrypt_file(&path, &copy, Some(&rotated)).unwrap();
+        assert_eq!(std::fs::read(&copy).unwrap(), b"plaintext");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn parse_rejects_bad_keyrings() {
+        assert!(BackupKeys::parse("").is_err());
+        assert!(BackupKeys::parse("# only a comment\n").is_err());
+        assert!(BackupKeys::parse("c2hvcnQ=").is_err());
+        assert!(BackupKeys::parse(&format!("bad id:{KEY_A}")).is_err());
+        assert!(BackupKeys::parse(&format!("a:{KEY_A}\na:{KEY_B}")).is_err());
+
+        let derived = BackupKeys::parse(KEY_A).unwrap();
+        assert!(derived.active_id().starts_with('k'));
+        assert_eq!(derived.active_id().len(), 9);
+        assert!(!format!("{derived:?}").contains(KEY_A));
+    }
+}
@@ -0,0 +1,1180 @@
+//! S3-compatible backup target.
+//!
+//! Objects are stored as `{prefix}{db_name}/{filename}` and every request
+//! is signed with AWS Signature Version 4, so AWS S3 and compatible stores
+//! (MinIO, Ceph RGW, Cloudflare R2, ...) all work. With an explicit
+//! endpoint the bucket is addressed path-style (`{endpoint}/{bucket}/...`),
+//! as most self-hosted stores expect; without one, virtual-hosted style on
+//! AWS (`https://{bucket}.s3.{region}.amazonaws.com/...`). Credentials
+//! come from `--backup-s3-access-key`/`--backup-s3-secret-key` or the
+//! standard `AWS_*` environment variables.
+//!
+//! Files up to [`MULTIPART_THRESHOLD`] are uploaded with a single `PUT`
+//! whose body is streamed from disk; larger ones (a single `PUT` is capped
+//! at 5 GiB) with a multipart upload, one part in memory at a time.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use crate::backup_target::{BackupTarget, TargetFile};
+use crate::error::ServiceError;
+
+/// Files larger than this are uploaded in parts.
+pub const MULTIPART_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Size of the parts of a multipart upload. S3 requires at least 5 MiB for
+/// every part but the last.
+const PART_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Maximum number of parts of a multipart upload. Larger files get larger
+/// parts.
+const MAX_PARTS: u64 = 10_000;
+
+/// Read buffer for hashing a file before it is streamed.
+const HASH_BUFFER: usize = 64 * 1024;
+
+/// Connection settings for an S3-compatible bucket.
+#[derive(Clone)]
+pub struct S3Config {
+    pub bucket: String,
+    /// Key prefix, empty or ending in `/`.
+    pub prefix: String,
+    pub region: String,
+    /// Endpoint URL of an S3-compatible store, e.g. `http://localhost:9000`.
+    /// `None` means AWS.
+    pub endpoint: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Session token of temporary credentials.
+    pub session_token: Option<String>,
+}
+
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Config")
+            .field("bucket", &self.bucket)
+            .field("prefix", &self.prefix)
+            .field("region", &self.region)
+            .field("endpoint", &self.endpoint)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"***")
+            .field("session_token", &self.session_token.as_ref().map(|_| "***"))
+            .finish()
+    }
+}
+
+impl S3Config {
+    /// Parses the bucket and prefix from an `s3://bucket/prefix` URL.
+    pub fn parse_url(url: &str) -> Result<(String, String), String> {
+        let rest = url
+            .strip_prefix("s3://")
+            .ok_or_else(|| format!("not an s3:// URL: {url}"))?;
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(format!("missing bucket in {url}"));
+        }
+        let prefix = prefix.trim_matches('/');
+        let prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("{prefix}/")
+        };
+        Ok((bucket.to_owned(), prefix))
+    }
+}
+
+/// Backups stored in an S3-compatible bucket.
+pub struct S3Target {
+    config: S3Config,
+    http: reqwest::Client,
+    /// Scheme and authority requests go to, e.g. `http://localhost:9000`.
+    origin: String,
+    /// Value of the `Host` header, as signed.
+    host: String,
+    /// Path of the bucket: `/{bucket}` path-style, empty virtual-hosted.
+    bucket_path: String,
+    /// See [`MULTIPART_THRESHOLD`].
+    multipart_threshold: u64,
+    /// See [`PART_SIZE`].
+    part_size: u64,
+}
+
+impl S3Target {
+    pub fn new(config: S3Config) -> Result<Self, String> {
+        let (origin, bucket_path) = match &config.endpoint {
+            Some(endpoint) => (
+                endpoint.trim_end_matches('/').to_owned(),
+                format!("/{}", uri_encode(&config.bucket, true)),
+            ),
+            None => (
+                format!(
+                    "https://{}.s3.{}.amazonaws.com",
+                    config.bucket, config.region
+                ),
+                String::new(),
+            ),
+        };
+        let url = reqwest::Url::parse(&origin)
+            .map_err(|e| format!("invalid S3 endpoint {origin}: {e}"))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_owned(),
+            (None, _) => return Err(format!("invalid S3 endpoint {origin}: no host")),
+        };
+        let http = reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| format!("failed to build S3 client: {e}"))?;
+        Ok(Self {
+            config,
+            http,
+            origin,
+            host,
+            bucket_path,
+            multipart_threshold: MULTIPART_THRESHOLD,
+            part_size: PART_SIZE,
+        })
+    }
+
+    fn key(&self, db_name: &str, filename: &str) -> Result<String, ServiceError> {
+        for part in [db_name, filename] {
+            if part.contains('/') || part.contains('\\') || part.contains("..") {
+                return Err(ServiceError::BadRequest(
+                    "invalid path parameter".to_string(),
+                ));
+            }
+        }
+        Ok(format!("{}{db_name}/{filename}", self.config.prefix))
+    }
+
+    /// Sends a signed request for `key` (or the bucket, when `None`).
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        key: Option<&str>,
+        query: &BTreeMap<&str, String>,
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, ServiceError> {
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        self.send_body(method, key, query, body.into(), &payload_hash, None)
+            .await
+    }
+
+    /// Like [`send`](Self::send), with a body that may be streamed: its
+    /// SHA-256 is passed in, and `length` is sent as `Content-Length` so
+    /// the body is not chunked.
+    async fn send_body(
+        &self,
+        method: reqwest::Method,
+        key: Option<&str>,
+        query: &BTreeMap<&str, String>,
+        body: reqwest::Body,
+        payload_hash: &str,
+        length: Option<u64>,
+    ) -> Result<reqwest::Response, ServiceError> {
+        let path = match key {
+            Some(key) => format!("{}/{}", self.bucket_path, uri_encode(key, false)),
+            None if self.bucket_path.is_empty() => "/".to_owned(),
+            None => self.bucket_path.clone(),
+        };
+        let query = canonical_query(query);
+        let amz_date = amz_date(crate::backup::unix_millis() / 1000);
+        let headers = sign(
+            &self.config,
+            &SignedRequest {
+                method: method.as_str(),
+                host: &self.host,
+                path: &path,
+                query: &query,
+                payload_hash,
+                amz_date: &amz_date,
+            },
+        );
+
+        let url = if query.is_empty() {
+            format!("{}{path}", self.origin)
+        } else {
+            format!("{}{path}?{query}", self.origin)
+        };
+        let mut request = self.http.request(method, url).body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(length) = length {
+            request = request.header(reqwest::header::CONTENT_LENGTH, length);
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| ServiceError::Internal(format!("S3 request failed: {e}")))
+    }
+
+    /// Turns an unsuccessful response into an error naming the S3 error
+    /// code.
+    async fn error(operation: &str, target: &str, response: reqwest::Response) -> ServiceError {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        let code = xml_blocks(&body, "Code").first().map_or_else(
+            || status.canonical_reason().unwrap_or("error").to_owned(),
+            |code| unescape(code),
+        );
+        ServiceError::Internal(format!("S3 {operation} {target} failed: {status} {code}"))
+    }
+
+    /// Uploads `source` (`size` bytes) to `key` with a single `PUT`,
+    /// streaming the body from disk after hashing it.
+    async fn put_file(&self, key: &str, source: &Path, size: u64) -> Result<(), ServiceError> {
+        let mut file = tokio::fs::File::open(source).await.map_err(read_error)?;
+        let mut hasher = Sha256::new();
+        let mut buf = vec![0; HASH_BUFFER];
+        loop {
+            let n = file.read(&mut buf).await.map_err(read_error)?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(&buf[..n]);
+        }
+        let payload_hash = hex::encode(hasher.finalize());
+
+        let file = tokio::fs::File::open(source).await.map_err(read_error)?;
+        let response = self
+            .send_body(
+                reqwest::Method::PUT,
+                Some(key),
+                &BTreeMap::new(),
+                file.into(),
+                &payload_hash,
+                Some(size),
+            )
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::error("upload", key, response).await);
+        }
+        Ok(())
+    }
+
+    /// Uploads `source` (`size` bytes) to `key` with a multipart upload,
+    /// aborting it if a part fails so the store drops the parts.
+    async fn put_file_multipart(
+        &self,
+        key: &str,
+        source: &Path,
+        size: u64,
+    ) -> Result<(), ServiceError> {
+        let mut query = BTreeMap::new();
+        query.insert("uploads", String::new());
+        let response = self
+            .send(reqwest::Method::POST, Some(key), &query, Vec::new())
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::error("upload", key, response).await);
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ServiceError::Internal(format!("S3 upload {key} failed: {e}")))?;
+        let upload_id = xml_blocks(&body, "UploadId")
+            .first()
+            .map(|id| unescape(id))
+            .ok_or_else(|| {
+                ServiceError::Internal(format!("S3 upload {key} failed: no upload ID"))
+            })?;
+
+        let result = self.put_parts(key, &upload_id, source, size).await;
+        if result.is_err() {
+            let mut query = BTreeMap::new();
+            query.insert("uploadId", upload_id);
+            if let Err(e) = self
+                .send(reqwest::Method::DELETE, Some(key), &query, Vec::new())
+                .await
+            {
+                tracing::warn!("failed to abort S3 upload {key}: {e}");
+            }
+        }
+        result
+    }
+
+    /// Uploads the parts of multipart upload `upload_id` and completes it.
+    async fn put_parts(
+        &self,
+        key: &str,
+        upload_id: &str,
+        source: &Path,
+        size: u64,
+    ) -> Result<(), ServiceError> {
+        let part_size = self.part_size.max(size.div_ceil(MAX_PARTS));
+        let mut file = tokio::fs::File::open(source).await.map_err(read_error)?;
+        let mut etags = Vec::new();
+        let mut remaining = size;
+        while remaining > 0 {
+            let len = remaining.min(part_size);
+            remaining -= len;
+            let mut part = vec![0; usize::try_from(len).unwrap_or(usize::MAX)];
+            file.read_exact(&mut part).await.map_err(read_error)?;
+
+            let mut query = BTreeMap::new();
+            query.insert("partNumber", (etags.len() + 1).to_string());
+            query.insert("uploadId", upload_id.to_owned());
+            let response = self
+                .send(reqwest::Method::PUT, Some(key), &query, part)
+                .await?;
+            if !response.status().is_success() {
+                return Err(Self::error("upload", key, response).await);
+            }
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| {
+                    ServiceError::Internal(format!("S3 upload {key} failed: part without ETag"))
+                })?;
+            etags.push(etag.to_owned());
+        }
+
+        let mut complete = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in etags.iter().enumerate() {
+            let _ = write!(
+                complete,
+                "<Part><PartNumber>{}</PartNumber><ETag>{etag}</ETag></Part>",
+                i + 1
+            );
+        }
+        complete.push_str("</CompleteMultipartUpload>");
+        let mut query = BTreeMap::new();
+        query.insert("uploadId", upload_id.to_owned());
+        let response = self
+            .send(
+                reqwest::Method::POST,
+                Some(key),
+                &query,
+                complete.into_bytes(),
+            )
+            .await?;
+        if !response.status().is_success() {
+            return Err(Self::error("upload", key, response).await);
+        }
+        // A completion that fails after it started is reported with 200
+        let body = response
+            .text()
+            .await
+            .map_err(|e| ServiceError::Internal(format!("S3 upload {key} failed: {e}")))?;
+        if let Some(error) = xml_blocks(&body, "Error").first() {
+            let code = xml_blocks(error, "Code")
+                .first()
+                .map_or_else(|| "error".to_owned(), |code| unescape(code));
+            return Err(ServiceError::Internal(format!(
+                "S3 upload {key} failed: {code}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Lists objects (named by key) and common prefixes under `prefix`,
+    /// following continuation tokens.
+    async fn list_prefix(
+        &self,
+        prefix: &str,
+        delimiter: bool,
+    ) -> Result<(Vec<TargetFile>, Vec<String>), ServiceError> {
+        let mut keys = Vec::new();
+        let mut prefixes = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = BTreeMap::new();
+            query.insert("list-type", "2".to_owned());
+            query.insert("prefix", prefix.to_owned());
+            if delimiter {
+                query.insert("delimiter", "/".to_owned());
+            }
+            if let Some(token) = token.take() {
+                query.insert("continuation-token", token);
+            }
+            let response = self
+                .send(reqwest::Method::GET, None, &query, Vec::new())
+                .await?;
+            if !response.status().is_success() {
+                return Err(Self::error("list", prefix, response).await);
+            }
+            let body = response
+                .text()
+                .await
+                .map_err(|e| ServiceError::Internal(format!("S3 list failed: {e}")))?;
+
+            for contents in xml_blocks(&body, "Contents") {
+                let Some(key) = xml_blocks(contents, "Key").first().map(|k| unescape(k)) else {
+                    continue;
+                };
+                keys.push(TargetFile {
+                    name: key,
+                    size_bytes: xml_blocks(contents, "Size")
+                        .first()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(0),
+                    modified_ms: xml_blocks(contents, "LastModified")
+                        .first()
+                        .and_then(|t| parse_timestamp_ms(t))
+                        .unwrap_or(0),
+                });
+            }
+            for common in xml_blocks(&body, "CommonPrefixes") {
+                prefixes.extend(xml_blocks(common, "Prefix").into_iter().map(unescape));
+            }
+            let truncated = xml_blocks(&body, "IsTruncated").first() == Some(&"true");
+            match xml_blocks(&body, "NextContinuationToken").first() {
+                Some(next) if truncated => token = Some(unescape(next)),
+                _ => return Ok((keys, prefixes)),
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BackupTarget for S3Target {
+    fn describe(&self) -> String {
+        format!("s3://{}/{}", self.config.bucket, self.config.prefix)
+    }
+
+    async fn upload(
+        &self,
+        db_name: &str,
+        filename: &str,
+        source: &Path,
+    ) -> Result<(), ServiceError> {
+        let key = self.key(db_name, filename)?;
+        let size = tokio::fs::metadata(source).await.map_err(read_error)?.len();
+        if size > self.multipart_threshold {
+            self.put_file_multipart(&key, source, size).await
+        } else {
+            self.put_file(&key, source, size).await
+        }
+    }
+
+    async fn download(
+        &self,
+        db_name: &str,
+        filename: &str,
+        dest: &Path,
+    ) -> Result<bool, ServiceError> {
+        let key = self.key(db_name, filename)?;
+        let mut response = self
+            .send(
+                reqwest::Method::GET,
+                Some(&key),
+                &BTreeMap::new(),
+                Vec::new(),
+            )
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(Self::error("download", &key, response).await);
+        }
+
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                ServiceError::Internal(format!("failed to create backup directory: {e}"))
+            })?;
+        }
+        // Write beside the destination and rename, so an interrupted
+        // download never leaves a truncated backup behind
+        let tmp = dest.with_extension("download.tmp");
+        let write_error =
+            |e: std::io::Error| ServiceError::Internal(format!("failed to write {filename}: {e}"));
+        let mut file = tokio::fs::File::create(&tmp).await.map_err(write_error)?;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| ServiceError::Internal(format!("S3 download {key} failed: {e}")))?
+        {
+            tokio::io::AsyncWriteExt::write_all(&mut file, &chunk)
+                .await
+                .map_err(write_error)?;
+        }
+        tokio::io::AsyncWriteExt::flush(&mut file)
+            .await
+            .map_err(write_error)?;
+        drop(file);
+        tokio::fs::rename(&tmp, dest).await.map_err(write_error)?;
+        Ok(true)
+    }
+
+    async fn list(&self, db_name: &str) -> Result<Vec<TargetFile>, ServiceError> {
+        let prefix = self.key(db_name, "")?;
+        let (files, _) = self.list_prefix(&prefix, true).await?;
+        Ok(files
+            .into_iter()
+            .filter_map(|file| {
+                let name = file.name.strip_prefix(&prefix)?;
+                (!name.is_empty()).then(|| TargetFile {
+                    name: name.to_owned(),
+                    ..file
+                })
+            })
+            .collect())
+    }
+
+    async fn list_databases(&self) -> Result<Vec<String>, ServiceError> {
+        let (_, prefixes) = self.list_prefix(&self.config.prefix, true).await?;
+        Ok(prefixes
+            .into_iter()
+            .filter_map(|p| {
+                p.strip_prefix(&self.config.prefix)
+                    .and_then(|p| p.strip_suffix('/'))
+                    .map(str::to_owned)
+            })
+            .collect())
+    }
+
+    async fn delete(&self, db_name: &str, filename: &str) -> Result<(), ServiceError> {
+        let key = self.key(db_name, filename)?;
+        let response = self
+            .send(
+                reqwest::Method::DELETE,
+                Some(&key),
+                &BTreeMap::new(),
+                Vec::new(),
+            )
+            .await?;
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+            return Err(Self::error("delete", &key, response).await);
+        }
+        Ok(())
+    }
+}
+
+fn read_error(e: std::io::Error) -> ServiceError {
+    ServiceError::Internal(format!("failed to read backup file: {e}"))
+}
+
+// ---------------------------------------------------------------------------
+// Signature Version 4
+// ---------------------------------------------------------------------------
+
+/// The parts of a request that are signed. `path` and `query` are already
+/// URI-encoded, `query` in canonical order.
+struct SignedRequest<'a> {
+    method: &'a str,
+    host: &'a str,
+    path: &'a str,
+    query: &'a str,
+    payload_hash: &'a str,
+    amz_date: &'a str,
+}
+
+/// Returns the headers that authenticate `request`, `Authorization` last.
+fn sign(config: &S3Config, request: &SignedRequest<'_>) -> Vec<(&'static str, String)> {
+    let mut headers = vec![
+        ("host", request.host.to_owned()),
+        ("x-amz-content-sha256", request.payload_hash.to_owned()),
+        ("x-amz-date", request.amz_date.to_owned()),
+    ];
+    if let Some(token) = &config.session_token {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+
+    let mut canonical_headers = String::new();
+    for (name, value) in &headers {
+        let _ = writeln!(canonical_headers, "{name}:{}", value.trim());
+    }
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+        request.method, request.path, request.query, request.payload_hash
+    );
+
+    let date = &request.amz_date[..8];
+    let scope = format!("{date}/{}/s3/aws4_request", config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{scope}\n{}",
+        request.amz_date,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = format!("AWS4{}", config.secret_access_key);
+    let key = hmac_sha256(key.as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, config.region.as_bytes());
+    let key = hmac_sha256(&key, b"s3");
+    let key = hmac_sha256(&key, b"aws4_request");
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    // `host` is set by the HTTP client from the URL
+    headers.remove(0);
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            config.access_key_id
+        ),
+    ));
+    headers
+}
+
+/// HMAC-SHA256 (RFC 2104).
+fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|b| b ^ 0x36));
+    inner.update(data);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters, and `/`
+/// unless `encode_slash` is set.
+fn uri_encode(s: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char);
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => {
+                let _ = write!(out, "%{b:02X}");
+            }
+        }
+    }
+    out
+}
+
+/// Query string with encoded parameters sorted by name.
+fn canonical_query(params: &BTreeMap<&str, String>) -> String {
+    params
+        .iter()
+        .map(|(k, v)| format!("{}={}", uri_encode(k, true), uri_encode(v, true)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// `YYYYMMDD'T'HHMMSS'Z'` for Unix time `secs`.
+fn amz_date(secs: u64) -> String {
+    let (year, month, day) = crate::backup::days_to_ymd(secs / 86400);
+    let time = secs % 86400;
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        time / 3600,
+        (time % 3600) / 60,
+        time % 60
+    )
+}
+
+/// Unix millis of an ISO 8601 UTC timestamp as S3 lists them
+/// (`2024-01-02T03:04:05.000Z`).
+fn parse_timestamp_ms(s: &str) -> Option<u64> {
+    let num = |range: std::ops::Range<usize>| s.get(range)?.parse::<u64>().ok();
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    let millis = match s.get(19..20) {
+        Some(".") => num(20..23).unwrap_or(0),
+        _ => 0,
+    };
+    if !(1..=12).contains(&month) || year < 1970 {
+        return None;
+    }
+    // Days from civil date (inverse of `backup::days_to_ymd`)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(((days * 86400 + hour * 3600 + minute * 60 + second) * 1000) + millis)
+}
+
+// ---------------------------------------------------------------------------
+// Minimal XML reading for S3 responses
+// ---------------------------------------------------------------------------
+
+/// Inner text of every `<tag>...</tag>` element in `xml`, in order.
+/// Sufficient for the flat, namespace-free responses S3 sends.
+fn xml_blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut out = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else {
+            break;
+        };
+        out.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    out
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use axum::Router;
+    use axum::body::Bytes;
+    use axum::extract::{Path as UrlPath, Query, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::get;
+
+    use super::*;
+
+    fn config(endpoint: Option<&str>, region: &str) -> S3Config {
+        S3Config {
+            bucket: "backups".to_owned(),
+            prefix: "nightly/".to_owned(),
+            region: region.to_owned(),
+            endpoint: endpoint.map(str::to_owned),
+            access_key_id: "AKIDEXAMPLE".to_owned(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_owned(),
+            session_token: None,
+        }
+    }
+
+    fn authorization(config: &S3Config, request: &SignedRequest<'_>) -> String {
+        sign(config, request)
+            .into_iter()
+            .find(|(name, _)| *name == "authorization")
+            .unwrap()
+            .1
+    }
+
+    // Expected signatures were computed with botocore's S3SigV4Auth.
+    #[test]
+    fn signs_like_the_reference_implementation() {
+        let minio = config(Some("http://localhost:9000"), "us-east-1");
+        let put = authorization(
+            &minio,
+            &SignedRequest {
+                method: "PUT",
+                host: "localhost:9000",
+                path: "/backups/nightly/default/backup_full_0000.grafeo",
+                query: "",
+                payload_hash: &hex::encode(Sha256::digest(b"hello")),
+                amz_date: "20240102T030405Z",
+            },
+        );
+        assert_eq!(
+            put,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240102/us-east-1/s3/aws4_request, \
+             SignedHeaders=host;x-amz-content-sha256;x-amz-date, \
+             Signature=bee27744638577befaf9da9cad5a161c7a03052f075fe6118b2cbe15517f15b8"
+        );
+
+        let mut query = BTreeMap::new();
+        query.insert("list-type", "2".to_owned());
+        query.insert("prefix", "nightly/default/".to_owned());
+        query.insert("delimiter", "/".to_owned());
+        let list = authorization(
+            &minio,
+            &SignedRequest {
+                method: "GET",
+                host: "localhost:9000",
+                path: "/backups",
+                query: &canonical_query(&query),
+                payload_hash: &hex::encode(Sha256::digest(b"")),
+                amz_date: "20240102T030405Z",
+            },
+        );
+        assert!(
+            list.ends_with(
+                "Signature=cf1c8267bc2aa6caf7e5ef5f7a8037411d27598366277fa7ed834501936b5f4b"
+            ),
+            "{list}"
+        );
+
+        let aws = config(None, "eu-west-1");
+        let get = authorization(
+            &aws,
+            &SignedRequest {
+                method: "GET",
+                host: "backups.s3.eu-west-1.amazonaws.com",
+                path: &format!("/{}", uri_encode("a b/x~y.grafeo", false)),
+                query: "",
+                payload_hash: &hex::encode(Sha256::digest(b"")),
+                amz_date: "20240102T030405Z",
+            },
+        );
+        assert!(
+            get.ends_with(
+                "Signature=fb413d8ac49b35958f6b186a7580c672e2120e94f4d89e034ce9afee9f1112ef"
+            ),
+            "{get}"
+        );
+    }
+
+    #[test]
+    fn hmac_matches_rfc_4231() {
+        // Test case 2
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn parses_s3_urls() {
+        assert_eq!(
+            S3Config::parse_url("s3://bucket/a/b/").unwrap(),
+            ("bucket".to_owned(), "a/b/".to_owned())
+        );
+        assert_eq!(
+            S3Config::parse_url("s3://bucket").unwrap(),
+            ("bucket".to_owned(), String::new())
+        );
+        assert!(S3Config::parse_url("s3:///prefix").is_err());
+        assert!(S3Config::parse_url("file:///tmp").is_err());
+    }
+
+    #[test]
+    fn amz_date_formats_utc() {
+        assert_eq!(amz_date(1_704_164_645), "20240102T030405Z");
+    }
+
+    #[test]
+    fn parses_list_timestamps() {
+        assert_eq!(
+            parse_timestamp_ms("2024-01-02T03:04:05.678Z"),
+            Some(1_704_164_645_678)
+        );
+        assert_eq!(parse_timestamp_ms("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp_ms("garbage"), None);
+    }
+
+    #[test]
+    fn reads_list_responses() {
+        let xml = "<ListBucketResult><Prefix>p/</Prefix>\
+            <Contents><Key>p/a&amp;b</Key><Size>1</Size></Contents>\
+            <CommonPrefixes><Prefix>p/db/</Prefix></CommonPrefixes>\
+            <IsTruncated>false</IsTruncated></ListBucketResult>";
+        let keys: Vec<String> = xml_blocks(xml, "Contents")
+            .into_iter()
+            .flat_map(|c| xml_blocks(c, "Key"))
+            .map(unescape)
+            .collect();
+        assert_eq!(keys, ["p/a&b"]);
+        let prefixes: Vec<&str> = xml_blocks(xml, "CommonPrefixes")
+            .into_iter()
+            .flat_map(|c| xml_blocks(c, "Prefix"))
+            .collect();
+        assert_eq!(prefixes, ["p/db/"]);
+    }
+
+    // -----------------------------------------------------------------------
+    // In-memory stand-in for an S3-compatible server
+    // -----------------------------------------------------------------------
+
+    type Objects = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Parts of in-progress multipart uploads, by upload ID and part number.
+    type Parts = Arc<Mutex<BTreeMap<String, BTreeMap<u32, Vec<u8>>>>>;
+
+    #[derive(Clone, Default)]
+    struct Store {
+        objects: Objects,
+        parts: Parts,
+        /// Rejects uploaded parts, to exercise aborts.
+        reject_parts: Arc<AtomicBool>,
+    }
+
+    fn check_signed(headers: &HeaderMap, body: &[u8]) -> Result<(), StatusCode> {
+        let auth = headers
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let hash = headers
+            .get("x-amz-content-sha256")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if !auth.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+            || hash != hex::encode(Sha256::digest(body))
+        {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Ok(())
+    }
+
+    async fn list_objects(
+        State(store): State<Store>,
+        headers: HeaderMap,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Result<String, StatusCode> {
+        check_signed(&headers, b"")?;
+        let prefix = params.get("prefix").cloned().unwrap_or_default();
+        let objects = store.objects.lock().unwrap();
+        let mut keys: Vec<&String> = objects.keys().filter(|k| k.starts_with(&prefix)).collect();
+        keys.sort();
+        let mut contents = String::new();
+        let mut prefixes = std::collections::BTreeSet::new();
+        for key in keys {
+            match key[prefix.len()..].find('/') {
+                Some(i) if params.contains_key("delimiter") => {
+                    prefixes.insert(format!("{prefix}{}", &key[prefix.len()..=prefix.len() + i]));
+                }
+                _ => {
+                    let _ = write!(
+                        contents,
+                        "<Contents><Key>{key}</Key><LastModified>2024-01-02T03:04:05.000Z\
+                         </LastModified><Size>{}</Size></Contents>",
+                        objects[key].len()
+                    );
+                }
+            }
+        }
+        let mut common = String::new();
+        for p in &prefixes {
+            let _ = write!(
+                common,
+                "<CommonPrefixes><Prefix>{p}</Prefix></CommonPrefixes>"
+            );
+        }
+        Ok(format!(
+            "<ListBucketResult><Prefix>{prefix}</Prefix>{contents}{common}\
+             <IsTruncated>false</IsTruncated></ListBucketResult>"
+        ))
+    }
+
+    async fn get_object(
+        State(store): State<Store>,
+        headers: HeaderMap,
+        UrlPath(key): UrlPath<String>,
+    ) -> Result<Vec<u8>, StatusCode> {
+        check_signed(&headers, b"")?;
+        store
+            .objects
+            .lock()
+            .unwrap()
+            .get(&key)
+            .cloned()
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    async fn put_object(
+        State(store): State<Store>,
+        headers: HeaderMap,
+        UrlPath(key): UrlPath<String>,
+        Query(params): Query<HashMap<String, String>>,
+        body: Bytes,
+    ) -> Result<(StatusCode, HeaderMap), StatusCode> {
+        check_signed(&headers, &body)?;
+        let mut reply = HeaderMap::new();
+        match (params.get("uploadId"), params.get("partNumber")) {
+            (Some(upload_id), Some(number)) => {
+                if store.reject_parts.load(Ordering::Relaxed) {
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+                let number: u32 = number.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+                let mut parts = store.parts.lock().unwrap();
+                let upload = parts.get_mut(upload_id).ok_or(StatusCode::NOT_FOUND)?;
+                let etag = format!("\"{}\"", hex::encode(Sha256::digest(&body)));
+                reply.insert("etag", etag.parse().unwrap());
+                upload.insert(number, body.to_vec());
+            }
+            _ => {
+                store.objects.lock().unwrap().insert(key, body.to_vec());
+            }
+        }
+        Ok((StatusCode::OK, reply))
+    }
+
+    async fn post_object(
+        State(store): State<Store>,
+        headers: HeaderMap,
+        UrlPath(key): UrlPath<String>,
+        Query(params): Query<HashMap<String, String>>,
+        body: Bytes,
+    ) -> Result<String, StatusCode> {
+        check_signed(&headers, &body)?;
+        if params.contains_key("uploads") {
+            let upload_id = format!("upload-{}", store.parts.lock().unwrap().len());
+            store
+                .parts
+                .lock()
+                .unwrap()
+                .insert(upload_id.clone(), BTreeMap::new());
+            return Ok(format!(
+                "<InitiateMultipartUploadResult><UploadId>{upload_id}</UploadId>\
+                 </InitiateMultipartUploadResult>"
+            ));
+        }
+        let upload_id = params.get("uploadId").ok_or(StatusCode::BAD_REQUEST)?;
+        let parts = store
+            .parts
+            .lock()
+            .unwrap()
+            .remove(upload_id)
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let listed = xml_blocks(std::str::from_utf8(&body).unwrap(), "PartNumber").len();
+        if listed != parts.len() {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let object = parts.into_values().flatten().collect();
+        store.objects.lock().unwrap().insert(key, object);
+        Ok("<CompleteMultipartUploadResult></CompleteMultipartUploadResult>".to_owned())
+    }
+
+    async fn delete_object(
+        State(store): State<Store>,
+        headers: HeaderMap,
+        UrlPath(key): UrlPath<String>,
+        Query(params): Query<HashMap<String, String>>,
+    ) -> Result<StatusCode, StatusCode> {
+        check_signed(&headers, b"")?;
+        if let Some(upload_id) = params.get("uploadId") {
+            store.parts.lock().unwrap().remove(upload_id);
+        } else {
+            store.objects.lock().unwrap().remove(&key);
+        }
+        Ok(StatusCode::NO_CONTENT)
+    }
+
+    /// Serves bucket `backups` path-style and returns its endpoint.
+    async fn spawn_store(store: Store) -> String {
+        let app = Router::new()
+            .route("/backups", get(list_objects))
+            .route(
+                "/backups/{*key}",
+                get(get_object)
+                    .put(put_object)
+                    .post(post_object)
+                    .delete(delete_object),
+            )
+            .with_state(store);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn s3_target_round_trip() {
+        let store = Store::default();
+        let objects = store.objects.clone();
+        let endpoint = spawn_store(store).await;
+        let target = S3Target::new(config(Some(&endpoint), "us-east-1")).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let source = dir.path().join("backup_full_0000.grafeo");
+        std::fs::write(&source, b"segment").unwrap();
+        target
+            .upload("default", "backup_full_0000.grafeo", &source)
+            .await
+            .unwrap();
+        target
+            .upload("other", "backup_full_0000.grafeo", &source)
+            .await
+            .unwrap();
+        assert!(
+            objects
+                .lock()
+                .unwrap()
+                .contains_key("nightly/default/backup_full_0000.grafeo")
+        );
+
+        let files = target.list("default").await.unwrap();
+        assert_eq!(
+            files,
+            [TargetFile {
+                name: "backup_full_0000.grafeo".to_owned(),
+                size_bytes: 7,
+                modified_ms: 1_704_164_645_000,
+            }]
+        );
+        assert_eq!(target.list_databases().await.unwrap(), ["default", "other"]);
+
+        let dest = dir.path().join("restore").join("copy.grafeo");
+        assert!(
+            target
+                .download("default", "backup_full_0000.grafeo", &dest)
+                .await
+                .unwrap()
+        );
+        assert_eq!(std::fs::read(&dest).unwrap(), b"segment");
+        assert!(
+            !target
+                .download("default", "missing.grafeo", &dest)
+                .await
+                .unwrap()
+        );
+
+        target
+            .delete("default", "backup_full_0000.grafeo")
+            .await
+            .unwrap();
+        assert!(target.list("default").await.unwrap().is_empty());
+        assert!(target.describe().starts_with("s3://backups/nightly/"));
+    }
+
+    #[tokio::test]
+    async fn s3_target_reports_rejected_requests() {
+        let endpoint = spawn_store(Store::default()).await;
+        let mut bad = config(Some(&endpoint), "us-east-1");
+        bad.access_key_id = "WRONG".to_owned();
+        let target = S3Target::new(bad).unwrap();
+
+        let err = target.list("default").await.unwrap_err();
+        assert!(err.to_string().contains("403"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn s3_target_uploads_large_files_in_parts() {
+        let store = Store::default();
+        let endpoint = spawn_store(store.clone()).await;
+        let mut target = S3Target::new(config(Some(&endpoint), "us-east-1")).unwrap();
+        target.multipart_threshold = 8;
+        target.part_size = 4;
+        let dir = tempfile::tempdir().unwrap();
+
+        let source = dir.path().join("backup_full_0000.grafeo");
+        std::fs::write(&source, b"0123456789").unwrap();
+        target
+            .upload("default", "backup_full_0000.grafeo", &source)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.objects.lock().unwrap()["nightly/default/backup_full_0000.grafeo"],
+            b"0123456789"
+        );
+        assert!(store.parts.lock().unwrap().is_empty());
+
+        // A rejected part aborts the upload
+        store.reject_parts.store(true, Ordering::Relaxed);
+        let err = target
+            .upload("default", "backup_full_0001.grafeo", &source)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("500"), "{err}");
+        assert!(store.parts.lock().unwrap().is_empty());
+        assert!(
+            !store
+                .objects
+                .lock()
+                .unwrap()
+                .contains_key("nightly/default/backup_full_0001.grafeo")
+        );
+    }
+}
@@ -0,0 +1,708 @@
+//! Scheduled backups.
+//!
+//! A [`BackupScheduler`] takes full and incremental backups of databases at
+//! per-database intervals and applies the schedule's retention after each
+//! successful run. Schedules, run counters and recent history are kept in
+//! `{backup_dir}/schedules.json`, so they survive restarts: a backup that
+//! fell due while the server was down runs once at the next check, and the
+//! following one is due an interval after that.
+//!
+//! Both kinds count from their last attempt, successful or not. A new
+//! schedule takes its first full backup right away; incrementals follow
+//! one interval after the latest backup of either kind. An incremental run
+//! with nothing committed since the last backup is recorded as a no-op.
+//!
+//! `/metrics` exports each schedule's next run, last success and last
+//! duration, and its run and failure counts, as `grafeo_backup_*` series
+//! labelled by `database` and `kind`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use grafeo_engine::GrafeoDB;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::ServiceState;
+use crate::backup::{BackupService, millis_to_iso, unix_millis};
+use crate::backup_crypto::BackupKeys;
+use crate::backup_target::{BackupTarget, LocalTarget};
+use crate::database::DatabaseManager;
+use crate::error::ServiceError;
+use crate::metrics::ScheduledBackup;
+use crate::types;
+
+/// Schedule file name, relative to the backup directory.
+const SCHEDULES_FILENAME: &str = "schedules.json";
+
+/// How often the background task checks for due backups.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Shortest interval a schedule may use.
+const MIN_INTERVAL_SECS: u64 = 60;
+
+/// Runs kept in each schedule's history.
+const HISTORY_LEN: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunKind {
+    Full,
+    Incremental,
+}
+
+impl RunKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Incremental => "incremental",
+        }
+    }
+}
+
+/// Counters and timestamps (Unix millis) of one backup kind.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct KindState {
+    last_attempt_ms: Option<u64>,
+    last_success_ms: Option<u64>,
+    last_duration_ms: u64,
+    runs: u64,
+    failures: u64,
+}
+
+/// A schedule as persisted in the schedule file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScheduleRecord {
+    schedule: types::BackupSchedule,
+    created_at_ms: u64,
+    #[serde(default)]
+    full: KindState,
+    #[serde(default)]
+    incremental: KindState,
+    /// Newest first.
+    #[serde(default)]
+    history: VecDeque<types::BackupRun>,
+}
+
+impl ScheduleRecord {
+    fn next_full_ms(&self) -> Option<u64> {
+        let interval = self.schedule.full_interval_secs? * 1000;
+        Some(
+            self.full
+                .last_attempt_ms
+                .map_or(self.created_at_ms, |t| t + interval),
+        )
+    }
+
+    fn next_incremental_ms(&self) -> Option<u64> {
+        let interval = self.schedule.incremental_interval_secs? * 1000;
+        let last = self
+            .full
+            .last_attempt_ms
+            .max(self.incremental.last_attempt_ms)
+            .unwrap_or(self.created_at_ms);
+        Some(last + interval)
+    }
+
+    /// The backup due at `now_ms`, if any. A due full backup takes
+    /// precedence and also resets the incremental interval.
+    fn due(&self, now_ms: u64) -> Option<RunKind> {
+        if self.next_full_ms().is_some_and(|t| t <= now_ms) {
+            Some(RunKind::Full)
+        } else if self.next_incremental_ms().is_some_and(|t| t <= now_ms) {
+            Some(RunKind::Incremental)
+        } else {
+            None
+        }
+    }
+
+    fn kind_mut(&mut self, kind: RunKind) -> &mut KindState {
+        match kind {
+            RunKind::Full => &mut self.full,
+            RunKind::Incremental => &mut self.incremental,
+        }
+    }
+
+    fn record(&mut self, kind: RunKind, started_ms: u64, run: types::BackupRun) {
+        let state = self.kind_mut(kind);
+        state.last_attempt_ms = Some(started_ms);
+        state.last_duration_ms = run.duration_ms;
+        state.runs += 1;
+        if run.success {
+            state.last_success_ms = Some(started_ms);
+        } else {
+            state.failures += 1;
+        }
+        self.history.push_front(run);
+        self.history.truncate(HISTORY_LEN);
+    }
+
+    fn status(&self, database: &str) -> types::BackupScheduleStatus {
+        types::BackupScheduleStatus {
+            database: database.to_owned(),
+            schedule: self.schedule.clone(),
+            next_full_at: self.next_full_ms().map(millis_to_iso),
+            next_incremental_at: self.next_incremental_ms().map(millis_to_iso),
+            history: self.history.iter().cloned().collect(),
+        }
+    }
+}
+
+/// Per-database backup schedules, persisted in the backup directory.
+pub struct BackupScheduler {
+    backup_dir: PathBuf,
+    target: Arc<dyn BackupTarget>,
+    keys: Option<Arc<BackupKeys>>,
+    schedules: Mutex<BTreeMap<String, ScheduleRecord>>,
+}
+
+impl BackupScheduler {
+    /// Loads the schedules saved in `backup_dir`. A missing file yields no
+    /// schedules; a corrupt one is logged and ignored, like the label
+    /// sidecars. Backups stay in `backup_dir` unless a target is set with
+    /// [`Self::with_target`].
+    pub fn load(backup_dir: &Path) -> Self {
+        let path = backup_dir.join(SCHEDULES_FILENAME);
+        let schedules = match std::fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "backup schedule file is corrupt, ignoring"
+                );
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+        if !schedules.is_empty() {
+            tracing::info!(count = schedules.len(), "Backup schedules loaded");
+        }
+        Self {
+            backup_dir: backup_dir.to_path_buf(),
+            target: Arc::new(LocalTarget::new(backup_dir.to_path_buf())),
+            keys: None,
+            schedules: Mutex::new(schedules),
+        }
+    }
+
+    /// Stores scheduled backups at `target`.
+    #[must_use]
+    pub fn with_target(mut self, target: Arc<dyn BackupTarget>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Encrypts scheduled backups with `keys`.
+    #[must_use]
+    pub fn with_keys(mut self, keys: Arc<BackupKeys>) -> Self {
+        self.keys = Some(keys);
+        self
+    }
+
+    /// Creates or replaces the schedule of `db_name`. Replacing keeps the
+    /// run history and counts from the last attempts.
+    pub fn set(
+        &self,
+        databases: &DatabaseManager,
+        db_name: &str,
+        schedule: types::BackupSchedule,
+    ) -> Result<types::BackupScheduleStatus, ServiceError> {
+        let entry = databases
+            .get(db_name)
+            .ok_or_else(|| ServiceError::NotFound(format!("database '{db_name}' not found")))?;
+        validate(&schedule)?;
+        if schedule.incremental_interval_secs.is_some() && entry.db().path().is_none() {
+            return Err(ServiceError::BadRequest(
+                "incremental backups require a persistent database".to_owned(),
+            ));
+        }
+
+        let mut schedules = self.schedules.lock();
+        let record = schedules
+            .entry(db_name.to_owned())
+            .and_modify(|r| r.schedule = schedule.clone())
+            .or_insert_with(|| ScheduleRecord {
+                schedule,
+                created_at_ms: unix_millis(),
+                full: KindState::default(),
+                incremental: KindState::default(),
+                history: VecDeque::new(),
+            });
+        let status = record.status(db_name);
+        self.save(&schedules)?;
+
+        tracing::info!(database = %db_name, "Backup schedule set");
+        Ok(status)
+    }
+
+    /// Returns the schedule of `db_name`.
+    pub fn get(&self, db_name: &str) -> Result<types::BackupScheduleStatus, ServiceError> {
+        self.schedules
+            .lock()
+            .get(db_name)
+            .map(|r| r.status(db_name))
+            .ok_or_else(|| {
+                ServiceError::NotFound(format!("no backup schedule for database '{db_name}'"))
+            })
+    }
+
+    /// Returns all schedules, ordered by database name.
+    pub fn list(&self) -> Vec<types::BackupScheduleStatus> {
+        self.schedules
+            .lock()
+            .iter()
+            .map(|(name, r)| r.status(name))
+            .collect()
+    }
+
+    /// Removes the schedule of `db_name`. Existing backups are kept.
+    pub fn remove(&self, db_name: &str) -> Result<(), ServiceError> {
+        let mut schedules = self.schedules.lock();
+        if schedules.remove(db_name).is_none() {
+            return Err(ServiceError::NotFound(format!(
+                "no backup schedule for database '{db_name}'"
+            )));
+        }
+        self.save(&schedules)?;
+        tracing::info!(database = %db_name, "Backup schedule removed");
+        Ok(())
+    }
+
+    /// Runs every backup due at `now_ms` (Unix millis) one after another,
+    /// then applies the schedule's retention. `default_keep` is used for
+    /// schedules without their own `keep`. Returns the number of runs.
+    pub async fn run_due(
+        &self,
+        databases: &DatabaseManager,
+        default_keep: Option<usize>,
+        now_ms: u64,
+    ) -> usize {
+        let due: Vec<(String, RunKind, types::BackupSchedule)> = self
+            .schedules
+            .lock()
+            .iter()
+            .filter_map(|(name, r)| Some((name.clone(), r.due(now_ms)?, r.schedule.clone())))
+            .collect();
+
+        for (db_name, kind, schedule) in &due {
+            let started = Instant::now();
+            let result = match kind {
+                RunKind::Full => BackupService::backup_database_to(
+                    databases,
+                    self.target.as_ref(),
+                    db_name,
+                    &self.backup_dir,
+                    None,
+                    self.keys.as_deref(),
+                )
+                .await
+                .map(Some),
+                RunKind::Incremental if !self.has_new_commits(databases, db_name) => Ok(None),
+                RunKind::Incremental => BackupService::backup_incremental_to(
+                    databases,
+                    self.target.as_ref(),
+                    db_name,
+                    &self.backup_dir,
+                    self.keys.as_deref(),
+                )
+                .await
+                .map(Some),
+            };
+            let duration_ms = started.elapsed().as_millis() as u64;
+
+            let run = match result {
+                Ok(entry) => types::BackupRun {
+                    kind: kind.label().to_owned(),
+                    started_at: millis_to_iso(now_ms),
+                    duration_ms,
+                    success: true,
+                    pruned: match entry {
+                        Some(_) => self.prune(db_name, schedule, default_keep).await,
+                        None => Vec::new(),
+                    },
+                    filename: entry.map(|e| e.filename),
+                    error: None,
+                },
+                Err(e) => {
+                    tracing::warn!(
+                        database = %db_name,
+                        kind = kind.label(),
+                        error = %e,
+                        "Scheduled backup failed"
+                    );
+                    types::BackupRun {
+                        kind: kind.label().to_owned(),
+                        started_at: millis_to_iso(now_ms),
+                        duration_ms,
+                        success: false,
+                        filename: None,
+                        error: Some(e.to_string()),
+                        pruned: Vec::new(),
+                    }
+                }
+            };
+
+            // The schedule may have been removed while the backup ran.
+            let mut schedules = self.schedules.lock();
+            if let Some(record) = schedules.get_mut(db_name) {
+                record.record(*kind, now_ms, run);
+                if let Err(e) = self.save(&schedules) {
+                    tracing::warn!(error = %e, "failed to save backup schedules");
+                }
+            }
+        }
+
+        due.len()
+    }
+
+    /// Per-database, per-kind series for the Prometheus endpoint.
+    pub fn metrics(&self) -> Vec<ScheduledBackup> {
+        let schedules = self.schedules.lock();
+        let mut out = Vec::new();
+        for (name, r) in schedules.iter() {
+            let kinds = [
+                (RunKind::Full, r.next_full_ms(), &r.full),
+                (
+                    RunKind::Incremental,
+                    r.next_incremental_ms(),
+                    &r.incremental,
+                ),
+            ];
+            for (kind, next_ms, state) in kinds {
+                let Some(next_ms) = next_ms else {
+                    continue;
+                };
+                out.push(ScheduledBackup {
+                    database: name.clone(),
+                    kind: kind.label(),
+                    next_run_at: next_ms / 1000,
+                    last_success_at: state.last_success_ms.map_or(0, |t| t / 1000),
+                    last_duration_ms: state.last_duration_ms,
+                    runs_total: state.runs,
+                    failures_total: state.failures,
+                });
+            }
+        }
+        out
+    }
+
+    /// Returns `false` when nothing was committed since the last backup in
+    /// the chain of `db_name`, so an incremental backup has nothing to
+    /// write. Any doubt is left to the backup itself to report.
+    fn has_new_commits(&self, databases: &DatabaseManager, db_name: &str) -> bool {
+        let Ok(entry) = databases.get_available(db_name) else {
+            return true;
+        };
+        let last_epoch = GrafeoDB::read_backup_manifest(&self.backup_dir.join(db_name))
+            .ok()
+            .flatten()
+            .and_then(|m| m.segments.last().map(|s| s.end_epoch));
+        last_epoch.is_none_or(|epoch| entry.db().current_epoch() > epoch)
+    }
+
+    /// Applies count and age retention after a successful run. Failures
+    /// are logged; the backup itself already succeeded.
+    async fn prune(
+        &self,
+        db_name: &str,
+        schedule: &types::BackupSchedule,
+        default_keep: Option<usize>,
+    ) -> Vec<String> {
+        let keep = schedule.keep.or(default_keep);
+        let max_age = schedule.max_age_secs.map(Duration::from_secs);
+        if keep.is_none() && max_age.is_none() {
+            return Vec::new();
+        }
+        BackupService::prune_backups(
+            self.target.as_ref(),
+            db_name,
+            &self.backup_dir,
+            keep,
+            max_age,
+        )
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!(database = %db_name, error = %e, "retention failed");
+            Vec::new()
+        })
+    }
+
+    /// Writes the schedules atomically (write tmp, rename). Callers hold
+    /// the lock so concurrent saves cannot clobber each other's temp file.
+    fn save(&self, schedules: &BTreeMap<String, ScheduleRecord>) -> Result<(), ServiceError> {
+        std::fs::create_dir_all(&self.backup_dir).map_err(|e| {
+            ServiceError::Internal(format!("failed to create backup directory: {e}"))
+        })?;
+        let path = self.backup_dir.join(SCHEDULES_FILENAME);
+        let tmp_path = path.with_extension("json.tmp");
+        let text = serde_json::to_string_pretty(schedules)
+            .map_err(|e| ServiceError::Internal(format!("failed to serialize schedules: {e}")))?;
+        std::fs::write(&tmp_path, text)
+            .map_err(|e| ServiceError::Internal(format!("failed to write schedules: {e}")))?;
+        std::fs::rename(&tmp_path, &path)
+            .map_err(|e| ServiceError::Internal(format!("failed to rename schedules: {e}")))
+    }
+}
+
+fn validate(schedule: &types::BackupSchedule) -> Result<(), ServiceError> {
+    if schedule.full_interval_secs.is_none() && schedule.incremental_interval_secs.is_none() {
+        return Err(ServiceError::BadRequest(
+            "schedule needs full_interval_secs or incremental_interval_secs".to_owned(),
+        ));
+    }
+    for interval in [
+        schedule.full_interval_secs,
+        schedule.incremental_interval_secs,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if interval < MIN_INTERVAL_SECS {
+            return Err(ServiceError::BadRequest(format!(
+                "backup intervals must be at least {MIN_INTERVAL_SECS} seconds"
+            )));
+        }
+    }
+    if schedule.keep == Some(0) {
+        return Err(ServiceError::BadRequest(
+            "keep must be at least 1".to_owned(),
+        ));
+    }
+    if schedule.max_age_secs == Some(0) {
+        return Err(ServiceError::BadRequest(
+            "max_age_secs must be at least 1".to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Spawns the task that runs due backups. No-op unless a backup directory
+/// is configured.
+pub fn start(state: ServiceState) {
+    if state.backup_scheduler().is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            if let Some(scheduler) = state.backup_scheduler() {
+                scheduler
+                    .run_due(state.databases(), state.backup_retention(), unix_millis())
+                    .await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn schedule(full: Option<u64>, incremental: Option<u64>) -> types::BackupSchedule {
+        types::BackupSchedule {
+            full_interval_secs: full,
+            incremental_interval_secs: incremental,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn set_validates_schedule() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr = DatabaseManager::new(None, false);
+        let scheduler = BackupScheduler::load(backup_dir.path());
+
+        let bad = [
+            schedule(None, None),
+            schedule(Some(10), None),
+            types::BackupSchedule {
+                keep: Some(0),
+                ..schedule(Some(3600), None)
+            },
+            types::BackupSchedule {
+                max_age_secs: Some(0),
+                ..schedule(Some(3600), None)
+            },
+            // In-memory databases have no WAL to back up incrementally
+            schedule(Some(3600), Some(600)),
+        ];
+        for s in bad {
+            let err = scheduler.set(&mgr, "default", s).unwrap_err();
+            assert!(matches!(err, ServiceError::BadRequest(_)), "{err:?}");
+        }
+
+        let err = scheduler
+            .set(&mgr, "missing", schedule(Some(3600), None))
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotFound(_)));
+        assert!(scheduler.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn runs_full_then_incremental_when_due() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr = DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let scheduler = BackupScheduler::load(backup_dir.path());
+
+        let status = scheduler
+            .set(&mgr, "default", schedule(Some(24 * 3600), Some(3600)))
+            .unwrap();
+        assert!(status.next_full_at.is_some());
+        let created = scheduler.schedules.lock()["default"].created_at_ms;
+
+        // The first full backup is due immediately
+        assert_eq!(scheduler.run_due(&mgr, None, created).await, 1);
+        assert_eq!(scheduler.run_due(&mgr, None, created + 1000).await, 0);
+
+        // An hour later the incremental is due
+        let db = mgr.get("default").unwrap().db();
+        db.session().execute("INSERT (:Test {v: 1})").unwrap();
+        assert_eq!(scheduler.run_due(&mgr, None, created + HOUR_MS).await, 1);
+
+        // Without new commits there is nothing to back up
+        assert_eq!(
+            scheduler.run_due(&mgr, None, created + 2 * HOUR_MS).await,
+            1
+        );
+
+        let status = scheduler.get("default").unwrap();
+        let kinds: Vec<&str> = status.history.iter().map(|r| r.kind.as_str()).collect();
+        assert_eq!(kinds, ["incremental", "incremental", "full"]);
+        assert!(status.history.iter().all(|r| r.success), "{status:?}");
+        assert!(status.history[0].filename.is_none());
+        assert!(status.history[1].filename.is_some());
+
+        let backups = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            backups[0].filename,
+            status.history[2].filename.clone().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn schedules_survive_reload() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr = DatabaseManager::new(None, false);
+        let scheduler = BackupScheduler::load(backup_dir.path());
+        scheduler
+            .set(&mgr, "default", schedule(Some(3600), None))
+            .unwrap();
+        let created = scheduler.schedules.lock()["default"].created_at_ms;
+        assert_eq!(scheduler.run_due(&mgr, None, created).await, 1);
+        let before = scheduler.get("default").unwrap();
+
+        let reloaded = BackupScheduler::load(backup_dir.path());
+        let after = reloaded.get("default").unwrap();
+        assert_eq!(after.schedule, before.schedule);
+        assert_eq!(after.next_full_at, before.next_full_at);
+        assert_eq!(after.history.len(), 1);
+
+        // Not due again until an interval after the last run
+        assert_eq!(reloaded.run_due(&mgr, None, created + 1000).await, 0);
+        assert_eq!(reloaded.run_due(&mgr, None, created + HOUR_MS).await, 1);
+    }
+
+    #[tokio::test]
+    async fn failed_run_is_recorded_and_retried_next_interval() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr = DatabaseManager::new(None, false);
+        mgr.create(&types::CreateDatabaseRequest {
+            name: "scratch".to_owned(),
+            database_type: types::DatabaseType::Lpg,
+            storage_mode: types::StorageMode::InMemory,
+            options: types::DatabaseOptions::default(),
+            schema_file: None,
+            schema_filename: None,
+        })
+        .unwrap();
+        let scheduler = BackupScheduler::load(backup_dir.path());
+        scheduler
+            .set(&mgr, "scratch", schedule(Some(3600), None))
+            .unwrap();
+        mgr.delete("scratch").unwrap();
+
+        let created = scheduler.schedules.lock()["scratch"].created_at_ms;
+        assert_eq!(scheduler.run_due(&mgr, None, created).await, 1);
+        assert_eq!(scheduler.run_due(&mgr, None, created + 1000).await, 0);
+
+        let status = scheduler.get("scratch").unwrap();
+        assert!(!status.history[0].success);
+        assert!(status.history[0].error.is_some());
+
+        let metrics = scheduler.metrics();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].kind, "full");
+        assert_eq!(metrics[0].runs_total, 1);
+        assert_eq!(metrics[0].failures_total, 1);
+        assert_eq!(metrics[0].last_success_at, 0);
+        assert_eq!(metrics[0].next_run_at, (created + HOUR_MS) / 1000);
+    }
+
+    #[tokio::test]
+    async fn retention_applies_after_runs() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mgr = DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let scheduler = BackupScheduler::load(backup_dir.path());
+        scheduler
+            .set(&mgr, "default", schedule(Some(3600), None))
+            .unwrap();
+        let created = scheduler.schedules.lock()["default"].created_at_ms;
+
+        for i in 0..3 {
+            scheduler
+                .run_due(&mgr, Some(2), created + i * HOUR_MS)
+                .await;
+        }
+
+        let status = scheduler.get("default").unwrap();
+        assert_eq!(status.history[0].pruned.len(), 1);
+        let backups = BackupService::list_backups(Some("default"), backup_dir.path()).unwrap();
+        assert_eq!(backups.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn runs_store_backups_at_target() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let remote_dir = tempfile::tempdir().unwrap();
+        let mgr = DatabaseManager::new(Some(data_dir.path().to_str().unwrap()), false);
+        let scheduler = BackupScheduler::load(backup_dir.path())
+            .with_target(Arc::new(LocalTarget::new(remote_dir.path().to_path_buf())));
+        scheduler
+            .set(&mgr, "default", schedule(Some(3600), None))
+            .unwrap();
+        let created = scheduler.schedules.lock()["default"].created_at_ms;
+
+        for i in 0..2 {
+            scheduler
+                .run_due(&mgr, Some(1), created + i * HOUR_MS)
+                .await;
+        }
+
+        let status = scheduler.get("default").unwrap();
+        let newest = status.history[0].filename.clone().unwrap();
+        let oldest = status.history[1].filename.clone().unwrap();
+        assert_eq!(status.history[0].pruned, std::slice::from_ref(&oldest));
+        let remote = remote_dir.path().join("default");
+        assert!(remote.join(&newest).exists());
+        assert!(!remote.join(&oldest).exists());
+    }
+
+    #[test]
+    fn remove_unknown_schedule_is_not_found() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let scheduler = BackupScheduler::load(backup_dir.path());
+        assert!(matches!(
+            scheduler.remove("default"),
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+}
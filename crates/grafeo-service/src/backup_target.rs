@@ -0,0 +1,287 @@
+//! Storage targets for backups.
+//!
+//! The engine always writes backups into the local backup directory
+//! (`{backup_dir}/{db_name}/`), which doubles as a cache. A [`BackupTarget`]
+//! is where they are kept: [`BackupService`](crate::backup::BackupService)
+//! uploads every new segment with the chain's manifest and label sidecar,
+//! lists and prunes what the target holds, and downloads whatever a
+//! restore needs but the local directory lacks, so a server on a new host
+//! can restore from backups another one took.
+//!
+//! Without `--backup-target` the target is the backup directory itself, so
+//! nothing is copied. A `file://` target copies backups to another
+//! directory, e.g. a network mount; `s3://` (feature `s3-backup`) stores
+//! them in an S3-compatible bucket.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::ServiceError;
+
+/// A place backups are stored, addressed by database and file name.
+#[async_trait::async_trait]
+pub trait BackupTarget: Send + Sync {
+    /// Human-readable location, for logs.
+    fn describe(&self) -> String;
+
+    /// Stores the local file `source` as `filename` of `db_name`,
+    /// replacing any previous copy.
+    async fn upload(
+        &self,
+        db_name: &str,
+        filename: &str,
+        source: &Path,
+    ) -> Result<(), ServiceError>;
+
+    /// Copies `filename` of `db_name` to `dest`. Returns `false` if the
+    /// target does not hold it.
+    async fn download(
+        &self,
+        db_name: &str,
+        filename: &str,
+        dest: &Path,
+    ) -> Result<bool, ServiceError>;
+
+    /// Lists the files stored for `db_name`.
+    async fn list(&self, db_name: &str) -> Result<Vec<TargetFile>, ServiceError>;
+
+    /// Lists the databases the target holds files for.
+    async fn list_databases(&self) -> Result<Vec<String>, ServiceError>;
+
+    /// Deletes `filename` of `db_name`. Deleting a missing file succeeds.
+    async fn delete(&self, db_name: &str, filename: &str) -> Result<(), ServiceError>;
+}
+
+/// A file held by a target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetFile {
+    pub name: String,
+    pub size_bytes: u64,
+    /// Last modification, Unix millis.
+    pub modified_ms: u64,
+}
+
+/// Where backups are stored, as configured with `--backup-target`.
+#[derive(Debug, Clone)]
+pub enum BackupTargetConfig {
+    /// A directory on a local or mounted filesystem (`file:///path`).
+    Local(PathBuf),
+    /// An S3-compatible bucket (`s3://bucket/prefix`).
+    #[cfg(feature = "s3-backup")]
+    S3(crate::backup_s3::S3Config),
+}
+
+impl BackupTargetConfig {
+    /// Builds the target.
+    pub fn build(&self) -> Result<Arc<dyn BackupTarget>, String> {
+        match self {
+            Self::Local(root) => Ok(Arc::new(LocalTarget::new(root.clone()))),
+            #[cfg(feature = "s3-backup")]
+            Self::S3(config) => Ok(Arc::new(crate::backup_s3::S3Target::new(config.clone())?)),
+        }
+    }
+}
+
+/// Backups stored in a directory tree (`{root}/{db_name}/{filename}`).
+pub struct LocalTarget {
+    root: PathBuf,
+}
+
+impl LocalTarget {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, db_name: &str, filename: &str) -> Result<PathBuf, ServiceError> {
+        for part in [db_name, filename] {
+            if part.contains('/') || part.contains('\\') || part.contains("..") {
+                return Err(ServiceError::BadRequest(
+                    "invalid path parameter".to_string(),
+                ));
+            }
+        }
+        Ok(self.root.join(db_name).join(filename))
+    }
+}
+
+/// Whether `a` and `b` name the same file, e.g. when the target is the
+/// backup directory itself.
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b
+        || matches!(
+            (std::fs::canonicalize(a), std::fs::canonicalize(b)),
+            (Ok(a), Ok(b)) if a == b
+        )
+}
+
+#[async_trait::async_trait]
+impl BackupTarget for LocalTarget {
+    fn describe(&self) -> String {
+        format!("file://{}", self.root.display())
+    }
+
+    async fn upload(
+        &self,
+        db_name: &str,
+        filename: &str,
+        source: &Path,
+    ) -> Result<(), ServiceError> {
+        let path = self.path(db_name, filename)?;
+        if same_file(source, &path) {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                ServiceError::Internal(format!("failed to create backup target directory: {e}"))
+            })?;
+        }
+        // Copy beside the destination and rename, so a reader never sees
+        // a partial file
+        let tmp = path.with_extension("upload.tmp");
+        tokio::fs::copy(source, &tmp)
+            .await
+            .map_err(|e| ServiceError::Internal(format!("failed to upload {filename}: {e}")))?;
+        tokio::fs::rename(&tmp, &path)
+            .await
+            .map_err(|e| ServiceError::Internal(format!("failed to upload {filename}: {e}")))?;
+        Ok(())
+    }
+
+    async fn download(
+        &self,
+        db_name: &str,
+        filename: &str,
+        dest: &Path,
+    ) -> Result<bool, ServiceError> {
+        let path = self.path(db_name, filename)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        if same_file(&path, dest) {
+            return Ok(true);
+        }
+        if let Some(parent) = dest.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| {
+                ServiceError::Internal(format!("failed to create backup directory: {e}"))
+            })?;
+        }
+        tokio::fs::copy(&path, dest)
+            .await
+            .map_err(|e| ServiceError::Internal(format!("failed to download {filename}: {e}")))?;
+        Ok(true)
+    }
+
+    async fn list(&self, db_name: &str) -> Result<Vec<TargetFile>, ServiceError> {
+        let dir = self.path(db_name, "")?;
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            return Ok(vec![]);
+        };
+        let mut files = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if let Ok(meta) = entry.metadata().await
+                && meta.is_file()
+                && let Some(name) = entry.file_name().to_str()
+                && Path::new(name).extension().is_none_or(|ext| ext != "tmp")
+            {
+                files.push(TargetFile {
+                    name: name.to_owned(),
+                    size_bytes: meta.len(),
+                    modified_ms: meta
+                        .modified()
+                        .ok()
+                        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map_or(0, |d| d.as_millis() as u64),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    async fn list_databases(&self) -> Result<Vec<String>, ServiceError> {
+        let Ok(mut entries) = tokio::fs::read_dir(&self.root).await else {
+            return Ok(vec![]);
+        };
+        let mut names = Vec::new();
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.file_type().await.is_ok_and(|t| t.is_dir())
+                && let Some(name) = entry.file_name().to_str()
+            {
+                names.push(name.to_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    async fn delete(&self, db_name: &str, filename: &str) -> Result<(), ServiceError> {
+        let path = self.path(db_name, filename)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(ServiceError::Internal(format!(
+                "failed to delete {filename}: {e}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn local_target_round_trip() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(root.path().to_path_buf());
+
+        let source = source_dir.path().join("a.grafeo");
+        std::fs::write(&source, b"segment").unwrap();
+        target.upload("default", "a.grafeo", &source).await.unwrap();
+
+        let files = target.list("default").await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "a.grafeo");
+        assert_eq!(files[0].size_bytes, 7);
+        assert!(files[0].modified_ms > 0);
+        assert_eq!(target.list_databases().await.unwrap(), ["default"]);
+        assert!(target.list("other").await.unwrap().is_empty());
+
+        let dest = source_dir.path().join("copy.grafeo");
+        assert!(target.download("default", "a.grafeo", &dest).await.unwrap());
+        assert_eq!(std::fs::read(&dest).unwrap(), b"segment");
+        assert!(!target.download("default", "b.grafeo", &dest).await.unwrap());
+
+        target.delete("default", "a.grafeo").await.unwrap();
+        target.delete("default", "a.grafeo").await.unwrap();
+        assert!(target.list("default").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn local_target_at_source_is_noop() {
+        let root = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(root.path().to_path_buf());
+        let path = root.path().join("default").join("a.grafeo");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, b"segment").unwrap();
+
+        target.upload("default", "a.grafeo", &path).await.unwrap();
+        assert!(target.download("default", "a.grafeo", &path).await.unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), b"segment");
+    }
+
+    #[tokio::test]
+    async fn local_target_rejects_traversal() {
+        let root = tempfile::tempdir().unwrap();
+        let target = LocalTarget::new(root.path().to_path_buf());
+        assert!(matches!(
+            target.delete("..", "x").await,
+            Err(ServiceError::BadRequest(_))
+        ));
+        assert!(matches!(
+            target.list("a/b").await,
+            Err(ServiceError::BadRequest(_))
+        ));
+    }
+}
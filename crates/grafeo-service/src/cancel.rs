@@ -0,0 +1,101 @@
+//! Cooperative query cancellation.
+//!
+//! The engine has no external interrupt hook, so cancellation is cooperative:
+//! `QueryService` checks a [`CancelToken`] before dispatch, between the
+//! statements of a batch, and between result chunks of streamable GQL reads.
+//!
+//! Tokens are tripped by `run_with_timeout` when the deadline passes, and by
+//! the [`CancelGuard`] it holds when the awaiting future is dropped, which is
+//! how a client disconnect (HTTP, WebSocket, GWP, Bolt) reaches the blocking
+//! task.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::ServiceError;
+
+/// Shared cancellation flag for one in-flight query.
+///
+/// Cheap to clone: all clones observe the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation. Idempotent.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` once cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Returns `Err(ServiceError::Cancelled)` if cancellation was requested.
+    pub fn check(&self) -> Result<(), ServiceError> {
+        if self.is_cancelled() {
+            Err(ServiceError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns a guard that cancels this token when dropped.
+    pub fn guard(&self) -> CancelGuard {
+        CancelGuard {
+            token: self.clone(),
+        }
+    }
+}
+
+/// Cancels its token on drop.
+///
+/// Held across the `.await` of a blocking query so that dropping the future
+/// (client went away, request aborted) stops the work instead of leaving it
+/// running on the blocking pool.
+pub struct CancelGuard {
+    token: CancelToken,
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_token_is_not_cancelled() {
+        let token = CancelToken::new();
+        assert!(!token.is_cancelled());
+        assert!(token.check().is_ok());
+    }
+
+    #[test]
+    fn cancel_is_visible_to_clones() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(matches!(clone.check(), Err(ServiceError::Cancelled)));
+    }
+
+    #[test]
+    fn guard_cancels_on_drop() {
+        let token = CancelToken::new();
+        let guard = token.guard();
+        assert!(!token.is_cancelled());
+        drop(guard);
+        assert!(token.is_cancelled());
+    }
+}
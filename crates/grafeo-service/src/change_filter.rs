@@ -0,0 +1,414 @@
+//! Server-side filters for change events.
+//!
+//! A [`ChangeFilter`] narrows the events of `GET /db/{name}/changes`, the
+//! SSE changefeed and WebSocket subscriptions to the entities a client
+//! cares about, so it does not have to receive and discard the rest.
+//! The [`ChangeHub`](crate::changefeed::ChangeHub) evaluates each distinct
+//! filter once per poll, before events are broadcast to its subscribers.
+//!
+//! Labels and edge types are only recorded on create events; for updates
+//! and deletes they are looked up on the live entity, or on its create
+//! event once the entity is gone.
+//!
+//! Predicates run as read-only queries under the identity of the client
+//! that asked for the filter.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use grafeo_common::types::{EdgeId, NodeId, PropertyKey, Value};
+use grafeo_engine::GrafeoDB;
+use grafeo_engine::auth::Identity;
+use grafeo_engine::cdc::{ChangeKind, EntityId};
+use serde::{Deserialize, Serialize};
+
+use crate::cancel::CancelToken;
+use crate::error::ServiceError;
+use crate::sync::ChangeEventDto;
+
+/// Events a predicate is evaluated for per query; cancellation is checked
+/// between queries.
+const PREDICATE_CHUNK: usize = 256;
+
+/// Selects the change events a subscriber receives.
+///
+/// Every set criterion must match; empty lists match everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ChangeFilter {
+    /// Entity types to include: `node`, `edge` or `triple`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entity_types: Vec<String>,
+    /// Node labels. When `labels` or `edge_types` is set, only nodes with
+    /// one of these labels and edges of one of `edge_types` match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Edge types. See `labels`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edge_types: Vec<String>,
+    /// Property keys, one of which the change must set or remove.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<String>,
+    /// GQL boolean expression over `after`, the properties the change set
+    /// (for updates, only the changed ones), e.g. `after.age >= 18`.
+    /// Events without `after`, such as deletes, never match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predicate: Option<String>,
+}
+
+impl ChangeFilter {
+    /// Whether the filter matches every event.
+    pub fn is_empty(&self) -> bool {
+        self.entity_types.is_empty()
+            && self.labels.is_empty()
+            && self.edge_types.is_empty()
+            && self.properties.is_empty()
+            && self.predicate.is_none()
+    }
+
+    /// Checks that `predicate` is a valid expression `identity` may run.
+    pub fn validate(&self, db: &GrafeoDB, identity: &Identity) -> Result<(), ServiceError> {
+        if self.predicate.is_some() {
+            self.eval_predicate(db, identity, vec![Value::Map(Arc::default())])?;
+        }
+        Ok(())
+    }
+
+    /// Returns the events of `events` the filter matches, in order, with
+    /// the predicate evaluated for `identity`.
+    pub fn apply(
+        &self,
+        db: &GrafeoDB,
+        identity: &Identity,
+        events: Vec<ChangeEventDto>,
+    ) -> Result<Vec<ChangeEventDto>, ServiceError> {
+        self.apply_cancellable(db, identity, events, &CancelToken::new())
+    }
+
+    /// Like [`apply`](Self::apply), but stops with
+    /// [`ServiceError::Cancelled`] once `cancel` is tripped. The token is
+    /// checked between predicate queries, each covering a few hundred
+    /// events, so a slow predicate stops at the next check.
+    pub fn apply_cancellable(
+        &self,
+        db: &GrafeoDB,
+        identity: &Identity,
+        events: Vec<ChangeEventDto>,
+        cancel: &CancelToken,
+    ) -> Result<Vec<ChangeEventDto>, ServiceError> {
+        if self.is_empty() {
+            return Ok(events);
+        }
+
+        let mut types = TypeCache::default();
+        let events: Vec<_> = events
+            .into_iter()
+            .filter(|e| self.matches_entity(db, &mut types, e) && self.matches_properties(e))
+            .collect();
+
+        if self.predicate.is_none() || events.is_empty() {
+            return Ok(events);
+        }
+        let mut matched = Vec::new();
+        for (n, chunk) in events.chunks(PREDICATE_CHUNK).enumerate() {
+            cancel.check()?;
+            let afters = chunk
+                .iter()
+                .map(|e| e.after.as_ref().map_or(Value::Null, json_to_map))
+                .collect();
+            let offset = n * PREDICATE_CHUNK;
+            matched.extend(
+                self.eval_predicate(db, identity, afters)?
+                    .into_iter()
+                    .map(|i| i + offset),
+            );
+        }
+        let mut matched = matched.into_iter();
+        let mut next = matched.next();
+        Ok(events
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                if next == Some(*i) {
+                    next = matched.next();
+                    true
+                } else {
+                    false
+                }
+            })
+            .map(|(_, e)| e)
+            .collect())
+    }
+
+    fn matches_entity(&self, db: &GrafeoDB, types: &mut TypeCache, event: &ChangeEventDto) -> bool {
+        if !self.entity_types.is_empty() && !self.entity_types.contains(&event.entity_type) {
+            return false;
+        }
+        if self.labels.is_empty() && self.edge_types.is_empty() {
+            return true;
+        }
+        match event.entity_type.as_str() {
+            "node" if !self.labels.is_empty() => types
+                .node_labels(db, event)
+                .iter()
+                .any(|l| self.labels.contains(l)),
+            "edge" if !self.edge_types.is_empty() => types
+                .edge_type(db, event)
+                .is_some_and(|t| self.edge_types.contains(t)),
+            _ => false,
+        }
+    }
+
+    fn matches_properties(&self, event: &ChangeEventDto) -> bool {
+        if self.properties.is_empty() {
+            return true;
+        }
+        let has_key = |props: &Option<serde_json::Value>| {
+            props
+                .as_ref()
+                .and_then(serde_json::Value::as_object)
+                .is_some_and(|map| self.properties.iter().any(|k| map.contains_key(k)))
+        };
+        has_key(&event.after) || has_key(&event.before)
+    }
+
+    /// Returns the indices of the values in `afters` the predicate holds for.
+    fn eval_predicate(
+        &self,
+        db: &GrafeoDB,
+        identity: &Identity,
+        afters: Vec<Value>,
+    ) -> Result<Vec<usize>, ServiceError> {
+        let Some(predicate) = &self.predicate else {
+            return Ok((0..afters.len()).collect());
+        };
+        let statement = format!(
+            "UNWIND range(0, size($afters) - 1) AS i LET after = $afters[i] \
+             FILTER ({predicate}) RETURN i"
+        );
+        let params = HashMap::from([("afters".to_string(), Value::List(afters.into()))]);
+
+        // Read-only, so a predicate cannot smuggle in a mutation.
+        let reader = crate::auth::cap_identity_read_only(identity.clone(), true);
+        let session = db.session_with_identity(reader);
+        let result = session
+            .execute_with_params(&statement, params)
+            .map_err(|e| ServiceError::BadRequest(format!("invalid predicate: {e}")))?;
+
+        let mut matched: Vec<usize> = result
+            .rows()
+            .iter()
+            .filter_map(|row| match row.first() {
+                Some(Value::Int64(i)) => usize::try_from(*i).ok(),
+                _ => None,
+            })
+            .collect();
+        matched.sort_unstable();
+        matched.dedup();
+        Ok(matched)
+    }
+}
+
+/// Labels and edge types of the entities seen by one [`ChangeFilter::apply`].
+#[derive(Default)]
+struct TypeCache {
+    labels: HashMap<u64, Vec<String>>,
+    edge_types: HashMap<u64, Option<String>>,
+}
+
+impl TypeCache {
+    fn node_labels(&mut self, db: &GrafeoDB, event: &ChangeEventDto) -> &[String] {
+        self.labels.entry(event.id).or_insert_with(|| {
+            if let Some(labels) = &event.labels {
+                return labels.clone();
+            }
+            let id = NodeId::new(event.id);
+            if let Some(node) = db.get_node(id) {
+                return node.labels.iter().map(ToString::to_string).collect();
+            }
+            created(db, EntityId::Node(id))
+                .and_then(|e| e.labels)
+                .unwrap_or_default()
+        })
+    }
+
+    fn edge_type(&mut self, db: &GrafeoDB, event: &ChangeEventDto) -> Option<&String> {
+        self.edge_types
+            .entry(event.id)
+            .or_insert_with(|| {
+                if let Some(edge_type) = &event.edge_type {
+                    return Some(edge_type.clone());
+                }
+                let id = EdgeId::new(event.id);
+                if let Some(edge) = db.get_edge(id) {
+                    return Some(edge.edge_type.to_string());
+                }
+                created(db, EntityId::Edge(id)).and_then(|e| e.edge_type)
+            })
+            .as_ref()
+    }
+}
+
+/// The create event of a deleted entity, which carries its labels or type.
+fn created(db: &GrafeoDB, id: EntityId) -> Option<grafeo_engine::cdc::ChangeEvent> {
+    db.history(id)
+        .ok()?
+        .into_iter()
+        .rev()
+        .find(|e| e.kind == ChangeKind::Create)
+}
+
+/// Converts the JSON properties of a [`ChangeEventDto`] into a map value.
+fn json_to_map(json: &serde_json::Value) -> Value {
+    let props: HashMap<String, Value> = serde_json::from_value(json.clone()).unwrap_or_default();
+    let map: BTreeMap<PropertyKey, Value> = props
+        .into_iter()
+        .map(|(k, v)| (PropertyKey::from(k.as_str()), v))
+        .collect();
+    Value::Map(Arc::new(map))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseManager;
+    use crate::sync::SyncService;
+
+    /// A database with a Person and a City, a LIVES_IN edge between them,
+    /// an update of the Person and a deleted Pet.
+    fn changes() -> (Arc<GrafeoDB>, Vec<ChangeEventDto>) {
+        let mgr = DatabaseManager::new(None, false);
+        mgr.set_cdc_enabled(true);
+        let db = mgr.get("default").unwrap().db();
+
+        let alix = db.create_node_with_props(
+            &["Person"],
+            [("name", Value::from("Alix")), ("age", Value::Int64(34))],
+        );
+        let city = db.create_node_with_props(&["City"], [("name", Value::from("Amsterdam"))]);
+        db.create_edge(alix, city, "LIVES_IN");
+        db.set_node_property(alix, "age", Value::Int64(35));
+        let pet = db.create_node_with_props(&["Pet"], [("name", Value::from("Rex"))]);
+        db.delete_node(pet);
+
+        let changes = SyncService::pull(&mgr, "default", 0, 100).unwrap().changes;
+        (db, changes)
+    }
+
+    fn kinds(events: &[ChangeEventDto]) -> Vec<(&str, &str)> {
+        events
+            .iter()
+            .map(|e| (e.entity_type.as_str(), e.kind.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let (db, changes) = changes();
+        let n = changes.len();
+        assert!(ChangeFilter::default().is_empty());
+        assert_eq!(
+            ChangeFilter::default()
+                .apply(&db, &Identity::anonymous(), changes)
+                .unwrap()
+                .len(),
+            n
+        );
+    }
+
+    #[test]
+    fn entity_types_select_edges() {
+        let (db, changes) = changes();
+        let filter = ChangeFilter {
+            entity_types: vec!["edge".into()],
+            ..ChangeFilter::default()
+        };
+        assert_eq!(
+            kinds(&filter.apply(&db, &Identity::anonymous(), changes).unwrap()),
+            [("edge", "create")]
+        );
+    }
+
+    #[test]
+    fn labels_match_updates_and_deletes() {
+        let (db, changes) = changes();
+        let filter = ChangeFilter {
+            labels: vec!["Person".into(), "Pet".into()],
+            ..ChangeFilter::default()
+        };
+        assert_eq!(
+            kinds(&filter.apply(&db, &Identity::anonymous(), changes).unwrap()),
+            [
+                ("node", "create"),
+                ("node", "update"),
+                ("node", "create"),
+                ("node", "delete")
+            ]
+        );
+    }
+
+    #[test]
+    fn edge_types_exclude_nodes() {
+        let (db, changes) = changes();
+        let filter = ChangeFilter {
+            edge_types: vec!["LIVES_IN".into()],
+            ..ChangeFilter::default()
+        };
+        assert_eq!(
+            kinds(&filter.apply(&db, &Identity::anonymous(), changes).unwrap()),
+            [("edge", "create")]
+        );
+    }
+
+    #[test]
+    fn properties_match_changed_keys() {
+        let (db, changes) = changes();
+        let filter = ChangeFilter {
+            properties: vec!["age".into()],
+            ..ChangeFilter::default()
+        };
+        assert_eq!(
+            kinds(&filter.apply(&db, &Identity::anonymous(), changes).unwrap()),
+            [("node", "create"), ("node", "update")]
+        );
+    }
+
+    #[test]
+    fn predicate_filters_on_after() {
+        let (db, changes) = changes();
+        let filter = ChangeFilter {
+            predicate: Some("after.name STARTS WITH 'A'".into()),
+            ..ChangeFilter::default()
+        };
+        let matched = filter.apply(&db, &Identity::anonymous(), changes).unwrap();
+        assert_eq!(kinds(&matched), [("node", "create"), ("node", "create")]);
+    }
+
+    #[test]
+    fn invalid_predicate_is_rejected() {
+        let (db, _) = changes();
+        let filter = ChangeFilter {
+            predicate: Some("after.age >".into()),
+            ..ChangeFilter::default()
+        };
+        assert!(matches!(
+            filter.validate(&db, &Identity::anonymous()),
+            Err(ServiceError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn predicate_cannot_write() {
+        let (db, _) = changes();
+        let filter = ChangeFilter {
+            predicate: Some("true) RETURN i NEXT INSERT (:Injected) RETURN 0 //".into()),
+            ..ChangeFilter::default()
+        };
+        assert!(filter.validate(&db, &Identity::anonymous()).is_err());
+        assert_eq!(db.node_count(), 2);
+    }
+}
@@ -1,61 +1,134 @@
 //! Real-time push changefeed for offline-first applications.
 //!
-//! `ChangeHub` manages one `tokio::sync::broadcast` channel per database.
-//! A lightweight background task polls the CDC log every 100 ms and broadcasts
-//! new `ChangeEventDto` values to all active subscribers.
+//! `ChangeHub` manages one `tokio::sync::broadcast` channel per database,
+//! [`ChangeFilter`] and user. A lightweight background task polls the CDC
+//! log every 100 ms, evaluates each filter subscribers asked for once, and
+//! broadcasts the matching `ChangeEventDto` values to that filter's
+//! subscribers. Filters are evaluated on a blocking thread, under the
+//! identity of their subscribers and within [`FILTER_TIMEOUT`] per poll; a
+//! channel whose filter misses the deadline is closed, ending its
+//! subscriptions rather than silently skipping events.
+//!
+//! A [`ChangeSubscription`] from [`ChangeHub::subscribe_from`] wraps such a
+//! channel for SSE and WebSocket subscribers: it delivers the stored history
+//! first, and when the subscriber lags behind the channel it reports a
+//! [`ChangeGap`] and backfills the dropped events from the CDC log.
+//!
+//! Replicas subscribe to a second channel of [`ChangeBatch`]es instead: each
+//! batch covers a contiguous epoch range in full, so a subscriber can tell
+//! from `since` whether it has missed anything.
 //!
 //! # Usage
 //!
 //! ```no_run
-//! # use grafeo_service::changefeed::ChangeHub;
+//! # use grafeo_engine::auth::Identity;
+//! # use grafeo_service::change_filter::ChangeFilter;
+//! # use grafeo_service::changefeed::{ChangeFeedItem, ChangeHub};
 //! # use grafeo_service::ServiceState;
-//! # async fn example(hub: &ChangeHub, state: ServiceState) {
-//! let mut receiver = hub.subscribe("default", 0, state);
+//! # async fn example(hub: &ChangeHub, state: ServiceState) -> Result<(), grafeo_service::error::ServiceError> {
+//! let mut subscription = hub
+//!     .subscribe_from("default", 0, ChangeFilter::default(), Identity::anonymous(), state)
+//!     .await?;
 //!
-//! while let Ok(event) = receiver.recv().await {
-//!     println!("live event: {:?}", event.kind);
+//! while let Some(item) = subscription.next().await {
+//!     match item? {
+//!         ChangeFeedItem::Change(event) => println!("event: {:?}", event.kind),
+//!         ChangeFeedItem::Gap(gap) => println!("resync after epoch {}", gap.last_epoch),
+//!     }
 //! }
+//! # Ok(())
 //! # }
 //! ```
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 use dashmap::DashMap;
+use grafeo_engine::auth::Identity;
 use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
-use tracing::debug;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
 
 use crate::ServiceState;
-use crate::sync::{ChangeEventDto, SyncService};
+use crate::cancel::CancelToken;
+use crate::change_filter::ChangeFilter;
+use crate::error::ServiceError;
+use crate::sync::{ChangeCursor, ChangeEventDto, ChangeGap, SyncService};
 
 /// Capacity of each per-database broadcast channel.
 const CHANNEL_CAPACITY: usize = 1_024;
 
+/// Capacity of each per-database batch channel.
+const BATCH_CHANNEL_CAPACITY: usize = 256;
+
 /// Interval between CDC polls for each active database channel.
 const POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Maximum events pulled per poll tick.
 const POLL_LIMIT: usize = 500;
 
+/// Maximum events a subscription backfills from the CDC log at a time.
+const BACKFILL_LIMIT: usize = 10_000;
+
+/// Time a poll tick may spend pulling events and evaluating filters.
+/// Channels whose filter has not been evaluated by then are closed.
+pub const FILTER_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// ChangeBatch
+// ---------------------------------------------------------------------------
+
+/// Every change committed in the epoch range `since..=server_epoch`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeBatch {
+    /// First epoch covered by this batch (inclusive).
+    pub since: u64,
+    /// Last epoch covered by this batch (inclusive). The next batch starts
+    /// at `server_epoch + 1`.
+    pub server_epoch: u64,
+    /// Change events, ordered by epoch ascending.
+    pub changes: Vec<ChangeEventDto>,
+}
+
 // ---------------------------------------------------------------------------
 // ChangeHub
 // ---------------------------------------------------------------------------
 
-struct ChannelState {
+/// Event channel of the subscribers sharing a filter and a user.
+#[derive(Clone)]
+struct EventChannel {
+    /// Identity the filter's predicate is evaluated for.
+    identity: Identity,
     sender: broadcast::Sender<ChangeEventDto>,
-    last_epoch: Arc<AtomicU64>,
+}
+
+/// Key of an [`EventChannel`]: the filter and the subscribers' user ID.
+type EventChannelKey = (ChangeFilter, String);
+
+struct ChannelState {
+    /// Event channels by the filter and user their subscribers share.
+    senders: Mutex<HashMap<EventChannelKey, EventChannel>>,
+    /// First epoch of the next event pull.
+    next_epoch: Arc<AtomicU64>,
+    batches: broadcast::Sender<Arc<ChangeBatch>>,
+    /// First epoch of the next batch.
+    next_batch_epoch: Arc<AtomicU64>,
     /// Handle to the background poll task. `None` means no task is running.
     task: Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 impl ChannelState {
     fn new() -> Self {
-        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let (batches, _) = broadcast::channel(BATCH_CHANNEL_CAPACITY);
         Self {
-            sender,
-            last_epoch: Arc::new(AtomicU64::new(0)),
+            senders: Mutex::new(HashMap::new()),
+            next_epoch: Arc::new(AtomicU64::new(0)),
+            batches,
+            next_batch_epoch: Arc::new(AtomicU64::new(0)),
             task: Mutex::new(None),
         }
     }
@@ -78,11 +151,18 @@ impl ChangeHub {
         }
     }
 
-    /// Subscribes to live change events for `db_name`.
+    /// Subscribes to live change events for `db_name` that `filter` matches,
+    /// with its predicate evaluated for `identity`.
     ///
     /// Historical events since `since_epoch` must be fetched separately via
-    /// `SyncService::pull()` — the receiver only yields events that arrive
-    /// after the subscription point.
+    /// `SyncService::pull_matching()` — the receiver only yields events that
+    /// arrive after the subscription point. A `RecvError::Lagged` from the
+    /// receiver means events were dropped; [`subscribe_from`](Self::subscribe_from)
+    /// handles both. Subscribers of the same user with equal filters share a
+    /// channel; callers should check the filter with
+    /// [`ChangeFilter::validate`] first, as events an invalid predicate
+    /// fails on are not delivered. The receiver is closed if evaluating the
+    /// filter exceeds [`FILTER_TIMEOUT`].
     ///
     /// A background poll task is started (or restarted) automatically if none
     /// is currently running for this database.
@@ -90,6 +170,8 @@ impl ChangeHub {
         &self,
         db_name: &str,
         since_epoch: u64,
+        filter: ChangeFilter,
+        identity: Identity,
         state: ServiceState,
     ) -> broadcast::Receiver<ChangeEventDto> {
         let channel = self
@@ -99,11 +181,93 @@ impl ChangeHub {
             .clone();
 
         // Advance epoch to at least `since_epoch` so the poll task starts from here.
-        channel.last_epoch.fetch_max(since_epoch, Ordering::Relaxed);
+        channel.next_epoch.fetch_max(since_epoch, Ordering::Relaxed);
 
+        // Subscribe before the task can observe zero receivers and stop.
+        let receiver = channel
+            .senders
+            .lock()
+            .entry((filter, identity.user_id().to_owned()))
+            .or_insert_with(|| EventChannel {
+                identity,
+                sender: broadcast::channel(CHANNEL_CAPACITY).0,
+            })
+            .sender
+            .subscribe();
         self.ensure_task_running(db_name, &channel, state);
+        receiver
+    }
+
+    /// Subscribes to the events of `db_name` with `epoch >= since` that
+    /// `filter` matches for `identity`: stored events first, then live ones.
+    ///
+    /// Fails if the database is unavailable, has CDC disabled, or the
+    /// filter's predicate is invalid.
+    pub async fn subscribe_from(
+        &self,
+        db_name: &str,
+        since: u64,
+        filter: ChangeFilter,
+        identity: Identity,
+        state: ServiceState,
+    ) -> Result<ChangeSubscription, ServiceError> {
+        // Subscribe at the current epoch before reading history, so events
+        // committed in between arrive live; the cursor drops duplicates.
+        let current = state
+            .databases()
+            .get_available(db_name)?
+            .db()
+            .current_epoch()
+            .0;
+        let receiver = self.subscribe(
+            db_name,
+            current.max(since),
+            filter.clone(),
+            identity.clone(),
+            state.clone(),
+        );
 
-        channel.sender.subscribe()
+        let mut subscription = ChangeSubscription {
+            state,
+            db_name: db_name.to_string(),
+            filter,
+            identity,
+            receiver,
+            cursor: ChangeCursor::new(since),
+            pending: VecDeque::new(),
+            backfill_from: None,
+        };
+        subscription.backfill(since).await?;
+        Ok(subscription)
+    }
+
+    /// Subscribes to live [`ChangeBatch`]es for `db_name`.
+    ///
+    /// Batches start at `since_epoch` or later: a subscriber that joins a
+    /// running channel only sees batches from the channel's current
+    /// position, so it must fetch earlier epochs itself (e.g. with
+    /// `SyncService::pull_complete()`) and skip batches it already has.
+    /// A `RecvError::Lagged` from the receiver means batches were dropped.
+    pub fn subscribe_batches(
+        &self,
+        db_name: &str,
+        since_epoch: u64,
+        state: ServiceState,
+    ) -> broadcast::Receiver<Arc<ChangeBatch>> {
+        let channel = self
+            .channels
+            .entry(db_name.to_string())
+            .or_insert_with(|| Arc::new(ChannelState::new()))
+            .clone();
+
+        channel
+            .next_batch_epoch
+            .fetch_max(since_epoch, Ordering::Relaxed);
+
+        // Subscribe before the task can observe zero receivers and stop.
+        let receiver = channel.batches.subscribe();
+        self.ensure_task_running(db_name, &channel, state);
+        receiver
     }
 
     /// Ensures a background poll task is running for `db_name`.
@@ -117,10 +281,7 @@ impl ChangeHub {
         };
 
         if needs_restart {
-            let db = db_name.to_string();
-            let sender = channel.sender.clone();
-            let last_epoch = Arc::clone(&channel.last_epoch);
-            let handle = tokio::spawn(poll_task(db, sender, last_epoch, state));
+            let handle = tokio::spawn(poll_task(db_name.to_string(), Arc::clone(channel), state));
             *guard = Some(handle);
         }
     }
@@ -133,43 +294,458 @@ impl Default for ChangeHub {
 }
 
 // ---------------------------------------------------------------------------
-// Background poll task
+// ChangeSubscription
 // ---------------------------------------------------------------------------
 
-async fn poll_task(
-    db_name: String,
-    sender: broadcast::Sender<ChangeEventDto>,
-    last_epoch: Arc<AtomicU64>,
+/// An item of a [`ChangeSubscription`].
+#[derive(Debug, Clone)]
+pub enum ChangeFeedItem {
+    /// A change event.
+    Change(Box<ChangeEventDto>),
+    /// Events were dropped from the broadcast channel. The events after
+    /// `last_epoch` follow, so the subscriber misses nothing, but caches
+    /// that track the stream's position can use it to resync.
+    Gap(ChangeGap),
+}
+
+/// Change events of a database in epoch order, without gaps or duplicates.
+/// Created by [`ChangeHub::subscribe_from`].
+pub struct ChangeSubscription {
     state: ServiceState,
-) {
+    db_name: String,
+    filter: ChangeFilter,
+    identity: Identity,
+    receiver: broadcast::Receiver<ChangeEventDto>,
+    cursor: ChangeCursor,
+    /// Backfilled events not yet returned.
+    pending: VecDeque<ChangeEventDto>,
+    /// Epoch of the next backfill page, while the CDC log may hold more
+    /// events to backfill.
+    backfill_from: Option<u64>,
+}
+
+impl ChangeSubscription {
+    /// Returns the next item, waiting for live events once the history is
+    /// delivered. Returns `None` when the channel closes, and an error if a
+    /// backfill fails, e.g. because the database was deleted.
+    pub async fn next(&mut self) -> Option<Result<ChangeFeedItem, ServiceError>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(ChangeFeedItem::Change(Box::new(event))));
+            }
+            if let Some(since) = self.backfill_from {
+                if let Err(e) = self.backfill(since).await {
+                    return Some(Err(e));
+                }
+                continue;
+            }
+            match self.receiver.recv().await {
+                Ok(event) => {
+                    if self.cursor.advance(&event) {
+                        return Some(Ok(ChangeFeedItem::Change(Box::new(event))));
+                    }
+                }
+                Err(RecvError::Lagged(n)) => {
+                    debug!(
+                        "changefeed subscriber for '{}' lagged by {n} events, backfilling",
+                        self.db_name
+                    );
+                    let gap = ChangeGap {
+                        last_epoch: self.cursor.epoch(),
+                    };
+                    self.backfill_from = Some(gap.last_epoch);
+                    return Some(Ok(ChangeFeedItem::Gap(gap)));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Queues the undelivered events of the CDC log page starting at
+    /// `since`.
+    async fn backfill(&mut self, since: u64) -> Result<(), ServiceError> {
+        let state = self.state.clone();
+        let db_name = self.db_name.clone();
+        let filter = self.filter.clone();
+        let identity = self.identity.clone();
+        let page = tokio::task::spawn_blocking(move || {
+            SyncService::pull_matching_complete(
+                state.databases(),
+                &db_name,
+                since,
+                BACKFILL_LIMIT,
+                &filter,
+                &identity,
+            )
+        })
+        .await
+        .map_err(|e| ServiceError::Internal(e.to_string()))??;
+
+        // Pages hold whole epochs, even one with more matching events than
+        // a page, so the next page starts after the last epoch.
+        self.backfill_from = match page.changes.last() {
+            Some(last) if page.changes.len() >= BACKFILL_LIMIT => Some(last.epoch + 1),
+            _ => None,
+        };
+        for event in page.changes {
+            if self.cursor.advance(&event) {
+                self.pending.push_back(event);
+            }
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Background poll task
+// ---------------------------------------------------------------------------
+
+async fn poll_task(db_name: String, channel: Arc<ChannelState>, state: ServiceState) {
     let mut interval = tokio::time::interval(POLL_INTERVAL);
     interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
     loop {
         interval.tick().await;
 
+        let senders: Vec<_> = {
+            let mut senders = channel.senders.lock();
+            senders.retain(|_, channel| channel.sender.receiver_count() > 0);
+            senders
+                .iter()
+                .map(|(key, channel)| (key.clone(), channel.clone()))
+                .collect()
+        };
+        let event_subscribers = !senders.is_empty();
+        let batch_subscribers = channel.batches.receiver_count() > 0;
+
         // Stop when there are no subscribers.
-        if sender.receiver_count() == 0 {
+        if !event_subscribers && !batch_subscribers {
             debug!("changefeed poll task: no subscribers for '{db_name}', stopping");
             break;
         }
 
-        let since = last_epoch.load(Ordering::Relaxed);
+        if event_subscribers {
+            let task = {
+                let (state, name, channel) = (state.clone(), db_name.clone(), Arc::clone(&channel));
+                move |cancel: &CancelToken| poll_events(&state, &name, &channel, &senders, cancel)
+            };
+            let result =
+                crate::query::run_with_timeout(Some(FILTER_TIMEOUT), CancelToken::new(), task)
+                    .await;
+            match result {
+                Ok(()) => {}
+                Err(ServiceError::Timeout) => {
+                    // The cancelled evaluation closes the channels it did
+                    // not get to.
+                    warn!("changefeed filters for '{db_name}' exceeded {FILTER_TIMEOUT:?}");
+                }
+                Err(e) => {
+                    debug!("changefeed poll error for '{db_name}': {e}");
+                    break;
+                }
+            }
+        }
+
+        if batch_subscribers {
+            let since = channel.next_batch_epoch.load(Ordering::Relaxed);
+
+            let pull = {
+                let (state, name) = (state.clone(), db_name.clone());
+                tokio::task::spawn_blocking(move || {
+                    SyncService::pull_complete(state.databases(), &name, since, POLL_LIMIT)
+                })
+            };
+            let resp = match pull
+                .await
+                .map_err(|e| ServiceError::Internal(e.to_string()))
+                .and_then(|r| r)
+            {
+                Ok(r) => r,
+                Err(e) => {
+                    debug!("changefeed batch poll error for '{db_name}': {e}");
+                    break;
+                }
+            };
+
+            // Empty pulls do not advance: events may still be recorded at
+            // the current epoch.
+            if !resp.changes.is_empty() {
+                channel
+                    .next_batch_epoch
+                    .store(resp.server_epoch + 1, Ordering::Relaxed);
+                let _ = channel.batches.send(Arc::new(ChangeBatch {
+                    since,
+                    server_epoch: resp.server_epoch,
+                    changes: resp.changes,
+                }));
+            }
+        }
+    }
+}
+
+/// Pulls the next whole epochs of events and sends each event channel the
+/// events its filter matches. Runs on a blocking thread.
+fn poll_events(
+    state: &ServiceState,
+    db_name: &str,
+    channel: &ChannelState,
+    senders: &[(EventChannelKey, EventChannel)],
+    cancel: &CancelToken,
+) -> Result<(), ServiceError> {
+    let since = channel.next_epoch.load(Ordering::Relaxed);
+    let resp = SyncService::pull_complete(state.databases(), db_name, since, POLL_LIMIT)?;
+
+    // Whole epochs were delivered, so the next pull starts after the last
+    // one instead of re-reading it.
+    let Some(last) = resp.changes.last() else {
+        return Ok(());
+    };
+    channel.next_epoch.store(last.epoch + 1, Ordering::Relaxed);
 
-        let resp = match SyncService::pull(state.databases(), &db_name, since, POLL_LIMIT) {
-            Ok(r) => r,
+    let db = state.databases().get_available(db_name)?.db();
+    for ((filter, user), event_channel) in senders {
+        let events = match filter.apply_cancellable(
+            &db,
+            &event_channel.identity,
+            resp.changes.clone(),
+            cancel,
+        ) {
+            Ok(events) => events,
+            Err(ServiceError::Cancelled) => {
+                // Closing the channel ends its subscriptions, which
+                // would otherwise miss these events.
+                debug!("changefeed filter for '{db_name}' cancelled, closing channel");
+                channel
+                    .senders
+                    .lock()
+                    .remove(&(filter.clone(), user.clone()));
+                continue;
+            }
             Err(e) => {
-                debug!("changefeed poll error for '{db_name}': {e}");
-                break;
+                debug!("changefeed filter error for '{db_name}': {e}");
+                continue;
             }
         };
+        for event in events {
+            // Ignore send errors — lagged receivers will get a `RecvError::Lagged`.
+            let _ = event_channel.sender.send(event);
+        }
+    }
+    Ok(())
+}
 
-        if !resp.changes.is_empty() {
-            last_epoch.store(resp.server_epoch, Ordering::Relaxed);
-            for event in resp.changes {
-                // Ignore send errors — lagged receivers will get a `RecvError::Lagged`.
-                let _ = sender.send(event);
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn cdc_state() -> ServiceState {
+        let state = ServiceState::new_in_memory(300);
+        state.databases().set_cdc_enabled(true);
+        state
+    }
+
+    fn create_nodes(state: &ServiceState, n: usize) {
+        let db = state.databases().get("default").unwrap().db();
+        for _ in 0..n {
+            db.create_node(&["Thing"]);
+        }
+    }
+
+    async fn next_change(subscription: &mut ChangeSubscription) -> ChangeEventDto {
+        match subscription.next().await {
+            Some(Ok(ChangeFeedItem::Change(event))) => *event,
+            other => panic!("expected a change, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscription_delivers_history_then_live_events() {
+        let state = cdc_state();
+        create_nodes(&state, 2);
+
+        let hub = ChangeHub::new();
+        let mut subscription = hub
+            .subscribe_from(
+                "default",
+                0,
+                ChangeFilter::default(),
+                Identity::anonymous(),
+                state.clone(),
+            )
+            .await
+            .unwrap();
+        create_nodes(&state, 1);
+
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            ids.push(next_change(&mut subscription).await.id);
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn live_events_beyond_a_poll_are_delivered_once() {
+        let state = cdc_state();
+        let db = state.databases().get("default").unwrap().db();
+        let session = db.session();
+        for i in 0..POLL_LIMIT {
+            session
+                .execute(&format!("INSERT (:Thing {{n: {i}}})"))
+                .unwrap();
+        }
+        let expected = SyncService::pull(state.databases(), "default", 0, usize::MAX)
+            .unwrap()
+            .changes;
+        assert!(expected.len() > POLL_LIMIT && expected.len() < CHANNEL_CAPACITY);
+
+        let hub = ChangeHub::new();
+        let mut receiver = hub.subscribe(
+            "default",
+            0,
+            ChangeFilter::default(),
+            Identity::anonymous(),
+            state.clone(),
+        );
+        let mut seen = HashSet::new();
+        for _ in 0..expected.len() {
+            let event = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+                .await
+                .expect("every event is delivered")
+                .unwrap();
+            assert!(seen.insert((event.epoch, event.id, event.kind)));
+        }
+        tokio::time::sleep(POLL_INTERVAL * 3).await;
+        assert!(matches!(
+            receiver.try_recv(),
+            Err(broadcast::error::TryRecvError::Empty)
+        ));
+    }
+
+    #[tokio::test]
+    async fn lagging_subscription_reports_gap_and_backfills() {
+        let state = cdc_state();
+        let hub = ChangeHub::new();
+        let mut subscription = hub
+            .subscribe_from(
+                "default",
+                0,
+                ChangeFilter::default(),
+                Identity::anonymous(),
+                state.clone(),
+            )
+            .await
+            .unwrap();
+
+        // More events than the channel holds, broadcast while nobody reads.
+        let total = CHANNEL_CAPACITY + POLL_LIMIT;
+        create_nodes(&state, total);
+        tokio::time::sleep(POLL_INTERVAL * 6).await;
+
+        let mut ids = HashSet::new();
+        let mut gaps = 0;
+        while ids.len() < total {
+            match subscription.next().await {
+                Some(Ok(ChangeFeedItem::Change(event))) => assert!(ids.insert(event.id)),
+                Some(Ok(ChangeFeedItem::Gap(gap))) => {
+                    assert_eq!(gap.last_epoch, 0);
+                    gaps += 1;
+                }
+                other => panic!("unexpected item {other:?}"),
+            }
+        }
+        assert_eq!(gaps, 1);
+    }
+
+    #[tokio::test]
+    async fn backfill_delivers_epochs_larger_than_a_page() {
+        let state = cdc_state();
+        // Direct API calls land in one epoch; the INSERT commits a later one.
+        create_nodes(&state, BACKFILL_LIMIT + 50);
+        let db = state.databases().get("default").unwrap().db();
+        db.session().execute("INSERT (:Later)").unwrap();
+
+        let hub = ChangeHub::new();
+        let mut subscription = hub
+            .subscribe_from(
+                "default",
+                0,
+                ChangeFilter::default(),
+                Identity::anonymous(),
+                state.clone(),
+            )
+            .await
+            .unwrap();
+
+        let total = BACKFILL_LIMIT + 51;
+        let mut ids = HashSet::new();
+        tokio::time::timeout(Duration::from_secs(30), async {
+            while ids.len() < total {
+                match subscription.next().await {
+                    Some(Ok(ChangeFeedItem::Change(event))) => {
+                        ids.insert(event.id);
+                    }
+                    Some(Ok(ChangeFeedItem::Gap(_))) => {}
+                    other => panic!("unexpected item {other:?}"),
+                }
             }
+        })
+        .await
+        .expect("every backfilled node is delivered");
+    }
+
+    #[test]
+    fn cancelled_filter_closes_its_channel() {
+        let state = cdc_state();
+        create_nodes(&state, 3);
+
+        let channel = ChannelState::new();
+        let filtered = ChangeFilter {
+            predicate: Some("after IS NOT NULL".into()),
+            ..ChangeFilter::default()
+        };
+        let mut receivers = Vec::new();
+        for filter in [ChangeFilter::default(), filtered] {
+            let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+            let event_channel = EventChannel {
+                identity: Identity::anonymous(),
+                sender,
+            };
+            channel
+                .senders
+                .lock()
+                .insert((filter, "anonymous".into()), event_channel);
+            receivers.push(receiver);
+        }
+        let senders: Vec<_> = channel
+            .senders
+            .lock()
+            .iter()
+            .map(|(key, c)| (key.clone(), c.clone()))
+            .collect();
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        poll_events(&state, "default", &channel, &senders, &cancel).unwrap();
+        drop(senders);
+
+        // An empty filter needs no evaluation, so its events still arrive;
+        // the predicate's channel is closed instead of skipping them.
+        let [mut unfiltered, mut filtered] = <[_; 2]>::try_from(receivers).unwrap();
+        for _ in 0..3 {
+            unfiltered.try_recv().unwrap();
         }
+        assert!(matches!(
+            filtered.try_recv(),
+            Err(broadcast::error::TryRecvError::Closed)
+        ));
+        assert_eq!(channel.senders.lock().len(), 1);
     }
 }
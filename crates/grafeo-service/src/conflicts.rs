@@ -0,0 +1,330 @@
+//! Conflict policies and the conflict log for sync push.
+//!
+//! Each database has a [`SyncPolicy`] deciding how `POST /db/{name}/sync`
+//! resolves a client change that races a server change, and a bounded log
+//! of the conflicts it resolved, kept for apps that let a user review them.
+//! For persistent databases both are stored next to the data file, in
+//! `sync-policy.json` and `sync-conflicts.json`; in-memory databases keep
+//! them in memory.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ServiceError;
+use crate::sync::{ConflictRecord, SyncChangeRequest};
+
+/// Maximum number of entries kept in a conflict log. The oldest entries are
+/// dropped first.
+const MAX_LOGGED_CONFLICTS: usize = 10_000;
+
+const POLICY_FILE: &str = "sync-policy.json";
+const LOG_FILE: &str = "sync-conflicts.json";
+
+/// How a client change that conflicts with a server change is resolved.
+///
+/// `lww` compares wall-clock timestamps and is the default. The other
+/// policies treat a server change as conflicting when the client has not
+/// seen it, i.e. when it committed after the request's `last_seen_epoch`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// The change with the later timestamp wins.
+    #[default]
+    Lww,
+    /// The server's change wins; the client change is skipped.
+    ServerWins,
+    /// The client change is applied over the server's change.
+    ClientWins,
+    /// The client change is rejected; with `atomic`, so is the changeset.
+    Reject,
+    /// Properties are resolved one by one, each with its policy in
+    /// `SyncPolicy.fields` (default `lww`). Only properties both sides
+    /// changed conflict; deletes are resolved with `lww`.
+    FieldMerge,
+}
+
+/// Sync conflict settings of a database.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SyncPolicy {
+    /// Policy applied to conflicting changes.
+    #[serde(default)]
+    pub policy: ConflictPolicy,
+    /// Per-property policies used by `field_merge`. Properties not listed
+    /// use `lww`; `field_merge` itself is not allowed here.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, ConflictPolicy>,
+    /// Record resolved conflicts in the database's conflict log, served by
+    /// `GET /db/{name}/sync/conflicts`.
+    #[serde(default)]
+    pub log_conflicts: bool,
+}
+
+impl SyncPolicy {
+    /// Returns the policy for a property under `field_merge`.
+    pub fn field(&self, key: &str) -> ConflictPolicy {
+        self.fields.get(key).copied().unwrap_or_default()
+    }
+
+    fn validate(&self) -> Result<(), ServiceError> {
+        match self
+            .fields
+            .iter()
+            .find(|(_, policy)| **policy == ConflictPolicy::FieldMerge)
+        {
+            Some((key, _)) => Err(ServiceError::BadRequest(format!(
+                "property '{key}' cannot use the field_merge policy"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A conflict recorded in the conflict log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoggedConflict {
+    /// Log sequence number, increasing from 1.
+    pub id: u64,
+    /// When the conflict was resolved (ms since Unix epoch).
+    pub recorded_at: u64,
+    /// `client_id` of the sync request.
+    pub client_id: String,
+    /// Server epoch after the sync request was applied.
+    pub epoch: u64,
+    /// `true` when the request was atomic and rolled back, so none of its
+    /// changes were applied.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub rolled_back: bool,
+    /// The conflict, including the server's value of the entity.
+    pub conflict: ConflictRecord,
+    /// The client's change.
+    pub change: SyncChangeRequest,
+}
+
+/// Response from `GET /db/{name}/sync/conflicts`.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ConflictsResponse {
+    /// Logged conflicts with `id >= since`, oldest first.
+    pub conflicts: Vec<LoggedConflict>,
+    /// `id` of the newest logged conflict, 0 if none was ever logged.
+    pub last_id: u64,
+}
+
+/// Sync policy and conflict log of one database.
+pub struct ConflictState {
+    /// Directory the state is persisted in; `None` for in-memory databases.
+    dir: Option<PathBuf>,
+    policy: RwLock<SyncPolicy>,
+    log: Mutex<ConflictLog>,
+}
+
+/// Bounded log of resolved conflicts.
+#[derive(Default, Serialize, Deserialize)]
+struct ConflictLog {
+    next_id: u64,
+    entries: VecDeque<LoggedConflict>,
+}
+
+impl ConflictState {
+    /// Loads the state persisted in `dir`, or starts with the defaults.
+    pub fn open(dir: Option<PathBuf>) -> Self {
+        let policy = dir
+            .as_deref()
+            .and_then(|d| load::<SyncPolicy>(&d.join(POLICY_FILE)))
+            .unwrap_or_default();
+        let log = dir
+            .as_deref()
+            .and_then(|d| load::<ConflictLog>(&d.join(LOG_FILE)))
+            .unwrap_or_default();
+        Self {
+            dir,
+            policy: RwLock::new(policy),
+            log: Mutex::new(log),
+        }
+    }
+
+    /// Returns the current policy.
+    pub fn policy(&self) -> SyncPolicy {
+        self.policy.read().clone()
+    }
+
+    /// Replaces the policy and persists it.
+    pub fn set_policy(&self, policy: SyncPolicy) -> Result<(), ServiceError> {
+        policy.validate()?;
+        let mut current = self.policy.write();
+        if let Some(dir) = &self.dir {
+            save(&dir.join(POLICY_FILE), &policy)?;
+        }
+        *current = policy;
+        Ok(())
+    }
+
+    /// Appends conflicts to the log, dropping the oldest entries beyond
+    /// the limit. `entries` have their `id` and `recorded_at` assigned.
+    pub fn record(&self, entries: impl IntoIterator<Item = LoggedConflict>) {
+        let recorded_at = now_ms();
+        let mut log = self.log.lock();
+        for mut entry in entries {
+            log.next_id += 1;
+            entry.id = log.next_id;
+            entry.recorded_at = recorded_at;
+            log.entries.push_back(entry);
+        }
+        let excess = log.entries.len().saturating_sub(MAX_LOGGED_CONFLICTS);
+        log.entries.drain(..excess);
+        if let Some(dir) = &self.dir
+            && let Err(e) = save(&dir.join(LOG_FILE), &*log)
+        {
+            tracing::warn!(error = %e, "Failed to persist sync conflict log");
+        }
+    }
+
+    /// Returns up to `limit` logged conflicts with `id >= since`, optionally
+    /// only those of one client.
+    pub fn list(&self, since: u64, limit: usize, client_id: Option<&str>) -> ConflictsResponse {
+        let log = self.log.lock();
+        let conflicts = log
+            .entries
+            .iter()
+            .filter(|e| e.id >= since && client_id.is_none_or(|c| e.client_id == c))
+            .take(limit)
+            .cloned()
+            .collect();
+        ConflictsResponse {
+            conflicts,
+            last_id: log.next_id,
+        }
+    }
+
+    /// Removes a logged conflict, e.g. once a user has resolved it.
+    pub fn dismiss(&self, id: u64) -> Result<(), ServiceError> {
+        let mut log = self.log.lock();
+        let Some(pos) = log.entries.iter().position(|e| e.id == id) else {
+            return Err(ServiceError::NotFound(format!("conflict {id} not found")));
+        };
+        log.entries.remove(pos);
+        if let Some(dir) = &self.dir {
+            save(&dir.join(LOG_FILE), &*log)?;
+        }
+        Ok(())
+    }
+}
+
+fn load<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+    let data = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Ignoring unreadable sync state");
+            None
+        }
+    }
+}
+
+/// Writes `value` as JSON to a temp file and renames it over `path`.
+fn save<T: Serialize>(path: &Path, value: &T) -> Result<(), ServiceError> {
+    let json = serde_json::to_string(value).map_err(|e| ServiceError::Internal(e.to_string()))?;
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, json)
+        .and_then(|()| std::fs::rename(&tmp_path, path))
+        .map_err(|e| ServiceError::Internal(format!("failed to write {}: {e}", path.display())))
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(client_id: &str) -> LoggedConflict {
+        LoggedConflict {
+            id: 0,
+            recorded_at: 0,
+            client_id: client_id.to_string(),
+            epoch: 1,
+            rolled_back: false,
+            conflict: ConflictRecord::new(0, "server_wins".to_string()),
+            change: SyncChangeRequest {
+                kind: "delete".to_string(),
+                entity_type: "node".to_string(),
+                id: Some(1.into()),
+                timestamp: 0,
+                labels: None,
+                edge_type: None,
+                src_id: None,
+                dst_id: None,
+                after: None,
+                crdt_op: None,
+                crdt_property: None,
+            },
+        }
+    }
+
+    #[test]
+    fn policy_rejects_nested_field_merge() {
+        let state = ConflictState::open(None);
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::FieldMerge,
+            fields: HashMap::from([("name".to_string(), ConflictPolicy::FieldMerge)]),
+            log_conflicts: false,
+        };
+        assert!(matches!(
+            state.set_policy(policy),
+            Err(ServiceError::BadRequest(_))
+        ));
+        assert_eq!(state.policy(), SyncPolicy::default());
+    }
+
+    #[test]
+    fn log_filters_and_dismisses() {
+        let state = ConflictState::open(None);
+        state.record([entry("a"), entry("b"), entry("a")]);
+
+        let all = state.list(0, 100, None);
+        assert_eq!(all.last_id, 3);
+        assert_eq!(
+            all.conflicts.iter().map(|c| c.id).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        let a = state.list(2, 100, Some("a"));
+        assert_eq!(a.conflicts.len(), 1);
+        assert_eq!(a.conflicts[0].id, 3);
+
+        state.dismiss(2).unwrap();
+        assert!(matches!(state.dismiss(2), Err(ServiceError::NotFound(_))));
+        assert_eq!(state.list(0, 100, None).conflicts.len(), 2);
+    }
+
+    #[test]
+    fn state_persists_in_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::ServerWins,
+            fields: HashMap::new(),
+            log_conflicts: true,
+        };
+        {
+            let state = ConflictState::open(Some(dir.path().to_path_buf()));
+            state.set_policy(policy.clone()).unwrap();
+            state.record([entry("a")]);
+        }
+        let state = ConflictState::open(Some(dir.path().to_path_buf()));
+        assert_eq!(state.policy(), policy);
+        state.record([entry("b")]);
+        let log = state.list(0, 100, None);
+        assert_eq!(
+            log.conflicts.iter().map(|c| c.id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! CRDT merge functions for conflict-free replicated data types.
 //!
-//! Operates on `Value::GCounter` and `Value::OnCounter` directly.
+//! Operates on `Value::GCounter` and `Value::OnCounter` directly. Registers
+//! and sets have no dedicated `Value` variant and are stored as a
+//! `Value::Map` tagged by a reserved `$`-prefixed key.
 //!
 //! ## GCounter
 //!
@@ -13,11 +15,31 @@
 //! each map is `Arc<HashMap<replica_id, count>>`.
 //! Merge: per-replica max over positive AND negative maps separately.
 //! Net value: `sum(pos) - sum(neg)`.
+//!
+//! ## LWW register
+//!
+//! A last-writer-wins register.
+//! `{"$lww": value, "$ts": hlc_timestamp, "$replica": replica_id}`.
+//! Merge: the assignment with the higher `(timestamp, replica_id)` wins.
+//!
+//! ## OR-set
+//!
+//! An observed-remove set. `{"$orset": {tag: element}, "$removed": [tag]}`.
+//! Each add gets a unique `replica_id:n` tag; a remove tombstones the tags
+//! it observed, so an add the remover had not seen survives.
+//! Merge: union of tags, minus the union of tombstones.
+//!
+//! ## Multi-value register
+//!
+//! A register that keeps concurrent assignments.
+//! `{"$mvreg": [{"$value": value, "$clock": {replica_id: n}}]}`.
+//! Each assignment carries a version vector; merge keeps every version not
+//! dominated by another.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::sync::Arc;
 
-use grafeo_common::types::Value;
+use grafeo_common::types::{PropertyKey, Value};
 
 use crate::sync::CrdtOp;
 
@@ -25,17 +47,180 @@ use crate::sync::CrdtOp;
 // Public API
 // ---------------------------------------------------------------------------
 
+/// A CRDT value stored as a tagged `Value::Map`, decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapCrdt {
+    /// Last-writer-wins register.
+    LwwRegister {
+        value: Value,
+        timestamp: u64,
+        replica_id: String,
+    },
+    /// Observed-remove set: live elements by tag, and removed tags.
+    OrSet {
+        elements: BTreeMap<String, Value>,
+        removed: BTreeSet<String>,
+    },
+    /// Multi-value register: concurrent values with their version vectors.
+    MvRegister { versions: Vec<Version> },
+}
+
+/// One value of a multi-value register.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    pub value: Value,
+    pub clock: BTreeMap<String, u64>,
+}
+
 /// Applies a CRDT operation to `current`, returning the new CRDT value.
 ///
-/// `current` should be an existing `Value::GCounter` or `Value::OnCounter`,
-/// or `Value::Null` (treated as an empty counter of the appropriate type).
+/// `current` should be an existing CRDT value of the type the operation
+/// targets, or `Value::Null` (treated as an empty value of that type).
 pub fn apply_op(current: &Value, op: &CrdtOp) -> Value {
     match op {
         CrdtOp::GrowAdd { amount, replica_id } => apply_grow_add(current, replica_id, *amount),
         CrdtOp::Increment { amount, replica_id } => {
             apply_on_increment(current, replica_id, *amount)
         }
+        CrdtOp::LwwAssign {
+            value,
+            timestamp,
+            replica_id,
+        } => {
+            let assigned = MapCrdt::LwwRegister {
+                value: value.clone(),
+                timestamp: *timestamp,
+                replica_id: replica_id.clone(),
+            }
+            .encode();
+            match decode(current) {
+                Some(MapCrdt::LwwRegister { .. }) => merge(current, &assigned),
+                _ => assigned,
+            }
+        }
+        CrdtOp::SetAdd {
+            element,
+            replica_id,
+        } => {
+            let (mut elements, removed) = or_set(current);
+            let tag = next_tag(elements.keys().chain(&removed), replica_id);
+            elements.insert(tag, element.clone());
+            MapCrdt::OrSet { elements, removed }.encode()
+        }
+        CrdtOp::SetRemove { element, tags } => {
+            let (mut elements, mut removed) = or_set(current);
+            elements.retain(|tag, e| {
+                let observed = e == element && tags.as_ref().is_none_or(|t| t.contains(tag));
+                if observed {
+                    removed.insert(tag.clone());
+                }
+                !observed
+            });
+            MapCrdt::OrSet { elements, removed }.encode()
+        }
+        CrdtOp::MvAssign {
+            value,
+            replica_id,
+            context,
+        } => {
+            let versions = match decode(current) {
+                Some(MapCrdt::MvRegister { versions }) => versions,
+                _ => Vec::new(),
+            };
+            // Without a context the client has seen every current value.
+            let mut clock: BTreeMap<String, u64> = match context {
+                Some(context) => context.iter().map(|(r, n)| (r.clone(), *n)).collect(),
+                None => versions.iter().fold(BTreeMap::new(), |mut joined, v| {
+                    join_clock(&mut joined, &v.clock);
+                    joined
+                }),
+            };
+            let seen = versions
+                .iter()
+                .filter_map(|v| v.clock.get(replica_id))
+                .chain(clock.get(replica_id))
+                .copied()
+                .max()
+                .unwrap_or(0);
+            clock.insert(replica_id.clone(), seen + 1);
+            let assigned = Version {
+                value: value.clone(),
+                clock,
+            };
+            MapCrdt::MvRegister {
+                versions: merge_versions(versions, vec![assigned]),
+            }
+            .encode()
+        }
+    }
+}
+
+/// Decodes a map-encoded CRDT value. Returns `None` for counters and for
+/// values that are not CRDTs.
+pub fn decode(value: &Value) -> Option<MapCrdt> {
+    let Value::Map(map) = value else {
+        return None;
+    };
+    let get = |key: &str| map.get(&PropertyKey::new(key));
+    if let Some(value) = get(LWW) {
+        let (Some(Value::Int64(ts)), Some(Value::String(replica))) =
+            (get(LWW_TIMESTAMP), get(LWW_REPLICA))
+        else {
+            return None;
+        };
+        return Some(MapCrdt::LwwRegister {
+            value: value.clone(),
+            timestamp: *ts as u64,
+            replica_id: replica.to_string(),
+        });
+    }
+    if let Some(Value::Map(elements)) = get(OR_SET) {
+        let removed = match get(OR_SET_REMOVED) {
+            Some(Value::List(tags)) => tags
+                .iter()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect(),
+            _ => BTreeSet::new(),
+        };
+        return Some(MapCrdt::OrSet {
+            elements: elements
+                .iter()
+                .map(|(tag, e)| (tag.to_string(), e.clone()))
+                .collect(),
+            removed,
+        });
+    }
+    if let Some(Value::List(versions)) = get(MV_REGISTER) {
+        let versions = versions
+            .iter()
+            .filter_map(|v| {
+                let Value::Map(v) = v else { return None };
+                let Some(Value::Map(clock)) = v.get(&PropertyKey::new(VERSION_CLOCK)) else {
+                    return None;
+                };
+                Some(Version {
+                    value: v
+                        .get(&PropertyKey::new(VERSION_VALUE))
+                        .cloned()
+                        .unwrap_or(Value::Null),
+                    clock: clock
+                        .iter()
+                        .filter_map(|(r, n)| match n {
+                            Value::Int64(n) => Some((r.to_string(), *n as u64)),
+                            _ => None,
+                        })
+                        .collect(),
+                })
+            })
+            .collect();
+        return Some(MapCrdt::MvRegister { versions });
     }
+    None
+}
+
+/// Returns `true` if `value` is a CRDT of any type.
+pub fn is_crdt(value: &Value) -> bool {
+    matches!(value, Value::GCounter(_) | Value::OnCounter { .. }) || decode(value).is_some()
 }
 
 /// Merges two CRDT values of the same type.
@@ -72,6 +257,51 @@ pub fn merge(a: &Value, b: &Value) -> Value {
                 neg: Arc::new(neg),
             }
         }
+        (Value::Map(_), Value::Map(_)) => match (decode(a), decode(b)) {
+            (
+                Some(MapCrdt::LwwRegister {
+                    timestamp: a_ts,
+                    replica_id: a_replica,
+                    ..
+                }),
+                Some(MapCrdt::LwwRegister {
+                    timestamp: b_ts,
+                    replica_id: b_replica,
+                    ..
+                }),
+            ) => {
+                if (b_ts, &b_replica) > (a_ts, &a_replica) {
+                    b.clone()
+                } else {
+                    a.clone()
+                }
+            }
+            (
+                Some(MapCrdt::OrSet {
+                    mut elements,
+                    mut removed,
+                }),
+                Some(MapCrdt::OrSet {
+                    elements: b_elements,
+                    removed: b_removed,
+                }),
+            ) => {
+                elements.extend(b_elements);
+                removed.extend(b_removed);
+                elements.retain(|tag, _| !removed.contains(tag));
+                MapCrdt::OrSet { elements, removed }.encode()
+            }
+            (
+                Some(MapCrdt::MvRegister { versions }),
+                Some(MapCrdt::MvRegister {
+                    versions: b_versions,
+                }),
+            ) => MapCrdt::MvRegister {
+                versions: merge_versions(versions, b_versions),
+            }
+            .encode(),
+            _ => a.clone(),
+        },
         // Mismatched types: return a unchanged.
         _ => a.clone(),
     }
@@ -94,10 +324,144 @@ pub fn read(value: &Value) -> i64 {
     }
 }
 
+/// Returns the logical value of a map-encoded CRDT: the value of an LWW
+/// register, the distinct elements of an OR-set, or the concurrent values
+/// of a multi-value register. Any other value is returned unchanged.
+pub fn read_value(value: &Value) -> Value {
+    match decode(value) {
+        Some(MapCrdt::LwwRegister { value, .. }) => value,
+        Some(MapCrdt::OrSet { elements, .. }) => {
+            let mut distinct: Vec<Value> = Vec::new();
+            for element in elements.into_values() {
+                if !distinct.contains(&element) {
+                    distinct.push(element);
+                }
+            }
+            Value::List(distinct.into())
+        }
+        Some(MapCrdt::MvRegister { versions }) => {
+            Value::List(versions.into_iter().map(|v| v.value).collect())
+        }
+        None => value.clone(),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Private helpers
 // ---------------------------------------------------------------------------
 
+const LWW: &str = "$lww";
+const LWW_TIMESTAMP: &str = "$ts";
+const LWW_REPLICA: &str = "$replica";
+const OR_SET: &str = "$orset";
+const OR_SET_REMOVED: &str = "$removed";
+const MV_REGISTER: &str = "$mvreg";
+const VERSION_VALUE: &str = "$value";
+const VERSION_CLOCK: &str = "$clock";
+
+impl MapCrdt {
+    fn encode(self) -> Value {
+        let entries: Vec<(&str, Value)> = match self {
+            Self::LwwRegister {
+                value,
+                timestamp,
+                replica_id,
+            } => vec![
+                (LWW, value),
+                (LWW_TIMESTAMP, Value::Int64(timestamp as i64)),
+                (LWW_REPLICA, Value::from(replica_id.as_str())),
+            ],
+            Self::OrSet { elements, removed } => vec![
+                (
+                    OR_SET,
+                    Value::Map(Arc::new(
+                        elements
+                            .into_iter()
+                            .map(|(tag, e)| (PropertyKey::new(tag), e))
+                            .collect(),
+                    )),
+                ),
+                (
+                    OR_SET_REMOVED,
+                    Value::List(removed.iter().map(|t| Value::from(t.as_str())).collect()),
+                ),
+            ],
+            Self::MvRegister { versions } => vec![(
+                MV_REGISTER,
+                Value::List(
+                    versions
+                        .into_iter()
+                        .map(|v| {
+                            let clock = v
+                                .clock
+                                .into_iter()
+                                .map(|(r, n)| (PropertyKey::new(r), Value::Int64(n as i64)))
+                                .collect();
+                            map([
+                                (VERSION_VALUE, v.value),
+                                (VERSION_CLOCK, Value::Map(Arc::new(clock))),
+                            ])
+                        })
+                        .collect(),
+                ),
+            )],
+        };
+        map(entries)
+    }
+}
+
+fn map<'a>(entries: impl IntoIterator<Item = (&'a str, Value)>) -> Value {
+    Value::Map(Arc::new(
+        entries
+            .into_iter()
+            .map(|(k, v)| (PropertyKey::new(k), v))
+            .collect(),
+    ))
+}
+
+fn or_set(current: &Value) -> (BTreeMap<String, Value>, BTreeSet<String>) {
+    match decode(current) {
+        Some(MapCrdt::OrSet { elements, removed }) => (elements, removed),
+        _ => (BTreeMap::new(), BTreeSet::new()),
+    }
+}
+
+/// Returns the next unused `replica_id:n` tag.
+fn next_tag<'a>(tags: impl Iterator<Item = &'a String>, replica_id: &str) -> String {
+    let last = tags
+        .filter_map(|t| t.rsplit_once(':'))
+        .filter(|(replica, _)| *replica == replica_id)
+        .filter_map(|(_, n)| n.parse::<u64>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("{replica_id}:{}", last + 1)
+}
+
+fn join_clock(into: &mut BTreeMap<String, u64>, clock: &BTreeMap<String, u64>) {
+    for (replica, n) in clock {
+        let entry = into.entry(replica.clone()).or_insert(0);
+        *entry = (*entry).max(*n);
+    }
+}
+
+/// `true` if `a` has seen everything `b` has, and more.
+fn dominates(a: &BTreeMap<String, u64>, b: &BTreeMap<String, u64>) -> bool {
+    a != b && b.iter().all(|(r, n)| a.get(r).is_some_and(|m| m >= n))
+}
+
+/// Unions two version lists, dropping versions another one supersedes.
+fn merge_versions(a: Vec<Version>, b: Vec<Version>) -> Vec<Version> {
+    let all: Vec<Version> = a.into_iter().chain(b).collect();
+    let mut kept: Vec<Version> = Vec::new();
+    for v in &all {
+        let superseded = all.iter().any(|w| dominates(&w.clock, &v.clock));
+        if !superseded && !kept.iter().any(|k| k.clock == v.clock) {
+            kept.push(v.clone());
+        }
+    }
+    kept
+}
+
 fn apply_grow_add(current: &Value, replica_id: &str, amount: u64) -> Value {
     let mut counts = match current {
         Value::GCounter(c) => (**c).clone(),
@@ -234,6 +598,128 @@ mod tests {
         assert_eq!(read(&val), 7);
     }
 
+    fn lww(value: &str, timestamp: u64, replica_id: &str) -> CrdtOp {
+        CrdtOp::LwwAssign {
+            value: Value::from(value),
+            timestamp,
+            replica_id: replica_id.to_string(),
+        }
+    }
+
+    fn add(element: &str, replica_id: &str) -> CrdtOp {
+        CrdtOp::SetAdd {
+            element: Value::from(element),
+            replica_id: replica_id.to_string(),
+        }
+    }
+
+    fn mv(value: &str, replica_id: &str, context: Option<&[(&str, u64)]>) -> CrdtOp {
+        CrdtOp::MvAssign {
+            value: Value::from(value),
+            replica_id: replica_id.to_string(),
+            context: context.map(|c| c.iter().map(|(r, n)| (r.to_string(), *n)).collect()),
+        }
+    }
+
+    fn strings(value: &Value) -> Vec<String> {
+        match read_value(value) {
+            Value::List(items) => items
+                .iter()
+                .map(|v| v.as_str().unwrap().to_string())
+                .collect(),
+            other => vec![other.as_str().unwrap().to_string()],
+        }
+    }
+
+    #[test]
+    fn lww_register_keeps_latest_assignment() {
+        let val = apply_op(&Value::Null, &lww("b", 20, "r2"));
+        // An older assignment arriving later does not win.
+        let val = apply_op(&val, &lww("a", 10, "r1"));
+        assert_eq!(strings(&val), ["b"]);
+        let val = apply_op(&val, &lww("c", 30, "r1"));
+        assert_eq!(strings(&val), ["c"]);
+    }
+
+    #[test]
+    fn lww_register_merge_breaks_ties_by_replica() {
+        let a = apply_op(&Value::Null, &lww("a", 10, "r1"));
+        let b = apply_op(&Value::Null, &lww("b", 10, "r2"));
+        assert_eq!(merge(&a, &b), merge(&b, &a));
+        assert_eq!(strings(&merge(&a, &b)), ["b"]);
+    }
+
+    #[test]
+    fn or_set_add_and_remove() {
+        let val = apply_op(&Value::Null, &add("red", "r1"));
+        let val = apply_op(&val, &add("blue", "r1"));
+        let val = apply_op(
+            &val,
+            &CrdtOp::SetRemove {
+                element: Value::from("red"),
+                tags: None,
+            },
+        );
+        assert_eq!(strings(&val), ["blue"]);
+        // The removed tag is not reused.
+        let val = apply_op(&val, &add("red", "r1"));
+        let Some(MapCrdt::OrSet { elements, .. }) = decode(&val) else {
+            panic!("not an OR-set");
+        };
+        assert!(elements.contains_key("r1:3"));
+    }
+
+    #[test]
+    fn or_set_concurrent_add_survives_remove() {
+        let base = apply_op(&Value::Null, &add("red", "r1"));
+        // r2 removes the add it observed while r3 adds "red" again.
+        let removed = apply_op(
+            &base,
+            &CrdtOp::SetRemove {
+                element: Value::from("red"),
+                tags: Some(vec!["r1:1".to_string()]),
+            },
+        );
+        let added = apply_op(&base, &add("red", "r3"));
+        let merged = merge(&removed, &added);
+        assert_eq!(merged, merge(&added, &removed));
+        assert_eq!(strings(&merged), ["red"]);
+        assert_eq!(merge(&merged, &merged), merged);
+    }
+
+    #[test]
+    fn mv_register_keeps_concurrent_values() {
+        let v1 = apply_op(&Value::Null, &mv("draft", "r1", None));
+        // Both replicas saw v1 ({r1: 1}) and assign concurrently.
+        let v2 = apply_op(&v1, &mv("from r2", "r2", Some(&[("r1", 1)])));
+        let v3 = apply_op(&v2, &mv("from r1", "r1", Some(&[("r1", 1)])));
+        let mut values = strings(&v3);
+        values.sort();
+        assert_eq!(values, ["from r1", "from r2"]);
+
+        // An assignment that saw both supersedes them.
+        let v4 = apply_op(&v3, &mv("resolved", "r2", None));
+        assert_eq!(strings(&v4), ["resolved"]);
+    }
+
+    #[test]
+    fn mv_register_merge_is_commutative_and_idempotent() {
+        let base = apply_op(&Value::Null, &mv("draft", "r1", None));
+        let a = apply_op(&base, &mv("a", "r1", None));
+        let b = apply_op(&base, &mv("b", "r2", None));
+        let ab = merge(&a, &b);
+        assert_eq!(strings(&ab).len(), 2);
+        let mut ab_values = strings(&ab);
+        let mut ba_values = strings(&merge(&b, &a));
+        ab_values.sort();
+        ba_values.sort();
+        assert_eq!(ab_values, ["a", "b"]);
+        assert_eq!(ab_values, ba_values);
+        assert_eq!(merge(&ab, &ab), ab);
+        // The merged state supersedes its inputs.
+        assert_eq!(merge(&ab, &a), ab);
+    }
+
     #[test]
     fn oncounter_merge_commutative() {
         let a = apply_op(
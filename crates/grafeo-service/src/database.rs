@@ -13,6 +13,7 @@ use std::sync::atomic::{AtomicU8, Ordering};
 use arc_swap::ArcSwap;
 use dashmap::DashMap;
 use grafeo_engine::{Config, DurabilityMode, GrafeoDB};
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use crate::error::ServiceError;
 use crate::types::{CreateDatabaseRequest, DatabaseType, StorageMode};
@@ -81,6 +82,8 @@ const STATE_RESTORING: u8 = 1;
 pub struct DatabaseEntry {
     inner: ArcSwap<GrafeoDB>,
     state: AtomicU8,
+    /// Guards the store's ID counters, see [`lock_id_counters`](Self::lock_id_counters).
+    id_counters: RwLock<()>,
     pub metadata: DatabaseMetadata,
 }
 
@@ -89,6 +92,7 @@ impl std::fmt::Debug for DatabaseEntry {
         f.debug_struct("DatabaseEntry")
             .field("inner", &"ArcSwap<GrafeoDB>")
             .field("state", &self.state.load(Ordering::Relaxed))
+            .field("id_counters", &"RwLock<()>")
             .field("metadata", &self.metadata.database_type)
             .finish()
     }
@@ -100,10 +104,28 @@ impl DatabaseEntry {
         Self {
             inner: ArcSwap::from(db),
             state: AtomicU8::new(STATE_AVAILABLE),
+            id_counters: RwLock::new(()),
             metadata,
         }
     }
 
+    /// Takes the exclusive guard for moving the store's ID counters.
+    ///
+    /// The engine allocates IDs from plain counters, so a writer that sets
+    /// them (to create an entity with a given ID) must keep every writer
+    /// that allocates from them out until it has restored them. Such
+    /// writers hold [`share_id_counters`](Self::share_id_counters) while
+    /// they may allocate.
+    pub fn lock_id_counters(&self) -> RwLockWriteGuard<'_, ()> {
+        self.id_counters.write()
+    }
+
+    /// Takes a shared guard that keeps the ID counters from being moved,
+    /// see [`lock_id_counters`](Self::lock_id_counters).
+    pub fn share_id_counters(&self) -> RwLockReadGuard<'_, ()> {
+        self.id_counters.read()
+    }
+
     /// Returns a snapshot of the current database handle.
     ///
     /// Lock-free on the read path. The returned `Arc` keeps the handle alive
@@ -157,7 +179,10 @@ pub struct DatabaseManager {
     read_only: bool,
     /// When `true`, enable CDC on every database (needed for replication).
     #[cfg(feature = "cdc")]
-    cdc_enabled: bool,
+    cdc_enabled: std::sync::atomic::AtomicBool,
+    /// Sync conflict policy and log of each database, loaded on first use.
+    #[cfg(feature = "sync")]
+    conflicts: DashMap<String, Arc<crate::conflicts::ConflictState>>,
 }
 
 impl DatabaseManager {
@@ -170,7 +195,9 @@ impl DatabaseManager {
             data_dir: data_dir.map(PathBuf::from),
             read_only,
             #[cfg(feature = "cdc")]
-            cdc_enabled: false,
+            cdc_enabled: std::sync::atomic::AtomicBool::new(false),
+            #[cfg(feature = "sync")]
+            conflicts: DashMap::new(),
         };
 
         if let Some(ref dir) = mgr.data_dir {
@@ -313,16 +340,35 @@ impl DatabaseManager {
 
     /// Enables or disables CDC on all current databases and future ones.
     ///
-    /// Called by `ServiceState` when the server is configured as a replication
-    /// primary, so that all mutations generate CDC events for replicas to consume.
+    /// Called by `ServiceState` when the server is configured as, or promoted
+    /// to, a replication primary, so that all mutations generate CDC events
+    /// for replicas to consume.
     #[cfg(feature = "cdc")]
-    pub fn set_cdc_enabled(&mut self, enabled: bool) {
-        self.cdc_enabled = enabled;
+    pub fn set_cdc_enabled(&self, enabled: bool) {
+        self.cdc_enabled.store(enabled, Ordering::Relaxed);
         for entry in &self.databases {
             entry.value().db().set_cdc_enabled(enabled);
         }
     }
 
+    /// Returns the sync conflict policy and log of an available database.
+    ///
+    /// For a persistent database they are loaded from its directory on
+    /// first use.
+    #[cfg(feature = "sync")]
+    pub fn conflict_state(
+        &self,
+        name: &str,
+    ) -> Result<Arc<crate::conflicts::ConflictState>, ServiceError> {
+        let entry = self.get_available(name)?;
+        let state = self.conflicts.entry(name.to_string()).or_insert_with(|| {
+            let db = entry.db();
+            let dir = db.path().and_then(Path::parent).map(Path::to_path_buf);
+            Arc::new(crate::conflicts::ConflictState::open(dir))
+        });
+        Ok(Arc::clone(&state))
+    }
+
     /// Returns a clone of the `Arc<DatabaseEntry>` for the given database name.
     pub fn get(&self, name: &str) -> Option<Arc<DatabaseEntry>> {
         self.databases.get(name).map(|e| Arc::clone(e.value()))
@@ -503,7 +549,7 @@ impl DatabaseManager {
         // If CDC is enabled (replication primary), activate it on this database
         // so mutations produce change events for replicas.
         #[cfg(feature = "cdc")]
-        if self.cdc_enabled {
+        if self.cdc_enabled.load(Ordering::Relaxed) {
             db.set_cdc_enabled(true);
         }
 
@@ -548,6 +594,8 @@ impl DatabaseManager {
         }
 
         let (_, entry) = removed.unwrap();
+        #[cfg(feature = "sync")]
+        self.conflicts.remove(name);
 
         // Close the engine
         if let Err(e) = entry.db().close() {
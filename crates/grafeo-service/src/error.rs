@@ -26,6 +26,10 @@ pub enum ServiceError {
     #[error("query execution timed out")]
     Timeout,
 
+    /// Query was cancelled before it completed.
+    #[error("query was cancelled")]
+    Cancelled,
+
     /// Missing or invalid authentication.
     #[error("unauthorized")]
     Unauthorized,
@@ -12,9 +12,19 @@
 pub mod admin;
 pub mod auth;
 pub mod backup;
+pub mod backup_crypto;
+#[cfg(feature = "s3-backup")]
+pub mod backup_s3;
+pub mod backup_schedule;
+pub mod backup_target;
+pub mod cancel;
+#[cfg(feature = "sync")]
+pub mod change_filter;
 #[cfg(feature = "push-changefeed")]
 pub mod changefeed;
 #[cfg(feature = "sync")]
+pub mod conflicts;
+#[cfg(feature = "sync")]
 pub mod crdt;
 pub mod database;
 pub mod error;
@@ -23,9 +33,15 @@ pub mod query;
 pub mod rate_limit;
 #[cfg(feature = "replication")]
 pub mod replication;
+pub mod running;
 pub mod schema;
 pub mod search;
 pub mod session;
+pub mod sparql_dataset;
+#[cfg(feature = "sparql")]
+pub mod sparql_params;
+mod sparql_scan;
+pub mod sse;
 pub mod stream;
 #[cfg(feature = "sync")]
 pub mod sync;
@@ -43,6 +59,7 @@ use database::DatabaseManager;
 
 use metrics::Metrics;
 use rate_limit::RateLimiter;
+use running::RunningQueryRegistry;
 use session::SessionRegistry;
 
 /// Configuration subset relevant to the service layer.
@@ -66,10 +83,27 @@ pub struct ServiceConfig {
     pub token_store_path: Option<String>,
     #[cfg(feature = "replication")]
     pub replication_mode: replication::ReplicationMode,
+    /// Replica lag in seconds beyond which the readiness probe fails.
+    /// 0 = disabled.
+    #[cfg(feature = "replication")]
+    pub replica_max_lag: u64,
+    /// Forward writes on a replica to the primary instead of rejecting them.
+    #[cfg(feature = "replication")]
+    pub replica_forward_writes: bool,
+    /// Token a replica presents to its primary, for primaries with
+    /// authentication enabled.
+    #[cfg(feature = "replication")]
+    pub replica_token: Option<String>,
     /// Directory for storing database backups.
     pub backup_dir: Option<String>,
     /// Number of backups to keep per database (retention policy).
     pub backup_retention: Option<usize>,
+    /// Where backups are stored. `None` keeps them in `backup_dir`, which
+    /// otherwise serves as a local cache. Ignored without `backup_dir`.
+    pub backup_target: Option<backup_target::BackupTargetConfig>,
+    /// Keys backups are encrypted with at rest. `None` writes plaintext
+    /// backups.
+    pub backup_keys: Option<backup_crypto::BackupKeys>,
 }
 
 /// Shared service state, cloneable across all transport handlers.
@@ -84,6 +118,7 @@ pub struct ServiceState {
 struct Inner {
     databases: DatabaseManager,
     sessions: SessionRegistry,
+    running_queries: RunningQueryRegistry,
     metrics: Metrics,
     rate_limiter: RateLimiter,
     session_ttl: u64,
@@ -95,18 +130,26 @@ struct Inner {
     #[cfg(feature = "push-changefeed")]
     change_hub: changefeed::ChangeHub,
     #[cfg(feature = "replication")]
-    replication_mode: replication::ReplicationMode,
+    replication_mode: parking_lot::RwLock<replication::ReplicationMode>,
     #[cfg(feature = "replication")]
     replication_state: Arc<replication::ReplicationState>,
+    #[cfg(feature = "replication")]
+    replica_max_lag: Option<Duration>,
+    #[cfg(feature = "replication")]
+    replica_forward_writes: bool,
+    #[cfg(feature = "replication")]
+    replica_token: Option<String>,
     backup_dir: Option<PathBuf>,
     backup_retention: Option<usize>,
+    backup_target: Option<Arc<dyn backup_target::BackupTarget>>,
+    backup_scheduler: Option<backup_schedule::BackupScheduler>,
+    backup_keys: Option<Arc<backup_crypto::BackupKeys>>,
 }
 
 impl ServiceState {
     /// Creates a new service state from config.
     pub fn new(config: &ServiceConfig) -> Self {
-        #[allow(unused_mut)]
-        let mut databases = DatabaseManager::new(config.data_dir.as_deref(), config.read_only);
+        let databases = DatabaseManager::new(config.data_dir.as_deref(), config.read_only);
 
         // Enable CDC on all databases when running as a replication primary,
         // so that mutations produce change events for replicas to consume.
@@ -115,10 +158,22 @@ impl ServiceState {
             databases.set_cdc_enabled(true);
         }
 
+        let backup_target: Option<Arc<dyn backup_target::BackupTarget>> = config
+            .backup_dir
+            .as_ref()
+            .map(|dir| match &config.backup_target {
+                Some(target) => target
+                    .build()
+                    .unwrap_or_else(|e| panic!("failed to set up backup target: {e}")),
+                None => Arc::new(backup_target::LocalTarget::new(PathBuf::from(dir))),
+            });
+        let backup_keys = config.backup_keys.clone().map(Arc::new);
+
         Self {
             inner: Arc::new(Inner {
                 databases,
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(
                     config.rate_limit,
@@ -169,15 +224,34 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: config.replication_mode.clone(),
+                replication_mode: parking_lot::RwLock::new(config.replication_mode.clone()),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(if let Some(ref dir) = config.data_dir {
                     replication::ReplicationState::with_persistence(std::path::PathBuf::from(dir))
                 } else {
                     replication::ReplicationState::new()
                 }),
+                #[cfg(feature = "replication")]
+                replica_max_lag: (config.replica_max_lag > 0)
+                    .then(|| Duration::from_secs(config.replica_max_lag)),
+                #[cfg(feature = "replication")]
+                replica_forward_writes: config.replica_forward_writes,
+                #[cfg(feature = "replication")]
+                replica_token: config.replica_token.clone(),
                 backup_dir: config.backup_dir.as_ref().map(PathBuf::from),
                 backup_retention: config.backup_retention,
+                backup_scheduler: config.backup_dir.as_ref().zip(backup_target.clone()).map(
+                    |(dir, target)| {
+                        let scheduler = backup_schedule::BackupScheduler::load(Path::new(dir))
+                            .with_target(target);
+                        match &backup_keys {
+                            Some(keys) => scheduler.with_keys(Arc::clone(keys)),
+                            None => scheduler,
+                        }
+                    },
+                ),
+                backup_target,
+                backup_keys,
             }),
         }
     }
@@ -188,6 +262,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, false),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(0, Duration::from_secs(60)),
                 session_ttl,
@@ -199,11 +274,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -215,6 +301,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, false),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(0, Duration::from_secs(60)),
                 session_ttl,
@@ -226,11 +313,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -242,6 +340,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, false),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(0, Duration::from_secs(60)),
                 session_ttl,
@@ -253,11 +352,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -272,6 +382,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, false),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(0, Duration::from_secs(60)),
                 session_ttl,
@@ -282,11 +393,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -301,6 +423,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, false),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(0, Duration::from_secs(60)),
                 session_ttl,
@@ -311,11 +434,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -326,6 +460,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, true),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(0, Duration::from_secs(60)),
                 session_ttl,
@@ -337,11 +472,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -356,6 +502,7 @@ impl ServiceState {
             inner: Arc::new(Inner {
                 databases: DatabaseManager::new(None, false),
                 sessions: SessionRegistry::new(),
+                running_queries: RunningQueryRegistry::new(),
                 metrics: Metrics::new(),
                 rate_limiter: RateLimiter::new(max_requests, window),
                 session_ttl,
@@ -367,11 +514,22 @@ impl ServiceState {
                 #[cfg(feature = "push-changefeed")]
                 change_hub: changefeed::ChangeHub::new(),
                 #[cfg(feature = "replication")]
-                replication_mode: replication::ReplicationMode::Standalone,
+                replication_mode: parking_lot::RwLock::new(
+                    replication::ReplicationMode::Standalone,
+                ),
                 #[cfg(feature = "replication")]
                 replication_state: Arc::new(replication::ReplicationState::new()),
+                #[cfg(feature = "replication")]
+                replica_max_lag: None,
+                #[cfg(feature = "replication")]
+                replica_forward_writes: false,
+                #[cfg(feature = "replication")]
+                replica_token: None,
                 backup_dir: None,
                 backup_retention: None,
+                backup_target: None,
+                backup_scheduler: None,
+                backup_keys: None,
             }),
         }
     }
@@ -386,6 +544,10 @@ impl ServiceState {
         &self.inner.sessions
     }
 
+    pub fn running_queries(&self) -> &RunningQueryRegistry {
+        &self.inner.running_queries
+    }
+
     pub fn metrics(&self) -> &Metrics {
         &self.inner.metrics
     }
@@ -423,7 +585,7 @@ impl ServiceState {
     /// Returns `true` if this instance is a read-only replica.
     #[cfg(feature = "replication")]
     pub fn is_replica(&self) -> bool {
-        self.inner.replication_mode.is_replica()
+        self.inner.replication_mode.read().is_replica()
     }
 
     /// Returns `true` if this instance is a read-only replica.
@@ -432,17 +594,97 @@ impl ServiceState {
         false
     }
 
+    /// Returns `true` if this primary has been superseded by a newer one and
+    /// must not accept writes.
+    #[cfg(feature = "replication")]
+    pub fn is_fenced(&self) -> bool {
+        self.inner.replication_mode.read().is_primary()
+            && self.inner.replication_state.is_superseded()
+    }
+
+    /// Returns `true` if this primary has been superseded by a newer one and
+    /// must not accept writes.
+    #[cfg(not(feature = "replication"))]
+    pub fn is_fenced(&self) -> bool {
+        false
+    }
+
     /// Returns `true` if client-facing queries should be read-only.
     ///
-    /// True when the server is in replica mode or global read-only mode.
+    /// True when the server is in replica mode, is a fenced primary, or is
+    /// in global read-only mode.
     pub fn is_query_read_only(&self) -> bool {
-        self.inner.read_only || self.is_replica()
+        self.inner.read_only || self.is_replica() || self.is_fenced()
+    }
+
+    /// Returns the current replication mode for this instance.
+    #[cfg(feature = "replication")]
+    pub fn replication_mode(&self) -> replication::ReplicationMode {
+        self.inner.replication_mode.read().clone()
+    }
+
+    /// Promotes this replica to primary and returns the new replication
+    /// term.
+    ///
+    /// Enables CDC so that writes produce change events for replicas, and
+    /// lifts the replica guard. The replication task stops following the
+    /// old primary on its next cycle.
+    #[cfg(feature = "replication")]
+    pub fn promote(&self) -> Result<u64, error::ServiceError> {
+        let mut mode = self.inner.replication_mode.write();
+        if !mode.is_replica() {
+            return Err(error::ServiceError::Conflict(
+                "only a replica can be promoted".to_string(),
+            ));
+        }
+        let term = self.inner.replication_state.begin_term();
+        self.inner.replication_state.reset([]);
+        self.inner.databases.set_cdc_enabled(true);
+        *mode = replication::ReplicationMode::Primary;
+        tracing::info!(term, "Promoted to replication primary");
+        Ok(term)
     }
 
-    /// Returns the replication mode for this instance.
+    /// Makes this node a replica of the primary at `primary_url`, which
+    /// leads `primary_term`.
+    ///
+    /// Fails with `Conflict` if this node already knows a newer term. Open
+    /// transactions are discarded and every local database is restored
+    /// from the new primary's snapshot before it is followed.
     #[cfg(feature = "replication")]
-    pub fn replication_mode(&self) -> &replication::ReplicationMode {
-        &self.inner.replication_mode
+    pub fn follow(
+        &self,
+        primary_url: String,
+        primary_term: u64,
+    ) -> Result<(), error::ServiceError> {
+        let state = &self.inner.replication_state;
+        let mut mode = self.inner.replication_mode.write();
+        if primary_term < state.term() {
+            return Err(error::ServiceError::Conflict(format!(
+                "primary term {primary_term} is older than known term {}",
+                state.term()
+            )));
+        }
+        *mode = replication::ReplicationMode::Replica {
+            primary_url: primary_url.clone(),
+        };
+        drop(mode);
+
+        let names: Vec<String> = self
+            .inner
+            .databases
+            .list()
+            .into_iter()
+            .map(|db| db.name)
+            .collect();
+        for name in &names {
+            self.inner.sessions.remove_by_database(name);
+        }
+        state.observe_term(primary_term);
+        state.reset(names);
+        self.inner.databases.set_cdc_enabled(false);
+        tracing::info!(primary = %primary_url, term = primary_term, "Following new primary");
+        Ok(())
     }
 
     /// Returns the shared replication state (epoch tracking, errors).
@@ -451,6 +693,26 @@ impl ServiceState {
         &self.inner.replication_state
     }
 
+    /// Returns the replica lag beyond which this instance reports itself
+    /// not ready, if configured.
+    #[cfg(feature = "replication")]
+    pub fn replica_max_lag(&self) -> Option<Duration> {
+        self.inner.replica_max_lag
+    }
+
+    /// Returns the token this replica presents to its primary, if
+    /// configured.
+    #[cfg(feature = "replication")]
+    pub fn replica_token(&self) -> Option<&str> {
+        self.inner.replica_token.as_deref()
+    }
+
+    /// Returns `true` if this replica forwards writes to its primary.
+    #[cfg(feature = "replication")]
+    pub fn forwards_writes(&self) -> bool {
+        self.is_replica() && self.inner.replica_forward_writes
+    }
+
     #[cfg(feature = "auth")]
     pub fn has_auth(&self) -> bool {
         self.inner.auth.as_ref().is_some_and(|a| a.is_enabled())
@@ -468,6 +730,23 @@ impl ServiceState {
         self.inner.backup_retention
     }
 
+    /// Returns where backups are stored, present when a backup directory
+    /// is configured.
+    pub fn backup_target(&self) -> Option<&Arc<dyn backup_target::BackupTarget>> {
+        self.inner.backup_target.as_ref()
+    }
+
+    /// Returns the keys backups are encrypted with, if configured.
+    pub fn backup_keys(&self) -> Option<&backup_crypto::BackupKeys> {
+        self.inner.backup_keys.as_deref()
+    }
+
+    /// Returns the backup scheduler, present when a backup directory is
+    /// configured.
+    pub fn backup_scheduler(&self) -> Option<&backup_schedule::BackupScheduler> {
+        self.inner.backup_scheduler.as_ref()
+    }
+
     // --- Maintenance ---
 
     /// Clean up expired sessions. Returns count removed.
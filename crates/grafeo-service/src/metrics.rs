@@ -56,6 +56,36 @@ const ALL_LANGUAGES: [Language; 6] = [
     Language::SqlPgq,
 ];
 
+/// Replication progress of one database on a replica.
+pub struct ReplicaLag {
+    pub database: String,
+    /// Last epoch applied locally.
+    pub applied_epoch: u64,
+    /// Latest epoch reported by the primary.
+    pub primary_epoch: u64,
+    /// Seconds since the replica last held everything the primary had.
+    pub lag_seconds: u64,
+    /// When a batch was last applied (Unix seconds, 0 if never).
+    pub last_applied_at: u64,
+}
+
+/// State of one kind of scheduled backup ("full" or "incremental") of one
+/// database.
+pub struct ScheduledBackup {
+    pub database: String,
+    pub kind: &'static str,
+    /// When the next run is due (Unix seconds).
+    pub next_run_at: u64,
+    /// When a run last succeeded (Unix seconds, 0 if never).
+    pub last_success_at: u64,
+    /// Duration of the last run in milliseconds.
+    pub last_duration_ms: u64,
+    /// Runs since the schedule was created.
+    pub runs_total: u64,
+    /// Failed runs since the schedule was created.
+    pub failures_total: u64,
+}
+
 /// Application-wide metrics collected via atomic counters.
 pub struct Metrics {
     gql: LanguageMetrics,
@@ -113,8 +143,13 @@ impl Metrics {
 
     /// Render all metrics in Prometheus text exposition format.
     ///
+    /// `replica_lag` adds per-database replication gauges; it is empty
+    /// unless the server is a replica. `backups` adds per-database backup
+    /// schedule series.
+    ///
     /// When `engine_metrics` is provided, the engine's own Prometheus output
     /// is appended after a blank separator line.
+    #[allow(clippy::too_many_arguments)]
     pub fn render(
         &self,
         databases_total: usize,
@@ -122,6 +157,8 @@ impl Metrics {
         edges_total: usize,
         active_sessions: usize,
         uptime_seconds: u64,
+        replica_lag: &[ReplicaLag],
+        backups: &[ScheduledBackup],
         engine_metrics: Option<&str>,
     ) -> String {
         let mut out = String::with_capacity(2048);
@@ -220,6 +257,89 @@ impl Metrics {
             .unwrap();
         }
 
+        // Per-database replication gauges (replicas only)
+        if !replica_lag.is_empty() {
+            replica_gauge(
+                &mut out,
+                "grafeo_replication_applied_epoch",
+                "Last epoch applied on this replica",
+                replica_lag,
+                |l| l.applied_epoch,
+            );
+            replica_gauge(
+                &mut out,
+                "grafeo_replication_primary_epoch",
+                "Latest epoch reported by the primary",
+                replica_lag,
+                |l| l.primary_epoch,
+            );
+            replica_gauge(
+                &mut out,
+                "grafeo_replication_epoch_lag",
+                "Epochs the primary is ahead of this replica",
+                replica_lag,
+                |l| l.primary_epoch.saturating_sub(l.applied_epoch),
+            );
+            replica_gauge(
+                &mut out,
+                "grafeo_replication_lag_seconds",
+                "Seconds since this replica last held everything the primary had",
+                replica_lag,
+                |l| l.lag_seconds,
+            );
+            replica_gauge(
+                &mut out,
+                "grafeo_replication_last_applied_timestamp_seconds",
+                "Unix time a replication batch was last applied",
+                replica_lag,
+                |l| l.last_applied_at,
+            );
+        }
+
+        // Per-database backup schedule series
+        if !backups.is_empty() {
+            backup_series(
+                &mut out,
+                "grafeo_backup_next_run_timestamp_seconds",
+                "gauge",
+                "Unix time the next scheduled backup is due",
+                backups,
+                |b| b.next_run_at as f64,
+            );
+            backup_series(
+                &mut out,
+                "grafeo_backup_last_success_timestamp_seconds",
+                "gauge",
+                "Unix time a scheduled backup last succeeded",
+                backups,
+                |b| b.last_success_at as f64,
+            );
+            backup_series(
+                &mut out,
+                "grafeo_backup_last_duration_seconds",
+                "gauge",
+                "Duration of the last scheduled backup",
+                backups,
+                |b| b.last_duration_ms as f64 / 1000.0,
+            );
+            backup_series(
+                &mut out,
+                "grafeo_backup_runs_total",
+                "counter",
+                "Scheduled backup runs",
+                backups,
+                |b| b.runs_total as f64,
+            );
+            backup_series(
+                &mut out,
+                "grafeo_backup_failures_total",
+                "counter",
+                "Failed scheduled backup runs",
+                backups,
+                |b| b.failures_total as f64,
+            );
+        }
+
         // Append engine-level Prometheus metrics (when available)
         if let Some(engine) = engine_metrics {
             out.push('\n');
@@ -236,6 +356,42 @@ fn gauge(out: &mut String, name: &str, help: &str, value: impl std::fmt::Display
     writeln!(out, "{name} {value}").unwrap();
 }
 
+fn replica_gauge(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    lag: &[ReplicaLag],
+    value: impl Fn(&ReplicaLag) -> u64,
+) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} gauge").unwrap();
+    for l in lag {
+        writeln!(out, "{name}{{database=\"{}\"}} {}", l.database, value(l)).unwrap();
+    }
+}
+
+fn backup_series(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    backups: &[ScheduledBackup],
+    value: impl Fn(&ScheduledBackup) -> f64,
+) {
+    writeln!(out, "# HELP {name} {help}").unwrap();
+    writeln!(out, "# TYPE {name} {kind}").unwrap();
+    for b in backups {
+        writeln!(
+            out,
+            "{name}{{database=\"{}\",kind=\"{}\"}} {}",
+            b.database,
+            b.kind,
+            value(b)
+        )
+        .unwrap();
+    }
+}
+
 /// Map a language string to a `Language` enum variant.
 pub fn determine_language(lang: Option<&str>) -> Language {
     match lang {
@@ -287,7 +443,7 @@ mod tests {
         m.record_query(Language::Gql, 2_500);
         m.record_query_error(Language::Cypher);
 
-        let output = m.render(2, 100, 50, 3, 60, None);
+        let output = m.render(2, 100, 50, 3, 60, &[], &[], None);
         assert!(output.contains("grafeo_databases_total 2"));
         assert!(output.contains("grafeo_nodes_total 100"));
         assert!(output.contains("grafeo_edges_total 50"));
@@ -295,5 +451,53 @@ mod tests {
         assert!(output.contains("grafeo_uptime_seconds 60"));
         assert!(output.contains("grafeo_queries_total{language=\"gql\"} 2"));
         assert!(output.contains("grafeo_query_errors_total{language=\"cypher\"} 1"));
+        assert!(!output.contains("grafeo_replication_"));
+        assert!(!output.contains("grafeo_backup_"));
+    }
+
+    #[test]
+    fn render_replica_lag_gauges() {
+        let m = Metrics::new();
+        let lag = [ReplicaLag {
+            database: "default".to_string(),
+            applied_epoch: 40,
+            primary_epoch: 42,
+            lag_seconds: 7,
+            last_applied_at: 1_700_000_000,
+        }];
+        let output = m.render(1, 0, 0, 0, 1, &lag, &[], None);
+        assert!(output.contains("# TYPE grafeo_replication_lag_seconds gauge"));
+        assert!(output.contains("grafeo_replication_applied_epoch{database=\"default\"} 40"));
+        assert!(output.contains("grafeo_replication_primary_epoch{database=\"default\"} 42"));
+        assert!(output.contains("grafeo_replication_epoch_lag{database=\"default\"} 2"));
+        assert!(output.contains("grafeo_replication_lag_seconds{database=\"default\"} 7"));
+        assert!(output.contains(
+            "grafeo_replication_last_applied_timestamp_seconds{database=\"default\"} 1700000000"
+        ));
+    }
+
+    #[test]
+    fn render_backup_schedule_series() {
+        let m = Metrics::new();
+        let backups = [ScheduledBackup {
+            database: "default".to_string(),
+            kind: "full",
+            next_run_at: 1_700_003_600,
+            last_success_at: 1_700_000_000,
+            last_duration_ms: 1_500,
+            runs_total: 4,
+            failures_total: 1,
+        }];
+        let output = m.render(1, 0, 0, 0, 1, &[], &backups, None);
+        assert!(output.contains("# TYPE grafeo_backup_runs_total counter"));
+        assert!(output.contains(
+            "grafeo_backup_next_run_timestamp_seconds{database=\"default\",kind=\"full\"} 1700003600"
+        ));
+        assert!(output.contains(
+            "grafeo_backup_last_duration_seconds{database=\"default\",kind=\"full\"} 1.5"
+        ));
+        assert!(
+            output.contains("grafeo_backup_failures_total{database=\"default\",kind=\"full\"} 1")
+        );
     }
 }
@@ -3,7 +3,7 @@
 //! Extracted from `routes/query.rs`, `routes/helpers.rs`, and `routes/batch.rs`.
 //! All transports (HTTP, GWP, Bolt) call through here instead of touching
 //! the engine directly. This ensures consistent language dispatch, timeout
-//! handling, and metrics recording across all protocols.
+//! handling, cancellation, and metrics recording across all protocols.
 
 use std::collections::HashMap;
 use std::time::Duration;
@@ -11,6 +11,7 @@ use std::time::Duration;
 use grafeo_engine::auth::{Identity, Role};
 use grafeo_engine::database::QueryResult;
 
+use crate::cancel::CancelToken;
 use crate::database::DatabaseManager;
 use crate::error::ServiceError;
 use crate::metrics::{Language, Metrics, determine_language};
@@ -42,6 +43,9 @@ impl QueryService {
     /// Creates a fresh session, dispatches by language, runs with timeout,
     /// records metrics, and returns raw `QueryResult`. Transport crates
     /// handle value encoding (JSON, GWP, PackStream).
+    ///
+    /// `cancel` lets the caller stop the query early; it is also tripped on
+    /// timeout and when the returned future is dropped.
     #[allow(clippy::too_many_arguments)]
     pub async fn execute(
         databases: &DatabaseManager,
@@ -53,16 +57,17 @@ impl QueryService {
         timeout: Option<Duration>,
         read_only: bool,
         identity: Option<Identity>,
+        cancel: CancelToken,
     ) -> Result<QueryResult, ServiceError> {
         let entry = databases.get_available(db_name)?;
 
         let lang = determine_language(language);
         let stmt = statement.to_owned();
 
-        let result = run_with_timeout(timeout, move || {
+        let result = run_with_timeout(timeout, cancel, move |cancel| {
             let db = entry.db();
             let session = create_session(&db, identity, read_only);
-            dispatch_query(&session, &stmt, lang, params.as_ref())
+            dispatch_query(&session, &stmt, lang, params.as_ref(), cancel)
         })
         .await;
 
@@ -91,6 +96,7 @@ impl QueryService {
         params: Option<HashMap<String, grafeo_common::Value>>,
         timeout: Option<Duration>,
         caller_token_id: Option<&str>,
+        cancel: CancelToken,
     ) -> Result<QueryResult, ServiceError> {
         let session_arc = sessions
             .get(session_id, ttl_secs, caller_token_id)
@@ -99,9 +105,15 @@ impl QueryService {
         let lang = determine_language(language);
         let stmt = statement.to_owned();
 
-        let result = run_with_timeout(timeout, move || {
+        let result = run_with_timeout(timeout, cancel, move |cancel| {
             let session = session_arc.lock();
-            dispatch_query(&session.engine_session, &stmt, lang, params.as_ref())
+            dispatch_query(
+                &session.engine_session,
+                &stmt,
+                lang,
+                params.as_ref(),
+                cancel,
+            )
         })
         .await;
 
@@ -200,6 +212,7 @@ impl QueryService {
 
     /// Batch execute: all queries in one implicit transaction.
     /// Rolls back on first failure.
+    #[allow(clippy::too_many_arguments)]
     pub async fn batch_execute(
         databases: &DatabaseManager,
         metrics: &Metrics,
@@ -208,6 +221,7 @@ impl QueryService {
         timeout: Option<Duration>,
         read_only: bool,
         identity: Option<Identity>,
+        cancel: CancelToken,
     ) -> Result<Vec<QueryResult>, ServiceError> {
         if queries.is_empty() {
             return Ok(vec![]);
@@ -222,7 +236,7 @@ impl QueryService {
             .map(|q| determine_language(q.language.as_deref()))
             .collect();
 
-        let results = run_with_timeout(timeout, move || {
+        let results = run_with_timeout(timeout, cancel, move |cancel| {
             let db = entry.db();
             let mut session = create_session(&db, identity, read_only);
             session
@@ -233,8 +247,18 @@ impl QueryService {
 
             for (idx, item) in queries.iter().enumerate() {
                 let lang = determine_language(item.language.as_deref());
-                match dispatch_query(&session, &item.statement, lang, item.params.as_ref()) {
+                match dispatch_query(
+                    &session,
+                    &item.statement,
+                    lang,
+                    item.params.as_ref(),
+                    cancel,
+                ) {
                     Ok(qr) => results.push(qr),
+                    Err(ServiceError::Cancelled) => {
+                        let _ = session.rollback();
+                        return Err(ServiceError::Cancelled);
+                    }
                     Err(e) => {
                         let _ = session.rollback();
                         return Err(ServiceError::BadRequest(format!(
@@ -244,6 +268,12 @@ impl QueryService {
                 }
             }
 
+            // Last chance to abandon the batch before its writes become visible.
+            if cancel.is_cancelled() {
+                let _ = session.rollback();
+                return Err(ServiceError::Cancelled);
+            }
+
             session
                 .commit()
                 .map_err(|e| ServiceError::Internal(e.to_string()))?;
@@ -270,9 +300,29 @@ impl QueryService {
         statement: &str,
         language: Option<&str>,
         params: Option<&HashMap<String, grafeo_common::Value>>,
+    ) -> Result<QueryResult, ServiceError> {
+        Self::dispatch_cancellable(session, statement, language, params, &CancelToken::new())
+    }
+
+    /// Like [`dispatch`](Self::dispatch), but stops early once `cancel` is
+    /// tripped.
+    ///
+    /// Cancellation is cooperative: the token is checked before the engine
+    /// is entered and, for streamable GQL reads, between result rows.
+    /// Anything else (mutations, other languages, parameterized queries,
+    /// reads that sort or aggregate) runs to completion once started.
+    ///
+    /// Transport crates run this inside [`run_with_timeout`] so that timeouts
+    /// and dropped connections reach the blocking task.
+    pub fn dispatch_cancellable(
+        session: &grafeo_engine::Session,
+        statement: &str,
+        language: Option<&str>,
+        params: Option<&HashMap<String, grafeo_common::Value>>,
+        cancel: &CancelToken,
     ) -> Result<QueryResult, ServiceError> {
         let lang = determine_language(language);
-        dispatch_query(session, statement, lang, params)
+        dispatch_query(session, statement, lang, params, cancel)
     }
 
     /// Provides direct access to a session Arc for transport-specific use
@@ -293,12 +343,23 @@ impl QueryService {
 // ---------------------------------------------------------------------------
 
 /// Dispatch a query to the appropriate engine method based on language.
+///
+/// `cancel` is checked before the engine is entered; parameterless GQL reads
+/// additionally check it between result rows (see [`execute_gql_streaming`]).
 fn dispatch_query(
     session: &grafeo_engine::Session,
     statement: &str,
     language: Language,
     params: Option<&HashMap<String, grafeo_common::Value>>,
+    cancel: &CancelToken,
 ) -> Result<QueryResult, ServiceError> {
+    cancel.check()?;
+
+    #[cfg(all(feature = "gql", feature = "lpg"))]
+    if matches!(language, Language::Gql) && params.is_none() {
+        return execute_gql_streaming(session, statement, cancel);
+    }
+
     let result = match (language, params) {
         // GQL (default)
         (Language::Gql, Some(p)) => session.execute_with_params(statement, p.clone()),
@@ -306,7 +367,11 @@ fn dispatch_query(
 
         // Cypher
         #[cfg(feature = "cypher")]
-        (Language::Cypher, _) => session.execute_cypher(statement),
+        (Language::Cypher, Some(p)) => {
+            session.execute_language(statement, "cypher", Some(p.clone()))
+        }
+        #[cfg(feature = "cypher")]
+        (Language::Cypher, None) => session.execute_cypher(statement),
         #[cfg(not(feature = "cypher"))]
         (Language::Cypher, _) => {
             return Err(ServiceError::BadRequest(
@@ -338,9 +403,14 @@ fn dispatch_query(
             ));
         }
 
-        // SPARQL
+        // SPARQL: no placeholders in the grammar, so parameters are bound
+        // as a VALUES block (see `sparql_params`).
         #[cfg(feature = "sparql")]
-        (Language::Sparql, _) => session.execute_sparql(statement),
+        (Language::Sparql, Some(p)) => {
+            session.execute_sparql(&crate::sparql_params::bind_params(statement, p)?)
+        }
+        #[cfg(feature = "sparql")]
+        (Language::Sparql, None) => session.execute_sparql(statement),
         #[cfg(not(feature = "sparql"))]
         (Language::Sparql, _) => {
             return Err(ServiceError::BadRequest(
@@ -361,19 +431,83 @@ fn dispatch_query(
         }
     };
 
+    result.map_err(map_engine_error)
+}
+
+/// Runs a parameterless GQL statement through the engine's pull-based
+/// stream, checking `cancel` before every row so a runaway traversal stops
+/// at the next chunk boundary instead of running to completion.
+///
+/// Only plain reads are streamed: statements that parse as mutations, DDL,
+/// session commands or EXPLAIN/PROFILE go straight to `Session::execute`,
+/// as do reads the engine rejects for needing a push-based pipeline
+/// (ORDER BY, aggregation, DISTINCT). Other streaming errors are returned
+/// as is. Statements that are not streamed are only cancellable before
+/// they start.
+#[cfg(all(feature = "gql", feature = "lpg"))]
+fn execute_gql_streaming(
+    session: &grafeo_engine::Session,
+    statement: &str,
+    cancel: &CancelToken,
+) -> Result<QueryResult, ServiceError> {
+    if !is_streamable_read(statement) {
+        return session.execute(statement).map_err(map_engine_error);
+    }
+
+    let start = std::time::Instant::now();
+    let stream = match session.execute_streaming(statement) {
+        Ok(stream) => stream,
+        Err(e) if e.to_string().contains(NOT_STREAMABLE) => {
+            return session.execute(statement).map_err(map_engine_error);
+        }
+        Err(e) => return Err(map_engine_error(e)),
+    };
+
+    let mut result = QueryResult::new(stream.columns().to_vec());
+    for row in stream.into_row_iter() {
+        cancel.check()?;
+        result.push_row(row.map_err(map_engine_error)?);
+    }
+
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let rows = result.rows().len() as u64;
+    Ok(result.with_metrics(elapsed_ms, rows))
+}
+
+/// Part of the engine's message for queries `execute_streaming` cannot run,
+/// e.g. reads that need a push-based pipeline. The engine has no distinct
+/// error kind for them (see [`map_engine_error`]).
+#[cfg(all(feature = "gql", feature = "lpg"))]
+const NOT_STREAMABLE: &str = "cannot be streamed";
+
+/// Whether `statement` parses as a plain GQL read, the only kind of
+/// statement `execute_streaming` accepts. Unparseable statements are left
+/// to `Session::execute` to report.
+#[cfg(all(feature = "gql", feature = "lpg"))]
+fn is_streamable_read(statement: &str) -> bool {
+    use grafeo_engine::query::translators::gql::{GqlTranslationResult, translate_full};
+
+    match translate_full(statement) {
+        Ok(GqlTranslationResult::Plan(plan)) => {
+            !plan.explain && !plan.profile && !plan.root.has_mutations()
+        }
+        _ => false,
+    }
+}
+
+/// Maps an engine error onto the service error space.
+fn map_engine_error(e: grafeo_common::utils::error::Error) -> ServiceError {
     // The engine wraps PermissionDenied as Error::Query(Semantic, ...) with no
     // distinct error kind, so string matching is the only way to distinguish
     // permission errors from other semantic errors (type mismatches, unknown
     // identifiers, etc.). Track: GrafeoDB/grafeo#TBD for a dedicated
     // QueryErrorKind::PermissionDenied variant.
-    result.map_err(|e| {
-        let msg = e.to_string();
-        if msg.contains("permission denied") {
-            ServiceError::Forbidden(msg)
-        } else {
-            ServiceError::BadRequest(msg)
-        }
-    })
+    let msg = e.to_string();
+    if msg.contains("permission denied") {
+        ServiceError::Forbidden(msg)
+    } else {
+        ServiceError::BadRequest(msg)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -383,12 +517,25 @@ fn dispatch_query(
 /// Run a blocking operation with optional timeout.
 ///
 /// Uses `tokio::task::spawn_blocking` to avoid blocking the async runtime.
-pub async fn run_with_timeout<F, T>(timeout: Option<Duration>, task: F) -> Result<T, ServiceError>
+/// The task receives `cancel` and should pass it to the dispatch functions.
+/// The token is tripped when the timeout fires and when the returned future
+/// is dropped before completion (e.g. the client disconnected), so the
+/// blocking thread is released at the next cancellation check.
+pub async fn run_with_timeout<F, T>(
+    timeout: Option<Duration>,
+    cancel: CancelToken,
+    task: F,
+) -> Result<T, ServiceError>
 where
-    F: FnOnce() -> Result<T, ServiceError> + Send + 'static,
+    F: FnOnce(&CancelToken) -> Result<T, ServiceError> + Send + 'static,
     T: Send + 'static,
 {
-    let handle = tokio::task::spawn_blocking(task);
+    let _guard = cancel.guard();
+    let token = cancel.clone();
+    let handle = tokio::task::spawn_blocking(move || {
+        token.check()?;
+        task(&token)
+    });
 
     if let Some(dur) = timeout
         && !dur.is_zero()
@@ -398,6 +545,7 @@ where
             Ok(Err(e)) => Err(ServiceError::Internal(e.to_string())),
             Err(_) => {
                 tracing::warn!("query timed out after {dur:?}");
+                cancel.cancel();
                 Err(ServiceError::Timeout)
             }
         }
@@ -434,6 +582,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -453,6 +602,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap_err();
@@ -472,6 +622,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap_err();
@@ -493,6 +644,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -514,6 +666,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -533,10 +686,11 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
-        let rendered = s.metrics().render(0, 0, 0, 0, 0, None);
+        let rendered = s.metrics().render(0, 0, 0, 0, 0, &[], &[], None);
         assert!(rendered.contains("grafeo_queries_total{language=\"gql\"} 1"));
     }
 
@@ -553,9 +707,10 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await;
-        let rendered = s.metrics().render(0, 0, 0, 0, 0, None);
+        let rendered = s.metrics().render(0, 0, 0, 0, 0, &[], &[], None);
         assert!(rendered.contains("grafeo_query_errors_total{language=\"gql\"} 1"));
     }
 
@@ -573,6 +728,7 @@ mod tests {
             Some(Duration::from_secs(10)),
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -619,6 +775,7 @@ mod tests {
             None,
             None,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -652,6 +809,7 @@ mod tests {
             None,
             None,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -680,6 +838,7 @@ mod tests {
             None,
             None,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap_err();
@@ -719,6 +878,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -740,6 +900,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -773,6 +934,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -803,6 +965,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap_err();
@@ -825,6 +988,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -849,6 +1013,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await;
 
@@ -863,6 +1028,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap();
@@ -884,6 +1050,7 @@ mod tests {
             None,
             false,
             None,
+            CancelToken::new(),
         )
         .await
         .unwrap_err();
@@ -953,6 +1120,7 @@ mod tests {
             None,
             false,
             Some(identity),
+            CancelToken::new(),
         )
         .await
         .unwrap_err();
@@ -968,20 +1136,24 @@ mod tests {
 
     #[tokio::test]
     async fn run_with_timeout_no_timeout() {
-        let result = run_with_timeout(None, || Ok::<_, ServiceError>(42)).await;
+        let result =
+            run_with_timeout(None, CancelToken::new(), |_| Ok::<_, ServiceError>(42)).await;
         assert_eq!(result.unwrap(), 42);
     }
 
     #[tokio::test]
     async fn run_with_timeout_zero_duration() {
         // Zero duration should behave like no timeout.
-        let result = run_with_timeout(Some(Duration::ZERO), || Ok::<_, ServiceError>(42)).await;
+        let result = run_with_timeout(Some(Duration::ZERO), CancelToken::new(), |_| {
+            Ok::<_, ServiceError>(42)
+        })
+        .await;
         assert_eq!(result.unwrap(), 42);
     }
 
     #[tokio::test]
     async fn run_with_timeout_generous_deadline() {
-        let result = run_with_timeout(Some(Duration::from_secs(10)), || {
+        let result = run_with_timeout(Some(Duration::from_secs(10)), CancelToken::new(), |_| {
             Ok::<_, ServiceError>("fast")
         })
         .await;
@@ -990,10 +1162,244 @@ mod tests {
 
     #[tokio::test]
     async fn run_with_timeout_propagates_error() {
-        let result = run_with_timeout(None, || {
+        let result = run_with_timeout(None, CancelToken::new(), |_| {
             Err::<(), _>(ServiceError::BadRequest("boom".to_string()))
         })
         .await;
         assert!(matches!(result.unwrap_err(), ServiceError::BadRequest(_)));
     }
+
+    /// Spins until the token is tripped, then reports that it was observed.
+    fn wait_for_cancel(
+        cancel: &CancelToken,
+        observed: &std::sync::mpsc::Sender<()>,
+    ) -> Result<(), ServiceError> {
+        while !cancel.is_cancelled() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let _ = observed.send(());
+        cancel.check()
+    }
+
+    #[tokio::test]
+    async fn run_with_timeout_cancels_task_on_timeout() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let err = run_with_timeout(
+            Some(Duration::from_millis(50)),
+            CancelToken::new(),
+            move |cancel| wait_for_cancel(cancel, &tx),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ServiceError::Timeout));
+        // The blocking task must see the cancellation and finish.
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("blocking task was not cancelled");
+    }
+
+    #[tokio::test]
+    async fn run_with_timeout_cancels_task_when_dropped() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let fut = run_with_timeout(None, CancelToken::new(), move |cancel| {
+            wait_for_cancel(cancel, &tx)
+        });
+        // Simulate a client disconnect: the awaiting future goes away.
+        let _ = tokio::time::timeout(Duration::from_millis(50), fut).await;
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("blocking task was not cancelled");
+    }
+
+    #[tokio::test]
+    async fn run_with_timeout_skips_already_cancelled_task() {
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let err = run_with_timeout(None, cancel, |_| Ok::<_, ServiceError>(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Cancelled));
+    }
+
+    // -----------------------------------------------------------------------
+    // dispatch_cancellable
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn dispatch_cancellable_rejects_cancelled_token() {
+        let s = state();
+        let entry = s.databases().get("default").unwrap();
+        let session = entry.db().session();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let err =
+            QueryService::dispatch_cancellable(&session, "MATCH (n) RETURN n", None, None, &cancel)
+                .unwrap_err();
+        assert!(matches!(err, ServiceError::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn dispatch_streams_gql_reads() {
+        let s = state();
+        let entry = s.databases().get("default").unwrap();
+        let session = entry.db().session();
+        for i in 0..3 {
+            QueryService::dispatch(&session, &format!("INSERT (:Item {{n: {i}}})"), None, None)
+                .unwrap();
+        }
+        let qr =
+            QueryService::dispatch(&session, "MATCH (i:Item) RETURN i.n AS n", None, None).unwrap();
+        assert_eq!(qr.columns, vec!["n"]);
+        assert_eq!(qr.rows().len(), 3);
+        assert!(qr.execution_time_ms.is_some());
+    }
+
+    #[test]
+    fn only_plain_gql_reads_are_streamable() {
+        assert!(is_streamable_read("MATCH (n) RETURN n"));
+        assert!(is_streamable_read("MATCH (n) RETURN n ORDER BY n.x"));
+        assert!(!is_streamable_read("INSERT (:Item)"));
+        assert!(!is_streamable_read("MATCH (n) SET n.x = 1 RETURN n"));
+        assert!(!is_streamable_read("EXPLAIN MATCH (n) RETURN n"));
+        assert!(!is_streamable_read("START TRANSACTION"));
+        assert!(!is_streamable_read("MATCH (n"));
+    }
+
+    #[tokio::test]
+    async fn dispatch_runs_unstreamable_reads() {
+        let s = state();
+        let entry = s.databases().get("default").unwrap();
+        let session = entry.db().session();
+        for i in [2, 0, 1] {
+            QueryService::dispatch(&session, &format!("INSERT (:Item {{n: {i}}})"), None, None)
+                .unwrap();
+        }
+        let qr = QueryService::dispatch(
+            &session,
+            "MATCH (i:Item) RETURN i.n AS n ORDER BY n",
+            None,
+            None,
+        )
+        .unwrap();
+        let rows: Vec<_> = qr.rows().iter().map(|row| row[0].clone()).collect();
+        assert_eq!(rows, [0i64, 1, 2].map(grafeo_common::Value::from));
+    }
+
+    #[tokio::test]
+    async fn cancelled_dispatch_does_not_write() {
+        let s = state();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let entry = s.databases().get("default").unwrap();
+        let err = run_with_timeout(None, CancelToken::new(), move |_| {
+            let db = entry.db();
+            let session = db.session();
+            dispatch_query(&session, "INSERT (:Never)", Language::Gql, None, &cancel)
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ServiceError::Cancelled));
+
+        let qr = QueryService::execute(
+            s.databases(),
+            s.metrics(),
+            "default",
+            "MATCH (n:Never) RETURN n",
+            None,
+            None,
+            None,
+            false,
+            None,
+            CancelToken::new(),
+        )
+        .await
+        .unwrap();
+        assert!(qr.rows().is_empty());
+    }
+
+    #[tokio::test]
+    async fn killed_query_returns_cancelled() {
+        let s = state();
+        let running = s.running_queries().register(
+            "default",
+            None,
+            "MATCH (n) RETURN n",
+            None,
+            crate::types::QueryTransport::Http,
+        );
+        assert!(s.running_queries().kill(running.id()));
+
+        let err = QueryService::execute(
+            s.databases(),
+            s.metrics(),
+            "default",
+            "MATCH (n) RETURN n",
+            None,
+            None,
+            None,
+            false,
+            None,
+            running.cancel_token(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, ServiceError::Cancelled));
+    }
+
+    #[cfg(feature = "cypher")]
+    #[test]
+    fn dispatch_binds_cypher_params() {
+        let s = state();
+        let entry = s.databases().get("default").unwrap();
+        let session = entry.db().session();
+        let params = HashMap::from([("name".to_string(), grafeo_common::Value::from("Ada"))]);
+        QueryService::dispatch(
+            &session,
+            "CREATE (:Person {name: $name})",
+            Some("cypher"),
+            Some(&params),
+        )
+        .unwrap();
+        let qr = QueryService::dispatch(
+            &session,
+            "MATCH (p:Person) WHERE p.name = $name RETURN p.name",
+            Some("cypher"),
+            Some(&params),
+        )
+        .unwrap();
+        assert_eq!(qr.rows().len(), 1);
+    }
+
+    #[cfg(feature = "sparql")]
+    #[test]
+    fn dispatch_binds_sparql_params() {
+        let s = state();
+        let entry = s.databases().get("default").unwrap();
+        let session = entry.db().session();
+        QueryService::dispatch(
+            &session,
+            "INSERT DATA { <http://ex.org/a> <http://ex.org/name> \"Ada\" . \
+             <http://ex.org/b> <http://ex.org/name> \"Bob\" }",
+            Some("sparql"),
+            None,
+        )
+        .unwrap();
+
+        let params = HashMap::from([("name".to_string(), grafeo_common::Value::from("Ada"))]);
+        let qr = QueryService::dispatch(
+            &session,
+            "SELECT ?s WHERE { ?s <http://ex.org/name> $name }",
+            Some("sparql"),
+            Some(&params),
+        )
+        .unwrap();
+        assert_eq!(qr.rows().len(), 1);
+
+        let err = QueryService::dispatch(
+            &session,
+            "INSERT DATA { <http://ex.org/c> <http://ex.org/name> \"Cy\" }",
+            Some("sparql"),
+            Some(&params),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest(_)));
+    }
 }
@@ -1,8 +1,8 @@
 //! Primary-replica replication for Grafeo databases.
 //!
-//! Replication is built on top of the CDC changefeed (`sync` feature).
-//! A replica continuously polls the primary's `GET /db/{name}/changes`
-//! endpoint and applies the returned events with `SyncService::apply()`.
+//! Replication is built on top of the CDC changefeed (`sync` and
+//! `push-changefeed` features). A replica follows each database on the
+//! primary and applies the received events with `SyncService::apply()`.
 //!
 //! # Modes
 //!
@@ -10,26 +10,82 @@
 //! |------|----------|
 //! | `Standalone` | Default. No replication. Reads and writes allowed. |
 //! | `Primary` | Announces itself as primary. Reads and writes allowed. |
-//! | `Replica` | Polls primary continuously. **Writes are rejected (503).** |
+//! | `Replica` | Follows the primary continuously. **Writes are rejected (503)**, or forwarded to the primary with `--replica-forward-writes`. |
 //!
 //! # Wire protocol
 //!
-//! The replica reuses the existing `GET /db/{name}/changes?since={epoch}&limit=500`
-//! endpoint — no new protocol required. `SyncService::apply()` is used to
-//! replay the returned `ChangeEventDto` entries.
+//! The replica catches up with `GET /db/{name}/changes?since={epoch}&limit=500`
+//! until it has seen everything, then holds a long-lived
+//! `GET /db/{name}/replication/stream?since={epoch}` subscription. The stream
+//! is Server-Sent Events: each `batch` event is a
+//! [`ChangeBatch`](crate::changefeed::ChangeBatch) pushed as soon as the
+//! primary's `ChangeHub` sees it. A `gap` event (the replica fell too far
+//! behind the broadcast channel) or a dropped connection sends the replica
+//! back to polling until it has caught up again.
+//!
+//! # Bootstrap
+//!
+//! A replica database with nothing replicated and no local data restores
+//! `GET /db/{name}/replication/snapshot` (a `.grafeo` file, its epoch in the
+//! `x-grafeo-snapshot-epoch` header) and follows from that epoch instead of
+//! replaying the whole change history. The primary retakes the snapshot if
+//! a commit races it. Primaries without the endpoint are replayed in full.
+//!
+//! Against a primary with authentication enabled, the replica presents
+//! `--replica-token` as a bearer token on every request.
+//!
+//! # Catalog
+//!
+//! `GET /admin/replication/catalog` lists the primary's databases as
+//! [`CatalogEntry`]s. On every cycle the replica creates the databases it is
+//! missing with matching settings and drops the ones the primary no longer
+//! has.
+//!
+//! # Failover and fencing
+//!
+//! Roles can change at runtime. `POST /admin/replication/promote` turns a
+//! replica into a primary and `POST /admin/replication/follow` points any
+//! node at a new primary (demoting a primary). Each promotion starts a new
+//! replication *term*, one higher than any term the node has seen. Terms
+//! travel in the `x-grafeo-replication-term` header: primaries send the
+//! term they lead, replicas the highest term they know.
+//!
+//! - A replica refuses changes from a primary whose term is lower than its
+//!   own, so it never follows a superseded primary back into the past.
+//! - A primary that sees a higher term from a peer is *fenced*: it keeps
+//!   serving reads but rejects writes until it is pointed at the new
+//!   primary with `follow`.
+//!
+//! A node that follows a new primary discards its replication progress
+//! and restores every database from the new primary's snapshot, since
+//! epochs are not comparable across primaries.
 //!
 //! # Per-database epoch tracking
 //!
 //! `ReplicationState` holds a `DashMap<db_name, AtomicU64>` tracking the
 //! last successfully applied epoch per database. The background task
 //! (in `grafeo-http`) updates these after each successful batch.
+//!
+//! # Lag
+//!
+//! Alongside the epochs, the replica records the primary's latest
+//! `server_epoch`, when it last applied a batch, and when it was last known
+//! to hold everything the primary had. `lag_seconds` is the time since that
+//! moment, or zero while the replica is following the live stream with
+//! nothing outstanding. The lag is exported as Prometheus gauges and, with
+//! `--replica-max-lag`, fails the readiness probe.
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use dashmap::DashMap;
-use serde::Serialize;
+use dashmap::{DashMap, DashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::database::DatabaseManager;
+use crate::metrics::ReplicaLag;
+use crate::types::{CreateDatabaseRequest, DatabaseOptions, DatabaseType, StorageMode};
 
 // ---------------------------------------------------------------------------
 // Public types
@@ -74,6 +130,15 @@ impl ReplicationMode {
 pub struct DbReplicationStatus {
     /// The last CDC epoch successfully applied on this replica.
     pub last_applied_epoch: u64,
+    /// The latest epoch the primary has reported for this database.
+    pub primary_epoch: u64,
+    /// Epochs the primary is ahead of this replica.
+    pub epoch_lag: u64,
+    /// When a batch was last applied (Unix seconds).
+    pub last_applied_at: Option<u64>,
+    /// Seconds since the replica last held everything the primary had.
+    /// `None` until the database has been followed.
+    pub lag_seconds: Option<u64>,
     /// Last error encountered, if any.
     pub last_error: Option<String>,
 }
@@ -85,21 +150,156 @@ pub struct ReplicationStatus {
     pub mode: String,
     /// Primary URL (replica mode only).
     pub primary_url: Option<String>,
+    /// Highest replication term this node knows of.
+    pub term: u64,
+    /// Whether this primary has been superseded and rejects writes.
+    pub fenced: bool,
     /// Per-database replication progress.
     pub databases: HashMap<String, DbReplicationStatus>,
 }
 
+/// A database on the primary, with the settings a replica needs to create a
+/// matching one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    /// Database name.
+    pub name: String,
+    /// Database type.
+    pub database_type: DatabaseType,
+    /// Storage mode on the primary.
+    pub storage_mode: StorageMode,
+    /// Resource and tuning options the database was created with.
+    pub options: DatabaseOptions,
+}
+
+impl CatalogEntry {
+    /// Builds the request that creates this database on a replica.
+    ///
+    /// A replica without a data directory creates persistent databases in
+    /// memory. Schema-typed databases are created as their underlying graph
+    /// model, since the schema file is not kept after creation; the schema
+    /// arrives with the bootstrap snapshot.
+    #[must_use]
+    pub fn create_request(&self, persistent_available: bool) -> CreateDatabaseRequest {
+        let storage_mode = if persistent_available {
+            self.storage_mode
+        } else {
+            StorageMode::InMemory
+        };
+        let database_type = match self.database_type {
+            DatabaseType::OwlSchema | DatabaseType::RdfsSchema => DatabaseType::Rdf,
+            DatabaseType::JsonSchema => DatabaseType::Lpg,
+            other => other,
+        };
+        CreateDatabaseRequest {
+            name: self.name.clone(),
+            database_type,
+            storage_mode,
+            options: self.options.clone(),
+            schema_file: None,
+            schema_filename: None,
+        }
+    }
+}
+
+/// Lists the databases on this server as catalog entries, sorted by name.
+#[must_use]
+pub fn catalog(databases: &DatabaseManager) -> Vec<CatalogEntry> {
+    databases
+        .list()
+        .into_iter()
+        .filter_map(|summary| {
+            let entry = databases.get(&summary.name)?;
+            let db = entry.db();
+            let persistent = db.path().is_some();
+            Some(CatalogEntry {
+                database_type: DatabaseType::from_name(&entry.metadata.database_type)
+                    .unwrap_or_default(),
+                storage_mode: if persistent {
+                    StorageMode::Persistent
+                } else {
+                    StorageMode::InMemory
+                },
+                options: DatabaseOptions {
+                    memory_limit_bytes: db.memory_limit(),
+                    wal_enabled: persistent.then(|| db.info().wal_enabled),
+                    wal_durability: None,
+                    backward_edges: Some(entry.metadata.backward_edges),
+                    threads: Some(entry.metadata.threads),
+                    // Spill paths are local to the primary's filesystem.
+                    spill_path: None,
+                },
+                name: summary.name,
+            })
+        })
+        .collect()
+}
+
+/// Per-database lag bookkeeping. Times are Unix milliseconds.
+#[derive(Debug, Clone, Copy)]
+struct Progress {
+    /// Latest `server_epoch` the primary reported.
+    primary_epoch: u64,
+    /// When a batch was last applied.
+    last_applied_at: Option<u64>,
+    /// When the replica last held everything the primary had.
+    caught_up_at: Option<u64>,
+    /// When the database was first followed; stands in for `caught_up_at`
+    /// until the first catch-up completes.
+    started_at: u64,
+    /// Whether the live stream is open.
+    streaming: bool,
+}
+
+impl Progress {
+    fn new(now: u64) -> Self {
+        Self {
+            primary_epoch: 0,
+            last_applied_at: None,
+            caught_up_at: None,
+            started_at: now,
+            streaming: false,
+        }
+    }
+
+    fn lag_millis(&self, applied: u64, now: u64) -> u64 {
+        if self.streaming && applied >= self.primary_epoch {
+            return 0;
+        }
+        now.saturating_sub(self.caught_up_at.unwrap_or(self.started_at))
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
 /// Shared replication state updated by the background poll task.
 ///
-/// Holds per-database last-applied epoch counters and the most recent error.
-/// Optionally persists epoch state to disk so replicas don't re-fetch from
-/// epoch 0 after a restart.
+/// Holds per-database last-applied epoch counters, lag bookkeeping and the
+/// most recent error. Optionally persists epoch state to disk so replicas
+/// don't re-fetch from epoch 0 after a restart.
 #[derive(Debug)]
 pub struct ReplicationState {
     /// Per-database last-applied epoch.
     pub epochs: DashMap<String, Arc<AtomicU64>>,
     /// Per-database last error.
     pub errors: DashMap<String, String>,
+    /// Per-database lag bookkeeping.
+    progress: DashMap<String, Progress>,
+    /// Woken whenever an epoch advances, for `wait_for_epoch`.
+    applied: tokio::sync::Notify,
+    /// Highest replication term seen.
+    term: AtomicU64,
+    /// Term in which this node was last promoted to primary.
+    led_term: AtomicU64,
+    /// Databases to restore from the primary's snapshot even though they
+    /// hold local data, after following a new primary.
+    resync: DashSet<String>,
+    /// Whether the replication supervisor has been started.
+    supervisor_started: AtomicBool,
     /// Data directory for epoch persistence. None = in-memory only.
     data_dir: Option<std::path::PathBuf>,
 }
@@ -109,6 +309,12 @@ impl Default for ReplicationState {
         Self {
             epochs: DashMap::new(),
             errors: DashMap::new(),
+            progress: DashMap::new(),
+            applied: tokio::sync::Notify::new(),
+            term: AtomicU64::new(0),
+            led_term: AtomicU64::new(0),
+            resync: DashSet::new(),
+            supervisor_started: AtomicBool::new(false),
             data_dir: None,
         }
     }
@@ -124,18 +330,113 @@ impl ReplicationState {
     /// Creates a new state with disk persistence.
     ///
     /// Loads previously saved epoch state from `{data_dir}/.replica-epochs`
-    /// if the file exists, so replicas resume from where they left off.
+    /// if the file exists, so replicas resume from where they left off, and
+    /// the replication term from `{data_dir}/.replication-term`, so a
+    /// superseded primary stays fenced across restarts.
     #[must_use]
     pub fn with_persistence(data_dir: std::path::PathBuf) -> Self {
         let state = Self {
-            epochs: DashMap::new(),
-            errors: DashMap::new(),
             data_dir: Some(data_dir),
+            ..Self::default()
         };
         state.load_epochs();
+        state.load_term();
         state
     }
 
+    // --- Terms ---
+
+    /// Returns the highest replication term this node has seen.
+    pub fn term(&self) -> u64 {
+        self.term.load(Ordering::Relaxed)
+    }
+
+    /// Returns the term this node leads while it is primary.
+    pub fn led_term(&self) -> u64 {
+        self.led_term.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if a newer term than the one this node leads has
+    /// been seen, i.e. another node was promoted since.
+    pub fn is_superseded(&self) -> bool {
+        self.term() > self.led_term()
+    }
+
+    /// Records a term reported by a peer. Returns `true` if it is newer
+    /// than any term seen before.
+    pub fn observe_term(&self, term: u64) -> bool {
+        let newer = self.term.fetch_max(term, Ordering::Relaxed) < term;
+        if newer {
+            self.save_term();
+        }
+        newer
+    }
+
+    /// Starts a new term led by this node and returns it.
+    pub fn begin_term(&self) -> u64 {
+        let term = self.term.fetch_add(1, Ordering::Relaxed) + 1;
+        self.led_term.store(term, Ordering::Relaxed);
+        self.save_term();
+        term
+    }
+
+    fn save_term(&self) {
+        let Some(ref dir) = self.data_dir else {
+            return;
+        };
+        let json = serde_json::json!({
+            "term": self.term(),
+            "led_term": self.led_term(),
+        });
+        let _ = std::fs::write(dir.join(".replication-term"), json.to_string());
+    }
+
+    fn load_term(&self) {
+        let Some(ref dir) = self.data_dir else {
+            return;
+        };
+        if let Ok(data) = std::fs::read_to_string(dir.join(".replication-term"))
+            && let Ok(json) = serde_json::from_str::<serde_json::Value>(&data)
+        {
+            let field = |name: &str| json.get(name).and_then(serde_json::Value::as_u64);
+            self.term
+                .store(field("term").unwrap_or(0), Ordering::Relaxed);
+            self.led_term
+                .store(field("led_term").unwrap_or(0), Ordering::Relaxed);
+        }
+    }
+
+    /// Discards all replication progress, for a node that starts following
+    /// a new primary. `resync` names the local databases that must be
+    /// restored from the primary's snapshot before following it.
+    pub fn reset(&self, resync: impl IntoIterator<Item = String>) {
+        self.epochs.clear();
+        self.errors.clear();
+        self.progress.clear();
+        self.resync.clear();
+        for db in resync {
+            self.resync.insert(db);
+        }
+        self.save_epochs();
+    }
+
+    /// Returns `true` if `db` must be restored from the primary's snapshot
+    /// regardless of its local data.
+    pub fn needs_resync(&self, db: &str) -> bool {
+        self.resync.contains(db)
+    }
+
+    /// Records that `db` was restored from the primary's snapshot.
+    pub fn resynced(&self, db: &str) {
+        self.resync.remove(db);
+    }
+
+    /// Marks the replication supervisor as started. Returns `false` if it
+    /// already was.
+    pub fn claim_supervisor(&self) -> bool {
+        !self.supervisor_started.swap(true, Ordering::AcqRel)
+    }
+
     /// Returns the last applied epoch for `db`, creating an entry if absent.
     pub fn last_epoch(&self, db: &str) -> u64 {
         self.epochs
@@ -148,14 +449,113 @@ impl ReplicationState {
     ///
     /// If persistence is enabled, writes the updated state to disk.
     pub fn advance_epoch(&self, db: &str, epoch: u64) {
-        let entry = self
-            .epochs
-            .entry(db.to_string())
-            .or_insert_with(|| Arc::new(AtomicU64::new(0)));
-        entry.fetch_max(epoch, Ordering::Relaxed);
+        let counter = Arc::clone(
+            &self
+                .epochs
+                .entry(db.to_string())
+                .or_insert_with(|| Arc::new(AtomicU64::new(0))),
+        );
+        // The entry guard is dropped above: `save_epochs` iterates the map.
+        let previous = counter.fetch_max(epoch, Ordering::Relaxed);
+        if epoch > previous {
+            let now = unix_millis();
+            let mut progress = self.progress_mut(db, now);
+            progress.last_applied_at = Some(now);
+            progress.primary_epoch = progress.primary_epoch.max(epoch);
+            drop(progress);
+            self.applied.notify_waiters();
+        }
         self.save_epochs();
     }
 
+    /// Waits until `db` has applied `epoch`, for at most `timeout`.
+    /// Returns `false` if the epoch was not reached in time.
+    pub async fn wait_for_epoch(&self, db: &str, epoch: u64, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Register before checking so an advance in between is not missed.
+            let notified = self.applied.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self
+                .epochs
+                .get(db)
+                .is_some_and(|e| e.load(Ordering::Relaxed) >= epoch)
+            {
+                return true;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                return false;
+            }
+        }
+    }
+
+    fn progress_mut(
+        &self,
+        db: &str,
+        now: u64,
+    ) -> dashmap::mapref::one::RefMut<'_, String, Progress> {
+        self.progress
+            .entry(db.to_string())
+            .or_insert_with(|| Progress::new(now))
+    }
+
+    /// Starts lag tracking for `db`; lag is counted from now until the
+    /// first catch-up completes.
+    pub fn track(&self, db: &str) {
+        self.progress_mut(db, unix_millis());
+    }
+
+    /// Records the primary's current epoch for `db`, as reported with a
+    /// batch of changes.
+    pub fn observe_primary_epoch(&self, db: &str, epoch: u64) {
+        let mut progress = self.progress_mut(db, unix_millis());
+        progress.primary_epoch = progress.primary_epoch.max(epoch);
+    }
+
+    /// Records that `db` holds everything the primary had as of now.
+    pub fn mark_caught_up(&self, db: &str) {
+        let now = unix_millis();
+        self.progress_mut(db, now).caught_up_at = Some(now);
+    }
+
+    /// Records whether the live stream for `db` is open. While it is,
+    /// batches arrive as they commit, so the replica is current whenever it
+    /// has applied everything it has been sent.
+    pub fn set_streaming(&self, db: &str, streaming: bool) {
+        let now = unix_millis();
+        let mut progress = self.progress_mut(db, now);
+        if progress.streaming && !streaming {
+            // Current up to the moment the stream dropped.
+            let applied = self.epochs.get(db).map_or(0, |e| e.load(Ordering::Relaxed));
+            if applied >= progress.primary_epoch {
+                progress.caught_up_at = Some(now);
+            }
+        }
+        progress.streaming = streaming;
+    }
+
+    /// Returns the databases whose lag exceeds `max`, sorted by name.
+    #[must_use]
+    pub fn lagging(&self, max: Duration) -> Vec<String> {
+        let now = unix_millis();
+        let max = max.as_millis() as u64;
+        let mut names: Vec<String> = self
+            .progress
+            .iter()
+            .filter(|p| {
+                let applied = self
+                    .epochs
+                    .get(p.key())
+                    .map_or(0, |e| e.load(Ordering::Relaxed));
+                p.value().lag_millis(applied, now) > max
+            })
+            .map(|p| p.key().clone())
+            .collect();
+        names.sort();
+        names
+    }
+
     /// Persists current epoch state to disk.
     fn save_epochs(&self) {
         let Some(ref dir) = self.data_dir else {
@@ -187,6 +587,14 @@ impl ReplicationState {
         }
     }
 
+    /// Drops all state for `db`, after the database was deleted.
+    pub fn forget(&self, db: &str) {
+        self.epochs.remove(db);
+        self.errors.remove(db);
+        self.progress.remove(db);
+        self.save_epochs();
+    }
+
     /// Records an error for `db`.
     pub fn set_error(&self, db: &str, err: String) {
         self.errors.insert(db.to_string(), err);
@@ -200,15 +608,25 @@ impl ReplicationState {
     /// Returns the current status snapshot.
     #[must_use]
     pub fn status(&self, mode: &ReplicationMode) -> ReplicationStatus {
+        self.status_at(mode, unix_millis())
+    }
+
+    fn status_at(&self, mode: &ReplicationMode, now: u64) -> ReplicationStatus {
         let mut databases = HashMap::new();
         for entry in &self.epochs {
             let db = entry.key().clone();
             let last_applied_epoch = entry.value().load(Ordering::Relaxed);
             let last_error = self.errors.get(&db).map(|e| e.value().clone());
+            let progress = self.progress.get(&db).map(|p| *p.value());
+            let primary_epoch = progress.map_or(last_applied_epoch, |p| p.primary_epoch);
             databases.insert(
                 db,
                 DbReplicationStatus {
                     last_applied_epoch,
+                    primary_epoch,
+                    epoch_lag: primary_epoch.saturating_sub(last_applied_epoch),
+                    last_applied_at: progress.and_then(|p| p.last_applied_at).map(|ms| ms / 1000),
+                    lag_seconds: progress.map(|p| p.lag_millis(last_applied_epoch, now) / 1000),
                     last_error,
                 },
             );
@@ -220,9 +638,34 @@ impl ReplicationState {
                 ReplicationMode::Replica { .. } => "replica".to_string(),
             },
             primary_url: mode.primary_url().map(str::to_string),
+            term: self.term(),
+            fenced: mode.is_primary() && self.is_superseded(),
             databases,
         }
     }
+
+    /// Returns per-database lag for the metrics endpoint, sorted by name.
+    /// Empty unless this instance is following a primary.
+    #[must_use]
+    pub fn lag_metrics(&self, mode: &ReplicationMode) -> Vec<ReplicaLag> {
+        if !mode.is_replica() {
+            return Vec::new();
+        }
+        let mut lag: Vec<ReplicaLag> = self
+            .status(mode)
+            .databases
+            .into_iter()
+            .map(|(database, s)| ReplicaLag {
+                database,
+                applied_epoch: s.last_applied_epoch,
+                primary_epoch: s.primary_epoch,
+                lag_seconds: s.lag_seconds.unwrap_or(0),
+                last_applied_at: s.last_applied_at.unwrap_or(0),
+            })
+            .collect();
+        lag.sort_by(|a, b| a.database.cmp(&b.database));
+        lag
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -289,6 +732,199 @@ mod tests {
         assert!(status.databases["default"].last_error.is_none());
     }
 
+    #[test]
+    fn lag_counts_from_last_catch_up() {
+        let state = ReplicationState::new();
+        let mode = ReplicationMode::Replica {
+            primary_url: "http://primary:7474".to_string(),
+        };
+        state.advance_epoch("default", 10);
+        state.observe_primary_epoch("default", 14);
+        state.progress.get_mut("default").unwrap().caught_up_at = Some(1_000);
+
+        let status = state
+            .status_at(&mode, 6_500)
+            .databases
+            .remove("default")
+            .unwrap();
+        assert_eq!(status.primary_epoch, 14);
+        assert_eq!(status.epoch_lag, 4);
+        assert_eq!(status.lag_seconds, Some(5));
+        assert!(status.last_applied_at.is_some());
+    }
+
+    #[test]
+    fn streaming_replica_with_nothing_outstanding_has_no_lag() {
+        let state = ReplicationState::new();
+        let mode = ReplicationMode::Replica {
+            primary_url: "http://primary:7474".to_string(),
+        };
+        state.advance_epoch("default", 8);
+        state.observe_primary_epoch("default", 8);
+        state.set_streaming("default", true);
+        state.progress.get_mut("default").unwrap().caught_up_at = Some(0);
+        let status = &state.status_at(&mode, 60_000).databases["default"];
+        assert_eq!(status.lag_seconds, Some(0));
+        assert!(state.lagging(Duration::from_secs(1)).is_empty());
+
+        // A batch the replica has not applied yet counts as lag again.
+        state.observe_primary_epoch("default", 9);
+        let status = &state.status_at(&mode, 60_000).databases["default"];
+        assert_eq!(status.epoch_lag, 1);
+        assert_eq!(status.lag_seconds, Some(60));
+        assert_eq!(state.lagging(Duration::from_secs(1)), vec!["default"]);
+    }
+
+    #[test]
+    fn lag_metrics_only_on_replicas() {
+        let state = ReplicationState::new();
+        state.track("default");
+        state.advance_epoch("default", 3);
+        assert!(state.lag_metrics(&ReplicationMode::Primary).is_empty());
+        let lag = state.lag_metrics(&ReplicationMode::Replica {
+            primary_url: "http://primary:7474".to_string(),
+        });
+        assert_eq!(lag.len(), 1);
+        assert_eq!(lag[0].database, "default");
+        assert_eq!(lag[0].applied_epoch, 3);
+        assert_eq!(lag[0].primary_epoch, 3);
+    }
+
+    #[tokio::test]
+    async fn wait_for_epoch_wakes_on_advance() {
+        let state = Arc::new(ReplicationState::new());
+        state.advance_epoch("default", 3);
+        assert!(
+            state
+                .wait_for_epoch("default", 3, Duration::from_millis(10))
+                .await
+        );
+        assert!(
+            !state
+                .wait_for_epoch("default", 5, Duration::from_millis(10))
+                .await
+        );
+
+        let waiter = {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                state
+                    .wait_for_epoch("default", 5, Duration::from_secs(5))
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        state.advance_epoch("default", 5);
+        assert!(waiter.await.unwrap());
+    }
+
+    #[test]
+    fn promotion_starts_a_new_term_and_fences_the_old_primary() {
+        let old = ReplicationState::new();
+        let new = ReplicationState::new();
+        assert!(!old.is_superseded());
+
+        let term = new.begin_term();
+        assert_eq!(term, 1);
+        assert!(!new.is_superseded());
+
+        // The old primary learns of term 1 from a peer.
+        assert!(old.observe_term(term));
+        assert!(!old.observe_term(term));
+        assert!(old.is_superseded());
+        assert!(old.status(&ReplicationMode::Primary).fenced);
+        assert!(
+            !old.status(&ReplicationMode::Replica {
+                primary_url: "http://new:7474".to_string(),
+            })
+            .fenced
+        );
+    }
+
+    #[test]
+    fn term_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = ReplicationState::with_persistence(dir.path().to_path_buf());
+        state.begin_term();
+        state.observe_term(3);
+
+        let reopened = ReplicationState::with_persistence(dir.path().to_path_buf());
+        assert_eq!(reopened.term(), 3);
+        assert_eq!(reopened.led_term(), 1);
+        assert!(reopened.is_superseded());
+    }
+
+    #[test]
+    fn reset_discards_progress_and_marks_resync() {
+        let state = ReplicationState::new();
+        state.advance_epoch("default", 9);
+        state.set_error("default", "boom".to_string());
+        state.reset(["default".to_string()]);
+        assert_eq!(state.last_epoch("default"), 0);
+        assert!(state.needs_resync("default"));
+        state.resynced("default");
+        assert!(!state.needs_resync("default"));
+    }
+
+    #[test]
+    fn forget_drops_epoch_and_error() {
+        let state = ReplicationState::new();
+        state.advance_epoch("gone", 7);
+        state.set_error("gone", "boom".to_string());
+        state.forget("gone");
+        assert!(
+            !state
+                .status(&ReplicationMode::Primary)
+                .databases
+                .contains_key("gone")
+        );
+        assert_eq!(state.last_epoch("gone"), 0);
+    }
+
+    #[test]
+    fn catalog_lists_databases_with_settings() {
+        let mgr = DatabaseManager::new(None, false);
+        mgr.create(&CreateDatabaseRequest {
+            name: "social".to_string(),
+            database_type: DatabaseType::Rdf,
+            storage_mode: StorageMode::InMemory,
+            options: DatabaseOptions {
+                backward_edges: Some(false),
+                threads: Some(2),
+                ..DatabaseOptions::default()
+            },
+            schema_file: None,
+            schema_filename: None,
+        })
+        .unwrap();
+
+        let catalog = catalog(&mgr);
+        let names: Vec<&str> = catalog.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["default", "social"]);
+        let social = &catalog[1];
+        assert_eq!(social.database_type, DatabaseType::Rdf);
+        assert_eq!(social.storage_mode, StorageMode::InMemory);
+        assert_eq!(social.options.backward_edges, Some(false));
+        assert_eq!(social.options.threads, Some(2));
+    }
+
+    #[test]
+    fn create_request_adapts_to_replica() {
+        let entry = CatalogEntry {
+            name: "onto".to_string(),
+            database_type: DatabaseType::OwlSchema,
+            storage_mode: StorageMode::Persistent,
+            options: DatabaseOptions::default(),
+        };
+        let req = entry.create_request(false);
+        assert_eq!(req.database_type, DatabaseType::Rdf);
+        assert_eq!(req.storage_mode, StorageMode::InMemory);
+        assert_eq!(
+            entry.create_request(true).storage_mode,
+            StorageMode::Persistent
+        );
+    }
+
     #[test]
     fn status_mode_strings() {
         let state = ReplicationState::new();
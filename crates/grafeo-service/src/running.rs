@@ -0,0 +1,214 @@
+//! Registry of in-flight queries.
+//!
+//! Every transport registers a query here for the duration of its
+//! execution, so operators can list what is running and kill a query
+//! that is pinning a database. Killing trips the query's [`CancelToken`];
+//! the entry disappears once the query notices and its guard is dropped.
+//!
+//! HTTP exposes the registry as `GET /admin/queries` and
+//! `DELETE /admin/queries/{id}`, GWP as the `grafeo.admin.list_queries()`
+//! and `grafeo.admin.kill_query($id)` procedures. Both require admin.
+
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+use uuid::Uuid;
+
+use crate::cancel::CancelToken;
+use crate::types::{QueryTransport, RunningQueryInfo};
+
+/// A registered in-flight query.
+struct RunningQuery {
+    database: String,
+    language: String,
+    statement: String,
+    user: Option<String>,
+    transport: QueryTransport,
+    started: Instant,
+    started_at_ms: u64,
+    cancel: CancelToken,
+}
+
+/// Thread-safe server-wide registry of in-flight queries.
+pub struct RunningQueryRegistry {
+    queries: DashMap<String, RunningQuery>,
+}
+
+impl Default for RunningQueryRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunningQueryRegistry {
+    /// Creates a new empty registry.
+    pub fn new() -> Self {
+        Self {
+            queries: DashMap::new(),
+        }
+    }
+
+    /// Registers a query and returns a guard that unregisters it on drop.
+    ///
+    /// `language` defaults to "gql" when `None`, matching query dispatch.
+    /// Pass the guard's [`cancel_token`](RunningQueryGuard::cancel_token)
+    /// to the executor so that [`kill`](Self::kill) can stop the query.
+    pub fn register(
+        &self,
+        database: &str,
+        language: Option<&str>,
+        statement: &str,
+        user: Option<&str>,
+        transport: QueryTransport,
+    ) -> RunningQueryGuard<'_> {
+        let id = Uuid::new_v4().to_string();
+        let cancel = CancelToken::new();
+        let started_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        self.queries.insert(
+            id.clone(),
+            RunningQuery {
+                database: database.to_owned(),
+                language: language.unwrap_or("gql").to_owned(),
+                statement: statement.to_owned(),
+                user: user.map(str::to_owned),
+                transport,
+                started: Instant::now(),
+                started_at_ms,
+                cancel: cancel.clone(),
+            },
+        );
+        RunningQueryGuard {
+            registry: self,
+            id,
+            cancel,
+        }
+    }
+
+    /// Lists in-flight queries, oldest first.
+    pub fn list(&self) -> Vec<RunningQueryInfo> {
+        let mut queries: Vec<(Instant, RunningQueryInfo)> = self
+            .queries
+            .iter()
+            .map(|entry| {
+                let q = entry.value();
+                let info = RunningQueryInfo {
+                    id: entry.key().clone(),
+                    database: q.database.clone(),
+                    language: q.language.clone(),
+                    statement: q.statement.clone(),
+                    user: q.user.clone(),
+                    transport: q.transport,
+                    started_at_ms: q.started_at_ms,
+                    elapsed_ms: q.started.elapsed().as_millis() as u64,
+                    cancelling: q.cancel.is_cancelled(),
+                };
+                (q.started, info)
+            })
+            .collect();
+        queries.sort_by_key(|(started, _)| *started);
+        queries.into_iter().map(|(_, info)| info).collect()
+    }
+
+    /// Requests cancellation of a query. Returns `false` if no query with
+    /// that ID is running.
+    pub fn kill(&self, id: &str) -> bool {
+        match self.queries.get(id) {
+            Some(entry) => {
+                entry.value().cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of in-flight queries.
+    pub fn active_count(&self) -> usize {
+        self.queries.len()
+    }
+}
+
+/// Keeps a query registered while it runs. Unregisters on drop.
+pub struct RunningQueryGuard<'a> {
+    registry: &'a RunningQueryRegistry,
+    id: String,
+    cancel: CancelToken,
+}
+
+impl RunningQueryGuard<'_> {
+    /// The registered query ID.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The token [`RunningQueryRegistry::kill`] trips for this query.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+}
+
+impl Drop for RunningQueryGuard<'_> {
+    fn drop(&mut self) {
+        self.registry.queries.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_lists_until_guard_dropped() {
+        let reg = RunningQueryRegistry::new();
+        let guard = reg.register(
+            "default",
+            None,
+            "MATCH (n) RETURN n",
+            Some("alice"),
+            QueryTransport::Http,
+        );
+
+        let list = reg.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].id, guard.id());
+        assert_eq!(list[0].database, "default");
+        assert_eq!(list[0].language, "gql");
+        assert_eq!(list[0].statement, "MATCH (n) RETURN n");
+        assert_eq!(list[0].user.as_deref(), Some("alice"));
+        assert_eq!(list[0].transport, QueryTransport::Http);
+        assert!(!list[0].cancelling);
+
+        drop(guard);
+        assert!(reg.list().is_empty());
+        assert_eq!(reg.active_count(), 0);
+    }
+
+    #[test]
+    fn list_is_oldest_first() {
+        let reg = RunningQueryRegistry::new();
+        let first = reg.register("a", Some("cypher"), "1", None, QueryTransport::Bolt);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = reg.register("b", Some("gql"), "2", None, QueryTransport::Gwp);
+
+        let ids: Vec<String> = reg.list().into_iter().map(|q| q.id).collect();
+        assert_eq!(ids, vec![first.id().to_owned(), second.id().to_owned()]);
+    }
+
+    #[test]
+    fn kill_trips_cancel_token() {
+        let reg = RunningQueryRegistry::new();
+        let guard = reg.register("default", None, "RETURN 1", None, QueryTransport::Http);
+        let token = guard.cancel_token();
+
+        assert!(reg.kill(guard.id()));
+        assert!(token.is_cancelled());
+        assert!(reg.list()[0].cancelling);
+    }
+
+    #[test]
+    fn kill_unknown_returns_false() {
+        let reg = RunningQueryRegistry::new();
+        assert!(!reg.kill("no-such-id"));
+    }
+}
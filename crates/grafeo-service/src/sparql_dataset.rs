@@ -0,0 +1,190 @@
+//! RDF dataset selection for the SPARQL 1.1 Protocol.
+//!
+//! Protocol clients scope a query with repeatable `default-graph-uri` and
+//! `named-graph-uri` parameters. These are applied by rewriting them into
+//! the query as `FROM` / `FROM NAMED` clauses, which the engine already
+//! honours, placed where the grammar expects the dataset clause (just
+//! before the WHERE clause):
+//!
+//! ```text
+//! SELECT * WHERE { ?s ?p ?o }      default-graph-uri=http://ex.org/g
+//! SELECT * FROM <http://ex.org/g> WHERE { ?s ?p ?o }
+//! ```
+//!
+//! As required by the protocol, a request that specifies a dataset both in
+//! its parameters and in the query text is rejected.
+
+use std::fmt::Write;
+
+use crate::error::ServiceError;
+use crate::sparql_scan::{is_valid_iri, outline};
+
+/// RDF dataset given by SPARQL protocol parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SparqlDataset {
+    /// `default-graph-uri` values, merged into the default graph.
+    pub default_graphs: Vec<String>,
+    /// `named-graph-uri` values, available to `GRAPH` patterns.
+    pub named_graphs: Vec<String>,
+}
+
+impl SparqlDataset {
+    /// Collects the dataset from decoded protocol parameters, in order.
+    /// Parameters other than `default-graph-uri` and `named-graph-uri` are
+    /// ignored.
+    pub fn from_pairs<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut dataset = Self::default();
+        for (key, value) in pairs {
+            match key {
+                "default-graph-uri" => dataset.default_graphs.push(value.to_owned()),
+                "named-graph-uri" => dataset.named_graphs.push(value.to_owned()),
+                _ => {}
+            }
+        }
+        dataset
+    }
+
+    /// Returns `true` if no dataset parameters were given.
+    pub fn is_empty(&self) -> bool {
+        self.default_graphs.is_empty() && self.named_graphs.is_empty()
+    }
+
+    /// Applies the dataset to a SPARQL query as `FROM` / `FROM NAMED`.
+    ///
+    /// Returns the statement unchanged when the dataset is empty. Fails with
+    /// `BadRequest` when the query already has a `FROM` clause, when the
+    /// statement is an update, or when a graph URI is not a valid IRI.
+    pub fn apply(&self, statement: &str) -> Result<String, ServiceError> {
+        if self.is_empty() {
+            return Ok(statement.to_owned());
+        }
+
+        let outline = outline(statement);
+        if outline.is_update() {
+            return Err(ServiceError::BadRequest(
+                "default-graph-uri and named-graph-uri apply only to queries".to_string(),
+            ));
+        }
+        if outline.has_from {
+            return Err(ServiceError::BadRequest(
+                "dataset specified both by protocol parameters and by FROM / FROM NAMED \
+                 in the query"
+                    .to_string(),
+            ));
+        }
+
+        let mut clause = String::from(" ");
+        for (keyword, iris) in [
+            ("FROM", &self.default_graphs),
+            ("FROM NAMED", &self.named_graphs),
+        ] {
+            for iri in iris {
+                if !is_valid_iri(iri) {
+                    return Err(ServiceError::BadRequest(format!(
+                        "invalid graph URI '{iri}'"
+                    )));
+                }
+                let _ = write!(clause, "{keyword} <{iri}> ");
+            }
+        }
+
+        // The dataset clause precedes the WHERE clause; without one (e.g.
+        // `DESCRIBE <iri>`) it precedes the solution modifiers.
+        let at = outline
+            .where_keyword
+            .or(outline.where_group)
+            .or(outline.modifier)
+            .unwrap_or(statement.len());
+
+        let mut scoped = String::with_capacity(statement.len() + clause.len());
+        scoped.push_str(&statement[..at]);
+        scoped.push_str(&clause);
+        scoped.push_str(&statement[at..]);
+        Ok(scoped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(default: &[&str], named: &[&str]) -> SparqlDataset {
+        SparqlDataset {
+            default_graphs: default.iter().map(|s| (*s).to_string()).collect(),
+            named_graphs: named.iter().map(|s| (*s).to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn from_pairs_collects_repeated_params() {
+        let ds = SparqlDataset::from_pairs([
+            ("query", "SELECT * {}"),
+            ("default-graph-uri", "http://ex.org/a"),
+            ("named-graph-uri", "http://ex.org/n"),
+            ("default-graph-uri", "http://ex.org/b"),
+        ]);
+        assert_eq!(
+            ds,
+            dataset(
+                &["http://ex.org/a", "http://ex.org/b"],
+                &["http://ex.org/n"]
+            )
+        );
+    }
+
+    #[test]
+    fn empty_dataset_leaves_query_unchanged() {
+        let q = "SELECT * FROM <http://ex.org/g> { ?s ?p ?o }";
+        assert_eq!(SparqlDataset::default().apply(q).unwrap(), q);
+    }
+
+    #[test]
+    fn inserts_before_where_keyword() {
+        let q = "SELECT ?s WHERE { ?s ?p ?o }";
+        let scoped = dataset(&["http://ex.org/a"], &["http://ex.org/n"])
+            .apply(q)
+            .unwrap();
+        assert_eq!(
+            scoped,
+            "SELECT ?s  FROM <http://ex.org/a> FROM NAMED <http://ex.org/n> WHERE { ?s ?p ?o }"
+        );
+    }
+
+    #[test]
+    fn inserts_after_construct_template() {
+        let q = "CONSTRUCT { ?s ?p ?o } { ?s ?p ?o }";
+        let scoped = dataset(&["http://ex.org/a"], &[]).apply(q).unwrap();
+        assert_eq!(
+            scoped,
+            "CONSTRUCT { ?s ?p ?o }  FROM <http://ex.org/a> { ?s ?p ?o }"
+        );
+    }
+
+    #[test]
+    fn describe_without_where_goes_before_modifiers() {
+        let q = "DESCRIBE <http://ex.org/x> LIMIT 1";
+        let scoped = dataset(&["http://ex.org/a"], &[]).apply(q).unwrap();
+        assert_eq!(
+            scoped,
+            "DESCRIBE <http://ex.org/x>  FROM <http://ex.org/a> LIMIT 1"
+        );
+    }
+
+    #[test]
+    fn rejects_dataset_in_both_places() {
+        let q = "SELECT * FROM NAMED <http://ex.org/n> { GRAPH ?g { ?s ?p ?o } }";
+        let err = dataset(&["http://ex.org/a"], &[]).apply(q).unwrap_err();
+        assert!(err.to_string().contains("both"));
+    }
+
+    #[test]
+    fn rejects_updates_and_bad_iris() {
+        let ds = dataset(&["http://ex.org/a"], &[]);
+        assert!(ds.apply("INSERT DATA { <a> <b> <c> }").is_err());
+        assert!(
+            dataset(&["http://ex.org/a> { }"], &[])
+                .apply("SELECT * { ?s ?p ?o }")
+                .is_err()
+        );
+    }
+}
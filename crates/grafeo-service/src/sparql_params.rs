@@ -0,0 +1,222 @@
+//! Parameter binding for SPARQL.
+//!
+//! The SPARQL grammar has no parameter placeholders: `$name` and `?name` are
+//! both ordinary variables, and the engine's SPARQL translator never emits
+//! parameter expressions. Parameters are therefore bound the way SPARQL
+//! pre-binds variables, with inline `VALUES` blocks placed at the start of
+//! the query's WHERE group:
+//!
+//! ```text
+//! SELECT ?name WHERE { ?p foaf:name ?name }      params: {"name": "Alice"}
+//! SELECT ?name WHERE { VALUES ?name { "Alice" } ?p foaf:name ?name }
+//! ```
+//!
+//! Values are serialized as SPARQL terms, never spliced in as raw text, so a
+//! parameter cannot change the shape of the query. A string of the form
+//! `<iri>` binds an IRI; every other string binds a plain literal.
+//!
+//! Parameters are rejected with `BadRequest` when the statement has no WHERE
+//! group to bind them in (e.g. `INSERT DATA`), when a name is not a valid
+//! variable name, and for values with no SPARQL term (lists, maps, vectors).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::hash::BuildHasher;
+
+use grafeo_common::Value;
+
+use crate::error::ServiceError;
+use crate::sparql_scan::{is_valid_iri, outline};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Binds `params` into `statement` with `VALUES` blocks.
+///
+/// Returns the statement unchanged when `params` is empty. Fails with
+/// `BadRequest` when the statement has no WHERE group to bind into (for
+/// example `INSERT DATA`), when a parameter name is not a valid SPARQL
+/// variable name, or when a value has no SPARQL term representation.
+pub fn bind_params<S: BuildHasher>(
+    statement: &str,
+    params: &HashMap<String, Value, S>,
+) -> Result<String, ServiceError> {
+    if params.is_empty() {
+        return Ok(statement.to_owned());
+    }
+
+    // Sort for a deterministic statement (plan cache, logs, tests).
+    let mut names: Vec<&String> = params.keys().collect();
+    names.sort();
+
+    // One single-variable block per parameter: joins the same as a single
+    // multi-variable row, and is the form every SPARQL parser accepts.
+    let mut block = String::from(" ");
+    for name in &names {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(ServiceError::BadRequest(format!(
+                "invalid SPARQL parameter name '{name}'"
+            )));
+        }
+        let term = to_term(name, &params[*name])?;
+        let _ = write!(block, "VALUES ?{name} {{ {term} }} ");
+    }
+
+    let at = outline(statement)
+        .where_group
+        .map(|i| i + 1)
+        .ok_or_else(|| {
+            ServiceError::BadRequest(
+                "parameters can only be bound into a SPARQL query or update with a WHERE clause"
+                    .to_string(),
+            )
+        })?;
+
+    let mut bound = String::with_capacity(statement.len() + block.len());
+    bound.push_str(&statement[..at]);
+    bound.push_str(&block);
+    bound.push_str(&statement[at..]);
+    Ok(bound)
+}
+
+/// Serializes a parameter value as a SPARQL data term.
+fn to_term(name: &str, value: &Value) -> Result<String, ServiceError> {
+    Ok(match value {
+        Value::Null => "UNDEF".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int64(n) => format!("\"{n}\"^^<{XSD}integer>"),
+        Value::Float64(f) => {
+            let lexical = if f.is_nan() {
+                "NaN".to_string()
+            } else if f.is_infinite() {
+                if *f > 0.0 { "INF" } else { "-INF" }.to_string()
+            } else {
+                format!("{f:?}")
+            };
+            format!("\"{lexical}\"^^<{XSD}double>")
+        }
+        Value::String(s) => match s.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            Some(iri) => {
+                if !is_valid_iri(iri) {
+                    return Err(ServiceError::BadRequest(format!(
+                        "parameter '{name}' is not a valid IRI"
+                    )));
+                }
+                format!("<{iri}>")
+            }
+            None => quote_literal(s),
+        },
+        other => {
+            return Err(ServiceError::BadRequest(format!(
+                "parameter '{name}': {} values cannot be bound in SPARQL",
+                other.type_name()
+            )));
+        }
+    })
+}
+
+/// Quotes a string as a SPARQL `STRING_LITERAL_QUOTE`.
+fn quote_literal(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn binds_values_into_where_group() {
+        let q = "SELECT ?name WHERE { ?p <http://xmlns.com/foaf/0.1/name> ?name }";
+        let bound = bind_params(q, &params(&[("name", Value::from("Alice"))])).unwrap();
+        assert_eq!(
+            bound,
+            "SELECT ?name WHERE { VALUES ?name { \"Alice\" }  ?p <http://xmlns.com/foaf/0.1/name> ?name }"
+        );
+    }
+
+    #[test]
+    fn empty_params_leave_statement_unchanged() {
+        let q = "SELECT * WHERE { ?s ?p ?o }";
+        assert_eq!(bind_params(q, &HashMap::new()).unwrap(), q);
+    }
+
+    #[test]
+    fn where_keyword_is_optional_for_select() {
+        let q = "SELECT * { ?s ?p $o }";
+        let bound = bind_params(q, &params(&[("o", Value::Int64(-3))])).unwrap();
+        assert!(bound.starts_with("SELECT * { VALUES ?o { \"-3\"^^<"));
+    }
+
+    #[test]
+    fn construct_binds_into_pattern_not_template() {
+        let q = "CONSTRUCT { ?s ?p ?o } { ?s ?p ?o }";
+        let bound = bind_params(q, &params(&[("s", Value::from("<http://ex.org/a>"))])).unwrap();
+        assert_eq!(
+            bound,
+            "CONSTRUCT { ?s ?p ?o } { VALUES ?s { <http://ex.org/a> }  ?s ?p ?o }"
+        );
+    }
+
+    #[test]
+    fn skips_braces_in_strings_iris_and_comments() {
+        let q = "PREFIX ex: <http://ex.org/{x}> # { not a group\n\
+                 DELETE { ?s ex:p \"}{\" } INSERT { ?s ex:p ?v } WHERE { ?s ex:p \"}{\" }";
+        let bound = bind_params(q, &params(&[("v", Value::Bool(true))])).unwrap();
+        assert!(bound.ends_with("WHERE { VALUES ?v { true }  ?s ex:p \"}{\" }"));
+    }
+
+    #[test]
+    fn variable_named_where_is_not_a_keyword() {
+        let q = "SELECT ?where { ?s ?p ?where }";
+        let bound = bind_params(q, &params(&[("where", Value::Null)])).unwrap();
+        assert!(bound.starts_with("SELECT ?where { VALUES ?where { UNDEF }"));
+    }
+
+    #[test]
+    fn insert_data_cannot_bind() {
+        let q = "INSERT DATA { <http://ex.org/a> <http://ex.org/p> \"x\" }";
+        let err = bind_params(q, &params(&[("x", Value::from("y"))])).unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest(_)));
+    }
+
+    #[test]
+    fn string_literals_are_escaped() {
+        let q = "SELECT * { ?s ?p ?o }";
+        let bound = bind_params(q, &params(&[("o", Value::from("a\"} ) }\n"))])).unwrap();
+        assert!(bound.contains(r#"{ "a\"} ) }\n" }"#));
+    }
+
+    #[test]
+    fn rejects_malformed_iri_and_names() {
+        let q = "SELECT * { ?s ?p ?o }";
+        assert!(bind_params(q, &params(&[("o", Value::from("<a> } <b>"))])).is_err());
+        assert!(bind_params(q, &params(&[("o) {", Value::Int64(1))])).is_err());
+    }
+
+    #[test]
+    fn rejects_unrepresentable_values() {
+        let q = "SELECT * { ?s ?p ?o }";
+        let list = Value::List(vec![Value::Int64(1)].into());
+        let err = bind_params(q, &params(&[("o", list)])).unwrap_err();
+        assert!(err.to_string().contains("cannot be bound"));
+    }
+}
@@ -0,0 +1,197 @@
+//! Lightweight top-level scanner for SPARQL statements.
+//!
+//! Finds the landmarks that server-side rewrites need (the query form, the
+//! WHERE group, dataset clauses) without a full parse. Strings, IRIs,
+//! comments and variables are skipped so that braces and keywords inside
+//! them are never mistaken for structure.
+
+/// Top-level landmarks of a SPARQL statement.
+#[derive(Debug, Default)]
+pub(crate) struct Outline {
+    /// Query form or update operation keyword, uppercased.
+    pub form: Option<String>,
+    /// Byte offset of the `WHERE` keyword, when spelled out.
+    pub where_keyword: Option<usize>,
+    /// Byte offset of the `{` that opens the WHERE group.
+    pub where_group: Option<usize>,
+    /// Whether a top-level `FROM` clause precedes the WHERE group.
+    pub has_from: bool,
+    /// Byte offset of the first top-level solution modifier, recorded only
+    /// when the statement has no WHERE group (e.g. `DESCRIBE <iri> LIMIT 1`).
+    pub modifier: Option<usize>,
+}
+
+impl Outline {
+    /// Whether the statement is a SPARQL Update rather than a query.
+    pub fn is_update(&self) -> bool {
+        !matches!(
+            self.form.as_deref(),
+            None | Some("SELECT" | "ASK" | "DESCRIBE" | "CONSTRUCT")
+        )
+    }
+}
+
+/// Scans `statement` up to its WHERE group.
+///
+/// The WHERE group is the first top-level group after a `WHERE` keyword;
+/// the keyword is optional for SELECT, ASK and DESCRIBE (first group) and
+/// CONSTRUCT (second group, after the template). Updates must spell it out.
+pub(crate) fn outline(statement: &str) -> Outline {
+    let bytes = statement.as_bytes();
+    let mut out = Outline::default();
+    let mut top_level_groups = 0usize;
+    let mut depth = 0usize;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        match c {
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'"' | b'\'' => i = skip_string(bytes, i),
+            b'<' => {
+                // An IRI runs to the next '>' with no whitespace; anything
+                // else is a comparison operator.
+                let end = bytes[i + 1..]
+                    .iter()
+                    .position(|&b| b == b'>' || b == b'<' || b.is_ascii_whitespace())
+                    .map(|p| i + 1 + p);
+                match end {
+                    Some(e) if bytes[e] == b'>' => i = e + 1,
+                    _ => i += 1,
+                }
+            }
+            b'?' | b'$' => {
+                i += 1;
+                while i < bytes.len() && is_name_byte(bytes[i]) {
+                    i += 1;
+                }
+            }
+            b'{' => {
+                if depth == 0 {
+                    let is_where_group = match out.form.as_deref() {
+                        _ if out.where_keyword.is_some() => true,
+                        Some("SELECT" | "ASK" | "DESCRIBE") => true,
+                        Some("CONSTRUCT") => top_level_groups == 1,
+                        _ => false,
+                    };
+                    if is_where_group {
+                        out.where_group = Some(i);
+                        out.modifier = None;
+                        return out;
+                    }
+                    top_level_groups += 1;
+                }
+                depth += 1;
+                i += 1;
+            }
+            b'}' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            _ if is_name_byte(c) => {
+                let start = i;
+                while i < bytes.len() && (is_name_byte(bytes[i]) || bytes[i] == b':') {
+                    i += 1;
+                }
+                if depth == 0 {
+                    let word = statement[start..i].to_ascii_uppercase();
+                    match word.as_str() {
+                        "WHERE" => out.where_keyword = Some(start),
+                        "FROM" => out.has_from = true,
+                        "ORDER" | "GROUP" | "HAVING" | "LIMIT" | "OFFSET" | "VALUES"
+                            if out.form.is_some() && out.modifier.is_none() =>
+                        {
+                            out.modifier = Some(start);
+                        }
+                        "SELECT" | "ASK" | "DESCRIBE" | "CONSTRUCT" | "INSERT" | "DELETE"
+                        | "WITH" | "LOAD" | "CLEAR" | "DROP" | "CREATE" | "COPY" | "MOVE"
+                        | "ADD"
+                            if out.form.is_none() =>
+                        {
+                            out.form = Some(word);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    out
+}
+
+/// Whether `iri` can be written as `<iri>` without escaping.
+pub(crate) fn is_valid_iri(iri: &str) -> bool {
+    !iri.is_empty()
+        && !iri.chars().any(|c| {
+            c.is_whitespace() || matches!(c, '<' | '>' | '"' | '{' | '}' | '|' | '^' | '`' | '\\')
+        })
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-' || !b.is_ascii()
+}
+
+/// Skips a short or long (triple-quoted) string literal starting at `i`.
+fn skip_string(bytes: &[u8], i: usize) -> usize {
+    let quote = bytes[i];
+    let long = bytes.len() >= i + 3 && bytes[i + 1] == quote && bytes[i + 2] == quote;
+    let mut j = if long { i + 3 } else { i + 1 };
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 2,
+            b if b == quote => {
+                if !long {
+                    return j + 1;
+                }
+                if bytes.len() >= j + 3 && bytes[j + 1] == quote && bytes[j + 2] == quote {
+                    return j + 3;
+                }
+                j += 1;
+            }
+            _ => j += 1,
+        }
+    }
+    bytes.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_where_keyword_and_group() {
+        let q = "SELECT ?s WHERE { ?s ?p ?o }";
+        let o = outline(q);
+        assert_eq!(o.form.as_deref(), Some("SELECT"));
+        assert_eq!(o.where_keyword, Some(10));
+        assert_eq!(o.where_group, Some(16));
+        assert!(!o.has_from && !o.is_update());
+    }
+
+    #[test]
+    fn detects_from_but_not_in_names() {
+        assert!(outline("SELECT * FROM <http://g> { ?s ?p ?o }").has_from);
+        assert!(!outline("SELECT ?from { ?s ex:from ?from }").has_from);
+        assert!(!outline("SELECT * { ?s ?p \"FROM\" }").has_from);
+    }
+
+    #[test]
+    fn records_modifier_without_where_group() {
+        let q = "DESCRIBE <http://ex.org/a> LIMIT 1";
+        let o = outline(q);
+        assert_eq!(o.where_group, None);
+        assert_eq!(o.modifier, Some(q.find("LIMIT").unwrap()));
+    }
+
+    #[test]
+    fn classifies_updates() {
+        assert!(outline("INSERT DATA { <a> <b> <c> }").is_update());
+        assert!(outline("PREFIX ex: <http://ex.org/> DELETE WHERE { ?s ex:p ?o }").is_update());
+        assert!(!outline("PREFIX ex: <http://ex.org/> ASK { ?s ex:p ?o }").is_update());
+    }
+}
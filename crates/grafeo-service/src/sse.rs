@@ -0,0 +1,107 @@
+//! Incremental parser for `text/event-stream` response bodies.
+//!
+//! Shared by the clients of the server's SSE endpoints: the replica's
+//! follower of `/db/{name}/replication/stream` and the `grafeo-sync`
+//! subscriber of `/db/{name}/changes/stream`.
+
+/// One dispatched SSE event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// The `event` field, or `message` if the event had none.
+    pub event: String,
+    /// The `data` lines, joined with `\n`.
+    pub data: String,
+}
+
+/// Incremental parser for the `text/event-stream` format.
+///
+/// Only `event:` and `data:` fields are interpreted; comments (keep-alives)
+/// and other fields are ignored, as are events without data.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buf: Vec<u8>,
+}
+
+impl SseParser {
+    /// Feeds a chunk of the response body, returning every event it completes.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buf.drain(..end + 2).collect();
+            let block = String::from_utf8_lossy(&block[..end]);
+
+            let mut event = String::new();
+            let mut data: Option<String> = None;
+            for line in block.lines() {
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match field {
+                    "event" => event = value.to_string(),
+                    "data" => match &mut data {
+                        Some(d) => {
+                            d.push('\n');
+                            d.push_str(value);
+                        }
+                        None => data = Some(value.to_string()),
+                    },
+                    _ => {}
+                }
+            }
+
+            if let Some(data) = data {
+                if event.is_empty() {
+                    event = "message".to_string();
+                }
+                events.push(SseEvent { event, data });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(data: &str) -> SseEvent {
+        SseEvent {
+            event: "message".into(),
+            data: data.into(),
+        }
+    }
+
+    #[test]
+    fn handles_split_chunks_and_keepalives() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b":\n\nevent: batch\ndata: {\"a\"").is_empty());
+        let events = parser.push(b":1}\n\nevent: gap\r\ndata: 7\r\n\r\ndata: x\ndata: y\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: "batch".into(),
+                    data: "{\"a\":1}".into()
+                },
+                SseEvent {
+                    event: "gap".into(),
+                    data: "7".into()
+                },
+                message("x\ny"),
+            ]
+        );
+    }
+
+    #[test]
+    fn joins_lines_split_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"data: {\"a\"").is_empty());
+        assert!(parser.push(b":1}\r").is_empty());
+        assert!(parser.push(b"\n\r").is_empty());
+        assert_eq!(
+            parser.push(b"\n:keep-alive\n\ndata:x\n\n"),
+            [message("{\"a\":1}"), message("x")]
+        );
+    }
+}
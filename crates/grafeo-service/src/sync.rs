@@ -6,15 +6,23 @@
 //!
 //! Clients poll `SyncService::pull()` with their last seen epoch and receive
 //! all mutations since that point. The `server_epoch` in the response becomes
-//! the `since` value for the next poll.
+//! the `since` value for the next poll. A [`ChangeFilter`] passed to
+//! `SyncService::pull_matching()` narrows the events server-side.
 //!
 //! # Push endpoint
 //!
 //! `POST /db/{name}/sync`
 //!
-//! Clients submit their local changesets. The server applies them with
-//! last-write-wins (LWW) conflict resolution, comparing wall-clock timestamps.
-//! Returns `{ server_epoch, applied, skipped, conflicts, id_mappings }`.
+//! Clients submit their local changesets. The server resolves changes that
+//! race a server change with the database's conflict policy (see
+//! [`crate::conflicts`]), by default last-write-wins (LWW) on wall-clock
+//! timestamps. A changeset applies in a single transaction; with
+//! `atomic: true` it is rolled back entirely if any change fails.
+//! Returns `{ server_epoch, applied, skipped, conflicts, id_mappings, rolled_back }`.
+//!
+//! The policy is read and set with `GET`/`PUT /db/{name}/sync/policy`, and
+//! resolved conflicts can be logged and listed with
+//! `GET /db/{name}/sync/conflicts`.
 //!
 //! # Protocol
 //!
@@ -23,6 +31,9 @@
 //! 3. On reconnect, call with `since=stored_epoch` to receive only new changes.
 //! 4. To push local changes, `POST /sync` with the changeset.
 //! 5. Update local IDs for any creates using the returned `id_mappings`.
+//!    Creates may carry a temporary string `id` that later changes in the
+//!    same push use as `id`, `src_id` or `dst_id`; `id_mappings` echoes it
+//!    as `temp_id`.
 //!
 //! # Limits
 //!
@@ -30,14 +41,20 @@
 //! `changes.len() == limit`, there may be more: poll again using the epoch of
 //! the last returned event as `since`.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use grafeo_common::types::{EdgeId, NodeId};
+use grafeo_engine::auth::Identity;
 use serde::{Deserialize, Serialize};
 
-use crate::database::DatabaseManager;
+use crate::change_filter::ChangeFilter;
+use crate::conflicts::{ConflictPolicy, ConflictsResponse, LoggedConflict, SyncPolicy};
+use crate::database::{DatabaseEntry, DatabaseManager};
 use crate::error::ServiceError;
 
+/// Events passed to [`ChangeFilter::apply`] at a time by filtered pulls.
+const FILTER_CHUNK: usize = 1_000;
+
 // ---------------------------------------------------------------------------
 // Pull wire types
 // ---------------------------------------------------------------------------
@@ -51,6 +68,11 @@ pub struct ChangesResponse {
     /// Change events with epoch >= the requested `since` value, ordered by
     /// epoch ascending.
     pub changes: Vec<ChangeEventDto>,
+    /// Whether every epoch in `changes` is complete, as requested with
+    /// `complete=true`: `server_epoch` is then the last epoch returned in
+    /// full. Absent from servers that may split epochs.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub complete: bool,
 }
 
 /// A single mutation event, safe for wire serialization.
@@ -109,6 +131,57 @@ pub struct ChangeEventDto {
     pub triple_graph: Option<String>,
 }
 
+/// A subscriber's position in the changefeed, used to deliver each event
+/// once when overlapping pulls, reconnects or backfills send it again.
+#[derive(Debug, Clone)]
+pub struct ChangeCursor {
+    /// Epoch of the last delivered event, or the starting epoch.
+    epoch: u64,
+    /// Events delivered at `epoch`, which a later pull returns again.
+    seen: HashSet<(String, u64, String, u64)>,
+}
+
+impl ChangeCursor {
+    pub fn new(since: u64) -> Self {
+        Self {
+            epoch: since,
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Epoch of the last delivered event, or the starting epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Moves the cursor to `event`. Returns `false` if it was delivered before.
+    pub fn advance(&mut self, event: &ChangeEventDto) -> bool {
+        if event.epoch < self.epoch {
+            return false;
+        }
+        if event.epoch > self.epoch {
+            self.epoch = event.epoch;
+            self.seen.clear();
+        }
+        self.seen.insert((
+            event.entity_type.clone(),
+            event.id,
+            event.kind.clone(),
+            event.timestamp,
+        ))
+    }
+}
+
+/// Reported to a subscriber that fell behind its broadcast channel, and
+/// sent as the data of the SSE `gap` event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeGap {
+    /// Epoch of the last event delivered before the gap, or the
+    /// subscription's `since` if there was none. The dropped events follow
+    /// the gap, backfilled from the CDC log.
+    pub last_epoch: u64,
+}
+
 // ---------------------------------------------------------------------------
 // Push wire types
 // ---------------------------------------------------------------------------
@@ -119,8 +192,9 @@ pub struct ChangeEventDto {
 pub struct SyncRequest {
     /// Opaque client identifier (device ID, user ID, etc.).
     pub client_id: String,
-    /// The last server epoch the client has processed. Informational only;
-    /// used for diagnostics and future incremental backup integration.
+    /// The last server epoch the client has processed. Server changes
+    /// committed after it are ones the client has not seen; all conflict
+    /// policies except `lww` treat them as conflicting.
     #[serde(default)]
     pub last_seen_epoch: u64,
     /// The changes the client wants to apply to the server.
@@ -131,25 +205,35 @@ pub struct SyncRequest {
     /// No changes are rejected — this is a diagnostic warning only.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub schema_version: Option<String>,
+    /// When `true`, the changeset is all-or-nothing: if any change conflicts
+    /// or fails, nothing is applied and `SyncResponse.rolled_back` is set.
+    /// When `false` (the default), failing changes are skipped and the rest
+    /// are applied. Either way the applied changes commit as one transaction.
+    #[serde(default)]
+    pub atomic: bool,
 }
 
 /// A single change the client wants to apply.
 ///
 /// The `id` field holds the server-assigned entity ID and is required for
-/// `"update"` and `"delete"` operations. For `"create"` operations it must
-/// be omitted (or `null`); the server assigns a new ID and returns the
-/// mapping in `SyncResponse.id_mappings`.
-#[derive(Debug, Serialize, Deserialize)]
+/// `"update"` and `"delete"` operations. For `"create"` operations the
+/// server assigns a new ID and returns the mapping in
+/// `SyncResponse.id_mappings`; the client may set `id` to a temporary string
+/// ID of its choosing and use that string in the `id`, `src_id` and `dst_id`
+/// of later changes in the same request, so an offline client can create a
+/// node and connect it in one push.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SyncChangeRequest {
     /// `"create"`, `"update"`, or `"delete"`.
     pub kind: String,
     /// `"node"` or `"edge"`.
     pub entity_type: String,
-    /// Server entity ID. Required for update/delete; absent for create.
-    pub id: Option<u64>,
+    /// Entity ID. Required for update/delete; absent or a temporary ID for
+    /// create.
+    pub id: Option<EntityRef>,
     /// Wall-clock timestamp from the client (ms since Unix epoch).
-    /// Used for LWW conflict detection on update and delete.
+    /// Used for LWW conflict resolution on update and delete.
     #[serde(default)]
     pub timestamp: u64,
     /// Node labels. Required for node creates.
@@ -157,9 +241,9 @@ pub struct SyncChangeRequest {
     /// Edge relationship type. Required for edge creates.
     pub edge_type: Option<String>,
     /// Edge source node ID. Required for edge creates.
-    pub src_id: Option<u64>,
+    pub src_id: Option<EntityRef>,
     /// Edge destination node ID. Required for edge creates.
-    pub dst_id: Option<u64>,
+    pub dst_id: Option<EntityRef>,
     /// Properties to write. Required for creates; property-delta for updates.
     pub after: Option<serde_json::Value>,
     /// CRDT operation to apply to `crdt_property`. When set, the named
@@ -169,11 +253,31 @@ pub struct SyncChangeRequest {
     pub crdt_property: Option<String>,
 }
 
+/// An entity referenced by a `SyncChangeRequest`.
+///
+/// A JSON number is a server entity ID; a JSON string is a temporary ID the
+/// client gave to an entity created earlier in the same request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum EntityRef {
+    /// Server-assigned entity ID.
+    Id(u64),
+    /// Client-assigned temporary ID, resolved within one request.
+    Temp(String),
+}
+
+impl From<u64> for EntityRef {
+    fn from(id: u64) -> Self {
+        Self::Id(id)
+    }
+}
+
 /// A CRDT operation applied to a single property on a node or edge.
 ///
 /// When `crdt_op` is present on a `SyncChangeRequest`, the sync service
 /// applies the operation via `crdt::apply_op` instead of the normal LWW path.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[serde(tag = "type")]
 pub enum CrdtOp {
@@ -181,6 +285,40 @@ pub enum CrdtOp {
     GrowAdd { amount: u64, replica_id: String },
     /// Add (positive or negative) `amount` to a PN-Counter property.
     Increment { amount: i64, replica_id: String },
+    /// Assign `value` to a last-writer-wins register. The assignment with
+    /// the highest HLC `timestamp` (then `replica_id`) wins.
+    LwwAssign {
+        #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+        value: grafeo_common::types::Value,
+        timestamp: u64,
+        replica_id: String,
+    },
+    /// Add `element` to an observed-remove set under a new tag.
+    SetAdd {
+        #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+        element: grafeo_common::types::Value,
+        replica_id: String,
+    },
+    /// Remove `element` from an observed-remove set. Only the given `tags`
+    /// are removed, so an add the client had not observed survives; without
+    /// `tags`, every tag the server holds for the element is removed.
+    SetRemove {
+        #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+        element: grafeo_common::types::Value,
+        #[serde(default)]
+        tags: Option<Vec<String>>,
+    },
+    /// Assign `value` to a multi-value register. `context` is the version
+    /// vector of the values the client observed; they are superseded, while
+    /// values assigned concurrently are kept. Without `context`, every value
+    /// the server holds is superseded.
+    MvAssign {
+        #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+        value: grafeo_common::types::Value,
+        replica_id: String,
+        #[serde(default)]
+        context: Option<HashMap<String, u64>>,
+    },
 }
 
 /// Response from `POST /db/{name}/sync`.
@@ -191,12 +329,13 @@ pub struct SyncResponse {
     pub server_epoch: u64,
     /// Number of changes that were applied successfully.
     pub applied: usize,
-    /// Number of changes that were skipped due to LWW conflicts.
+    /// Number of changes that were skipped because the server's change won
+    /// the conflict.
     pub skipped: usize,
     /// Conflicts that were detected. Skipped changes appear here.
     pub conflicts: Vec<ConflictRecord>,
     /// Server-assigned IDs for each `"create"` change in the request, in
-    /// the order they appeared.
+    /// the order they appeared, including the temporary IDs they replace.
     pub id_mappings: Vec<IdMapping>,
     /// `true` when the client supplied `schema_version` and it differed from
     /// the server's computed version. The client should re-fetch the schema
@@ -206,16 +345,56 @@ pub struct SyncResponse {
     /// and property keys). Always present so clients can detect drift even
     /// without sending their own `schema_version`.
     pub server_schema_version: String,
+    /// `true` when an `atomic` request was rolled back because a change
+    /// conflicted or failed. `conflicts` names the change; nothing was
+    /// applied and `id_mappings` is empty.
+    #[serde(default)]
+    pub rolled_back: bool,
 }
 
 /// A conflict detected during sync apply.
-#[derive(Debug, Serialize, Deserialize)]
+///
+/// A change that is invalid or targets a missing entity carries only a
+/// `reason`. A change that raced a server change also names the `policy`
+/// that resolved it and the server's side of the conflict.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ConflictRecord {
     /// Zero-based index of the change in the request's `changes` array.
     pub request_index: usize,
     /// Human-readable reason for the conflict.
     pub reason: String,
+    /// Conflict policy that resolved the conflict.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<ConflictPolicy>,
+    /// ID of the entity the change targeted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<u64>,
+    /// Properties changed on both sides, for `field_merge`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub properties: Vec<String>,
+    /// The entity's properties on the server before the change, in the
+    /// representation used by `after`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_value: Option<serde_json::Value>,
+    /// Epoch of the server's latest change to the entity.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_epoch: Option<u64>,
+}
+
+impl ConflictRecord {
+    /// Creates a record that carries only a reason.
+    pub fn new(request_index: usize, reason: String) -> Self {
+        Self {
+            request_index,
+            reason,
+            policy: None,
+            entity_id: None,
+            properties: Vec::new(),
+            server_value: None,
+            server_epoch: None,
+        }
+    }
 }
 
 /// Maps a client create request to the server-assigned entity ID.
@@ -226,6 +405,9 @@ pub struct IdMapping {
     pub request_index: usize,
     /// Server-assigned entity ID.
     pub server_id: u64,
+    /// Temporary ID the client gave the created entity, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_id: Option<String>,
 }
 
 // ---------------------------------------------------------------------------
@@ -262,6 +444,156 @@ impl SyncService {
         since: u64,
         limit: usize,
     ) -> Result<ChangesResponse, ServiceError> {
+        let (server_epoch, raw) = Self::changes_since(databases, db_name, since)?;
+        let changes = raw.into_iter().take(limit).map(to_dto).collect();
+
+        Ok(ChangesResponse {
+            server_epoch,
+            changes,
+            complete: false,
+        })
+    }
+
+    /// Like [`pull`](Self::pull), but only returns the events `filter`
+    /// matches, with its predicate evaluated for `identity`; `limit` caps
+    /// the matching events.
+    ///
+    /// `server_epoch` is the current epoch when every matching event was
+    /// returned, and the epoch of the last returned event otherwise.
+    pub fn pull_matching(
+        databases: &DatabaseManager,
+        db_name: &str,
+        since: u64,
+        limit: usize,
+        filter: &ChangeFilter,
+        identity: &Identity,
+    ) -> Result<ChangesResponse, ServiceError> {
+        if filter.is_empty() {
+            return Self::pull(databases, db_name, since, limit);
+        }
+        let db = databases.get_available(db_name)?.db();
+        filter.validate(&db, identity)?;
+
+        let (server_epoch, raw) = Self::changes_since(databases, db_name, since)?;
+        let mut raw = raw.into_iter().map(to_dto).peekable();
+        let mut changes = Vec::new();
+        while raw.peek().is_some() && changes.len() < limit {
+            let chunk: Vec<_> = raw.by_ref().take(FILTER_CHUNK).collect();
+            changes.extend(filter.apply(&db, identity, chunk)?);
+        }
+
+        let server_epoch = if changes.len() > limit {
+            changes.truncate(limit);
+            changes.last().map_or(server_epoch, |e| e.epoch)
+        } else if raw.peek().is_some() {
+            changes.last().map_or(since, |e| e.epoch)
+        } else {
+            server_epoch
+        };
+
+        Ok(ChangesResponse {
+            server_epoch,
+            changes,
+            complete: false,
+        })
+    }
+
+    /// Like [`pull`](Self::pull), but never splits an epoch across responses.
+    ///
+    /// When more than `limit` events are available, the response stops after
+    /// the last epoch it can return in full and `server_epoch` is lowered to
+    /// that epoch, so `server_epoch + 1` is always a safe `since` for the
+    /// next call. An epoch that alone exceeds `limit` is returned whole.
+    /// Used by replication, which must not skip or replay events.
+    pub fn pull_complete(
+        databases: &DatabaseManager,
+        db_name: &str,
+        since: u64,
+        limit: usize,
+    ) -> Result<ChangesResponse, ServiceError> {
+        let (server_epoch, mut raw) = Self::changes_since(databases, db_name, since)?;
+
+        let server_epoch = if raw.len() > limit {
+            let cut = raw[limit].epoch;
+            let mut end = raw.partition_point(|e| e.epoch < cut);
+            if end == 0 {
+                end = raw.partition_point(|e| e.epoch <= cut);
+            }
+            raw.truncate(end);
+            raw[end - 1].epoch.0
+        } else {
+            server_epoch
+        };
+
+        Ok(ChangesResponse {
+            server_epoch,
+            changes: raw.into_iter().map(to_dto).collect(),
+            complete: true,
+        })
+    }
+
+    /// Like [`pull_matching`](Self::pull_matching), but never splits an
+    /// epoch across responses, as [`pull_complete`](Self::pull_complete):
+    /// an epoch with more than `limit` matching events is returned whole.
+    pub fn pull_matching_complete(
+        databases: &DatabaseManager,
+        db_name: &str,
+        since: u64,
+        limit: usize,
+        filter: &ChangeFilter,
+        identity: &Identity,
+    ) -> Result<ChangesResponse, ServiceError> {
+        if filter.is_empty() {
+            return Self::pull_complete(databases, db_name, since, limit);
+        }
+        let db = databases.get_available(db_name)?.db();
+        filter.validate(&db, identity)?;
+
+        let (server_epoch, raw) = Self::changes_since(databases, db_name, since)?;
+        let mut raw = raw.into_iter().map(to_dto).peekable();
+        let mut changes = Vec::new();
+        while raw.peek().is_some() && changes.len() <= limit {
+            let chunk: Vec<_> = raw.by_ref().take(FILTER_CHUNK).collect();
+            changes.extend(filter.apply(&db, identity, chunk)?);
+        }
+        if changes.len() <= limit {
+            return Ok(ChangesResponse {
+                server_epoch,
+                changes,
+                complete: true,
+            });
+        }
+
+        let cut = changes[limit].epoch;
+        let mut end = changes.partition_point(|e| e.epoch < cut);
+        if end == 0 {
+            // The first epoch alone exceeds the limit: match the rest of it.
+            loop {
+                let chunk: Vec<_> = std::iter::from_fn(|| raw.next_if(|e| e.epoch == cut))
+                    .take(FILTER_CHUNK)
+                    .collect();
+                if chunk.is_empty() {
+                    break;
+                }
+                changes.extend(filter.apply(&db, identity, chunk)?);
+            }
+            end = changes.partition_point(|e| e.epoch <= cut);
+        }
+        changes.truncate(end);
+
+        Ok(ChangesResponse {
+            server_epoch: changes[end - 1].epoch,
+            changes,
+            complete: true,
+        })
+    }
+
+    /// Returns the current epoch and every CDC event with `epoch >= since`.
+    fn changes_since(
+        databases: &DatabaseManager,
+        db_name: &str,
+        since: u64,
+    ) -> Result<(u64, Vec<grafeo_engine::cdc::ChangeEvent>), ServiceError> {
         let entry = databases.get_available(db_name)?;
 
         if !entry.db().is_cdc_enabled() {
@@ -274,31 +606,48 @@ impl SyncService {
         let since_id = grafeo_common::types::EpochId(since);
         let until_id = grafeo_common::types::EpochId(server_epoch);
 
-        let raw = entry
+        let mut raw = entry
             .db()
             .changes_between(since_id, until_id)
             .map_err(|e| ServiceError::Internal(e.to_string()))?;
+        // The log orders events by epoch only. A transaction records all of
+        // its events in one epoch, so order them by timestamp within it to
+        // keep each create ahead of the changes that depend on it.
+        raw.sort_by_key(|e| (e.epoch, e.timestamp));
 
-        let changes = raw.into_iter().take(limit).map(to_dto).collect();
-
-        Ok(ChangesResponse {
-            server_epoch,
-            changes,
-        })
+        Ok((server_epoch, raw))
     }
 
-    /// Applies a client changeset to `db_name` using LWW conflict resolution.
+    /// Applies a client changeset to `db_name`, resolving conflicts with the
+    /// database's conflict policy.
+    ///
+    /// # Transactions
+    ///
+    /// All changes run in a single engine transaction, so readers, CDC
+    /// consumers and replicas see either none or all of the applied changes.
+    /// With `request.atomic` set, the first change that is invalid, targets
+    /// a missing entity or fails in the engine rolls the whole transaction
+    /// back (`rolled_back` in the response). Otherwise each change runs under
+    /// a savepoint: such a change is recorded in `conflicts` and skipped, and
+    /// the rest commit. Changes that lose a conflict are skipped in both
+    /// modes; changes rejected by the `reject` policy count as failed.
     ///
     /// # Conflict resolution
     ///
     /// For `update` and `delete` operations the server checks its CDC log for
-    /// the target entity. If the server has a recorded change with a
-    /// wall-clock `timestamp` strictly greater than the client's
-    /// `change.timestamp`, the server's version is considered newer and the
-    /// client change is skipped (recorded in `conflicts`).
+    /// changes to the target entity that race the client's, and resolves
+    /// them with the database's [`SyncPolicy`]. Under the default `lww`
+    /// policy, a server change with a wall-clock `timestamp` strictly greater
+    /// than the client's `change.timestamp` wins and the client change is
+    /// skipped. The other policies consider the server changes committed
+    /// after `request.last_seen_epoch`. Each conflict is reported in
+    /// `conflicts` with the server's current value and epoch, and recorded
+    /// in the conflict log when the policy enables it. CRDT values merge
+    /// instead of conflicting.
     ///
     /// `create` operations are never conflicted: the server assigns a fresh ID
-    /// and returns the mapping in `id_mappings`.
+    /// and returns the mapping in `id_mappings`. Updates of entities that do
+    /// not exist are conflicts; deletes of them succeed.
     ///
     /// # Idempotency
     ///
@@ -310,8 +659,105 @@ impl SyncService {
         databases: &DatabaseManager,
         db_name: &str,
         request: SyncRequest,
+    ) -> Result<SyncResponse, ServiceError> {
+        Self::apply_changes(databases, db_name, request, false)
+    }
+
+    /// Applies a batch of a primary's change events on a replica.
+    ///
+    /// Unlike [`apply`](Self::apply), creates carry the primary's entity ID
+    /// in `change.id` and the replica creates the entity with that same ID,
+    /// so later events and edge endpoints, which reference primary IDs, hit
+    /// the right entities, and ID-based queries return the same entities on
+    /// both. A create whose ID already exists was applied by an earlier,
+    /// interrupted attempt and is not repeated.
+    ///
+    /// Events are applied in order without the LWW check: the primary has
+    /// already ordered them, and the replica's own change timestamps are
+    /// later than the primary's. The batch is always atomic.
+    ///
+    /// The store's ID counters are moved to each replicated ID while it is
+    /// created, under [`DatabaseEntry::lock_id_counters`]. Sync pushes, the
+    /// only other writes a replica accepts, hold the shared guard, so they
+    /// never allocate an ID in between.
+    pub fn replicate(
+        databases: &DatabaseManager,
+        db_name: &str,
+        request: SyncRequest,
+    ) -> Result<SyncResponse, ServiceError> {
+        let request = SyncRequest {
+            atomic: true,
+            ..request
+        };
+        Self::apply_changes(databases, db_name, request, true)
+    }
+
+    /// Returns the conflict policy of `db_name`.
+    pub fn policy(databases: &DatabaseManager, db_name: &str) -> Result<SyncPolicy, ServiceError> {
+        Ok(databases.conflict_state(db_name)?.policy())
+    }
+
+    /// Sets the conflict policy of `db_name`. It applies to sync requests
+    /// that start afterwards and is persisted with a persistent database.
+    pub fn set_policy(
+        databases: &DatabaseManager,
+        db_name: &str,
+        policy: SyncPolicy,
+    ) -> Result<SyncPolicy, ServiceError> {
+        if databases.is_read_only() {
+            return Err(ServiceError::ReadOnly);
+        }
+        databases
+            .conflict_state(db_name)?
+            .set_policy(policy.clone())?;
+        Ok(policy)
+    }
+
+    /// Returns up to `limit` entries of the conflict log of `db_name` with
+    /// `id >= since`, optionally only those pushed by `client_id`.
+    ///
+    /// Conflicts are logged only while the policy's `log_conflicts` is set.
+    pub fn conflicts(
+        databases: &DatabaseManager,
+        db_name: &str,
+        since: u64,
+        limit: usize,
+        client_id: Option<&str>,
+    ) -> Result<ConflictsResponse, ServiceError> {
+        Ok(databases
+            .conflict_state(db_name)?
+            .list(since, limit, client_id))
+    }
+
+    /// Removes a conflict from the conflict log of `db_name`, e.g. once a
+    /// user has resolved it.
+    pub fn dismiss_conflict(
+        databases: &DatabaseManager,
+        db_name: &str,
+        id: u64,
+    ) -> Result<(), ServiceError> {
+        databases.conflict_state(db_name)?.dismiss(id)
+    }
+
+    fn apply_changes(
+        databases: &DatabaseManager,
+        db_name: &str,
+        request: SyncRequest,
+        replicated: bool,
     ) -> Result<SyncResponse, ServiceError> {
         let entry = databases.get_available(db_name)?;
+        let conflict_state = if replicated {
+            None
+        } else {
+            Some(databases.conflict_state(db_name)?)
+        };
+        let policy = conflict_state
+            .as_ref()
+            .map(|state| state.policy())
+            .unwrap_or_default();
+
+        // Pushes allocate IDs from the counters a replicated create moves.
+        let _id_counters = (!replicated).then(|| entry.share_id_counters());
 
         let db_handle = entry.db();
         let db = &*db_handle;
@@ -319,241 +765,128 @@ impl SyncService {
         let mut skipped = 0usize;
         let mut conflicts: Vec<ConflictRecord> = Vec::new();
         let mut id_mappings: Vec<IdMapping> = Vec::new();
+        let mut rolled_back = false;
+
+        // Mutations go through parameterized GQL so that the session's write
+        // store records them in the WAL and the CDC log, which the direct
+        // session CRUD API bypasses.
+        let mut session = db.session();
+        session
+            .begin_transaction()
+            .map_err(|e| ServiceError::Internal(e.to_string()))?;
 
-        // Use the database directly for mutations. Each operation auto-commits.
-        // TODO: wrap in a session transaction once the deadlock in persistent
-        // mode is resolved (see replication_task spawn_blocking investigation).
+        // Server IDs of entities created so far, by the client's temporary ID.
+        let mut temp_ids: HashMap<String, u64> = HashMap::new();
 
         for (idx, change) in request.changes.iter().enumerate() {
-            match change.kind.as_str() {
-                "create" => match change.entity_type.as_str() {
-                    "node" => {
-                        let labels: Vec<&str> = change
-                            .labels
-                            .as_deref()
-                            .unwrap_or(&[])
-                            .iter()
-                            .map(String::as_str)
-                            .collect();
-                        let new_id = if let Some(after) = &change.after {
-                            let props: Vec<(String, grafeo_common::types::Value)> =
-                                json_to_props(after).collect();
-                            let props_refs: Vec<(&str, grafeo_common::types::Value)> =
-                                props.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
-                            db.create_node_with_props(&labels, props_refs)
-                        } else {
-                            db.create_node(&labels)
+            if !request.atomic {
+                session
+                    .savepoint(CHANGE_SAVEPOINT)
+                    .map_err(|e| ServiceError::Internal(e.to_string()))?;
+            }
+
+            let outcome = match resolve_refs(change, &temp_ids) {
+                Ok(refs) => Applier {
+                    entry: &entry,
+                    db,
+                    session: &session,
+                    replicated,
+                    policy: &policy,
+                    last_seen_epoch: request.last_seen_epoch,
+                }
+                .apply(change, &refs),
+                Err(reason) => Outcome::Rejected(reason),
+            };
+            let failed = matches!(
+                outcome,
+                Outcome::Rejected(_) | Outcome::Conflicted(_) | Outcome::Failed(_)
+            );
+            let conflict = match outcome {
+                Outcome::Applied(created) => {
+                    if let Some(server_id) = created {
+                        let temp_id = match &change.id {
+                            Some(EntityRef::Temp(temp)) => Some(temp.clone()),
+                            _ => None,
                         };
-                        id_mappings.push(IdMapping {
-                            request_index: idx,
-                            server_id: new_id.as_u64(),
-                        });
-                        applied += 1;
-                    }
-                    "edge" => match (change.src_id, change.dst_id, &change.edge_type) {
-                        (Some(src), Some(dst), Some(et)) => {
-                            let new_id = if let Some(after) = &change.after {
-                                let props: Vec<(String, grafeo_common::types::Value)> =
-                                    json_to_props(after).collect();
-                                let props_refs: Vec<(&str, grafeo_common::types::Value)> =
-                                    props.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
-                                db.create_edge_with_props(
-                                    NodeId::new(src),
-                                    NodeId::new(dst),
-                                    et,
-                                    props_refs,
-                                )
-                            } else {
-                                db.create_edge(NodeId::new(src), NodeId::new(dst), et)
-                            };
-                            id_mappings.push(IdMapping {
-                                request_index: idx,
-                                server_id: new_id.as_u64(),
-                            });
-                            applied += 1;
-                        }
-                        _ => {
-                            conflicts.push(ConflictRecord {
-                                request_index: idx,
-                                reason: "edge_create_missing_src_dst_or_type".to_string(),
-                            });
+                        if let Some(temp) = &temp_id {
+                            temp_ids.insert(temp.clone(), server_id);
                         }
-                    },
-                    _ => {
-                        conflicts.push(ConflictRecord {
+                        id_mappings.push(IdMapping {
                             request_index: idx,
-                            reason: format!("unknown_entity_type:{}", change.entity_type),
+                            server_id,
+                            temp_id,
                         });
                     }
-                },
-
-                "update" => {
-                    let raw_id = match change.id {
-                        Some(id) => id,
-                        None => {
-                            conflicts.push(ConflictRecord {
-                                request_index: idx,
-                                reason: "update_missing_id".to_string(),
-                            });
-                            continue;
-                        }
-                    };
-
-                    // CRDT path: apply counter operation directly, bypassing LWW.
-                    if let (Some(op), Some(prop_key)) = (&change.crdt_op, &change.crdt_property) {
-                        match change.entity_type.as_str() {
-                            "node" => {
-                                let node_id = NodeId::new(raw_id);
-                                let current = db
-                                    .get_node(node_id)
-                                    .and_then(|n| n.get_property(prop_key).cloned())
-                                    .unwrap_or(grafeo_common::types::Value::Null);
-                                let merged = crate::crdt::apply_op(&current, op);
-                                db.set_node_property(node_id, prop_key, merged);
-                                applied += 1;
-                            }
-                            "edge" => {
-                                let edge_id = EdgeId::new(raw_id);
-                                let current = db
-                                    .get_edge(edge_id)
-                                    .and_then(|e| e.get_property(prop_key).cloned())
-                                    .unwrap_or(grafeo_common::types::Value::Null);
-                                let merged = crate::crdt::apply_op(&current, op);
-                                db.set_edge_property(edge_id, prop_key, merged);
-                                applied += 1;
-                            }
-                            _ => {
-                                conflicts.push(ConflictRecord {
-                                    request_index: idx,
-                                    reason: format!("unknown_entity_type:{}", change.entity_type),
-                                });
-                            }
-                        }
-                        continue;
-                    }
-
-                    // LWW path: apply `after` properties with timestamp conflict check.
-                    let after = match &change.after {
-                        Some(v) => v,
-                        None => {
-                            conflicts.push(ConflictRecord {
-                                request_index: idx,
-                                reason: "update_missing_after".to_string(),
-                            });
-                            continue;
-                        }
-                    };
-
-                    match change.entity_type.as_str() {
-                        "node" => {
-                            let node_id = NodeId::new(raw_id);
-                            if server_is_newer(
-                                db,
-                                grafeo_engine::cdc::EntityId::Node(node_id),
-                                change.timestamp,
-                            ) {
-                                conflicts.push(ConflictRecord {
-                                    request_index: idx,
-                                    reason: "server_newer".to_string(),
-                                });
-                                skipped += 1;
-                            } else {
-                                for (key, val) in json_to_props(after) {
-                                    db.set_node_property(node_id, &key, val);
-                                }
-                                applied += 1;
-                            }
-                        }
-                        "edge" => {
-                            let edge_id = EdgeId::new(raw_id);
-                            if server_is_newer(
-                                db,
-                                grafeo_engine::cdc::EntityId::Edge(edge_id),
-                                change.timestamp,
-                            ) {
-                                conflicts.push(ConflictRecord {
-                                    request_index: idx,
-                                    reason: "server_newer".to_string(),
-                                });
-                                skipped += 1;
-                            } else {
-                                for (key, val) in json_to_props(after) {
-                                    db.set_edge_property(edge_id, &key, val);
-                                }
-                                applied += 1;
-                            }
-                        }
-                        _ => {
-                            conflicts.push(ConflictRecord {
-                                request_index: idx,
-                                reason: format!("unknown_entity_type:{}", change.entity_type),
-                            });
-                        }
+                    applied += 1;
+                    None
+                }
+                Outcome::Resolved(conflict) => {
+                    applied += 1;
+                    Some(*conflict)
+                }
+                Outcome::Skipped(conflict) => {
+                    skipped += 1;
+                    Some(*conflict)
+                }
+                Outcome::Conflicted(conflict) => Some(*conflict),
+                Outcome::Rejected(reason) => Some(ConflictRecord::new(idx, reason)),
+                Outcome::Failed(reason) => {
+                    if !request.atomic {
+                        session
+                            .rollback_to_savepoint(CHANGE_SAVEPOINT)
+                            .map_err(|e| ServiceError::Internal(e.to_string()))?;
                     }
+                    Some(ConflictRecord::new(idx, reason))
                 }
+            };
 
-                "delete" => {
-                    let raw_id = match change.id {
-                        Some(id) => id,
-                        None => {
-                            conflicts.push(ConflictRecord {
-                                request_index: idx,
-                                reason: "delete_missing_id".to_string(),
-                            });
-                            continue;
-                        }
-                    };
+            if !request.atomic {
+                session
+                    .release_savepoint(CHANGE_SAVEPOINT)
+                    .map_err(|e| ServiceError::Internal(e.to_string()))?;
+            }
 
-                    match change.entity_type.as_str() {
-                        "node" => {
-                            let node_id = NodeId::new(raw_id);
-                            if server_is_newer(
-                                db,
-                                grafeo_engine::cdc::EntityId::Node(node_id),
-                                change.timestamp,
-                            ) {
-                                conflicts.push(ConflictRecord {
-                                    request_index: idx,
-                                    reason: "server_newer".to_string(),
-                                });
-                                skipped += 1;
-                            } else {
-                                db.delete_node(node_id);
-                                applied += 1;
-                            }
-                        }
-                        "edge" => {
-                            let edge_id = EdgeId::new(raw_id);
-                            if server_is_newer(
-                                db,
-                                grafeo_engine::cdc::EntityId::Edge(edge_id),
-                                change.timestamp,
-                            ) {
-                                conflicts.push(ConflictRecord {
-                                    request_index: idx,
-                                    reason: "server_newer".to_string(),
-                                });
-                                skipped += 1;
-                            } else {
-                                db.delete_edge(edge_id);
-                                applied += 1;
-                            }
-                        }
-                        _ => {
-                            conflicts.push(ConflictRecord {
-                                request_index: idx,
-                                reason: format!("unknown_entity_type:{}", change.entity_type),
-                            });
-                        }
-                    }
-                }
+            if let Some(conflict) = conflict {
+                conflicts.push(ConflictRecord {
+                    request_index: idx,
+                    ..conflict
+                });
+            }
+            if failed && request.atomic {
+                rolled_back = true;
+                break;
+            }
+        }
 
-                _ => {
-                    conflicts.push(ConflictRecord {
-                        request_index: idx,
-                        reason: format!("unknown_kind:{}", change.kind),
-                    });
-                }
+        if rolled_back {
+            session
+                .rollback()
+                .map_err(|e| ServiceError::Internal(e.to_string()))?;
+            applied = 0;
+            id_mappings.clear();
+        } else {
+            session
+                .commit()
+                .map_err(|e| ServiceError::Internal(e.to_string()))?;
+        }
+
+        let server_epoch = db.current_epoch().0;
+        if let Some(state) = conflict_state.filter(|_| policy.log_conflicts) {
+            let logged: Vec<_> = conflicts
+                .iter()
+                .filter(|c| c.policy.is_some())
+                .map(|c| LoggedConflict {
+                    id: 0,
+                    recorded_at: 0,
+                    client_id: request.client_id.clone(),
+                    epoch: server_epoch,
+                    rolled_back,
+                    conflict: c.clone(),
+                    change: request.changes[c.request_index].clone(),
+                })
+                .collect();
+            if !logged.is_empty() {
+                state.record(logged);
             }
         }
 
@@ -564,38 +897,628 @@ impl SyncService {
             .is_some_and(|cv| cv != server_schema);
 
         Ok(SyncResponse {
-            server_epoch: db.current_epoch().0,
+            server_epoch,
             applied,
             skipped,
             conflicts,
             id_mappings,
             schema_mismatch,
             server_schema_version: server_schema,
+            rolled_back,
         })
     }
 }
 
 // ---------------------------------------------------------------------------
-// Helpers
+// Apply
 // ---------------------------------------------------------------------------
 
-/// Returns `true` if the server has a CDC event for `entity_id` with a
-/// `timestamp` strictly greater than `client_timestamp`.
+/// Savepoint taken before each change of a best-effort apply.
+const CHANGE_SAVEPOINT: &str = "sync_change";
+
+/// Result of applying a single change.
+enum Outcome {
+    /// The change was applied; carries the server ID of a created entity.
+    Applied(Option<u64>),
+    /// The change conflicted and was applied, in full or in part.
+    Resolved(Box<ConflictRecord>),
+    /// The change lost a conflict against a server change.
+    Skipped(Box<ConflictRecord>),
+    /// The change conflicted and the `reject` policy refused it.
+    Conflicted(Box<ConflictRecord>),
+    /// The change is invalid or targets a missing entity; nothing was written.
+    Rejected(String),
+    /// The engine failed while applying the change.
+    Failed(String),
+}
+
+/// Applies changes through an engine session with an open transaction.
 ///
-/// When there is no CDC history for the entity (e.g., it was created via a
-/// GQL session, which does not record to the CDC log), the function returns
-/// `false` — the client change is applied unconditionally.
-fn server_is_newer(
-    db: &grafeo_engine::GrafeoDB,
-    entity_id: grafeo_engine::cdc::EntityId,
-    client_timestamp: u64,
-) -> bool {
-    db.history(entity_id).is_ok_and(|events| {
-        let client_ts = grafeo_common::types::HlcTimestamp::from_u64(client_timestamp);
-        events.iter().any(|e| e.timestamp > client_ts)
+/// Existing entities are bound by ID with `UNWIND [$id] AS x`: the engine
+/// has no ID seek, but its write operators take a node ID from any column,
+/// so updates and deletes go through the query engine (and so the CDC log
+/// and the WAL) without scanning. Edges are reached by expanding from their
+/// source node bound the same way. Existence is checked beforehand through
+/// the session's direct lookups.
+struct Applier<'a> {
+    entry: &'a DatabaseEntry,
+    db: &'a grafeo_engine::GrafeoDB,
+    session: &'a grafeo_engine::Session,
+    /// Replaying a primary's events: keep their IDs and skip conflict checks.
+    replicated: bool,
+    policy: &'a SyncPolicy,
+    /// Server changes after this epoch were not seen by the client.
+    last_seen_epoch: u64,
+}
+
+impl Applier<'_> {
+    fn apply(&self, change: &SyncChangeRequest, refs: &Refs) -> Outcome {
+        match change.kind.as_str() {
+            "create" => match change.entity_type.as_str() {
+                "node" => self.create_node(change, refs.id),
+                "edge" => match (refs.src, refs.dst, &change.edge_type) {
+                    (Some(src), Some(dst), Some(et)) => {
+                        self.create_edge(change, refs.id, src, dst, et)
+                    }
+                    _ => Outcome::Rejected("edge_create_missing_src_dst_or_type".to_string()),
+                },
+                other => Outcome::Rejected(format!("unknown_entity_type:{other}")),
+            },
+
+            "update" => {
+                let Some(raw_id) = refs.id else {
+                    return Outcome::Rejected("update_missing_id".to_string());
+                };
+
+                // CRDT path: apply the operation directly, bypassing LWW.
+                if let (Some(op), Some(prop_key)) = (&change.crdt_op, &change.crdt_property) {
+                    return self.merge_crdt(change, raw_id, op, prop_key);
+                }
+
+                // LWW path: apply `after` properties with timestamp conflict check.
+                let Some(after) = &change.after else {
+                    return Outcome::Rejected("update_missing_after".to_string());
+                };
+                let Some(target) = self.locate(&change.entity_type, raw_id) else {
+                    return self.missing(&change.entity_type);
+                };
+                let mut props: Vec<_> = json_to_props(after).collect();
+                let conflict = match self.resolve(&target, change.timestamp, Some(&mut props)) {
+                    Ok(conflict) => conflict,
+                    Err(outcome) => return outcome,
+                };
+                for (key, value) in &mut props {
+                    if crate::crdt::is_crdt(value) {
+                        let current = self.property(&target, key);
+                        if crate::crdt::is_crdt(&current) {
+                            *value = crate::crdt::merge(&current, value);
+                        }
+                    }
+                }
+                let props = props_map(props);
+                let outcome = self.run(
+                    &format!("{} SET x += $props", target.pattern),
+                    target.params([("props", props)]),
+                    None,
+                );
+                resolved(outcome, conflict)
+            }
+
+            "delete" => {
+                let Some(raw_id) = refs.id else {
+                    return Outcome::Rejected("delete_missing_id".to_string());
+                };
+                let Some(target) = self.locate(&change.entity_type, raw_id) else {
+                    // Already gone: deletes are idempotent.
+                    return match self.missing(&change.entity_type) {
+                        Outcome::Rejected(_) => Outcome::Applied(None),
+                        other => other,
+                    };
+                };
+                let conflict = match self.resolve(&target, change.timestamp, None) {
+                    Ok(conflict) => conflict,
+                    Err(outcome) => return outcome,
+                };
+                let delete = if target.is_node() {
+                    "DETACH DELETE x"
+                } else {
+                    "DELETE x"
+                };
+                let outcome = self.run(
+                    &format!("{} {delete}", target.pattern),
+                    target.params([]),
+                    None,
+                );
+                resolved(outcome, conflict)
+            }
+
+            other => Outcome::Rejected(format!("unknown_kind:{other}")),
+        }
+    }
+
+    /// Creates a node; `id` is the primary's ID when replicating.
+    fn create_node(&self, change: &SyncChangeRequest, id: Option<u64>) -> Outcome {
+        let labels = change.labels.as_deref().unwrap_or(&[]);
+        let mut pattern = String::from("n");
+        for label in labels {
+            let Some(label) = quote(label) else {
+                return Outcome::Rejected(format!("invalid_label:{label}"));
+            };
+            pattern.push(':');
+            pattern.push_str(&label);
+        }
+        let (props, params) = match inline_props(change.after.as_ref()) {
+            Ok(inline) => inline,
+            Err(reason) => return Outcome::Rejected(reason),
+        };
+        // INSERT returns the created node whatever the RETURN clause says.
+        let insert = || {
+            self.run(
+                &format!("INSERT ({pattern}{props}) RETURN n"),
+                params,
+                Some("n"),
+            )
+        };
+        if !self.replicated {
+            return insert();
+        }
+        let Some(id) = id else {
+            return Outcome::Rejected("create_missing_id".to_string());
+        };
+        if self.session.get_node(NodeId::new(id)).is_some() {
+            return Outcome::Applied(Some(id));
+        }
+        let store = self.db.store();
+        let outcome = {
+            let _id_counters = self.entry.lock_id_counters();
+            let next = store.next_node_id();
+            store.set_next_node_id(id);
+            let outcome = insert();
+            store.set_next_node_id(next.max(id + 1));
+            outcome
+        };
+        expect_id(outcome, id)
+    }
+
+    /// Creates an edge; `id` is the primary's ID when replicating.
+    fn create_edge(
+        &self,
+        change: &SyncChangeRequest,
+        id: Option<u64>,
+        src: u64,
+        dst: u64,
+        et: &str,
+    ) -> Outcome {
+        if !self.session.node_exists(NodeId::new(src))
+            || !self.session.node_exists(NodeId::new(dst))
+        {
+            return Outcome::Rejected("edge_endpoint_not_found".to_string());
+        }
+        let Some(edge_type) = quote(et) else {
+            return Outcome::Rejected(format!("invalid_edge_type:{et}"));
+        };
+        let (props, mut params) = match inline_props(change.after.as_ref()) {
+            Ok(inline) => inline,
+            Err(reason) => return Outcome::Rejected(reason),
+        };
+        params.insert("src".to_string(), id_param(src));
+        params.insert("dst".to_string(), id_param(dst));
+        let insert = || {
+            self.run(
+                &format!(
+                    "UNWIND [$src] AS a UNWIND [$dst] AS b \
+                     INSERT (a)-[e:{edge_type}{props}]->(b) RETURN id(e)"
+                ),
+                params,
+                Some("e"),
+            )
+        };
+        if !self.replicated {
+            return insert();
+        }
+        let Some(id) = id else {
+            return Outcome::Rejected("create_missing_id".to_string());
+        };
+        if self.session.get_edge(EdgeId::new(id)).is_some() {
+            return Outcome::Applied(Some(id));
+        }
+        let store = self.db.store();
+        let outcome = {
+            let _id_counters = self.entry.lock_id_counters();
+            let next = store.next_edge_id();
+            store.set_next_edge_id(id);
+            let outcome = insert();
+            store.set_next_edge_id(next.max(id + 1));
+            outcome
+        };
+        expect_id(outcome, id)
+    }
+
+    /// Checks an update (with its properties) or a delete against the
+    /// server's changes to the entity and resolves a conflict with the
+    /// policy. Returns the conflict to report if the change, possibly with
+    /// fewer properties, is still applied, or the outcome if it is not.
+    fn resolve(
+        &self,
+        target: &Target,
+        timestamp: u64,
+        props: Option<&mut Vec<(String, grafeo_common::types::Value)>>,
+    ) -> Result<Option<ConflictRecord>, Outcome> {
+        if self.replicated {
+            return Ok(None);
+        }
+        let history = self.db.history(target.entity_id()).unwrap_or_default();
+        let client_ts = grafeo_common::types::HlcTimestamp::from_u64(timestamp);
+        // Whether a server change races the client's under a policy.
+        let races = |policy: ConflictPolicy, event: &grafeo_engine::cdc::ChangeEvent| {
+            if policy == ConflictPolicy::Lww {
+                event.timestamp > client_ts
+            } else {
+                event.epoch.0 > self.last_seen_epoch
+            }
+        };
+        let is_crdt =
+            |(_, value): &(String, grafeo_common::types::Value)| crate::crdt::is_crdt(value);
+
+        let (policy, props) = match (self.policy.policy, props) {
+            (ConflictPolicy::FieldMerge, Some(props)) => {
+                return self.merge_fields(target, props, &history, races);
+            }
+            (ConflictPolicy::FieldMerge, None) => (ConflictPolicy::Lww, None),
+            (policy, props) => (policy, props),
+        };
+        // CRDT values merge with the server's value rather than replace it,
+        // so only plain values conflict.
+        if !history.iter().any(|e| races(policy, e))
+            || props.as_ref().is_some_and(|p| p.iter().all(is_crdt))
+        {
+            return Ok(None);
+        }
+        let reason = match policy {
+            ConflictPolicy::Lww => "server_newer",
+            ConflictPolicy::ServerWins => "server_wins",
+            ConflictPolicy::ClientWins => "client_wins",
+            _ => "concurrent_change",
+        };
+        let conflict = self.conflict(target, reason, Vec::new(), &history);
+        match policy {
+            ConflictPolicy::ClientWins => Ok(Some(conflict)),
+            ConflictPolicy::Lww | ConflictPolicy::ServerWins => match props {
+                Some(props) => {
+                    props.retain(is_crdt);
+                    if props.is_empty() {
+                        Err(Outcome::Skipped(Box::new(conflict)))
+                    } else {
+                        Ok(Some(conflict))
+                    }
+                }
+                None => Err(Outcome::Skipped(Box::new(conflict))),
+            },
+            _ => Err(Outcome::Conflicted(Box::new(conflict))),
+        }
+    }
+
+    /// Resolves an update under `field_merge`: each property the server
+    /// changed too is resolved with its own policy.
+    fn merge_fields(
+        &self,
+        target: &Target,
+        props: &mut Vec<(String, grafeo_common::types::Value)>,
+        history: &[grafeo_engine::cdc::ChangeEvent],
+        races: impl Fn(ConflictPolicy, &grafeo_engine::cdc::ChangeEvent) -> bool,
+    ) -> Result<Option<ConflictRecord>, Outcome> {
+        let mut conflicting = Vec::new();
+        let mut rejected = false;
+        props.retain(|(key, value)| {
+            if crate::crdt::is_crdt(value) {
+                return true;
+            }
+            let policy = self.policy.field(key);
+            let changed = history
+                .iter()
+                .any(|e| races(policy, e) && e.after.as_ref().is_some_and(|a| a.contains_key(key)));
+            if !changed {
+                return true;
+            }
+            conflicting.push(key.clone());
+            match policy {
+                ConflictPolicy::ClientWins => true,
+                ConflictPolicy::Reject => {
+                    rejected = true;
+                    true
+                }
+                _ => false,
+            }
+        });
+        if conflicting.is_empty() {
+            return Ok(None);
+        }
+        conflicting.sort();
+        if rejected {
+            let conflict = self.conflict(target, "concurrent_change", conflicting, history);
+            return Err(Outcome::Conflicted(Box::new(conflict)));
+        }
+        let conflict = self.conflict(target, "field_merge", conflicting, history);
+        if props.is_empty() {
+            Err(Outcome::Skipped(Box::new(conflict)))
+        } else {
+            Ok(Some(conflict))
+        }
+    }
+
+    /// Describes a conflict with the server's side of it.
+    fn conflict(
+        &self,
+        target: &Target,
+        reason: &str,
+        properties: Vec<String>,
+        history: &[grafeo_engine::cdc::ChangeEvent],
+    ) -> ConflictRecord {
+        let properties_of = |props: &grafeo_common::types::PropertyMap| {
+            props
+                .iter()
+                .map(|(k, v)| (k.as_str().to_string(), v.clone()))
+                .collect::<HashMap<_, _>>()
+        };
+        let server_value = if target.is_node() {
+            self.session
+                .get_node(NodeId::new(target.id))
+                .map(|n| properties_of(&n.properties))
+        } else {
+            self.session
+                .get_edge(EdgeId::new(target.id))
+                .map(|e| properties_of(&e.properties))
+        };
+        ConflictRecord {
+            policy: Some(self.policy.policy),
+            entity_id: Some(target.id),
+            properties,
+            server_value: server_value.map(props_to_json),
+            server_epoch: history.iter().map(|e| e.epoch.0).max(),
+            ..ConflictRecord::new(0, reason.to_string())
+        }
+    }
+
+    fn merge_crdt(
+        &self,
+        change: &SyncChangeRequest,
+        raw_id: u64,
+        op: &CrdtOp,
+        prop_key: &str,
+    ) -> Outcome {
+        let Some(target) = self.locate(&change.entity_type, raw_id) else {
+            return self.missing(&change.entity_type);
+        };
+        let merged = crate::crdt::apply_op(&self.property(&target, prop_key), op);
+        let props = props_map([(prop_key.to_string(), merged)]);
+        self.run(
+            &format!("{} SET x += $props", target.pattern),
+            target.params([("props", props)]),
+            None,
+        )
+    }
+
+    /// Reads a property of an entity, as seen by the transaction.
+    fn property(&self, target: &Target, key: &str) -> grafeo_common::types::Value {
+        let value = if target.is_node() {
+            self.session
+                .get_node(NodeId::new(target.id))
+                .and_then(|n| n.get_property(key).cloned())
+        } else {
+            self.session
+                .get_edge(EdgeId::new(target.id))
+                .and_then(|e| e.get_property(key).cloned())
+        };
+        value.unwrap_or(grafeo_common::types::Value::Null)
+    }
+
+    /// Resolves an existing node or edge to a pattern binding it to `x`.
+    fn locate(&self, entity_type: &str, raw_id: u64) -> Option<Target> {
+        match entity_type {
+            "node" => self
+                .session
+                .node_exists(NodeId::new(raw_id))
+                .then_some(Target {
+                    pattern: "UNWIND [$id] AS x",
+                    id: raw_id,
+                    src: None,
+                }),
+            "edge" => {
+                let edge = self.session.get_edge(EdgeId::new(raw_id))?;
+                Some(Target {
+                    pattern: "UNWIND [$src] AS a MATCH (a)-[x]->() WHERE id(x) = $id",
+                    id: raw_id,
+                    src: Some(edge.src.as_u64()),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The outcome for a change whose target entity could not be found.
+    fn missing(&self, entity_type: &str) -> Outcome {
+        match entity_type {
+            "node" | "edge" => Outcome::Rejected(format!("{entity_type}_not_found")),
+            other => Outcome::Rejected(format!("unknown_entity_type:{other}")),
+        }
+    }
+
+    /// Runs one mutation. With `created` set, the statement must produce
+    /// the new entity bound to that variable, and its ID is returned.
+    fn run(
+        &self,
+        statement: &str,
+        params: HashMap<String, grafeo_common::types::Value>,
+        created: Option<&str>,
+    ) -> Outcome {
+        let result = match self.session.execute_with_params(statement, params) {
+            Ok(result) => result,
+            Err(e) => return Outcome::Failed(format!("engine_error:{e}")),
+        };
+        let Some(var) = created else {
+            return Outcome::Applied(None);
+        };
+        match result.rows().first().and_then(|row| row.first()) {
+            Some(value) => match returned_id(value) {
+                Some(id) => Outcome::Applied(Some(id)),
+                None => Outcome::Failed(format!("engine_error:no id returned for {var}")),
+            },
+            // No endpoints were bound, so nothing was created.
+            None => Outcome::Rejected("edge_endpoint_not_found".to_string()),
+        }
+    }
+}
+
+/// Reports an applied change that conflicted as resolved.
+fn resolved(outcome: Outcome, conflict: Option<ConflictRecord>) -> Outcome {
+    match (outcome, conflict) {
+        (Outcome::Applied(_), Some(conflict)) => Outcome::Resolved(Box::new(conflict)),
+        (outcome, _) => outcome,
+    }
+}
+
+/// A change's entity references, with temporary IDs resolved. For a create
+/// tagged with a temporary ID, `id` is `None`.
+struct Refs {
+    id: Option<u64>,
+    src: Option<u64>,
+    dst: Option<u64>,
+}
+
+/// Resolves the temporary IDs in a change against the entities created
+/// earlier in the request.
+fn resolve_refs(
+    change: &SyncChangeRequest,
+    temp_ids: &HashMap<String, u64>,
+) -> Result<Refs, String> {
+    let lookup = |r: &Option<EntityRef>| match r {
+        None => Ok(None),
+        Some(EntityRef::Id(id)) => Ok(Some(*id)),
+        Some(EntityRef::Temp(temp)) => temp_ids
+            .get(temp)
+            .map(|id| Some(*id))
+            .ok_or_else(|| format!("unknown_temp_id:{temp}")),
+    };
+    let id = match &change.id {
+        Some(EntityRef::Temp(temp)) if change.kind == "create" => {
+            if temp_ids.contains_key(temp) {
+                return Err(format!("duplicate_temp_id:{temp}"));
+            }
+            None
+        }
+        id => lookup(id)?,
+    };
+    Ok(Refs {
+        id,
+        src: lookup(&change.src_id)?,
+        dst: lookup(&change.dst_id)?,
     })
 }
 
+/// Checks that a replicated create got the primary's ID.
+fn expect_id(outcome: Outcome, id: u64) -> Outcome {
+    match outcome {
+        Outcome::Applied(Some(created)) if created != id => {
+            Outcome::Failed(format!("id_mismatch:{id}:{created}"))
+        }
+        other => other,
+    }
+}
+
+/// An existing entity, located for a statement that binds it to `x`.
+struct Target {
+    pattern: &'static str,
+    id: u64,
+    /// Source node of an edge.
+    src: Option<u64>,
+}
+
+impl Target {
+    fn is_node(&self) -> bool {
+        self.src.is_none()
+    }
+
+    fn entity_id(&self) -> grafeo_engine::cdc::EntityId {
+        if self.is_node() {
+            grafeo_engine::cdc::EntityId::Node(NodeId::new(self.id))
+        } else {
+            grafeo_engine::cdc::EntityId::Edge(EdgeId::new(self.id))
+        }
+    }
+
+    fn params<const N: usize>(
+        &self,
+        extra: [(&str, grafeo_common::types::Value); N],
+    ) -> HashMap<String, grafeo_common::types::Value> {
+        let mut params: HashMap<String, grafeo_common::types::Value> =
+            extra.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+        params.insert("id".to_string(), id_param(self.id));
+        if let Some(src) = self.src {
+            params.insert("src".to_string(), id_param(src));
+        }
+        params
+    }
+}
+
+/// Quotes a label, edge type or property key as a GQL identifier. Names
+/// containing a backtick cannot be quoted.
+fn quote(name: &str) -> Option<String> {
+    (!name.is_empty() && !name.contains('`')).then(|| format!("`{name}`"))
+}
+
+/// Builds an inline ` {key: $pN, ...}` property map for an INSERT, with
+/// its parameters. Inline maps (unlike `SET x += $map` on a new entity)
+/// are recorded in the CDC log.
+fn inline_props(
+    after: Option<&serde_json::Value>,
+) -> Result<(String, HashMap<String, grafeo_common::types::Value>), String> {
+    let mut params = HashMap::new();
+    let mut entries = Vec::new();
+    for (i, (key, value)) in after.map(json_to_props).into_iter().flatten().enumerate() {
+        let quoted = quote(&key).ok_or_else(|| format!("invalid_property_key:{key}"))?;
+        entries.push(format!("{quoted}: $p{i}"));
+        params.insert(format!("p{i}"), value);
+    }
+    let inline = if entries.is_empty() {
+        String::new()
+    } else {
+        format!(" {{{}}}", entries.join(", "))
+    };
+    Ok((inline, params))
+}
+
+fn props_map(
+    props: impl IntoIterator<Item = (String, grafeo_common::types::Value)>,
+) -> grafeo_common::types::Value {
+    let map: std::collections::BTreeMap<_, _> = props
+        .into_iter()
+        .map(|(k, v)| (grafeo_common::types::PropertyKey::new(k), v))
+        .collect();
+    grafeo_common::types::Value::Map(std::sync::Arc::new(map))
+}
+
+fn id_param(id: u64) -> grafeo_common::types::Value {
+    grafeo_common::types::Value::Int64(id as i64)
+}
+
+/// Reads an entity ID from a returned `id(x)` or from a returned node map.
+fn returned_id(value: &grafeo_common::types::Value) -> Option<u64> {
+    match value {
+        grafeo_common::types::Value::Int64(id) => Some(*id as u64),
+        grafeo_common::types::Value::Map(map) => {
+            match map.get(&grafeo_common::types::PropertyKey::new("_id")) {
+                Some(grafeo_common::types::Value::Int64(id)) => Some(*id as u64),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
 /// Computes a stable schema version string for `db`.
 ///
 /// Collects all node label names and property key names, sorts them, and
@@ -690,11 +1613,30 @@ mod tests {
     use crate::database::DatabaseManager;
 
     fn make_manager() -> DatabaseManager {
-        let mut mgr = DatabaseManager::new(None, false);
+        let mgr = DatabaseManager::new(None, false);
         mgr.set_cdc_enabled(true);
         mgr
     }
 
+    #[test]
+    fn change_cursor_skips_delivered_events() {
+        let event = |epoch: u64, id: u64| -> ChangeEventDto {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "entity_type": "node", "kind": "create",
+                "epoch": epoch, "timestamp": id,
+            }))
+            .unwrap()
+        };
+        let mut cursor = ChangeCursor::new(5);
+        assert!(cursor.advance(&event(5, 1)));
+        assert!(cursor.advance(&event(6, 2)));
+        // A repeated pull of epoch 6 and an older event are dropped
+        assert!(!cursor.advance(&event(6, 2)));
+        assert!(!cursor.advance(&event(5, 1)));
+        assert!(cursor.advance(&event(6, 3)));
+        assert_eq!(cursor.epoch(), 6);
+    }
+
     #[test]
     fn pull_without_cdc_returns_error() {
         let mgr = DatabaseManager::new(None, false);
@@ -763,6 +1705,133 @@ mod tests {
         assert!(empty.changes.is_empty());
     }
 
+    #[test]
+    fn pull_matching_applies_filter_and_limit() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        for _ in 0..3 {
+            entry.db().create_node(&["Person"]);
+            entry.db().create_node(&["City"]);
+        }
+        let filter = ChangeFilter {
+            labels: vec!["Person".into()],
+            ..ChangeFilter::default()
+        };
+
+        let all =
+            SyncService::pull_matching(&mgr, "default", 0, 1000, &filter, &Identity::anonymous())
+                .unwrap();
+        assert_eq!(all.changes.len(), 3);
+        assert!(
+            all.changes
+                .iter()
+                .all(|e| e.labels == Some(vec!["Person".into()]))
+        );
+        assert_eq!(all.server_epoch, entry.db().current_epoch().0);
+
+        let first =
+            SyncService::pull_matching(&mgr, "default", 0, 2, &filter, &Identity::anonymous())
+                .unwrap();
+        assert_eq!(first.changes.len(), 2);
+        assert_eq!(first.server_epoch, first.changes[1].epoch);
+    }
+
+    #[test]
+    fn pull_matching_rejects_invalid_predicate() {
+        let mgr = make_manager();
+        let filter = ChangeFilter {
+            predicate: Some("after.name =".into()),
+            ..ChangeFilter::default()
+        };
+        let err =
+            SyncService::pull_matching(&mgr, "default", 0, 10, &filter, &Identity::anonymous())
+                .unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest(_)));
+    }
+
+    #[test]
+    fn pull_complete_stops_at_epoch_boundary() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        let session = entry.db().session();
+        for name in ["a", "b", "c"] {
+            session
+                .execute(&format!("INSERT (:Thing {{name: '{name}'}})"))
+                .unwrap();
+        }
+
+        let all = SyncService::pull(&mgr, "default", 0, 1000).unwrap();
+
+        // Each insert spans one epoch; a limit of 2 stops after the first.
+        let first = SyncService::pull_complete(&mgr, "default", 0, 2).unwrap();
+        assert!(!first.changes.is_empty());
+        assert!(first.changes.iter().all(|c| c.epoch == first.server_epoch));
+        assert!(first.server_epoch < all.server_epoch);
+
+        let rest =
+            SyncService::pull_complete(&mgr, "default", first.server_epoch + 1, 1000).unwrap();
+        assert_eq!(first.changes.len() + rest.changes.len(), all.changes.len());
+    }
+
+    #[test]
+    fn pull_complete_never_splits_an_epoch() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        // Direct API calls all land in the same epoch.
+        for _ in 0..5 {
+            entry.db().create_node(&["Thing"]);
+        }
+        let resp = SyncService::pull_complete(&mgr, "default", 0, 3).unwrap();
+        assert_eq!(resp.changes.len(), 5);
+    }
+
+    #[test]
+    fn pull_matching_complete_returns_oversized_epochs_whole() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        // Direct API calls all land in the same epoch, which spans more
+        // than one filter chunk.
+        for _ in 0..FILTER_CHUNK + 200 {
+            entry.db().create_node(&["Person"]);
+        }
+        entry.db().create_node(&["City"]);
+        entry
+            .db()
+            .session()
+            .execute("INSERT (:Person {name: 'later'})")
+            .unwrap();
+        let filter = ChangeFilter {
+            labels: vec!["Person".into()],
+            ..ChangeFilter::default()
+        };
+
+        let first = SyncService::pull_matching_complete(
+            &mgr,
+            "default",
+            0,
+            3,
+            &filter,
+            &Identity::anonymous(),
+        )
+        .unwrap();
+        assert!(first.complete);
+        assert_eq!(first.changes.len(), FILTER_CHUNK + 200);
+        assert!(first.changes.iter().all(|c| c.epoch == first.server_epoch));
+
+        let rest = SyncService::pull_matching_complete(
+            &mgr,
+            "default",
+            first.server_epoch + 1,
+            3,
+            &filter,
+            &Identity::anonymous(),
+        )
+        .unwrap();
+        assert!(!rest.changes.is_empty());
+        assert!(rest.changes.iter().all(|c| c.epoch > first.server_epoch));
+        assert_eq!(rest.server_epoch, entry.db().current_epoch().0);
+    }
+
     #[test]
     fn change_event_dto_serializes_cleanly() {
         let mgr = make_manager();
@@ -789,6 +1858,7 @@ mod tests {
             last_seen_epoch: 0,
             changes: vec![],
             schema_version: None,
+            atomic: false,
         };
         let err = SyncService::apply(&mgr, "nonexistent", req).unwrap_err();
         assert!(matches!(err, ServiceError::NotFound(_)));
@@ -814,6 +1884,7 @@ mod tests {
                 crdt_property: None,
             }],
             schema_version: None,
+            atomic: false,
         };
         let resp = SyncService::apply(&mgr, "default", req).unwrap();
         assert_eq!(resp.applied, 1);
@@ -836,7 +1907,7 @@ mod tests {
             changes: vec![SyncChangeRequest {
                 kind: "update".to_string(),
                 entity_type: "node".to_string(),
-                id: Some(node.as_u64()),
+                id: Some(node.as_u64().into()),
                 timestamp: u64::MAX, // very new — no server conflict
                 labels: None,
                 edge_type: None,
@@ -847,6 +1918,7 @@ mod tests {
                 crdt_property: None,
             }],
             schema_version: None,
+            atomic: false,
         };
         let resp = SyncService::apply(&mgr, "default", req).unwrap();
         assert_eq!(resp.applied, 1);
@@ -872,7 +1944,7 @@ mod tests {
             changes: vec![SyncChangeRequest {
                 kind: "delete".to_string(),
                 entity_type: "node".to_string(),
-                id: Some(node.as_u64()),
+                id: Some(node.as_u64().into()),
                 timestamp: u64::MAX,
                 labels: None,
                 edge_type: None,
@@ -883,6 +1955,7 @@ mod tests {
                 crdt_property: None,
             }],
             schema_version: None,
+            atomic: false,
         };
         let resp = SyncService::apply(&mgr, "default", req).unwrap();
         assert_eq!(resp.applied, 1);
@@ -907,7 +1980,7 @@ mod tests {
             changes: vec![SyncChangeRequest {
                 kind: "update".to_string(),
                 entity_type: "node".to_string(),
-                id: Some(node.as_u64()),
+                id: Some(node.as_u64().into()),
                 timestamp: 0, // older than server's CDC event
                 labels: None,
                 edge_type: None,
@@ -918,6 +1991,7 @@ mod tests {
                 crdt_property: None,
             }],
             schema_version: None,
+            atomic: false,
         };
         let resp = SyncService::apply(&mgr, "default", req).unwrap();
         assert_eq!(resp.skipped, 1);
@@ -953,6 +2027,7 @@ mod tests {
             last_seen_epoch: 0,
             changes: vec![],
             schema_version: None,
+            atomic: false,
         };
         let resp = SyncService::apply(&mgr, "default", req).unwrap();
         // A version is always returned (non-empty hex string).
@@ -969,6 +2044,7 @@ mod tests {
             changes: vec![],
             // Deliberately wrong version.
             schema_version: Some("0000000000000000".to_string()),
+            atomic: false,
         };
         let resp = SyncService::apply(&mgr, "default", req).unwrap();
         // An empty DB may actually hash to the stale value, so we just check
@@ -993,6 +2069,7 @@ mod tests {
             last_seen_epoch: 0,
             changes: vec![],
             schema_version: None,
+            atomic: false,
         };
         let empty_resp = SyncService::apply(&mgr, "default", empty_req).unwrap();
         let version_before = empty_resp.server_schema_version.clone();
@@ -1006,6 +2083,7 @@ mod tests {
             last_seen_epoch: 0,
             changes: vec![],
             schema_version: Some(version_before),
+            atomic: false,
         };
         let stale_resp = SyncService::apply(&mgr, "default", stale_req).unwrap();
         assert!(
@@ -1014,6 +2092,607 @@ mod tests {
         );
     }
 
+    fn create_person(name: &str) -> SyncChangeRequest {
+        SyncChangeRequest {
+            kind: "create".to_string(),
+            entity_type: "node".to_string(),
+            id: None,
+            timestamp: 0,
+            labels: Some(vec!["Person".to_string()]),
+            edge_type: None,
+            src_id: None,
+            dst_id: None,
+            after: Some(serde_json::json!({ "name": { "String": name } })),
+            crdt_op: None,
+            crdt_property: None,
+        }
+    }
+
+    fn update_missing_node() -> SyncChangeRequest {
+        SyncChangeRequest {
+            kind: "update".to_string(),
+            id: Some(999_999.into()),
+            timestamp: u64::MAX,
+            labels: None,
+            ..create_person("Gus")
+        }
+    }
+
+    #[test]
+    fn apply_atomic_rolls_back_on_failure() {
+        let mgr = make_manager();
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![create_person("Alix"), update_missing_node()],
+            schema_version: None,
+            atomic: true,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert!(resp.rolled_back);
+        assert_eq!(resp.applied, 0);
+        assert!(resp.id_mappings.is_empty());
+        assert_eq!(resp.conflicts.len(), 1);
+        assert_eq!(resp.conflicts[0].request_index, 1);
+        assert_eq!(resp.conflicts[0].reason, "node_not_found");
+
+        let entry = mgr.get("default").unwrap();
+        assert_eq!(entry.db().node_count(), 0);
+        let changes = SyncService::pull(&mgr, "default", 0, 1000).unwrap();
+        assert!(changes.changes.is_empty());
+    }
+
+    #[test]
+    fn apply_best_effort_skips_failed_changes() {
+        let mgr = make_manager();
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![
+                create_person("Alix"),
+                update_missing_node(),
+                create_person("Gus"),
+            ],
+            schema_version: None,
+            atomic: false,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert!(!resp.rolled_back);
+        assert_eq!(resp.applied, 2);
+        assert_eq!(resp.id_mappings.len(), 2);
+        assert_eq!(resp.conflicts.len(), 1);
+        assert_eq!(resp.conflicts[0].request_index, 1);
+
+        let entry = mgr.get("default").unwrap();
+        assert_eq!(entry.db().node_count(), 2);
+    }
+
+    #[test]
+    fn apply_commits_changeset_in_one_epoch() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        let existing = entry.db().create_node(&["Person"]);
+        let since = entry.db().current_epoch().0 + 1;
+
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![
+                create_person("Alix"),
+                SyncChangeRequest {
+                    kind: "update".to_string(),
+                    id: Some(existing.as_u64().into()),
+                    timestamp: u64::MAX,
+                    labels: None,
+                    ..create_person("Gus")
+                },
+            ],
+            schema_version: None,
+            atomic: true,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert_eq!(resp.applied, 2);
+
+        let changes = SyncService::pull(&mgr, "default", since, 1000).unwrap();
+        // The create records its properties as a separate update event.
+        let created = resp.id_mappings[0].server_id;
+        let events: Vec<(&str, u64)> = changes
+            .changes
+            .iter()
+            .map(|c| (c.kind.as_str(), c.id))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                ("create", created),
+                ("update", created),
+                ("update", existing.as_u64())
+            ]
+        );
+        assert!(changes.changes.iter().all(|c| c.epoch == resp.server_epoch));
+    }
+
+    #[test]
+    fn apply_edge_create_rejects_missing_endpoint() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        let src = entry.db().create_node(&["Person"]);
+
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![SyncChangeRequest {
+                kind: "create".to_string(),
+                entity_type: "edge".to_string(),
+                id: None,
+                timestamp: 0,
+                labels: None,
+                edge_type: Some("KNOWS".to_string()),
+                src_id: Some(src.as_u64().into()),
+                dst_id: Some(999_999.into()),
+                after: None,
+                crdt_op: None,
+                crdt_property: None,
+            }],
+            schema_version: None,
+            atomic: false,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert_eq!(resp.applied, 0);
+        assert_eq!(resp.conflicts[0].reason, "edge_endpoint_not_found");
+        assert_eq!(entry.db().edge_count(), 0);
+    }
+
+    #[test]
+    fn apply_cost_does_not_grow_with_label_size() {
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        let db = entry.db();
+        let people: Vec<u64> = (0..100_000)
+            .map(|_| db.create_node(&["Person"]).as_u64())
+            .collect();
+        // The deleted node is not among the updated ones: with `temporal`,
+        // the engine trips a debug assertion when a transaction writes a
+        // node's properties and then detach-deletes it.
+        let knows = db.create_edge(
+            NodeId::new(people[99_900]),
+            NodeId::new(people[99_901]),
+            "KNOWS",
+        );
+        let likes = db.create_edge(
+            NodeId::new(people[99_998]),
+            NodeId::new(people[99_999]),
+            "LIKES",
+        );
+
+        // A scan of the label per change would take minutes here.
+        let mut changes = Vec::new();
+        for i in 0..50 {
+            let id = people[99_999 - i];
+            changes.push(update_props(
+                id,
+                u64::MAX,
+                serde_json::json!({ "n": { "Int64": i } }),
+            ));
+        }
+        changes.push(SyncChangeRequest {
+            entity_type: "edge".to_string(),
+            ..update_props(
+                likes.as_u64(),
+                u64::MAX,
+                serde_json::json!({ "w": { "Int64": 1 } }),
+            )
+        });
+        changes.push(SyncChangeRequest {
+            kind: "create".to_string(),
+            entity_type: "edge".to_string(),
+            id: None,
+            labels: None,
+            edge_type: Some("KNOWS".to_string()),
+            src_id: Some(people[0].into()),
+            dst_id: Some(people[1].into()),
+            after: None,
+            ..create_person("Gus")
+        });
+        changes.push(SyncChangeRequest {
+            kind: "delete".to_string(),
+            after: None,
+            ..update_props(people[99_900], u64::MAX, serde_json::json!({}))
+        });
+        let started = std::time::Instant::now();
+        let resp = push(&mgr, "device-1", 0, changes, true);
+        let elapsed = started.elapsed();
+
+        assert_eq!(resp.applied, 53, "{:?}", resp.conflicts);
+        assert!(
+            elapsed < std::time::Duration::from_secs(5),
+            "53 changes took {elapsed:?}"
+        );
+        let node = db.get_node(NodeId::new(people[99_999])).unwrap();
+        assert!(
+            node.properties
+                .contains_key(&grafeo_common::types::PropertyKey::new("n"))
+        );
+        assert!(db.get_node(NodeId::new(people[99_900])).is_none());
+        assert!(db.get_edge(knows).is_none());
+        let likes = db.get_edge(likes).unwrap();
+        assert!(
+            likes
+                .properties
+                .contains_key(&grafeo_common::types::PropertyKey::new("w"))
+        );
+        assert_eq!(db.edge_count(), 2);
+    }
+
+    #[test]
+    fn apply_resolves_temp_ids() {
+        let mgr = make_manager();
+        // Wire format: temporary IDs are strings, server IDs are numbers.
+        let req: SyncRequest = serde_json::from_value(serde_json::json!({
+            "client_id": "device-1",
+            "changes": [
+                { "kind": "create", "entity_type": "node", "id": "alix", "labels": ["Person"] },
+                { "kind": "create", "entity_type": "node", "id": "gus", "labels": ["Person"] },
+                { "kind": "create", "entity_type": "edge", "id": "knows",
+                  "src_id": "alix", "dst_id": "gus", "edge_type": "KNOWS" },
+                { "kind": "update", "entity_type": "node", "id": "gus", "timestamp": u64::MAX,
+                  "after": { "name": { "String": "Gus" } } },
+            ],
+        }))
+        .unwrap();
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert!(resp.conflicts.is_empty(), "{:?}", resp.conflicts);
+        assert_eq!(resp.applied, 4);
+
+        let temp_ids: Vec<Option<&str>> = resp
+            .id_mappings
+            .iter()
+            .map(|m| m.temp_id.as_deref())
+            .collect();
+        assert_eq!(temp_ids, [Some("alix"), Some("gus"), Some("knows")]);
+
+        let entry = mgr.get("default").unwrap();
+        let edge = entry
+            .db()
+            .get_edge(EdgeId::new(resp.id_mappings[2].server_id))
+            .unwrap();
+        assert_eq!(edge.src.as_u64(), resp.id_mappings[0].server_id);
+        assert_eq!(edge.dst.as_u64(), resp.id_mappings[1].server_id);
+        let gus = entry.db().get_node(edge.dst).unwrap();
+        assert!(
+            gus.properties
+                .contains_key(&grafeo_common::types::PropertyKey::new("name"))
+        );
+    }
+
+    #[test]
+    fn apply_rejects_unknown_and_duplicate_temp_ids() {
+        let mgr = make_manager();
+        let tagged = |temp: &str| SyncChangeRequest {
+            id: Some(EntityRef::Temp(temp.to_string())),
+            ..create_person("Alix")
+        };
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![
+                tagged("alix"),
+                tagged("alix"),
+                SyncChangeRequest {
+                    kind: "update".to_string(),
+                    id: Some(EntityRef::Temp("vincent".to_string())),
+                    timestamp: u64::MAX,
+                    ..create_person("Vincent")
+                },
+            ],
+            schema_version: None,
+            atomic: false,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert_eq!(resp.applied, 1);
+        let reasons: Vec<&str> = resp.conflicts.iter().map(|c| c.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            ["duplicate_temp_id:alix", "unknown_temp_id:vincent"]
+        );
+    }
+
+    #[test]
+    fn apply_merges_crdt_state_from_stale_updates() {
+        use crate::crdt::{apply_op, read_value};
+
+        let mgr = make_manager();
+        let entry = mgr.get("default").unwrap();
+        let node = entry.db().create_node(&["Person"]);
+        let add = |element: &str, replica_id: &str| CrdtOp::SetAdd {
+            element: grafeo_common::types::Value::from(element),
+            replica_id: replica_id.to_string(),
+        };
+        let update = |timestamp, after, crdt_op| SyncChangeRequest {
+            kind: "update".to_string(),
+            id: Some(node.as_u64().into()),
+            timestamp,
+            labels: None,
+            after,
+            crdt_op,
+            crdt_property: Some("tags".to_string()),
+            ..create_person("Gus")
+        };
+
+        // device-1 pushes an operation; device-2 pushes its whole set state
+        // along with a stale plain property.
+        let offline = apply_op(&grafeo_common::types::Value::Null, &add("blue", "device-2"));
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![
+                update(u64::MAX, None, Some(add("red", "device-1"))),
+                update(
+                    0,
+                    Some(serde_json::json!({
+                        "tags": serde_json::to_value(&offline).unwrap(),
+                        "name": { "String": "Stale" },
+                    })),
+                    None,
+                ),
+            ],
+            schema_version: None,
+            atomic: false,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert_eq!(resp.applied, 2, "conflicts: {:?}", resp.conflicts);
+
+        let node_data = entry.db().get_node(node).unwrap();
+        let tags = node_data
+            .properties
+            .get(&grafeo_common::types::PropertyKey::new("tags"))
+            .unwrap();
+        let grafeo_common::types::Value::List(elements) = read_value(tags) else {
+            panic!("expected a list, got {tags:?}");
+        };
+        let mut elements: Vec<_> = elements.iter().filter_map(|v| v.as_str()).collect();
+        elements.sort_unstable();
+        assert_eq!(elements, ["blue", "red"]);
+        let name_key = grafeo_common::types::PropertyKey::new("name");
+        assert!(node_data.properties.get(&name_key).is_none());
+    }
+
+    fn push(
+        mgr: &DatabaseManager,
+        client_id: &str,
+        last_seen_epoch: u64,
+        changes: Vec<SyncChangeRequest>,
+        atomic: bool,
+    ) -> SyncResponse {
+        let req = SyncRequest {
+            client_id: client_id.to_string(),
+            last_seen_epoch,
+            changes,
+            schema_version: None,
+            atomic,
+        };
+        SyncService::apply(mgr, "default", req).unwrap()
+    }
+
+    fn update_props(id: u64, timestamp: u64, after: serde_json::Value) -> SyncChangeRequest {
+        SyncChangeRequest {
+            kind: "update".to_string(),
+            id: Some(id.into()),
+            timestamp,
+            labels: None,
+            after: Some(after),
+            ..create_person("Gus")
+        }
+    }
+
+    /// Creates a person, then has `device-2` rename it. Returns the node ID
+    /// and the epoch `device-1` last saw, before the rename.
+    fn renamed_by_other_client(mgr: &DatabaseManager, policy: SyncPolicy) -> (u64, u64) {
+        SyncService::set_policy(mgr, "default", policy).unwrap();
+        let created = push(mgr, "device-1", 0, vec![create_person("Alix")], false);
+        let id = created.id_mappings[0].server_id;
+        let rename = update_props(
+            id,
+            u64::MAX,
+            serde_json::json!({ "name": { "String": "Gus" } }),
+        );
+        let renamed = push(mgr, "device-2", created.server_epoch, vec![rename], false);
+        assert_eq!(renamed.applied, 1);
+        assert!(renamed.conflicts.is_empty());
+        (id, created.server_epoch)
+    }
+
+    fn name_of(mgr: &DatabaseManager, id: u64) -> Option<grafeo_common::types::Value> {
+        let node = mgr.get("default").unwrap().db().get_node(NodeId::new(id))?;
+        node.properties
+            .get(&grafeo_common::types::PropertyKey::new("name"))
+            .cloned()
+    }
+
+    #[test]
+    fn apply_server_wins_skips_unseen_conflict() {
+        let mgr = make_manager();
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::ServerWins,
+            log_conflicts: true,
+            ..SyncPolicy::default()
+        };
+        let (id, seen) = renamed_by_other_client(&mgr, policy);
+
+        // device-1 has not seen the rename; its later timestamp would win
+        // under LWW.
+        let stale = update_props(
+            id,
+            u64::MAX,
+            serde_json::json!({ "name": { "String": "Vincent" } }),
+        );
+        let resp = push(&mgr, "device-1", seen, vec![stale], false);
+        assert_eq!(resp.skipped, 1);
+        let conflict = &resp.conflicts[0];
+        assert_eq!(conflict.reason, "server_wins");
+        assert_eq!(conflict.policy, Some(ConflictPolicy::ServerWins));
+        assert_eq!(conflict.entity_id, Some(id));
+        assert_eq!(
+            conflict.server_value,
+            Some(serde_json::json!({ "name": { "String": "Gus" } }))
+        );
+        assert!(conflict.server_epoch.is_some_and(|e| e > seen));
+        assert_eq!(name_of(&mgr, id), Some("Gus".into()));
+
+        let log = SyncService::conflicts(&mgr, "default", 0, 100, None).unwrap();
+        assert_eq!(log.conflicts.len(), 1);
+        assert_eq!(log.conflicts[0].client_id, "device-1");
+        assert_eq!(log.conflicts[0].conflict.reason, "server_wins");
+        assert_eq!(log.conflicts[0].change.kind, "update");
+
+        // Once the client has seen the rename, its update applies.
+        let fresh = update_props(
+            id,
+            2,
+            serde_json::json!({ "name": { "String": "Vincent" } }),
+        );
+        let resp = push(&mgr, "device-1", resp.server_epoch, vec![fresh], false);
+        assert_eq!(resp.applied, 1);
+        assert!(resp.conflicts.is_empty());
+    }
+
+    #[test]
+    fn apply_client_wins_overwrites_and_reports() {
+        let mgr = make_manager();
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::ClientWins,
+            ..SyncPolicy::default()
+        };
+        let (id, seen) = renamed_by_other_client(&mgr, policy);
+
+        let stale = update_props(
+            id,
+            0,
+            serde_json::json!({ "name": { "String": "Vincent" } }),
+        );
+        let resp = push(&mgr, "device-1", seen, vec![stale], false);
+        assert_eq!(resp.applied, 1);
+        assert_eq!(resp.conflicts.len(), 1);
+        assert_eq!(resp.conflicts[0].reason, "client_wins");
+        assert_eq!(name_of(&mgr, id), Some("Vincent".into()));
+        // Logging is off by default.
+        let log = SyncService::conflicts(&mgr, "default", 0, 100, None).unwrap();
+        assert!(log.conflicts.is_empty());
+    }
+
+    #[test]
+    fn apply_reject_policy_rolls_back_atomic_push() {
+        let mgr = make_manager();
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::Reject,
+            ..SyncPolicy::default()
+        };
+        let (id, seen) = renamed_by_other_client(&mgr, policy);
+
+        let stale = update_props(
+            id,
+            u64::MAX,
+            serde_json::json!({ "name": { "String": "Vincent" } }),
+        );
+        let resp = push(
+            &mgr,
+            "device-1",
+            seen,
+            vec![create_person("Jules"), stale],
+            true,
+        );
+        assert!(resp.rolled_back);
+        assert_eq!(resp.conflicts.len(), 1);
+        assert_eq!(resp.conflicts[0].request_index, 1);
+        assert_eq!(resp.conflicts[0].reason, "concurrent_change");
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 1);
+        assert_eq!(name_of(&mgr, id), Some("Gus".into()));
+    }
+
+    #[test]
+    fn apply_field_merge_resolves_properties_separately() {
+        let mgr = make_manager();
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::FieldMerge,
+            fields: HashMap::from([("nickname".to_string(), ConflictPolicy::ServerWins)]),
+            log_conflicts: false,
+        };
+        let (id, seen) = renamed_by_other_client(&mgr, policy);
+        let nickname = update_props(id, 1, serde_json::json!({ "nickname": { "String": "G" } }));
+        let resp = push(&mgr, "device-2", seen, vec![nickname], false);
+        assert_eq!(resp.applied, 1);
+
+        // `name` loses under LWW, `nickname` under server-wins, and `age`
+        // was not changed on the server.
+        let stale = update_props(
+            id,
+            0,
+            serde_json::json!({
+                "name": { "String": "Vincent" },
+                "nickname": { "String": "V" },
+                "age": { "Int64": 30 },
+            }),
+        );
+        let resp = push(&mgr, "device-1", seen, vec![stale], false);
+        assert_eq!(resp.applied, 1);
+        assert_eq!(resp.conflicts.len(), 1);
+        assert_eq!(resp.conflicts[0].reason, "field_merge");
+        assert_eq!(resp.conflicts[0].properties, ["name", "nickname"]);
+
+        let node = mgr
+            .get("default")
+            .unwrap()
+            .db()
+            .get_node(NodeId::new(id))
+            .unwrap();
+        let prop = |key: &str| {
+            node.properties
+                .get(&grafeo_common::types::PropertyKey::new(key))
+                .cloned()
+        };
+        assert_eq!(prop("name"), Some("Gus".into()));
+        assert_eq!(prop("nickname"), Some("G".into()));
+        assert_eq!(prop("age"), Some(grafeo_common::types::Value::Int64(30)));
+    }
+
+    #[test]
+    fn policy_persists_with_database() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::Reject,
+            ..SyncPolicy::default()
+        };
+        {
+            let mgr = DatabaseManager::new(Some(path), false);
+            SyncService::set_policy(&mgr, "default", policy.clone()).unwrap();
+        }
+        let mgr = DatabaseManager::new(Some(path), false);
+        assert_eq!(SyncService::policy(&mgr, "default").unwrap(), policy);
+        assert!(matches!(
+            SyncService::policy(&mgr, "missing"),
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn apply_on_persistent_database() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mgr = DatabaseManager::new(Some(dir.path().to_str().unwrap()), false);
+        mgr.set_cdc_enabled(true);
+
+        let req = SyncRequest {
+            client_id: "device-1".to_string(),
+            last_seen_epoch: 0,
+            changes: vec![create_person("Alix"), create_person("Gus")],
+            schema_version: None,
+            atomic: true,
+        };
+        let resp = SyncService::apply(&mgr, "default", req).unwrap();
+        assert_eq!(resp.applied, 2);
+        assert_eq!(mgr.get("default").unwrap().db().node_count(), 2);
+    }
+
     #[test]
     fn pull_edge_creates_carry_src_dst_type() {
         let mgr = make_manager();
@@ -1069,11 +2748,11 @@ mod tests {
             .map(|dto| SyncChangeRequest {
                 kind: dto.kind,
                 entity_type: dto.entity_type,
-                id: Some(dto.id),
+                id: Some(dto.id.into()),
                 labels: dto.labels,
                 edge_type: dto.edge_type,
-                src_id: dto.src_id,
-                dst_id: dto.dst_id,
+                src_id: dto.src_id.map(EntityRef::from),
+                dst_id: dto.dst_id.map(EntityRef::from),
                 after: dto.after,
                 timestamp: dto.timestamp,
                 crdt_op: None,
@@ -1088,6 +2767,7 @@ mod tests {
             last_seen_epoch: 0,
             changes: sync_changes,
             schema_version: None,
+            atomic: false,
         };
         let apply_resp = SyncService::apply(&replica, "default", request).unwrap();
         assert!(
@@ -1149,11 +2829,11 @@ mod tests {
             .map(|dto| SyncChangeRequest {
                 kind: dto.kind,
                 entity_type: dto.entity_type,
-                id: Some(dto.id),
+                id: Some(dto.id.into()),
                 labels: dto.labels,
                 edge_type: dto.edge_type,
-                src_id: dto.src_id,
-                dst_id: dto.dst_id,
+                src_id: dto.src_id.map(EntityRef::from),
+                dst_id: dto.dst_id.map(EntityRef::from),
                 after: dto.after,
                 timestamp: dto.timestamp,
                 crdt_op: None,
@@ -1167,6 +2847,7 @@ mod tests {
             last_seen_epoch: 0,
             changes: sync_changes,
             schema_version: None,
+            atomic: false,
         };
         let apply_resp = SyncService::apply(&replica, "default", request).unwrap();
         assert!(
@@ -1181,6 +2862,168 @@ mod tests {
         );
     }
 
+    /// Converts pulled events to the changes a replica applies.
+    fn to_replicated(changes: Vec<ChangeEventDto>) -> SyncRequest {
+        SyncRequest {
+            client_id: "replica-1".to_string(),
+            last_seen_epoch: 0,
+            changes: changes
+                .into_iter()
+                .map(|dto| SyncChangeRequest {
+                    kind: dto.kind,
+                    entity_type: dto.entity_type,
+                    id: Some(dto.id.into()),
+                    labels: dto.labels,
+                    edge_type: dto.edge_type,
+                    src_id: dto.src_id.map(EntityRef::from),
+                    dst_id: dto.dst_id.map(EntityRef::from),
+                    after: dto.after,
+                    timestamp: dto.timestamp,
+                    crdt_op: None,
+                    crdt_property: None,
+                })
+                .collect(),
+            schema_version: None,
+            atomic: true,
+        }
+    }
+
+    #[test]
+    fn replicate_preserves_primary_ids() {
+        let primary = make_manager();
+        let primary_db = primary.get("default").unwrap();
+        let db = primary_db.db();
+
+        // A rolled-back transaction leaves a gap in the primary's IDs.
+        let mut session = db.session();
+        session.begin_transaction().unwrap();
+        session.execute("INSERT (:Person {name: 'Gone'})").unwrap();
+        session.rollback().unwrap();
+
+        // The lower ID commits last, so its create is replayed second.
+        let mut first = db.session();
+        first.begin_transaction().unwrap();
+        first.execute("INSERT (:Person {name: 'Alix'})").unwrap();
+        let mut second = db.session();
+        second.begin_transaction().unwrap();
+        second.execute("INSERT (:Person {name: 'Gus'})").unwrap();
+        second.commit().unwrap();
+        first.commit().unwrap();
+
+        db.session()
+            .execute(
+                "MATCH (a:Person {name: 'Alix'}), (b:Person {name: 'Gus'}) \
+                 INSERT (a)-[:KNOWS]->(b)",
+            )
+            .unwrap();
+        let ids = |db: &grafeo_engine::GrafeoDB| {
+            let result = db
+                .execute(
+                    "MATCH (a:Person)-[e:KNOWS]->(b:Person) \
+                     RETURN id(a), id(e), id(b), a.name, b.name",
+                )
+                .unwrap();
+            result.rows()[0].clone()
+        };
+        let expected = ids(&db);
+
+        let changes = SyncService::pull(&primary, "default", 0, 1000).unwrap();
+        let replica = DatabaseManager::new(None, false);
+        let resp =
+            SyncService::replicate(&replica, "default", to_replicated(changes.changes.clone()))
+                .unwrap();
+        assert!(resp.conflicts.is_empty(), "{:?}", resp.conflicts);
+
+        let replica_db = replica.get("default").unwrap();
+        assert_eq!(ids(&replica_db.db()), expected);
+        assert_eq!(replica_db.db().node_count(), 2);
+
+        // Replaying the batch (e.g. after a crash) creates nothing new.
+        SyncService::replicate(&replica, "default", to_replicated(changes.changes)).unwrap();
+        assert_eq!(replica_db.db().node_count(), 2);
+        assert_eq!(replica_db.db().edge_count(), 1);
+
+        // Later events reference the same entities on both.
+        let since = db.current_epoch().0 + 1;
+        db.session()
+            .execute("MATCH (a:Person {name: 'Alix'}) SET a.city = 'Paris'")
+            .unwrap();
+        let changes = SyncService::pull(&primary, "default", since, 1000).unwrap();
+        SyncService::replicate(&replica, "default", to_replicated(changes.changes)).unwrap();
+        let city = replica_db
+            .db()
+            .execute("MATCH (a:Person {name: 'Alix'}) RETURN a.city")
+            .unwrap();
+        assert_eq!(
+            city.rows()[0][0],
+            grafeo_common::types::Value::from("Paris")
+        );
+    }
+
+    #[test]
+    fn replicated_creates_do_not_race_pushed_ones() {
+        const CREATES: u64 = 300;
+        let replica = DatabaseManager::new(None, false);
+        let request = |changes| SyncRequest {
+            client_id: "replica-1".to_string(),
+            last_seen_epoch: 0,
+            changes,
+            schema_version: None,
+            atomic: true,
+        };
+        let replicated = |id: u64| SyncChangeRequest {
+            id: Some(id.into()),
+            labels: Some(vec!["Replicated".to_string()]),
+            after: None,
+            ..create_person("")
+        };
+
+        // Replicated creates below the counter move it back and forth.
+        SyncService::replicate(&replica, "default", request(vec![replicated(10_000)])).unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..CREATES {
+                    let push = SyncRequest {
+                        client_id: "device-1".to_string(),
+                        ..request(vec![create_person("Alix")])
+                    };
+                    SyncService::apply(&replica, "default", push).unwrap();
+                }
+            });
+            for id in 1..=CREATES {
+                let resp =
+                    SyncService::replicate(&replica, "default", request(vec![replicated(id)]))
+                        .unwrap();
+                assert_eq!(resp.applied, 1, "{resp:?}");
+            }
+        });
+
+        let db = replica.get("default").unwrap().db();
+        let ids = |label: &str| -> Vec<u64> {
+            db.execute(&format!("MATCH (n:{label}) RETURN id(n)"))
+                .unwrap()
+                .rows()
+                .iter()
+                .map(|row| match row[0] {
+                    grafeo_common::types::Value::Int64(id) => id as u64,
+                    ref other => panic!("unexpected id {other:?}"),
+                })
+                .collect()
+        };
+        let mut replicated_ids = ids("Replicated");
+        replicated_ids.sort_unstable();
+        assert_eq!(replicated_ids.len() as u64, CREATES + 1);
+        assert!(
+            replicated_ids[..CREATES as usize]
+                .iter()
+                .copied()
+                .eq(1..=CREATES)
+        );
+        let pushed_ids = ids("Person");
+        assert_eq!(pushed_ids.len() as u64, CREATES);
+        assert!(pushed_ids.iter().all(|&id| id > 10_000));
+    }
+
     /// Verifies that the epoch persistence round-trips correctly.
     #[test]
     #[cfg(feature = "replication")]
@@ -58,6 +58,21 @@ impl DatabaseType {
         matches!(self, Self::OwlSchema | Self::RdfsSchema | Self::JsonSchema)
     }
 
+    /// Parses a display name as stored in `DatabaseMetadata`. Also accepts
+    /// the engine's graph model names (`"LPG"`, `"RDF"`), which is what
+    /// databases reopened from disk record.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Lpg,
+            Self::Rdf,
+            Self::OwlSchema,
+            Self::RdfsSchema,
+            Self::JsonSchema,
+        ]
+        .into_iter()
+        .find(|t| t.as_str().eq_ignore_ascii_case(name))
+    }
+
     /// Display name for API responses.
     pub fn as_str(self) -> &'static str {
         match self {
@@ -95,7 +110,7 @@ impl StorageMode {
     }
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct DatabaseOptions {
     /// Memory limit in bytes. Default: 512 MB.
@@ -443,11 +458,98 @@ pub struct BackupEntry {
     pub start_epoch: u64,
     /// End epoch (inclusive).
     pub end_epoch: u64,
-    /// CRC-32 checksum of the backup file.
+    /// CRC-32 checksum of the backup file (of its plaintext, if encrypted).
     pub checksum: u32,
     /// Optional user-supplied label (stored in a sidecar file, not the filename).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub label: Option<String>,
+    /// Result of the most recent verification, if the backup was verified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification: Option<BackupVerification>,
+    /// ID of the key the backup is encrypted with. Absent for plaintext
+    /// backups.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_key_id: Option<String>,
+}
+
+/// Result of verifying a backup's integrity without restoring it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackupVerification {
+    /// Database the backup belongs to.
+    pub database: String,
+    /// Verified backup filename.
+    pub filename: String,
+    /// When the verification ran (ISO 8601).
+    pub verified_at: String,
+    /// Whether every check passed.
+    pub ok: bool,
+    /// Whether the file matches the size and checksum the manifest recorded.
+    /// Absent for backups the manifest does not track, which have no
+    /// checksum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum_ok: Option<bool>,
+    /// Segments checked, in chain order: for a full backup, itself and the
+    /// incrementals built on it; for an incremental, the full backup and
+    /// incrementals a restore to its end epoch replays.
+    pub chain: Vec<String>,
+    /// Problems found. Empty when `ok`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issues: Vec<String>,
+    /// Node count of the backup, when it was opened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_count: Option<u64>,
+    /// Edge count of the backup, when it was opened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edge_count: Option<u64>,
+}
+
+/// Request body for verifying a backup.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VerifyBackupRequest {
+    /// Also load the backup into a scratch in-memory database and count its
+    /// nodes and edges. Slower, but catches snapshots that pass their
+    /// checksum yet fail to load.
+    #[serde(default)]
+    pub open: bool,
+}
+
+/// Request body for verifying every backup.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VerifyBackupsRequest {
+    /// Only verify this database's backups.
+    #[serde(default)]
+    pub database: Option<String>,
+    /// Also load each backup into a scratch in-memory database. See
+    /// [`VerifyBackupRequest::open`].
+    #[serde(default)]
+    pub open: bool,
+}
+
+/// Request body for re-encrypting backups with the active backup key.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RekeyBackupsRequest {
+    /// Only re-encrypt this database's backups.
+    #[serde(default)]
+    pub database: Option<String>,
+}
+
+/// A backup file re-encrypted with the active backup key.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RekeyedBackup {
+    /// Database the backup belongs to.
+    pub database: String,
+    /// Backup filename.
+    pub filename: String,
+    /// Key the backup was encrypted with before. Absent if it was plaintext.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_key_id: Option<String>,
+    /// Key the backup is encrypted with now.
+    pub key_id: String,
 }
 
 /// Request body for creating a backup.
@@ -483,6 +585,87 @@ pub struct RestoreToEpochRequest {
     pub epoch: u64,
 }
 
+/// Request to restore a backup into a new database.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RestoreAsRequest {
+    /// Name of the database to create.
+    pub name: String,
+    /// Backup filename to restore. Exactly one of `backup` and `epoch` must
+    /// be set.
+    #[serde(default)]
+    pub backup: Option<String>,
+    /// Epoch to restore to by replaying the backup chain.
+    #[serde(default)]
+    pub epoch: Option<u64>,
+    /// Storage mode of the new database: in-memory (default) or persistent.
+    #[serde(default)]
+    pub storage_mode: StorageMode,
+}
+
+/// Backup schedule of a database.
+///
+/// Full and incremental backups run independently at their own interval;
+/// retention is applied after every successful run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackupSchedule {
+    /// Seconds between full backups. Omit to schedule no full backups.
+    #[serde(default)]
+    pub full_interval_secs: Option<u64>,
+    /// Seconds between incremental backups. Requires a persistent database
+    /// and a prior full backup.
+    #[serde(default)]
+    pub incremental_interval_secs: Option<u64>,
+    /// Number of full backups to keep. Defaults to `--backup-retention`.
+    #[serde(default)]
+    pub keep: Option<usize>,
+    /// Delete backups older than this many seconds, except the ones a
+    /// restore to any point inside that window still needs.
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+}
+
+/// Outcome of one scheduled backup run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackupRun {
+    /// Backup kind: "full" or "incremental".
+    pub kind: String,
+    /// When the run started (ISO 8601).
+    pub started_at: String,
+    /// How long the backup took, in milliseconds.
+    pub duration_ms: u64,
+    /// Whether the backup was created.
+    pub success: bool,
+    /// Filename of the created backup. Absent when an incremental run found
+    /// nothing committed since the last backup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Why the backup failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Backups deleted by the retention policy after this run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pruned: Vec<String>,
+}
+
+/// A database's backup schedule with its next and most recent runs.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BackupScheduleStatus {
+    /// Database name.
+    pub database: String,
+    /// The configured schedule.
+    pub schedule: BackupSchedule,
+    /// When the next full backup is due (ISO 8601), if scheduled.
+    pub next_full_at: Option<String>,
+    /// When the next incremental backup is due (ISO 8601), if scheduled.
+    pub next_incremental_at: Option<String>,
+    /// Most recent runs, newest first.
+    pub history: Vec<BackupRun>,
+}
+
 // ============================================================================
 // Token management types
 // ============================================================================
@@ -705,6 +888,61 @@ pub struct ShaclViolation {
     pub message: Option<String>,
 }
 
+// ============================================================================
+// Running query types
+// ============================================================================
+
+/// Transport a query arrived on.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum QueryTransport {
+    /// HTTP REST endpoints (query, batch, transaction, SPARQL protocol).
+    Http,
+    /// HTTP WebSocket endpoint.
+    WebSocket,
+    /// GQL Wire Protocol (gRPC).
+    Gwp,
+    /// Bolt v5 protocol.
+    Bolt,
+}
+
+impl QueryTransport {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Http => "http",
+            Self::WebSocket => "websocket",
+            Self::Gwp => "gwp",
+            Self::Bolt => "bolt",
+        }
+    }
+}
+
+/// A query that is currently executing.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RunningQueryInfo {
+    /// Query ID, usable with `DELETE /admin/queries/{id}`.
+    pub id: String,
+    /// Database the query runs against.
+    pub database: String,
+    /// Query language (e.g. "gql", "cypher", "sparql", "batch").
+    pub language: String,
+    /// Statement text as submitted by the client.
+    pub statement: String,
+    /// Authenticated user, or `null` when auth is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Transport the query arrived on.
+    pub transport: QueryTransport,
+    /// Start time as milliseconds since the Unix epoch.
+    pub started_at_ms: u64,
+    /// Milliseconds elapsed since the query started.
+    pub elapsed_ms: u64,
+    /// Whether a kill has been requested and the query is winding down.
+    pub cancelling: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
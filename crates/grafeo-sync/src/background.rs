@@ -0,0 +1,125 @@
+//! Background sync loop for a [`LocalStore`].
+//!
+//! [`BackgroundSync::spawn`] runs [`LocalStore::sync_once`] on a tokio task:
+//! right away, then every `interval` and whenever a local change is queued.
+//! After a failed run, e.g. while the device is offline, the loop waits
+//! with exponential backoff and ignores local changes until a run succeeds.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Notify, watch};
+use tokio::task::JoinHandle;
+
+use crate::SyncClient;
+use crate::store::LocalStore;
+
+/// Settings of the background sync loop.
+#[derive(Debug, Clone)]
+pub struct BackgroundSync {
+    interval: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for BackgroundSync {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+}
+
+impl BackgroundSync {
+    /// Creates settings that sync every `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the delay after the first failed run and the cap the delay
+    /// doubles up to on further failures. Defaults to 1 s and 5 min.
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Starts syncing `store` with `client` on a tokio task.
+    pub fn spawn(self, store: Arc<LocalStore>, client: SyncClient) -> SyncHandle {
+        let trigger = Arc::new(Notify::new());
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let task = tokio::spawn(run(self, store, client, Arc::clone(&trigger), stop_rx));
+        SyncHandle {
+            trigger,
+            stop: stop_tx,
+            task,
+        }
+    }
+}
+
+/// Handle to a running background sync loop. Dropping it stops the loop.
+pub struct SyncHandle {
+    trigger: Arc<Notify>,
+    stop: watch::Sender<bool>,
+    task: JoinHandle<()>,
+}
+
+impl SyncHandle {
+    /// Starts a sync run now, also while backing off after a failure.
+    pub fn sync_now(&self) {
+        self.trigger.notify_one();
+    }
+
+    /// Stops the loop, waiting for a run in progress to finish.
+    pub async fn stop(self) {
+        let _ = self.stop.send(true);
+        let _ = self.task.await;
+    }
+}
+
+async fn run(
+    options: BackgroundSync,
+    store: Arc<LocalStore>,
+    client: SyncClient,
+    trigger: Arc<Notify>,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut delay = Duration::ZERO;
+    let mut backoff = options.initial_backoff;
+    let mut failing = false;
+
+    loop {
+        tokio::select! {
+            () = tokio::time::sleep(delay) => {}
+            () = trigger.notified() => {}
+            () = store.changed.notified(), if !failing => {}
+            _ = stop.changed() => return,
+        }
+
+        match store.sync_once(&client).await {
+            Ok(report) => {
+                tracing::debug!(
+                    pushed = report.pushed,
+                    pulled = report.pulled,
+                    conflicts = report.conflicts.len(),
+                    "Background sync completed"
+                );
+                failing = false;
+                backoff = options.initial_backoff;
+                delay = options.interval;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, retry_in = ?backoff, "Background sync failed");
+                failing = true;
+                delay = backoff;
+                backoff = (backoff * 2).min(options.max_backoff);
+            }
+        }
+    }
+}
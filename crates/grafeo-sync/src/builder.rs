@@ -0,0 +1,238 @@
+//! Builder for [`SyncClient`] with credentials, TLS and retry options.
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::time::Duration;
+
+use url::Url;
+
+use crate::{SyncClient, SyncError};
+
+/// Credentials sent with every request, matching the mechanisms the
+/// server's `auth` feature accepts.
+#[derive(Clone)]
+pub enum Credentials {
+    /// `Authorization: Bearer <token>`.
+    Bearer(String),
+    /// `X-API-Key: <key>`, checked against the same tokens as `Bearer`.
+    ApiKey(String),
+    /// `Authorization: Basic`, for the server's `--auth-user`/`--auth-password`.
+    Basic { username: String, password: String },
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(***)"),
+            Self::ApiKey(_) => f.write_str("ApiKey(***)"),
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+        }
+    }
+}
+
+impl Credentials {
+    pub(crate) fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            Self::Bearer(token) => request.bearer_auth(token),
+            Self::ApiKey(key) => request.header("x-api-key", key),
+            Self::Basic { username, password } => request.basic_auth(username, Some(password)),
+        }
+    }
+}
+
+/// How failed requests are retried.
+///
+/// Requests answered with 429 or 503 and requests that could not connect
+/// are retried. Pulls are also retried after timeouts; pushes are not, as
+/// the server may have applied them. A `Retry-After` header on a 429 or 503
+/// response replaces the backoff, up to `max_backoff`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt. `0` disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further one.
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// No retries.
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retries up to `max_retries` times with the default backoff
+    /// (200 ms, doubling up to 10 s).
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+}
+
+/// Builder for a [`SyncClient`], created by [`SyncClient::builder`].
+#[derive(Debug)]
+pub struct SyncClientBuilder {
+    base_url: String,
+    db_name: String,
+    client_id: String,
+    credentials: Option<Credentials>,
+    root_certificates: Vec<Vec<u8>>,
+    root_certificates_only: bool,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    retry: RetryPolicy,
+}
+
+impl SyncClientBuilder {
+    pub(crate) fn new(base_url: &str, db_name: &str, client_id: &str) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            db_name: db_name.to_string(),
+            client_id: client_id.to_string(),
+            credentials: None,
+            root_certificates: Vec::new(),
+            root_certificates_only: false,
+            timeout: None,
+            connect_timeout: None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Authenticates with a bearer token.
+    #[must_use]
+    pub fn bearer_token(self, token: impl Into<String>) -> Self {
+        self.credentials(Credentials::Bearer(token.into()))
+    }
+
+    /// Authenticates with an API key sent in `X-API-Key`.
+    #[must_use]
+    pub fn api_key(self, key: impl Into<String>) -> Self {
+        self.credentials(Credentials::ApiKey(key.into()))
+    }
+
+    /// Authenticates with HTTP Basic credentials.
+    #[must_use]
+    pub fn basic_auth(self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials(Credentials::Basic {
+            username: username.into(),
+            password: password.into(),
+        })
+    }
+
+    /// Sets the credentials sent with every request, replacing any set before.
+    #[must_use]
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Trusts the PEM-encoded CA certificates in `pem`, e.g. the CA of a
+    /// server started with `--tls-cert`, in addition to the system roots.
+    #[must_use]
+    pub fn root_certificate_pem(mut self, pem: impl Into<Vec<u8>>) -> Self {
+        self.root_certificates.push(pem.into());
+        self
+    }
+
+    /// Trusts only the certificates added with
+    /// [`root_certificate_pem`](Self::root_certificate_pem), not the system roots.
+    #[must_use]
+    pub fn root_certificates_only(mut self, only: bool) -> Self {
+        self.root_certificates_only = only;
+        self
+    }
+
+    /// Sets the total timeout of each request attempt. Default: none.
+    /// Does not apply to [`SyncClient::subscribe`] streams.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the timeout for establishing a connection. Default: none.
+    #[must_use]
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how failed requests are retried. Default: no retries.
+    #[must_use]
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Builds the client.
+    pub fn build(self) -> Result<SyncClient, SyncError> {
+        let base = Url::parse(&self.base_url).map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
+
+        let changes_url = base
+            .join(&format!("db/{}/changes", self.db_name))
+            .map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
+
+        let stream_url = base
+            .join(&format!("db/{}/changes/stream", self.db_name))
+            .map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
+
+        let sync_url = base
+            .join(&format!("db/{}/sync", self.db_name))
+            .map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
+
+        let mut certificates = Vec::new();
+        for pem in &self.root_certificates {
+            let bundle = reqwest::Certificate::from_pem_bundle(pem)
+                .map_err(|e| SyncError::InvalidCertificate(e.to_string()))?;
+            if bundle.is_empty() {
+                return Err(SyncError::InvalidCertificate(
+                    "no certificate found in PEM data".to_string(),
+                ));
+            }
+            certificates.extend(bundle);
+        }
+
+        let client = |timeout: Option<Duration>| {
+            let mut http = reqwest::Client::builder();
+            if self.root_certificates_only {
+                http = http.tls_certs_only(certificates.clone());
+            } else if !certificates.is_empty() {
+                http = http.tls_certs_merge(certificates.clone());
+            }
+            if let Some(timeout) = timeout {
+                http = http.timeout(timeout);
+            }
+            if let Some(timeout) = self.connect_timeout {
+                http = http.connect_timeout(timeout);
+            }
+            http.build()
+        };
+
+        Ok(SyncClient {
+            http: client(self.timeout)?,
+            stream_http: client(None)?,
+            changes_url,
+            stream_url,
+            sync_url,
+            client_id: self.client_id,
+            last_epoch: Arc::new(AtomicU64::new(0)),
+            atomic: false,
+            credentials: self.credentials,
+            retry: self.retry,
+        })
+    }
+}
@@ -1,5 +1,7 @@
 //! Error type for the grafeo-sync client.
 
+use std::time::Duration;
+
 /// Errors that can occur during sync operations.
 #[derive(Debug, thiserror::Error)]
 pub enum SyncError {
@@ -7,11 +9,91 @@ pub enum SyncError {
     #[error("invalid URL: {0}")]
     InvalidUrl(String),
 
+    /// A root certificate passed to the builder is not valid PEM.
+    #[error("invalid certificate: {0}")]
+    InvalidCertificate(String),
+
     /// An HTTP transport error occurred (connection refused, timeout, etc.).
     #[error("HTTP transport error: {0}")]
     Transport(#[from] reqwest::Error),
 
-    /// The server returned a non-2xx status code.
+    /// The server returned 401: credentials are missing or invalid.
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// The server returned 403: the credentials lack access to the database
+    /// or operation.
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
+    /// The server returned 429.
+    #[error("rate limited: {body}")]
+    RateLimited {
+        /// Delay the server asked for in `Retry-After`.
+        retry_after: Option<Duration>,
+        body: String,
+    },
+
+    /// The server returned 503, e.g. while a database is being restored.
+    #[error("service unavailable: {body}")]
+    Unavailable {
+        /// Delay the server asked for in `Retry-After`.
+        retry_after: Option<Duration>,
+        body: String,
+    },
+
+    /// The server returned another non-2xx status code.
     #[error("server returned {status}: {body}")]
     ServerError { status: u16, body: String },
+
+    /// An epoch holds more changes than a pull returns, and the server
+    /// cannot return it whole.
+    #[error("epoch {epoch} has more than {limit} changes and the server cannot return it whole")]
+    EpochTooLarge { epoch: u64, limit: usize },
+
+    /// The local store could not be opened or its sync state persisted.
+    #[error("local store error: {0}")]
+    Store(String),
+}
+
+impl SyncError {
+    /// Converts a non-2xx response into the matching variant.
+    pub(crate) async fn from_response(resp: reqwest::Response) -> Self {
+        let status = resp.status().as_u16();
+        let retry_after = resp
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_secs);
+        let body = resp.text().await.unwrap_or_default();
+        match status {
+            401 => Self::Unauthorized(body),
+            403 => Self::Forbidden(body),
+            429 => Self::RateLimited { retry_after, body },
+            503 => Self::Unavailable { retry_after, body },
+            _ => Self::ServerError { status, body },
+        }
+    }
+
+    /// Whether a request that failed with this error may be sent again.
+    /// Timed-out requests may have reached the server, so they are only
+    /// retried when `idempotent`.
+    pub(crate) fn is_retryable(&self, idempotent: bool) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::Unavailable { .. } => true,
+            Self::Transport(e) => e.is_connect() || (idempotent && e.is_timeout()),
+            _ => false,
+        }
+    }
+
+    /// Delay the server asked for before retrying.
+    pub(crate) fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } | Self::Unavailable { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
 }
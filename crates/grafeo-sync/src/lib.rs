@@ -50,6 +50,70 @@
 //! }
 //! # }
 //! ```
+//!
+//! # Authentication and TLS
+//!
+//! Servers built with the `auth` or `tls` features need a client from
+//! [`SyncClient::builder`]:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use grafeo_sync::{RetryPolicy, SyncClient};
+//! # fn example() -> Result<(), grafeo_sync::SyncError> {
+//! let client = SyncClient::builder("https://graph.example.com", "default", "device-1")
+//!     .bearer_token("my-token")
+//!     .root_certificate_pem(std::fs::read("ca.pem").expect("CA certificate"))
+//!     .timeout(Duration::from_secs(30))
+//!     .retry(RetryPolicy::new(3))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Offline store
+//!
+//! [`LocalStore`] keeps a local replica with an outbox of pending changes
+//! and does the pull/push bookkeeping above; [`BackgroundSync`] runs it in
+//! the background, retrying with backoff while the server is unreachable.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use std::time::Duration;
+//! # use grafeo_sync::{BackgroundSync, LocalStore, SyncClient};
+//! # async fn example() -> Result<(), grafeo_sync::SyncError> {
+//! let store = Arc::new(LocalStore::open("./offline")?);
+//! store.on_conflict(|c| eprintln!("change lost: {}", c.conflict.reason));
+//!
+//! let client = SyncClient::new("http://localhost:7474", "default", "device-1")?;
+//! let sync = BackgroundSync::new(Duration::from_secs(30)).spawn(Arc::clone(&store), client);
+//!
+//! // Works offline; the change is pushed on the next successful sync.
+//! store.create_node(&["Person"], [("name".to_string(), "Alix".into())])?;
+//!
+//! sync.stop().await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Live subscriptions
+//!
+//! With the server's `push-changefeed` feature, [`SyncClient::subscribe`]
+//! streams change events as they are committed instead of polling. The
+//! stream reconnects on its own, resuming from the last delivered epoch.
+//!
+//! ```no_run
+//! # use futures_util::StreamExt;
+//! # use grafeo_sync::SyncClient;
+//! # async fn example() -> Result<(), grafeo_sync::SyncError> {
+//! let client = SyncClient::new("http://localhost:7474", "default", "device-1")?;
+//! let mut changes = std::pin::pin!(client.subscribe(0));
+//! while let Some(event) = changes.next().await {
+//!     let event = event?;
+//!     println!("{} {} {} at epoch {}", event.kind, event.entity_type, event.id, event.epoch);
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -57,27 +121,44 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use grafeo_service::sync::{ChangesResponse, SyncChangeRequest, SyncRequest, SyncResponse};
 use url::Url;
 
+pub use background::{BackgroundSync, SyncHandle};
+pub use builder::{Credentials, RetryPolicy, SyncClientBuilder};
 pub use error::SyncError;
+pub use store::{LocalStore, SyncConflict, SyncReport};
 
+mod background;
+mod builder;
 mod error;
+mod store;
+mod subscribe;
 
 /// Async HTTP client for the Grafeo sync protocol.
 ///
-/// Wraps the `GET /db/{name}/changes` and `POST /db/{name}/sync` endpoints.
+/// Wraps the `GET /db/{name}/changes`, `GET /db/{name}/changes/stream` and
+/// `POST /db/{name}/sync` endpoints.
 /// Thread-safe: cloning the struct shares the underlying HTTP client and epoch counter.
 #[derive(Clone)]
 pub struct SyncClient {
     http: reqwest::Client,
+    /// Client for long-lived streams: as `http`, without the request timeout.
+    stream_http: reqwest::Client,
     changes_url: Url,
+    stream_url: Url,
     sync_url: Url,
     /// Opaque identifier for this client/device.
     pub client_id: String,
     /// Last server epoch the client has processed. Updated by `advance_epoch()`.
     last_epoch: Arc<AtomicU64>,
+    /// Whether pushed changesets are applied all-or-nothing.
+    atomic: bool,
+    /// Credentials sent with every request.
+    credentials: Option<Credentials>,
+    /// How failed requests are retried.
+    retry: RetryPolicy,
 }
 
 impl SyncClient {
-    /// Creates a new sync client.
+    /// Creates a new sync client without credentials, timeouts or retries.
     ///
     /// # Arguments
     ///
@@ -85,23 +166,13 @@ impl SyncClient {
     /// * `db_name` — Name of the database to sync (e.g. `"default"`).
     /// * `client_id` — Stable opaque identifier for this device/session.
     pub fn new(base_url: &str, db_name: &str, client_id: &str) -> Result<Self, SyncError> {
-        let base = Url::parse(base_url).map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
-
-        let changes_url = base
-            .join(&format!("db/{db_name}/changes"))
-            .map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
-
-        let sync_url = base
-            .join(&format!("db/{db_name}/sync"))
-            .map_err(|e| SyncError::InvalidUrl(e.to_string()))?;
-
-        Ok(Self {
-            http: reqwest::Client::new(),
-            changes_url,
-            sync_url,
-            client_id: client_id.to_string(),
-            last_epoch: Arc::new(AtomicU64::new(0)),
-        })
+        Self::builder(base_url, db_name, client_id).build()
+    }
+
+    /// Returns a builder for a client with credentials, custom TLS roots,
+    /// timeouts or a retry policy. Arguments are as for [`new`](Self::new).
+    pub fn builder(base_url: &str, db_name: &str, client_id: &str) -> SyncClientBuilder {
+        SyncClientBuilder::new(base_url, db_name, client_id)
     }
 
     /// Overrides the starting epoch (useful when resuming from a persisted bookmark).
@@ -111,6 +182,16 @@ impl SyncClient {
         self
     }
 
+    /// Makes pushes all-or-nothing: if any change conflicts, the server rolls
+    /// back the whole changeset and reports `rolled_back` in the response.
+    ///
+    /// By default the server skips conflicting changes and applies the rest.
+    #[must_use]
+    pub fn with_atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
     /// Returns the last epoch the client has acknowledged.
     #[must_use]
     pub fn last_epoch(&self) -> u64 {
@@ -130,21 +211,35 @@ impl SyncClient {
     /// `limit` is capped at 10 000 by the server. If `response.changes.len() == limit`,
     /// there may be more events: call `advance_epoch(response.server_epoch)` and pull again.
     pub async fn pull(&self, limit: usize) -> Result<ChangesResponse, SyncError> {
-        let since = self.last_epoch();
+        self.changes_since(self.last_epoch(), limit, false).await
+    }
 
+    /// Like [`pull`](Self::pull), but never splits an epoch: an epoch with
+    /// more than `limit` events is returned whole. If the response is
+    /// `complete`, its `server_epoch` is the last epoch it holds in full, so
+    /// a full response is continued from `server_epoch + 1`. Servers that
+    /// predate this return an ordinary pull, with `complete` unset.
+    pub async fn pull_complete(&self, limit: usize) -> Result<ChangesResponse, SyncError> {
+        self.changes_since(self.last_epoch(), limit, true).await
+    }
+
+    /// Pulls change events with `epoch >= since`, in whole epochs if
+    /// `complete`.
+    pub(crate) async fn changes_since(
+        &self,
+        since: u64,
+        limit: usize,
+        complete: bool,
+    ) -> Result<ChangesResponse, SyncError> {
         let mut url = self.changes_url.clone();
         url.query_pairs_mut()
             .append_pair("since", &since.to_string())
             .append_pair("limit", &limit.to_string());
-
-        let resp = self.http.get(url).send().await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status().as_u16();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(SyncError::ServerError { status, body });
+        if complete {
+            url.query_pairs_mut().append_pair("complete", "true");
         }
 
+        let resp = self.send(|| self.http.get(url.clone()), true).await?;
         Ok(resp.json::<ChangesResponse>().await?)
     }
 
@@ -157,22 +252,53 @@ impl SyncClient {
             last_seen_epoch: self.last_epoch(),
             changes,
             schema_version: None,
+            atomic: self.atomic,
         };
 
         let resp = self
-            .http
-            .post(self.sync_url.clone())
-            .json(&request)
-            .send()
+            .send(
+                || self.http.post(self.sync_url.clone()).json(&request),
+                false,
+            )
             .await?;
+        Ok(resp.json::<SyncResponse>().await?)
+    }
 
-        if !resp.status().is_success() {
-            let status = resp.status().as_u16();
-            let body = resp.text().await.unwrap_or_default();
-            return Err(SyncError::ServerError { status, body });
+    /// Sends the request built by `request` with the client's credentials,
+    /// retrying per the retry policy. Non-2xx responses become errors.
+    async fn send(
+        &self,
+        request: impl Fn() -> reqwest::RequestBuilder,
+        idempotent: bool,
+    ) -> Result<reqwest::Response, SyncError> {
+        let mut backoff = self.retry.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            let error = match self.authorize(request()).send().await {
+                Ok(resp) if resp.status().is_success() => return Ok(resp),
+                Ok(resp) => SyncError::from_response(resp).await,
+                Err(e) => SyncError::Transport(e),
+            };
+            if attempt >= self.retry.max_retries || !error.is_retryable(idempotent) {
+                return Err(error);
+            }
+            let delay = error
+                .retry_after()
+                .unwrap_or(backoff)
+                .min(self.retry.max_backoff);
+            tracing::debug!(error = %error, attempt, delay = ?delay, "Retrying sync request");
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+            backoff = (backoff * 2).min(self.retry.max_backoff);
         }
+    }
 
-        Ok(resp.json::<SyncResponse>().await?)
+    /// Adds the client's credentials to a request.
+    fn authorize(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.credentials {
+            Some(credentials) => credentials.apply(request),
+            None => request,
+        }
     }
 
     /// Pulls server changes then pushes `local_changes` in a single round-trip pair.
@@ -195,9 +321,14 @@ impl SyncClient {
 mod tests {
     use std::net::SocketAddr;
 
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
     use axum::Router;
     use axum::extract::{Path, Query};
-    use axum::response::Json;
+    use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Json};
     use axum::routing::{get, post};
     use grafeo_service::sync::{ChangesResponse, SyncRequest, SyncResponse};
     use serde::Deserialize;
@@ -222,6 +353,7 @@ mod tests {
         Json(ChangesResponse {
             server_epoch: q.since + 10,
             changes: vec![],
+            complete: false,
         })
     }
 
@@ -237,6 +369,7 @@ mod tests {
             id_mappings: vec![],
             schema_mismatch: false,
             server_schema_version: "abc123".to_string(),
+            rolled_back: req.atomic,
         })
     }
 
@@ -274,6 +407,78 @@ mod tests {
         format!("http://{addr}")
     }
 
+    async fn serve(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        format!("http://{addr}")
+    }
+
+    /// Body accepted both as a `ChangesResponse` and a `SyncResponse`.
+    fn empty_body() -> Json<serde_json::Value> {
+        Json(serde_json::json!({
+            "server_epoch": 1, "changes": [], "applied": 0, "skipped": 0,
+            "conflicts": [], "id_mappings": [], "schema_mismatch": false,
+            "server_schema_version": "",
+        }))
+    }
+
+    /// Answers 401 unless the request carries `Bearer secret`,
+    /// `X-API-Key: secret` or Basic `admin:pw`.
+    async fn spawn_auth_server() -> String {
+        async fn check(headers: HeaderMap) -> Result<Json<serde_json::Value>, StatusCode> {
+            let authorization = headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok());
+            let api_key = headers.get("x-api-key").and_then(|v| v.to_str().ok());
+            if matches!(authorization, Some("Bearer secret" | "Basic YWRtaW46cHc="))
+                || api_key == Some("secret")
+            {
+                Ok(empty_body())
+            } else {
+                Err(StatusCode::UNAUTHORIZED)
+            }
+        }
+        serve(Router::new().route("/db/{name}/changes", get(check))).await
+    }
+
+    /// Answers the first `failures` requests with `status` and
+    /// `Retry-After: 0`, later ones successfully, after `delay`. Returns the
+    /// request counter.
+    async fn spawn_flaky_server(
+        status: StatusCode,
+        failures: usize,
+        delay: Duration,
+    ) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let respond = move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                tokio::time::sleep(delay).await;
+                if n < failures {
+                    (status, [(RETRY_AFTER, "0")]).into_response()
+                } else {
+                    empty_body().into_response()
+                }
+            }
+        };
+        let app = Router::new()
+            .route("/db/{name}/changes", get(respond.clone()))
+            .route("/db/{name}/sync", post(respond));
+        (serve(app).await, hits)
+    }
+
+    fn retry(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(10),
+        }
+    }
+
     // ---------------------------------------------------------------------------
     // Constructor / epoch helpers (no server needed)
     // ---------------------------------------------------------------------------
@@ -386,6 +591,16 @@ mod tests {
         assert_eq!(resp.server_epoch, 8); // last_seen_epoch=3, mock returns 3+5
     }
 
+    #[tokio::test]
+    async fn push_sends_atomic_flag() {
+        let base = spawn_mock_server().await;
+        let client = SyncClient::new(&base, "default", "dev-1").unwrap();
+        assert!(!client.push(vec![]).await.unwrap().rolled_back);
+
+        let client = client.with_atomic(true);
+        assert!(client.push(vec![]).await.unwrap().rolled_back); // mock echoes `atomic`
+    }
+
     #[tokio::test]
     async fn push_returns_server_error() {
         let base = spawn_error_server().await;
@@ -408,4 +623,118 @@ mod tests {
         assert_eq!(pulled.server_epoch, 10);
         assert_eq!(pushed.applied, 0);
     }
+
+    // ---------------------------------------------------------------------------
+    // Builder: credentials, errors and retries
+    // ---------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn builder_sends_each_credential_kind() {
+        let base = spawn_auth_server().await;
+        let builders = [
+            SyncClient::builder(&base, "default", "dev-1").bearer_token("secret"),
+            SyncClient::builder(&base, "default", "dev-1").api_key("secret"),
+            SyncClient::builder(&base, "default", "dev-1").basic_auth("admin", "pw"),
+        ];
+        for builder in builders {
+            builder.build().unwrap().pull(10).await.unwrap();
+        }
+
+        let err = SyncClient::new(&base, "default", "dev-1")
+            .unwrap()
+            .pull(10)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SyncError::Unauthorized(_)));
+    }
+
+    #[test]
+    fn credentials_debug_hides_secrets() {
+        let basic = Credentials::Basic {
+            username: "admin".to_string(),
+            password: "pw".to_string(),
+        };
+        assert!(!format!("{basic:?}").contains("pw"));
+        assert!(!format!("{:?}", Credentials::Bearer("secret".to_string())).contains("secret"));
+    }
+
+    #[test]
+    fn builder_rejects_invalid_root_certificate() {
+        let result = SyncClient::builder("https://localhost:7474", "default", "dev-1")
+            .root_certificate_pem("not a certificate")
+            .build();
+        assert!(matches!(result, Err(SyncError::InvalidCertificate(_))));
+    }
+
+    async fn pull_error(status: StatusCode) -> SyncError {
+        let (base, _) = spawn_flaky_server(status, 1, Duration::ZERO).await;
+        let client = SyncClient::new(&base, "default", "dev-1").unwrap();
+        client.pull(10).await.unwrap_err()
+    }
+
+    #[tokio::test]
+    async fn status_codes_map_to_typed_errors() {
+        let err = pull_error(StatusCode::FORBIDDEN).await;
+        assert!(matches!(err, SyncError::Forbidden(_)));
+
+        let err = pull_error(StatusCode::TOO_MANY_REQUESTS).await;
+        assert!(matches!(err, SyncError::RateLimited { retry_after: Some(d), .. } if d.is_zero()));
+
+        let err = pull_error(StatusCode::SERVICE_UNAVAILABLE).await;
+        assert!(matches!(
+            err,
+            SyncError::Unavailable {
+                retry_after: Some(_),
+                ..
+            }
+        ));
+
+        let err = pull_error(StatusCode::BAD_REQUEST).await;
+        assert!(matches!(err, SyncError::ServerError { status: 400, .. }));
+    }
+
+    #[tokio::test]
+    async fn retry_recovers_from_unavailable_server() {
+        let (base, hits) =
+            spawn_flaky_server(StatusCode::SERVICE_UNAVAILABLE, 2, Duration::ZERO).await;
+        let client = SyncClient::builder(&base, "default", "dev-1")
+            .retry(retry(3))
+            .build()
+            .unwrap();
+
+        client.pull(10).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_after_max_retries() {
+        let (base, hits) =
+            spawn_flaky_server(StatusCode::TOO_MANY_REQUESTS, 10, Duration::ZERO).await;
+        let client = SyncClient::builder(&base, "default", "dev-1")
+            .retry(retry(2))
+            .build()
+            .unwrap();
+
+        let err = client.push(vec![]).await.unwrap_err();
+        assert!(matches!(err, SyncError::RateLimited { .. }));
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn timed_out_push_is_not_retried() {
+        let (base, hits) = spawn_flaky_server(StatusCode::OK, 0, Duration::from_millis(500)).await;
+        let client = SyncClient::builder(&base, "default", "dev-1")
+            .timeout(Duration::from_millis(50))
+            .retry(retry(2))
+            .build()
+            .unwrap();
+
+        let err = client.push(vec![]).await.unwrap_err();
+        assert!(matches!(&err, SyncError::Transport(e) if e.is_timeout()));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // Pulls are safe to repeat.
+        client.pull(10).await.unwrap_err();
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+    }
 }
@@ -0,0 +1,1147 @@
+//! Offline local store: an embedded replica of a server database.
+//!
+//! [`LocalStore`] wraps a local [`GrafeoDB`]. Writes made through the store
+//! are applied locally right away and queued in an outbox of
+//! [`SyncChangeRequest`]s; [`LocalStore::sync_once`] pushes the outbox, then
+//! pulls the server's change events and applies them locally. Entities
+//! created offline are pushed with temporary IDs, and the server IDs they
+//! receive are remembered so later changes and pulled events reach the
+//! right local entity.
+//!
+//! Pulls return whole epochs, so an epoch larger than a pull is never half
+//! applied; a server that cannot send one whole fails the sync with
+//! [`SyncError::EpochTooLarge`]. A pushed update the server rejects is
+//! reset to the server's value and reported to the
+//! [`on_conflict`](LocalStore::on_conflict) callback.
+//!
+//! A store opened from a directory keeps its database in `data.grafeo` and
+//! its sync state (epoch, outbox and ID map) in `sync-state.json`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use grafeo_common::types::{EdgeId, HlcClock, HlcTimestamp, NodeId, Value};
+use grafeo_engine::GrafeoDB;
+use grafeo_service::sync::{
+    ChangeEventDto, ConflictRecord, EntityRef, SyncChangeRequest, SyncResponse,
+};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::{SyncClient, SyncError};
+
+const DATA_FILE: &str = "data.grafeo";
+const STATE_FILE: &str = "sync-state.json";
+
+/// Maximum number of outbox changes sent per push.
+const PUSH_BATCH: usize = 1_000;
+
+/// Maximum number of events requested per pull (the server's cap).
+const PULL_LIMIT: usize = 10_000;
+
+/// A conflict the server reported for a pushed change.
+#[derive(Debug, Clone)]
+pub struct SyncConflict {
+    /// The server's conflict record.
+    pub conflict: ConflictRecord,
+    /// The change as it was pushed.
+    pub change: SyncChangeRequest,
+}
+
+/// Summary of a [`LocalStore::sync_once`] run.
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// Number of pushed changes the server applied.
+    pub pushed: usize,
+    /// Number of pulled change events applied to the local store.
+    pub pulled: usize,
+    /// Conflicts the server reported for pushed changes.
+    pub conflicts: Vec<SyncConflict>,
+}
+
+type ConflictCallback = Arc<dyn Fn(&SyncConflict) + Send + Sync>;
+
+/// Local replica of a server database with an outbox of pending changes.
+///
+/// Read through [`db()`](Self::db); write through the store's methods so
+/// the changes are queued for the server. Writes made on the database
+/// directly stay local.
+pub struct LocalStore {
+    db: GrafeoDB,
+    /// Directory the store is persisted in; `None` for in-memory stores.
+    dir: Option<PathBuf>,
+    state: Mutex<SyncState>,
+    on_conflict: RwLock<Option<ConflictCallback>>,
+    /// Timestamps local changes. Advanced past pulled events, so a local
+    /// change made after seeing a server change wins LWW against it.
+    clock: HlcClock,
+    /// Serializes `sync_once` runs.
+    syncing: tokio::sync::Mutex<()>,
+    /// Notified when a local change is queued.
+    pub(crate) changed: Notify,
+}
+
+/// Sync state persisted next to the local database.
+#[derive(Default, Serialize, Deserialize)]
+struct SyncState {
+    /// Server epoch the store has pulled up to.
+    epoch: u64,
+    /// Local changes not yet delivered to the server, oldest first.
+    outbox: Vec<SyncChangeRequest>,
+    nodes: IdMap,
+    edges: IdMap,
+}
+
+/// Map between local and server IDs of one entity type. Persisted as a
+/// local → server map.
+#[derive(Default, Deserialize)]
+#[serde(from = "HashMap<u64, u64>")]
+struct IdMap {
+    to_server: HashMap<u64, u64>,
+    to_local: HashMap<u64, u64>,
+}
+
+impl From<HashMap<u64, u64>> for IdMap {
+    fn from(to_server: HashMap<u64, u64>) -> Self {
+        let to_local = to_server.iter().map(|(&l, &s)| (s, l)).collect();
+        Self {
+            to_server,
+            to_local,
+        }
+    }
+}
+
+impl Serialize for IdMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_server.serialize(serializer)
+    }
+}
+
+impl IdMap {
+    fn insert(&mut self, local: u64, server: u64) {
+        self.to_server.insert(local, server);
+        self.to_local.insert(server, local);
+    }
+
+    fn remove_server(&mut self, server: u64) -> Option<u64> {
+        let local = self.to_local.remove(&server)?;
+        self.to_server.remove(&local);
+        Some(local)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    Node,
+    Edge,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Node => "node",
+            Self::Edge => "edge",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "node" => Some(Self::Node),
+            "edge" => Some(Self::Edge),
+            _ => None,
+        }
+    }
+}
+
+/// Temporary ID of a local entity that has no server ID yet.
+fn temp_id(kind: Kind, local: u64) -> String {
+    format!("{}:{local}", kind.name())
+}
+
+fn parse_temp_id(temp: &str) -> Option<(Kind, u64)> {
+    let (kind, local) = temp.split_once(':')?;
+    Some((Kind::from_name(kind)?, local.parse().ok()?))
+}
+
+impl SyncState {
+    fn ids(&self, kind: Kind) -> &IdMap {
+        match kind {
+            Kind::Node => &self.nodes,
+            Kind::Edge => &self.edges,
+        }
+    }
+
+    fn ids_mut(&mut self, kind: Kind) -> &mut IdMap {
+        match kind {
+            Kind::Node => &mut self.nodes,
+            Kind::Edge => &mut self.edges,
+        }
+    }
+
+    /// Reference to a local entity: its server ID once known, otherwise
+    /// its temporary ID.
+    fn entity_ref(&self, kind: Kind, local: u64) -> EntityRef {
+        match self.ids(kind).to_server.get(&local) {
+            Some(&server) => EntityRef::Id(server),
+            None => EntityRef::Temp(temp_id(kind, local)),
+        }
+    }
+
+    /// Local ID of the entity a change refers to.
+    fn local_id(&self, kind: Kind, entity: &EntityRef) -> Option<u64> {
+        match entity {
+            EntityRef::Id(server) => self.ids(kind).to_local.get(server).copied(),
+            EntityRef::Temp(temp) => parse_temp_id(temp)
+                .filter(|(k, _)| *k == kind)
+                .map(|(_, local)| local),
+        }
+    }
+
+    /// Replaces temporary IDs that have been mapped since the change was
+    /// queued, e.g. by an earlier push, with server IDs.
+    fn resolve(&self, mut change: SyncChangeRequest) -> SyncChangeRequest {
+        let entity_kind = Kind::from_name(&change.entity_type).unwrap_or(Kind::Node);
+        let refs = [
+            (entity_kind, change.id.as_mut()),
+            (Kind::Node, change.src_id.as_mut()),
+            (Kind::Node, change.dst_id.as_mut()),
+        ];
+        for (kind, entity) in refs {
+            if let Some(entity) = entity
+                && let EntityRef::Temp(temp) = entity
+                && let Some((k, local)) = parse_temp_id(temp)
+                && k == kind
+                && let Some(&server) = self.ids(kind).to_server.get(&local)
+            {
+                *entity = EntityRef::Id(server);
+            }
+        }
+        change
+    }
+
+    /// Properties with updates still in the outbox, as `(kind, local ID, key)`.
+    fn pending_properties(&self) -> HashSet<(Kind, u64, String)> {
+        let mut pending = HashSet::new();
+        for change in self.outbox.iter().filter(|c| c.kind == "update") {
+            let Some(kind) = Kind::from_name(&change.entity_type) else {
+                continue;
+            };
+            let Some(local) = change.id.as_ref().and_then(|e| self.local_id(kind, e)) else {
+                continue;
+            };
+            for key in changed_keys(change) {
+                pending.insert((kind, local, key));
+            }
+        }
+        pending
+    }
+}
+
+impl LocalStore {
+    /// Creates a store backed by an in-memory database. Nothing survives
+    /// the process.
+    pub fn in_memory() -> Self {
+        Self::with_state(GrafeoDB::new_in_memory(), None, SyncState::default())
+    }
+
+    /// Opens the store persisted in `dir`, creating it if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, SyncError> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)
+            .map_err(|e| SyncError::Store(format!("failed to create {}: {e}", dir.display())))?;
+        let state_path = dir.join(STATE_FILE);
+        let state = match std::fs::read_to_string(&state_path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                SyncError::Store(format!("failed to parse {}: {e}", state_path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SyncState::default(),
+            Err(e) => {
+                return Err(SyncError::Store(format!(
+                    "failed to read {}: {e}",
+                    state_path.display()
+                )));
+            }
+        };
+        let db =
+            GrafeoDB::open(dir.join(DATA_FILE)).map_err(|e| SyncError::Store(e.to_string()))?;
+        Ok(Self::with_state(db, Some(dir.to_path_buf()), state))
+    }
+
+    fn with_state(db: GrafeoDB, dir: Option<PathBuf>, state: SyncState) -> Self {
+        Self {
+            db,
+            dir,
+            state: Mutex::new(state),
+            on_conflict: RwLock::new(None),
+            clock: HlcClock::new(),
+            syncing: tokio::sync::Mutex::new(()),
+            changed: Notify::new(),
+        }
+    }
+
+    /// Returns the local database.
+    pub fn db(&self) -> &GrafeoDB {
+        &self.db
+    }
+
+    /// Returns the server epoch the store has pulled up to.
+    pub fn last_epoch(&self) -> u64 {
+        self.state.lock().epoch
+    }
+
+    /// Returns the number of local changes waiting to be pushed.
+    pub fn pending(&self) -> usize {
+        self.state.lock().outbox.len()
+    }
+
+    /// Returns the server ID of a local node, once it has one.
+    pub fn server_node_id(&self, id: NodeId) -> Option<u64> {
+        self.state.lock().nodes.to_server.get(&id.as_u64()).copied()
+    }
+
+    /// Returns the local node replicating a server node.
+    pub fn local_node_id(&self, server_id: u64) -> Option<NodeId> {
+        self.state
+            .lock()
+            .nodes
+            .to_local
+            .get(&server_id)
+            .map(|&l| NodeId::new(l))
+    }
+
+    /// Returns the server ID of a local edge, once it has one.
+    pub fn server_edge_id(&self, id: EdgeId) -> Option<u64> {
+        self.state.lock().edges.to_server.get(&id.as_u64()).copied()
+    }
+
+    /// Returns the local edge replicating a server edge.
+    pub fn local_edge_id(&self, server_id: u64) -> Option<EdgeId> {
+        self.state
+            .lock()
+            .edges
+            .to_local
+            .get(&server_id)
+            .map(|&l| EdgeId::new(l))
+    }
+
+    /// Registers a callback invoked for each conflict the server reports,
+    /// replacing any previous one.
+    ///
+    /// By the time it runs, a change the server did not apply has been
+    /// dropped from the outbox and, for updates, the properties it touched
+    /// are reset to the server's values. A skipped delete is not undone.
+    pub fn on_conflict(&self, callback: impl Fn(&SyncConflict) + Send + Sync + 'static) {
+        *self.on_conflict.write() = Some(Arc::new(callback));
+    }
+
+    // -----------------------------------------------------------------------
+    // Local writes
+    // -----------------------------------------------------------------------
+
+    /// Creates a node and queues it for the server.
+    pub fn create_node(
+        &self,
+        labels: &[&str],
+        properties: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<NodeId, SyncError> {
+        let props: HashMap<String, Value> = properties.into_iter().collect();
+        let id = self.db.create_node_with_props(labels, props.clone());
+        self.enqueue(|state| SyncChangeRequest {
+            labels: Some(labels.iter().map(|l| (*l).to_string()).collect()),
+            after: Some(props_to_json(&props)),
+            ..change(
+                "create",
+                Kind::Node,
+                state.entity_ref(Kind::Node, id.as_u64()),
+            )
+        })?;
+        Ok(id)
+    }
+
+    /// Sets a node property and queues the update for the server.
+    pub fn set_node_property(&self, id: NodeId, key: &str, value: Value) -> Result<(), SyncError> {
+        if self.db.get_node(id).is_none() {
+            return Err(SyncError::Store(format!("node {} not found", id.as_u64())));
+        }
+        self.db.set_node_property(id, key, value.clone());
+        self.enqueue(|state| SyncChangeRequest {
+            after: Some(props_to_json(&HashMap::from([(key.to_string(), value)]))),
+            ..change(
+                "update",
+                Kind::Node,
+                state.entity_ref(Kind::Node, id.as_u64()),
+            )
+        })
+    }
+
+    /// Deletes a node with its edges and queues the delete for the server.
+    pub fn delete_node(&self, id: NodeId) -> Result<(), SyncError> {
+        if !self.db.delete_node(id) {
+            return Err(SyncError::Store(format!("node {} not found", id.as_u64())));
+        }
+        self.enqueue(|state| {
+            change(
+                "delete",
+                Kind::Node,
+                state.entity_ref(Kind::Node, id.as_u64()),
+            )
+        })
+    }
+
+    /// Creates an edge and queues it for the server.
+    pub fn create_edge(
+        &self,
+        src: NodeId,
+        dst: NodeId,
+        edge_type: &str,
+        properties: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<EdgeId, SyncError> {
+        for node in [src, dst] {
+            if self.db.get_node(node).is_none() {
+                return Err(SyncError::Store(format!(
+                    "node {} not found",
+                    node.as_u64()
+                )));
+            }
+        }
+        let props: HashMap<String, Value> = properties.into_iter().collect();
+        let id = self
+            .db
+            .create_edge_with_props(src, dst, edge_type, props.clone());
+        self.enqueue(|state| SyncChangeRequest {
+            edge_type: Some(edge_type.to_string()),
+            src_id: Some(state.entity_ref(Kind::Node, src.as_u64())),
+            dst_id: Some(state.entity_ref(Kind::Node, dst.as_u64())),
+            after: Some(props_to_json(&props)),
+            ..change(
+                "create",
+                Kind::Edge,
+                state.entity_ref(Kind::Edge, id.as_u64()),
+            )
+        })?;
+        Ok(id)
+    }
+
+    /// Sets an edge property and queues the update for the server.
+    pub fn set_edge_property(&self, id: EdgeId, key: &str, value: Value) -> Result<(), SyncError> {
+        if self.db.get_edge(id).is_none() {
+            return Err(SyncError::Store(format!("edge {} not found", id.as_u64())));
+        }
+        self.db.set_edge_property(id, key, value.clone());
+        self.enqueue(|state| SyncChangeRequest {
+            after: Some(props_to_json(&HashMap::from([(key.to_string(), value)]))),
+            ..change(
+                "update",
+                Kind::Edge,
+                state.entity_ref(Kind::Edge, id.as_u64()),
+            )
+        })
+    }
+
+    /// Deletes an edge and queues the delete for the server.
+    pub fn delete_edge(&self, id: EdgeId) -> Result<(), SyncError> {
+        if !self.db.delete_edge(id) {
+            return Err(SyncError::Store(format!("edge {} not found", id.as_u64())));
+        }
+        self.enqueue(|state| {
+            change(
+                "delete",
+                Kind::Edge,
+                state.entity_ref(Kind::Edge, id.as_u64()),
+            )
+        })
+    }
+
+    fn enqueue(
+        &self,
+        change: impl FnOnce(&SyncState) -> SyncChangeRequest,
+    ) -> Result<(), SyncError> {
+        {
+            let mut state = self.state.lock();
+            let mut change = change(&state);
+            change.timestamp = self.clock.now().as_u64();
+            state.outbox.push(change);
+            self.save(&state)?;
+        }
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    // -----------------------------------------------------------------------
+    // Sync
+    // -----------------------------------------------------------------------
+
+    /// Pushes the outbox to the server, then pulls and applies the server's
+    /// changes since the store's epoch.
+    ///
+    /// The store drives `client`'s epoch: it is moved to the store's epoch
+    /// before pushing and advanced with each pull. Changes stay in the
+    /// outbox until the server has answered for them, so a failed run is
+    /// retried by the next one. Pulled updates do not overwrite properties
+    /// with local updates still in the outbox.
+    pub async fn sync_once(&self, client: &SyncClient) -> Result<SyncReport, SyncError> {
+        let _running = self.syncing.lock().await;
+        client.advance_epoch(self.last_epoch());
+
+        let mut report = SyncReport::default();
+        self.push_outbox(client, &mut report).await?;
+        self.pull_changes(client, &mut report).await?;
+        Ok(report)
+    }
+
+    async fn push_outbox(
+        &self,
+        client: &SyncClient,
+        report: &mut SyncReport,
+    ) -> Result<(), SyncError> {
+        loop {
+            let batch: Vec<_> = {
+                let state = self.state.lock();
+                state
+                    .outbox
+                    .iter()
+                    .take(PUSH_BATCH)
+                    .map(|c| state.resolve(c.clone()))
+                    .collect()
+            };
+            if batch.is_empty() {
+                return Ok(());
+            }
+
+            let response = client.push(batch.clone()).await?;
+            let conflicts = self.settle(&batch, &response)?;
+            report.pushed += response.applied;
+
+            let callback = self.on_conflict.read().clone();
+            if let Some(callback) = callback {
+                conflicts.iter().for_each(|c| callback(c));
+            }
+            report.conflicts.extend(conflicts);
+
+            // The rest of a rolled-back batch is retried by the next run.
+            if response.rolled_back {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Records the server IDs of created entities and removes the changes
+    /// the server has answered for from the outbox.
+    ///
+    /// Every change of an applied batch is answered. In a rolled-back batch,
+    /// only changes whose conflict would recur are: they are dropped and the
+    /// others are kept to be pushed again.
+    fn settle(
+        &self,
+        batch: &[SyncChangeRequest],
+        response: &SyncResponse,
+    ) -> Result<Vec<SyncConflict>, SyncError> {
+        let mut state = self.state.lock();
+        for mapping in &response.id_mappings {
+            if let Some((kind, local)) = mapping.temp_id.as_deref().and_then(parse_temp_id) {
+                state.ids_mut(kind).insert(local, mapping.server_id);
+            }
+        }
+
+        let mut dropped = HashSet::new();
+        let mut conflicts = Vec::with_capacity(response.conflicts.len());
+        for record in &response.conflicts {
+            let Some(change) = batch.get(record.request_index) else {
+                continue;
+            };
+            let kept_by_server = matches!(record.reason.as_str(), "client_wins" | "field_merge");
+            if !response.rolled_back || !kept_by_server {
+                dropped.insert(record.request_index);
+                if record.reason != "client_wins" {
+                    self.revert(&state, record, change);
+                }
+            }
+            conflicts.push(SyncConflict {
+                conflict: record.clone(),
+                change: change.clone(),
+            });
+        }
+
+        if response.rolled_back {
+            let mut index = 0;
+            state.outbox.retain(|_| {
+                let keep = !dropped.contains(&index);
+                index += 1;
+                keep
+            });
+        } else {
+            state.outbox.drain(..batch.len());
+        }
+        self.save(&state)?;
+        Ok(conflicts)
+    }
+
+    /// Resets the properties a dropped update touched to the server's
+    /// values. With `field_merge`, only the properties both sides changed
+    /// are reset; the server's next change event carries those it took from
+    /// the client.
+    fn revert(&self, state: &SyncState, record: &ConflictRecord, change: &SyncChangeRequest) {
+        let Some(server_value) = &record.server_value else {
+            return;
+        };
+        let Some(kind) = Kind::from_name(&change.entity_type) else {
+            return;
+        };
+        if change.kind != "update" {
+            return;
+        }
+        let Some(local) = change.id.as_ref().and_then(|e| state.local_id(kind, e)) else {
+            return;
+        };
+        let keys = if record.properties.is_empty() {
+            changed_keys(change)
+        } else {
+            record.properties.clone()
+        };
+        let mut server = json_to_props(server_value);
+        for key in keys {
+            match server.remove(&key) {
+                Some(value) => self.set_property(kind, local, &key, value),
+                None => self.remove_property(kind, local, &key),
+            }
+        }
+    }
+
+    async fn pull_changes(
+        &self,
+        client: &SyncClient,
+        report: &mut SyncReport,
+    ) -> Result<(), SyncError> {
+        let mut since = client.last_epoch();
+        loop {
+            let pulled = client.changes_since(since, PULL_LIMIT, true).await?;
+            let full = pulled.changes.len() >= PULL_LIMIT;
+            // A full page of whole epochs continues after its last epoch. A
+            // server that may split epochs is pulled again from the last
+            // epoch of a full page, whose events are then applied twice,
+            // which is harmless; one epoch filling a page cannot advance.
+            let last = pulled.changes.last().map_or(since, |e| e.epoch);
+            let (epoch, next) = if !full {
+                (pulled.server_epoch, None)
+            } else if pulled.complete {
+                (pulled.server_epoch, Some(pulled.server_epoch + 1))
+            } else if last > since {
+                (last, Some(last))
+            } else {
+                return Err(SyncError::EpochTooLarge {
+                    epoch: since,
+                    limit: PULL_LIMIT,
+                });
+            };
+
+            report.pulled += self.apply_pulled(&pulled.changes, epoch)?;
+            client.advance_epoch(epoch);
+            match next {
+                Some(next) => since = next,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Applies pulled events and moves the store's epoch to `epoch`.
+    fn apply_pulled(&self, events: &[ChangeEventDto], epoch: u64) -> Result<usize, SyncError> {
+        let mut state = self.state.lock();
+        let pending = state.pending_properties();
+        let applied = events
+            .iter()
+            .filter(|event| self.apply_event(&mut state, &pending, event))
+            .count();
+        state.epoch = state.epoch.max(epoch);
+        self.save(&state)?;
+        Ok(applied)
+    }
+
+    /// Applies one server event. Returns `false` for events that do not
+    /// change the store: triples, creates of entities the store already
+    /// has (such as its own pushed creates) and changes to entities it
+    /// does not have.
+    fn apply_event(
+        &self,
+        state: &mut SyncState,
+        pending: &HashSet<(Kind, u64, String)>,
+        event: &ChangeEventDto,
+    ) -> bool {
+        let Some(kind) = Kind::from_name(&event.entity_type) else {
+            return false;
+        };
+        self.clock.update(HlcTimestamp::from_u64(event.timestamp));
+        match event.kind.as_str() {
+            "create" => {
+                if state.ids(kind).to_local.contains_key(&event.id) {
+                    return false;
+                }
+                let props = event.after.as_ref().map(json_to_props).unwrap_or_default();
+                let local = match kind {
+                    Kind::Node => {
+                        let labels: Vec<&str> =
+                            event.labels.iter().flatten().map(String::as_str).collect();
+                        self.db.create_node_with_props(&labels, props).as_u64()
+                    }
+                    Kind::Edge => {
+                        let endpoint = |id: Option<u64>| {
+                            id.and_then(|s| state.nodes.to_local.get(&s).copied())
+                        };
+                        let (Some(src), Some(dst), Some(edge_type)) = (
+                            endpoint(event.src_id),
+                            endpoint(event.dst_id),
+                            event.edge_type.as_deref(),
+                        ) else {
+                            tracing::debug!(id = event.id, "Skipping edge with unknown endpoints");
+                            return false;
+                        };
+                        self.db
+                            .create_edge_with_props(
+                                NodeId::new(src),
+                                NodeId::new(dst),
+                                edge_type,
+                                props,
+                            )
+                            .as_u64()
+                    }
+                };
+                state.ids_mut(kind).insert(local, event.id);
+            }
+            "update" => {
+                let Some(&local) = state.ids(kind).to_local.get(&event.id) else {
+                    return false;
+                };
+                if !self.exists(kind, local) {
+                    return false;
+                }
+                let after = event.after.as_ref().map(json_to_props).unwrap_or_default();
+                let before = event.before.as_ref().map(json_to_props).unwrap_or_default();
+                let is_pending = |key: &str| pending.contains(&(kind, local, key.to_string()));
+                for key in before.keys().filter(|k| !after.contains_key(*k)) {
+                    if !is_pending(key) {
+                        self.remove_property(kind, local, key);
+                    }
+                }
+                for (key, value) in after {
+                    if !is_pending(&key) {
+                        self.set_property(kind, local, &key, value);
+                    }
+                }
+            }
+            "delete" => {
+                let Some(local) = state.ids_mut(kind).remove_server(event.id) else {
+                    return false;
+                };
+                match kind {
+                    Kind::Node => self.db.delete_node(NodeId::new(local)),
+                    Kind::Edge => self.db.delete_edge(EdgeId::new(local)),
+                };
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn exists(&self, kind: Kind, local: u64) -> bool {
+        match kind {
+            Kind::Node => self.db.get_node(NodeId::new(local)).is_some(),
+            Kind::Edge => self.db.get_edge(EdgeId::new(local)).is_some(),
+        }
+    }
+
+    fn set_property(&self, kind: Kind, local: u64, key: &str, value: Value) {
+        match kind {
+            Kind::Node => self.db.set_node_property(NodeId::new(local), key, value),
+            Kind::Edge => self.db.set_edge_property(EdgeId::new(local), key, value),
+        }
+    }
+
+    fn remove_property(&self, kind: Kind, local: u64, key: &str) {
+        match kind {
+            Kind::Node => self.db.remove_node_property(NodeId::new(local), key),
+            Kind::Edge => self.db.remove_edge_property(EdgeId::new(local), key),
+        };
+    }
+
+    /// Writes the sync state to a temp file and renames it over the state
+    /// file. No-op for in-memory stores.
+    fn save(&self, state: &SyncState) -> Result<(), SyncError> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let path = dir.join(STATE_FILE);
+        let json = serde_json::to_string(state).map_err(|e| SyncError::Store(e.to_string()))?;
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, json)
+            .and_then(|()| std::fs::rename(&tmp_path, &path))
+            .map_err(|e| SyncError::Store(format!("failed to write {}: {e}", path.display())))
+    }
+}
+
+/// A change of `kind` to `entity`, without a timestamp.
+fn change(kind: &str, entity_type: Kind, entity: EntityRef) -> SyncChangeRequest {
+    SyncChangeRequest {
+        kind: kind.to_string(),
+        entity_type: entity_type.name().to_string(),
+        id: Some(entity),
+        timestamp: 0,
+        labels: None,
+        edge_type: None,
+        src_id: None,
+        dst_id: None,
+        after: None,
+        crdt_op: None,
+        crdt_property: None,
+    }
+}
+
+/// Property keys an update writes.
+fn changed_keys(change: &SyncChangeRequest) -> Vec<String> {
+    let mut keys: Vec<String> = change
+        .after
+        .as_ref()
+        .and_then(serde_json::Value::as_object)
+        .map(|after| after.keys().cloned().collect())
+        .unwrap_or_default();
+    keys.extend(change.crdt_property.clone());
+    keys
+}
+
+fn props_to_json(props: &HashMap<String, Value>) -> serde_json::Value {
+    serde_json::to_value(props)
+        .unwrap_or_else(|_| serde_json::Value::Object(serde_json::Map::default()))
+}
+
+fn json_to_props(json: &serde_json::Value) -> HashMap<String, Value> {
+    serde_json::from_value(json.clone()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::Mutex as StdMutex;
+
+    use axum::Router;
+    use axum::extract::{Path as UrlPath, Query, State};
+    use axum::response::Json;
+    use axum::routing::{MethodRouter, get, post};
+    use grafeo_service::conflicts::{ConflictPolicy, SyncPolicy};
+    use grafeo_service::database::DatabaseManager;
+    use grafeo_service::sync::{ChangesResponse, SyncRequest, SyncService};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    // ---------------------------------------------------------------------------
+    // Server backed by a real SyncService
+    // ---------------------------------------------------------------------------
+
+    #[derive(Deserialize)]
+    struct PullQuery {
+        since: u64,
+        limit: usize,
+        #[serde(default)]
+        complete: bool,
+    }
+
+    async fn changes(
+        State(databases): State<Arc<DatabaseManager>>,
+        UrlPath(name): UrlPath<String>,
+        Query(q): Query<PullQuery>,
+    ) -> Json<ChangesResponse> {
+        let resp = if q.complete {
+            SyncService::pull_complete(&databases, &name, q.since, q.limit)
+        } else {
+            SyncService::pull(&databases, &name, q.since, q.limit)
+        };
+        Json(resp.unwrap())
+    }
+
+    /// A server that predates `complete` pulls.
+    async fn split_changes(
+        State(databases): State<Arc<DatabaseManager>>,
+        UrlPath(name): UrlPath<String>,
+        Query(q): Query<PullQuery>,
+    ) -> Json<ChangesResponse> {
+        Json(SyncService::pull(&databases, &name, q.since, q.limit).unwrap())
+    }
+
+    async fn sync(
+        State(databases): State<Arc<DatabaseManager>>,
+        UrlPath(name): UrlPath<String>,
+        Json(req): Json<SyncRequest>,
+    ) -> Json<SyncResponse> {
+        Json(SyncService::apply(&databases, &name, req).unwrap())
+    }
+
+    async fn spawn_server() -> (String, Arc<DatabaseManager>) {
+        spawn_server_with(get(changes)).await
+    }
+
+    async fn spawn_server_with(
+        changes: MethodRouter<Arc<DatabaseManager>>,
+    ) -> (String, Arc<DatabaseManager>) {
+        let databases = Arc::new(DatabaseManager::new(None, false));
+        databases.set_cdc_enabled(true);
+        let app = Router::new()
+            .route("/db/{name}/changes", changes)
+            .route("/db/{name}/sync", post(sync))
+            .with_state(Arc::clone(&databases));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        (format!("http://{addr}"), databases)
+    }
+
+    fn client(base: &str, client_id: &str) -> SyncClient {
+        SyncClient::new(base, "default", client_id).unwrap()
+    }
+
+    fn name(value: &str) -> [(String, Value); 1] {
+        [("name".to_string(), Value::from(value))]
+    }
+
+    fn local_name(store: &LocalStore, id: NodeId) -> Option<Value> {
+        store.db().get_node(id)?.get_property("name").cloned()
+    }
+
+    fn server_name(databases: &DatabaseManager, id: u64) -> Option<Value> {
+        let node = databases
+            .get("default")
+            .unwrap()
+            .db()
+            .get_node(NodeId::new(id))?;
+        node.get_property("name").cloned()
+    }
+
+    // ---------------------------------------------------------------------------
+    // Push / pull
+    // ---------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn offline_changes_are_pushed_with_server_ids() {
+        let (base, databases) = spawn_server().await;
+        let store = LocalStore::in_memory();
+
+        let alix = store.create_node(&["Person"], name("Alix")).unwrap();
+        let gus = store.create_node(&["Person"], name("Gus")).unwrap();
+        let knows = store.create_edge(alix, gus, "KNOWS", []).unwrap();
+        store
+            .set_node_property(gus, "name", Value::from("Gustavo"))
+            .unwrap();
+        assert_eq!(store.pending(), 4);
+
+        let report = store.sync_once(&client(&base, "device-1")).await.unwrap();
+        assert_eq!(report.pushed, 4);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(store.pending(), 0);
+
+        let alix_id = store.server_node_id(alix).unwrap();
+        let gus_id = store.server_node_id(gus).unwrap();
+        assert_eq!(server_name(&databases, alix_id), Some(Value::from("Alix")));
+        assert_eq!(
+            server_name(&databases, gus_id),
+            Some(Value::from("Gustavo"))
+        );
+        let edge_id = store.server_edge_id(knows).unwrap();
+        let db = databases.get("default").unwrap().db();
+        let edge = db.get_edge(EdgeId::new(edge_id)).unwrap();
+        assert_eq!(edge.src, NodeId::new(alix_id));
+        assert_eq!(edge.dst, NodeId::new(gus_id));
+
+        // Pulling back its own creates does not duplicate them locally.
+        assert_eq!(store.db().node_count(), 2);
+        assert_eq!(store.local_node_id(alix_id), Some(alix));
+    }
+
+    #[tokio::test]
+    async fn pulled_changes_are_applied_locally() {
+        let (base, _databases) = spawn_server().await;
+        let (a, b) = (LocalStore::in_memory(), LocalStore::in_memory());
+        let (client_a, client_b) = (client(&base, "device-a"), client(&base, "device-b"));
+
+        let alix = a.create_node(&["Person"], name("Alix")).unwrap();
+        let gus = a.create_node(&["Person"], name("Gus")).unwrap();
+        a.create_edge(alix, gus, "KNOWS", []).unwrap();
+        a.sync_once(&client_a).await.unwrap();
+
+        let report = b.sync_once(&client_b).await.unwrap();
+        assert!(report.pulled >= 3);
+        let alix_b = b.local_node_id(a.server_node_id(alix).unwrap()).unwrap();
+        let gus_b = b.local_node_id(a.server_node_id(gus).unwrap()).unwrap();
+        assert_eq!(local_name(&b, alix_b), Some(Value::from("Alix")));
+        assert_eq!(b.db().edge_count(), 1);
+
+        b.set_node_property(alix_b, "name", Value::from("Alix B."))
+            .unwrap();
+        b.delete_node(gus_b).unwrap();
+        b.sync_once(&client_b).await.unwrap();
+
+        a.sync_once(&client_a).await.unwrap();
+        assert_eq!(local_name(&a, alix), Some(Value::from("Alix B.")));
+        assert!(a.db().get_node(gus).is_none());
+        assert_eq!(a.db().edge_count(), 0);
+        assert_eq!(a.last_epoch(), b.last_epoch());
+    }
+
+    #[tokio::test]
+    async fn epochs_larger_than_a_pull_are_applied_whole() {
+        let (base, databases) = spawn_server().await;
+        let db = databases.get("default").unwrap().db();
+        // Direct API calls all land in the same epoch.
+        for _ in 0..PULL_LIMIT + 50 {
+            db.create_node(&["Bulk"]);
+        }
+        db.session().execute("INSERT (:Later)").unwrap();
+
+        let store = LocalStore::in_memory();
+        let report = store.sync_once(&client(&base, "device")).await.unwrap();
+        assert!(report.pulled >= PULL_LIMIT + 51);
+        assert_eq!(store.db().node_count(), PULL_LIMIT + 51);
+    }
+
+    #[tokio::test]
+    async fn epoch_a_server_cannot_return_whole_is_not_skipped() {
+        let (base, databases) = spawn_server_with(get(split_changes)).await;
+        let db = databases.get("default").unwrap().db();
+        for _ in 0..PULL_LIMIT + 50 {
+            db.create_node(&["Bulk"]);
+        }
+
+        let store = LocalStore::in_memory();
+        let err = store.sync_once(&client(&base, "device")).await.unwrap_err();
+        assert!(matches!(err, SyncError::EpochTooLarge { .. }));
+        assert_eq!(store.last_epoch(), 0);
+    }
+
+    #[tokio::test]
+    async fn failed_push_keeps_outbox() {
+        let store = LocalStore::in_memory();
+        store.create_node(&["Person"], name("Alix")).unwrap();
+
+        // Nothing listens on port 9 (discard).
+        let offline = client("http://127.0.0.1:9", "device-1");
+        assert!(store.sync_once(&offline).await.is_err());
+        assert_eq!(store.pending(), 1);
+
+        let (base, databases) = spawn_server().await;
+        store.sync_once(&client(&base, "device-1")).await.unwrap();
+        assert_eq!(store.pending(), 0);
+        assert_eq!(databases.get("default").unwrap().db().node_count(), 1);
+    }
+
+    // ---------------------------------------------------------------------------
+    // Conflicts
+    // ---------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn skipped_update_is_reported_and_reverted() {
+        let (base, databases) = spawn_server().await;
+        let policy = SyncPolicy {
+            policy: ConflictPolicy::ServerWins,
+            ..SyncPolicy::default()
+        };
+        SyncService::set_policy(&databases, "default", policy).unwrap();
+        let (a, b) = (LocalStore::in_memory(), LocalStore::in_memory());
+        let (client_a, client_b) = (client(&base, "device-a"), client(&base, "device-b"));
+
+        let alix = a.create_node(&["Person"], name("Alix")).unwrap();
+        a.sync_once(&client_a).await.unwrap();
+        b.sync_once(&client_b).await.unwrap();
+        let server_id = a.server_node_id(alix).unwrap();
+        let alix_b = b.local_node_id(server_id).unwrap();
+        b.set_node_property(alix_b, "name", Value::from("Gus"))
+            .unwrap();
+        b.sync_once(&client_b).await.unwrap();
+
+        // `a` renames without having seen `b`'s rename.
+        let seen = Arc::new(StdMutex::new(Vec::new()));
+        let seen_by_callback = Arc::clone(&seen);
+        a.on_conflict(move |c| {
+            seen_by_callback
+                .lock()
+                .unwrap()
+                .push(c.conflict.reason.clone());
+        });
+        a.set_node_property(alix, "name", Value::from("Vincent"))
+            .unwrap();
+        let report = a.sync_once(&client_a).await.unwrap();
+
+        assert_eq!(report.pushed, 0);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(*seen.lock().unwrap(), ["server_wins"]);
+        assert_eq!(a.pending(), 0);
+        assert_eq!(local_name(&a, alix), Some(Value::from("Gus")));
+        assert_eq!(server_name(&databases, server_id), Some(Value::from("Gus")));
+    }
+
+    // ---------------------------------------------------------------------------
+    // Persistence
+    // ---------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn store_persists_outbox_epoch_and_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let (base, databases) = spawn_server().await;
+
+        let alix = {
+            let store = LocalStore::open(dir.path()).unwrap();
+            store.create_node(&["Person"], name("Alix")).unwrap()
+        };
+
+        let (server_id, epoch) = {
+            let store = LocalStore::open(dir.path()).unwrap();
+            assert_eq!(store.pending(), 1);
+            assert_eq!(local_name(&store, alix), Some(Value::from("Alix")));
+            store.sync_once(&client(&base, "device-1")).await.unwrap();
+            (store.server_node_id(alix).unwrap(), store.last_epoch())
+        };
+
+        let store = LocalStore::open(dir.path()).unwrap();
+        assert_eq!(store.pending(), 0);
+        assert_eq!(store.last_epoch(), epoch);
+        assert_eq!(store.server_node_id(alix), Some(server_id));
+
+        // An update of the created node goes to the same server node.
+        store
+            .set_node_property(alix, "name", Value::from("Alix M."))
+            .unwrap();
+        store.sync_once(&client(&base, "device-1")).await.unwrap();
+        assert_eq!(databases.get("default").unwrap().db().node_count(), 1);
+        assert_eq!(
+            server_name(&databases, server_id),
+            Some(Value::from("Alix M."))
+        );
+    }
+
+    // ---------------------------------------------------------------------------
+    // Background sync
+    // ---------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn background_sync_pushes_local_changes() {
+        let (base, databases) = spawn_server().await;
+        let store = Arc::new(LocalStore::in_memory());
+        let handle = crate::BackgroundSync::new(std::time::Duration::from_secs(3600))
+            .spawn(Arc::clone(&store), client(&base, "device-1"));
+
+        store.create_node(&["Person"], name("Alix")).unwrap();
+        let server = databases.get("default").unwrap().db();
+        for _ in 0..100 {
+            if server.node_count() == 1 && store.pending() == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(server.node_count(), 1);
+        assert_eq!(store.pending(), 0);
+
+        handle.stop().await;
+    }
+}
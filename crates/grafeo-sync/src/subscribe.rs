@@ -0,0 +1,459 @@
+//! Live change subscriptions over the server's SSE changefeed.
+//!
+//! [`SyncClient::subscribe`] streams `GET /db/{name}/changes/stream`
+//! (server feature `push-changefeed`). The subscription keeps a cursor of
+//! the last delivered epoch: after a disconnect it reconnects from that
+//! epoch and drops the events it already delivered.
+//!
+//! A server drops events for a subscriber that falls behind and reports it
+//! with an `event: gap` carrying `{"last_epoch": N}`. The subscription then
+//! pulls the events after its cursor from `GET /db/{name}/changes` and
+//! delivers them, dropping the copies the server sends after the gap. Epochs
+//! are not contiguous (other databases and filtered-out writes advance them
+//! too), so a jump in epochs alone does not trigger a pull.
+
+use futures_util::{Stream, StreamExt};
+use grafeo_service::sse::SseParser;
+use grafeo_service::sync::{ChangeCursor, ChangeEventDto, ChangeGap};
+
+use crate::{SyncClient, SyncError};
+
+/// Page size of the pulls that backfill a gap.
+const BACKFILL_LIMIT: usize = 10_000;
+
+impl SyncClient {
+    /// Subscribes to the database's change events with `epoch >= since`,
+    /// pushed by the server as they are committed.
+    ///
+    /// The stream reconnects after disconnects and on 429, 503 and
+    /// connection errors, backing off per the client's [`RetryPolicy`]
+    /// (without its retry limit), and resumes from the last delivered
+    /// epoch without repeating events. When the server reports a `gap`,
+    /// the events lost to subscriber lag are backfilled from
+    /// `GET /db/{name}/changes`, in whole epochs. Other
+    /// errors, e.g. [`SyncError::Unauthorized`], a 404 from a server
+    /// without `push-changefeed`, or [`SyncError::EpochTooLarge`] from a
+    /// server that cannot backfill an epoch whole, end the stream after
+    /// being yielded.
+    ///
+    /// The stream does not move the client's epoch; call
+    /// [`advance_epoch`](Self::advance_epoch) after applying events if
+    /// pulls and pushes should see them.
+    ///
+    /// [`RetryPolicy`]: crate::RetryPolicy
+    pub fn subscribe(
+        &self,
+        since: u64,
+    ) -> impl Stream<Item = Result<ChangeEventDto, SyncError>> + Send + 'static {
+        let client = self.clone();
+        async_stream::stream! {
+            let mut cursor = ChangeCursor::new(since);
+            let mut backoff = client.retry.initial_backoff;
+            loop {
+                let resp = match client.open_stream(cursor.epoch()).await {
+                    Ok(resp) => resp,
+                    Err(e) if e.is_retryable(true) => {
+                        let delay = e.retry_after().unwrap_or(backoff).min(client.retry.max_backoff);
+                        tracing::debug!(error = %e, delay = ?delay, "Change stream unavailable; retrying");
+                        tokio::time::sleep(delay).await;
+                        backoff = (backoff * 2).min(client.retry.max_backoff);
+                        continue;
+                    }
+                    Err(e) => {
+                        yield Err(e);
+                        return;
+                    }
+                };
+                backoff = client.retry.initial_backoff;
+
+                let mut body = resp.bytes_stream();
+                let mut parser = SseParser::default();
+                'read: while let Some(chunk) = body.next().await {
+                    let Ok(chunk) = chunk else {
+                        break;
+                    };
+                    for sse in parser.push(&chunk) {
+                        match sse.event.as_str() {
+                            "message" => {
+                                let event: ChangeEventDto = match serde_json::from_str(&sse.data) {
+                                    Ok(event) => event,
+                                    Err(e) => {
+                                        tracing::debug!(error = %e, "Ignoring malformed change event");
+                                        continue;
+                                    }
+                                };
+                                if cursor.advance(&event) {
+                                    yield Ok(event);
+                                }
+                            }
+                            "gap" => {
+                                let gap = serde_json::from_str::<ChangeGap>(&sse.data).ok();
+                                tracing::debug!(
+                                    last_epoch = gap.map(|g| g.last_epoch),
+                                    epoch = cursor.epoch(),
+                                    "Change stream subscriber lagged; backfilling"
+                                );
+                                match client.backfill(&mut cursor).await {
+                                    Ok(missed) => {
+                                        for missed in missed {
+                                            yield Ok(missed);
+                                        }
+                                    }
+                                    Err(e @ SyncError::EpochTooLarge { .. }) => {
+                                        yield Err(e);
+                                        return;
+                                    }
+                                    Err(e) => {
+                                        tracing::debug!(error = %e, "Change stream backfill failed; reconnecting");
+                                        break 'read;
+                                    }
+                                }
+                            }
+                            other => tracing::debug!(event = other, "Ignoring change stream event"),
+                        }
+                    }
+                }
+
+                tracing::debug!(epoch = cursor.epoch(), "Change stream disconnected; reconnecting");
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+
+    async fn open_stream(&self, since: u64) -> Result<reqwest::Response, SyncError> {
+        let mut url = self.stream_url.clone();
+        url.query_pairs_mut()
+            .append_pair("since", &since.to_string());
+        let resp = self
+            .authorize(self.stream_http.get(url))
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await?;
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(SyncError::from_response(resp).await)
+        }
+    }
+
+    /// Returns the events after the cursor that it has not delivered,
+    /// advancing the cursor past them.
+    ///
+    /// Fails with [`SyncError::EpochTooLarge`] if an epoch holds more events
+    /// than a page and the server cannot return it whole.
+    async fn backfill(&self, cursor: &mut ChangeCursor) -> Result<Vec<ChangeEventDto>, SyncError> {
+        let mut missed = Vec::new();
+        let mut from = cursor.epoch();
+        loop {
+            let page = self.changes_since(from, BACKFILL_LIMIT, true).await?;
+            let Some(last) = page.changes.last().map(|e| e.epoch) else {
+                return Ok(missed);
+            };
+            let full = page.changes.len() >= BACKFILL_LIMIT;
+            if full && !page.complete && last == from {
+                return Err(SyncError::EpochTooLarge {
+                    epoch: from,
+                    limit: BACKFILL_LIMIT,
+                });
+            }
+            for event in page.changes {
+                if cursor.advance(&event) {
+                    missed.push(event);
+                }
+            }
+            if !full {
+                return Ok(missed);
+            }
+            // A complete page ends with a whole epoch; otherwise its last
+            // epoch may continue on the next page.
+            from = if page.complete { last + 1 } else { last };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use axum::Router;
+    use axum::extract::{Query, State};
+    use axum::http::StatusCode;
+    use axum::response::sse::{Event, Sse};
+    use axum::response::{IntoResponse, Json, Response};
+    use axum::routing::get;
+    use futures_util::stream;
+    use grafeo_service::sync::ChangesResponse;
+    use serde::Deserialize;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::RetryPolicy;
+
+    // ---------------------------------------------------------------------------
+    // Mock changefeed
+    // ---------------------------------------------------------------------------
+
+    fn event(epoch: u64, id: u64) -> ChangeEventDto {
+        serde_json::from_value(serde_json::json!({
+            "id": id, "entity_type": "node", "kind": "create",
+            "epoch": epoch, "timestamp": id,
+        }))
+        .unwrap()
+    }
+
+    /// What a stream connection sends.
+    #[derive(Clone)]
+    enum Sent {
+        Change(Box<ChangeEventDto>),
+        /// A `gap` event with this `last_epoch`.
+        Gap(u64),
+    }
+
+    fn change(epoch: u64, id: u64) -> Sent {
+        Sent::Change(Box::new(event(epoch, id)))
+    }
+
+    /// Changefeed whose `n`th stream connection sends `connections[n]` and
+    /// closes; the last one stays open. `/changes` serves `history`.
+    struct Feed {
+        connections: Vec<Vec<Sent>>,
+        history: Vec<ChangeEventDto>,
+        /// `since` of each stream connection.
+        requests: Mutex<Vec<u64>>,
+        status: StatusCode,
+        /// Whether `/changes` honors `complete=true`.
+        complete_pulls: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct SinceQuery {
+        #[serde(default)]
+        since: u64,
+        #[serde(default = "no_limit")]
+        limit: usize,
+        #[serde(default)]
+        complete: bool,
+    }
+
+    fn no_limit() -> usize {
+        usize::MAX
+    }
+
+    async fn feed_stream(State(feed): State<Arc<Feed>>, Query(q): Query<SinceQuery>) -> Response {
+        if feed.status != StatusCode::OK {
+            return feed.status.into_response();
+        }
+        let n = {
+            let mut requests = feed.requests.lock().unwrap();
+            requests.push(q.since);
+            requests.len() - 1
+        };
+        let events = feed.connections.get(n).cloned().unwrap_or_default();
+        let last = n + 1 >= feed.connections.len();
+        let events = stream::iter(events).map(|sent| {
+            Ok::<_, Infallible>(match sent {
+                Sent::Change(e) => Event::default().data(serde_json::to_string(&e).unwrap()),
+                Sent::Gap(last_epoch) => Event::default()
+                    .event("gap")
+                    .data(serde_json::to_string(&ChangeGap { last_epoch }).unwrap()),
+            })
+        });
+        if last {
+            Sse::new(events.chain(stream::pending())).into_response()
+        } else {
+            Sse::new(events).into_response()
+        }
+    }
+
+    async fn feed_changes(
+        State(feed): State<Arc<Feed>>,
+        Query(q): Query<SinceQuery>,
+    ) -> Json<ChangesResponse> {
+        let complete = q.complete && feed.complete_pulls;
+        let mut changes: Vec<_> = feed
+            .history
+            .iter()
+            .filter(|e| e.epoch >= q.since)
+            .cloned()
+            .collect();
+        if changes.len() > q.limit {
+            let end = if complete {
+                let last = changes[q.limit - 1].epoch;
+                changes.partition_point(|e| e.epoch <= last)
+            } else {
+                q.limit
+            };
+            changes.truncate(end);
+        }
+        Json(ChangesResponse {
+            server_epoch: changes.last().map_or(q.since, |e| e.epoch),
+            changes,
+            complete,
+        })
+    }
+
+    async fn spawn_feed(feed: Feed) -> (SyncClient, Arc<Feed>) {
+        let feed = Arc::new(feed);
+        let app = Router::new()
+            .route("/db/{name}/changes/stream", get(feed_stream))
+            .route("/db/{name}/changes", get(feed_changes))
+            .with_state(Arc::clone(&feed));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let client = SyncClient::builder(&format!("http://{addr}"), "default", "dev-1")
+            .retry(RetryPolicy {
+                max_retries: 0,
+                initial_backoff: Duration::from_millis(1),
+                max_backoff: Duration::from_millis(10),
+            })
+            .build()
+            .unwrap();
+        (client, feed)
+    }
+
+    async fn take_ids(
+        stream: impl Stream<Item = Result<ChangeEventDto, SyncError>>,
+        n: usize,
+    ) -> Vec<u64> {
+        let events =
+            tokio::time::timeout(Duration::from_secs(5), stream.take(n).collect::<Vec<_>>())
+                .await
+                .unwrap();
+        events.into_iter().map(|e| e.unwrap().id).collect()
+    }
+
+    // ---------------------------------------------------------------------------
+    // Tests
+    // ---------------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn subscribe_resumes_without_repeating_events() {
+        let (client, feed) = spawn_feed(Feed {
+            connections: vec![
+                vec![change(5, 1), change(6, 2), change(6, 3)],
+                // The reconnect repeats epoch 6.
+                vec![change(6, 2), change(6, 3), change(7, 4)],
+            ],
+            history: vec![],
+            requests: Mutex::new(Vec::new()),
+            status: StatusCode::OK,
+            complete_pulls: true,
+        })
+        .await;
+
+        let ids = take_ids(client.subscribe(5), 4).await;
+        assert_eq!(ids, [1, 2, 3, 4]);
+        assert_eq!(*feed.requests.lock().unwrap(), [5, 6]);
+    }
+
+    #[tokio::test]
+    async fn subscribe_backfills_after_a_gap() {
+        let history = vec![event(1, 1), event(2, 2), event(3, 3), event(4, 4)];
+        let (client, _feed) = spawn_feed(Feed {
+            // Events of epochs 2 and 3 were dropped; the server's own
+            // backfill repeats epoch 4.
+            connections: vec![vec![change(1, 1), Sent::Gap(1), change(4, 4)]],
+            history,
+            requests: Mutex::new(Vec::new()),
+            status: StatusCode::OK,
+            complete_pulls: true,
+        })
+        .await;
+
+        let ids = take_ids(client.subscribe(1), 4).await;
+        assert_eq!(ids, [1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn subscribe_does_not_backfill_skipped_epochs_without_a_gap() {
+        let (client, _feed) = spawn_feed(Feed {
+            // Epochs 2 and 3 hold writes the subscription does not see.
+            connections: vec![vec![change(1, 1), change(4, 4)]],
+            history: vec![event(1, 1), event(2, 2), event(3, 3), event(4, 4)],
+            requests: Mutex::new(Vec::new()),
+            status: StatusCode::OK,
+            complete_pulls: true,
+        })
+        .await;
+
+        let ids = take_ids(client.subscribe(1), 2).await;
+        assert_eq!(ids, [1, 4]);
+    }
+
+    /// History with an epoch 2 of more events than a backfill page, between
+    /// single events at epochs 1 and 3.
+    fn oversized_history() -> Vec<ChangeEventDto> {
+        let mut history = vec![event(1, 1)];
+        history.extend((0..BACKFILL_LIMIT as u64 + 5).map(|i| event(2, 100 + i)));
+        history.push(event(3, 3));
+        history
+    }
+
+    #[tokio::test]
+    async fn subscribe_backfills_epochs_larger_than_a_page() {
+        let (client, _feed) = spawn_feed(Feed {
+            connections: vec![vec![change(1, 1), Sent::Gap(1), change(3, 3)]],
+            history: oversized_history(),
+            requests: Mutex::new(Vec::new()),
+            status: StatusCode::OK,
+            complete_pulls: true,
+        })
+        .await;
+
+        let ids = take_ids(client.subscribe(1), BACKFILL_LIMIT + 7).await;
+        assert_eq!(ids.len(), BACKFILL_LIMIT + 7);
+        assert_eq!(ids.last(), Some(&3));
+    }
+
+    #[tokio::test]
+    async fn subscribe_ends_when_an_epoch_cannot_be_backfilled_whole() {
+        let (client, _feed) = spawn_feed(Feed {
+            connections: vec![vec![change(1, 1), Sent::Gap(1), change(3, 3)]],
+            history: oversized_history(),
+            requests: Mutex::new(Vec::new()),
+            status: StatusCode::OK,
+            complete_pulls: false,
+        })
+        .await;
+
+        let items: Vec<_> =
+            tokio::time::timeout(Duration::from_secs(5), client.subscribe(1).collect())
+                .await
+                .unwrap();
+        let last = items.last().unwrap();
+        assert!(matches!(
+            last,
+            Err(SyncError::EpochTooLarge { epoch: 2, .. })
+        ));
+        assert!(
+            items
+                .iter()
+                .all(|item| !matches!(item, Ok(e) if e.epoch == 3))
+        );
+    }
+
+    #[tokio::test]
+    async fn subscribe_ends_after_fatal_error() {
+        let (client, _feed) = spawn_feed(Feed {
+            connections: vec![],
+            history: vec![],
+            requests: Mutex::new(Vec::new()),
+            status: StatusCode::UNAUTHORIZED,
+            complete_pulls: true,
+        })
+        .await;
+
+        let items: Vec<_> = client.subscribe(0).collect().await;
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0], Err(SyncError::Unauthorized(_))));
+    }
+}
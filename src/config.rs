@@ -1,6 +1,9 @@
 //! Server configuration via CLI args and environment variables.
 
 use clap::Parser;
+#[cfg(feature = "backup-encryption")]
+use grafeo_service::backup_crypto::BackupKeys;
+use grafeo_service::backup_target::BackupTargetConfig;
 
 /// HTTP server for the Grafeo graph database.
 #[derive(Parser, Debug, Clone)]
@@ -85,6 +88,62 @@ pub struct Config {
     #[arg(long, env = "GRAFEO_BACKUP_RETENTION")]
     pub backup_retention: Option<usize>,
 
+    /// Where backups are kept: `file:///path` or, with the `s3-backup`
+    /// feature, `s3://bucket/prefix`. --backup-dir then serves as a local
+    /// cache. Default: --backup-dir itself.
+    #[arg(long, env = "GRAFEO_BACKUP_TARGET", requires = "backup_dir")]
+    pub backup_target: Option<String>,
+
+    /// Endpoint of an S3-compatible store (e.g. http://localhost:9000).
+    /// Omit for AWS.
+    #[cfg(feature = "s3-backup")]
+    #[arg(long, env = "GRAFEO_BACKUP_S3_ENDPOINT")]
+    pub backup_s3_endpoint: Option<String>,
+
+    /// Region of the backup bucket.
+    #[cfg(feature = "s3-backup")]
+    #[arg(long, default_value = "us-east-1", env = "AWS_REGION")]
+    pub backup_s3_region: String,
+
+    /// Access key for the backup bucket.
+    #[cfg(feature = "s3-backup")]
+    #[arg(long, env = "AWS_ACCESS_KEY_ID")]
+    pub backup_s3_access_key: Option<String>,
+
+    /// Secret key for the backup bucket.
+    #[cfg(feature = "s3-backup")]
+    #[arg(long, env = "AWS_SECRET_ACCESS_KEY", hide_env_values = true)]
+    pub backup_s3_secret_key: Option<String>,
+
+    /// Session token of temporary credentials for the backup bucket.
+    #[cfg(feature = "s3-backup")]
+    #[arg(long, env = "AWS_SESSION_TOKEN", hide_env_values = true)]
+    pub backup_s3_session_token: Option<String>,
+
+    /// File holding the keys backups are encrypted with at rest: one
+    /// base64-encoded 32-byte key per line, optionally as `<id>:<key>`.
+    /// The first key encrypts new backups; the others only decrypt, for
+    /// key rotation.
+    #[cfg(feature = "backup-encryption")]
+    #[arg(
+        long,
+        env = "GRAFEO_BACKUP_ENCRYPTION_KEY_FILE",
+        requires = "backup_dir",
+        conflicts_with = "backup_encryption_key"
+    )]
+    pub backup_encryption_key_file: Option<String>,
+
+    /// Backup encryption keys given directly, comma-separated, in the
+    /// format of --backup-encryption-key-file.
+    #[cfg(feature = "backup-encryption")]
+    #[arg(
+        long,
+        env = "GRAFEO_BACKUP_ENCRYPTION_KEY",
+        hide_env_values = true,
+        requires = "backup_dir"
+    )]
+    pub backup_encryption_key: Option<String>,
+
     /// Log level.
     #[arg(long, default_value = "info", env = "GRAFEO_LOG_LEVEL")]
     pub log_level: String,
@@ -137,6 +196,25 @@ pub struct Config {
     #[cfg(feature = "replication")]
     #[arg(long, env = "GRAFEO_PRIMARY_URL")]
     pub primary_url: Option<String>,
+
+    /// Replica lag in seconds beyond which /ready returns 503, so load
+    /// balancers stop routing reads to a stale replica. 0 = disabled.
+    #[cfg(feature = "replication")]
+    #[arg(long, default_value_t = 0, env = "GRAFEO_REPLICA_MAX_LAG")]
+    pub replica_max_lag: u64,
+
+    /// Forward writes, transactions, batches and sync requests received by
+    /// a replica to the primary instead of rejecting them, and wait for the
+    /// write to replicate back before responding.
+    #[cfg(feature = "replication")]
+    #[arg(long, default_value_t = false, env = "GRAFEO_REPLICA_FORWARD_WRITES")]
+    pub replica_forward_writes: bool,
+
+    /// Token a replica presents to its primary as a bearer token, when the
+    /// primary has authentication enabled.
+    #[cfg(feature = "replication")]
+    #[arg(long, env = "GRAFEO_REPLICA_TOKEN")]
+    pub replica_token: Option<String>,
 }
 
 impl Config {
@@ -149,4 +227,54 @@ impl Config {
     pub fn tls_enabled(&self) -> bool {
         self.tls_cert.is_some() && self.tls_key.is_some()
     }
+
+    /// Parses `--backup-target` and its credentials.
+    pub fn backup_target(&self) -> Result<Option<BackupTargetConfig>, String> {
+        let Some(url) = self.backup_target.as_deref() else {
+            return Ok(None);
+        };
+        if let Some(path) = url.strip_prefix("file://") {
+            if path.is_empty() {
+                return Err(format!("missing path in {url}"));
+            }
+            return Ok(Some(BackupTargetConfig::Local(path.into())));
+        }
+        #[cfg(feature = "s3-backup")]
+        if url.starts_with("s3://") {
+            let (bucket, prefix) = grafeo_service::backup_s3::S3Config::parse_url(url)?;
+            let (Some(access_key_id), Some(secret_access_key)) = (
+                self.backup_s3_access_key.clone(),
+                self.backup_s3_secret_key.clone(),
+            ) else {
+                return Err("s3:// backup targets require --backup-s3-access-key and \
+                     --backup-s3-secret-key"
+                    .to_owned());
+            };
+            return Ok(Some(BackupTargetConfig::S3(
+                grafeo_service::backup_s3::S3Config {
+                    bucket,
+                    prefix,
+                    region: self.backup_s3_region.clone(),
+                    endpoint: self.backup_s3_endpoint.clone(),
+                    access_key_id,
+                    secret_access_key,
+                    session_token: self.backup_s3_session_token.clone(),
+                },
+            )));
+        }
+        Err(format!("unsupported backup target: {url}"))
+    }
+
+    /// Loads the backup encryption keys from `--backup-encryption-key-file`
+    /// or `--backup-encryption-key`.
+    #[cfg(feature = "backup-encryption")]
+    pub fn backup_keys(&self) -> Result<Option<BackupKeys>, String> {
+        if let Some(path) = &self.backup_encryption_key_file {
+            return BackupKeys::load(std::path::Path::new(path)).map(Some);
+        }
+        self.backup_encryption_key
+            .as_deref()
+            .map(BackupKeys::parse)
+            .transpose()
+    }
 }
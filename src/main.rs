@@ -62,8 +62,23 @@ async fn main() {
         token_store_path: config.token_store_path.clone(),
         #[cfg(feature = "replication")]
         replication_mode,
+        #[cfg(feature = "replication")]
+        replica_max_lag: config.replica_max_lag,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: config.replica_forward_writes,
+        #[cfg(feature = "replication")]
+        replica_token: config.replica_token.clone(),
         backup_dir: config.backup_dir.clone(),
         backup_retention: config.backup_retention,
+        backup_target: config
+            .backup_target()
+            .unwrap_or_else(|e| panic!("invalid --backup-target: {e}")),
+        #[cfg(feature = "backup-encryption")]
+        backup_keys: config
+            .backup_keys()
+            .unwrap_or_else(|e| panic!("invalid backup encryption key: {e}")),
+        #[cfg(not(feature = "backup-encryption"))]
+        backup_keys: None,
     };
 
     let service = ServiceState::new(&service_config);
@@ -92,6 +107,9 @@ async fn main() {
         }
     });
 
+    // Spawn scheduled backup task (no-op without --backup-dir)
+    grafeo_service::backup_schedule::start(service.clone());
+
     // Spawn replication background task (no-op unless in Replica mode)
     #[cfg(feature = "replication")]
     grafeo_http::replication_task::start(service.clone());
@@ -177,6 +177,26 @@ async fn cypher_endpoint_works() {
     assert!(!rows.is_empty());
 }
 
+#[tokio::test]
+async fn cypher_endpoint_binds_params() {
+    let base = spawn_server().await;
+    let client = Client::new();
+
+    let resp = client
+        .post(format!("{base}/cypher"))
+        .json(&json!({
+            "query": "CREATE (n:Movie {title: $title}) RETURN n.title",
+            "params": {"title": {"String": "Heat"}}
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["rows"][0][0], "Heat");
+}
+
 // ---------------------------------------------------------------------------
 // Transaction lifecycle
 // ---------------------------------------------------------------------------
@@ -384,6 +404,92 @@ async fn sparql_endpoint_works() {
     assert!(body["columns"].as_array().is_some());
 }
 
+#[tokio::test]
+async fn sparql_endpoint_binds_params() {
+    let base = spawn_server().await;
+    let client = Client::new();
+
+    let resp = client
+        .post(format!("{base}/sparql"))
+        .json(&json!({
+            "query": "INSERT DATA { <http://ex.org/a> <http://ex.org/name> \"Ada\" . \
+                      <http://ex.org/b> <http://ex.org/name> \"Bob\" }"
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = client
+        .post(format!("{base}/sparql"))
+        .json(&json!({
+            "query": "SELECT ?s WHERE { ?s <http://ex.org/name> $name }",
+            "params": {"name": {"String": "Ada"}}
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["rows"].as_array().unwrap().len(), 1);
+
+    // Nothing to bind into: rejected rather than silently ignored.
+    let resp = client
+        .post(format!("{base}/sparql"))
+        .json(&json!({
+            "query": "INSERT DATA { <http://ex.org/c> <http://ex.org/name> \"Cy\" }",
+            "params": {"name": {"String": "Cy"}}
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn sparql_protocol_default_graph_uri_scopes_query() {
+    let base = spawn_server().await;
+    let client = Client::new();
+
+    let resp = client
+        .post(format!("{base}/db/default/sparql"))
+        .header("content-type", "application/sparql-update")
+        .body(
+            "INSERT DATA { GRAPH <http://ex.org/g1> { <http://ex.org/a> <http://ex.org/p> \"1\" } \
+             GRAPH <http://ex.org/g2> { <http://ex.org/b> <http://ex.org/p> \"2\" } }",
+        )
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success(), "insert: {}", resp.status());
+
+    let query =
+        "query=SELECT%20%3Fs%20WHERE%20%7B%20%3Fs%20%3Chttp%3A%2F%2Fex.org%2Fp%3E%20%3Fo%20%7D";
+    let g1 = "default-graph-uri=http%3A%2F%2Fex.org%2Fg1";
+    let g2 = "default-graph-uri=http%3A%2F%2Fex.org%2Fg2";
+    let resp = client
+        .get(format!("{base}/db/default/sparql?{query}&{g1}"))
+        .header("accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    let rows = body["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 1, "rows: {rows:?}");
+
+    // Repeated default-graph-uri merges both graphs into the default graph.
+    let resp = client
+        .get(format!("{base}/db/default/sparql?{query}&{g1}&{g2}"))
+        .header("accept", "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["rows"].as_array().unwrap().len(), 2);
+}
+
 // ---------------------------------------------------------------------------
 // OpenAPI / Swagger UI
 // ---------------------------------------------------------------------------
@@ -2475,6 +2581,35 @@ async fn admin_stats_not_found() {
     assert_eq!(resp.status(), 404);
 }
 
+#[tokio::test]
+async fn admin_list_queries_returns_array() {
+    let base = spawn_server().await;
+    let client = Client::new();
+
+    let resp = client
+        .get(format!("{base}/admin/queries"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let body: Value = resp.json().await.unwrap();
+    assert!(body.is_array());
+}
+
+#[tokio::test]
+async fn admin_kill_unknown_query_returns_404() {
+    let base = spawn_server().await;
+    let client = Client::new();
+
+    let resp = client
+        .delete(format!("{base}/admin/queries/no-such-id"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
 #[tokio::test]
 async fn admin_wal_status_in_memory() {
     let base = spawn_server().await;
@@ -4446,8 +4581,16 @@ fn sync_state() -> grafeo_server::AppState {
         token_store_path: None,
         #[cfg(feature = "replication")]
         replication_mode: grafeo_service::replication::ReplicationMode::Primary,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
         backup_dir: None,
         backup_retention: None,
+        backup_target: None,
+        backup_keys: None,
     };
     let service = grafeo_service::ServiceState::new(&config);
     grafeo_server::AppState::new(
@@ -4768,6 +4911,126 @@ async fn sync_lww_conflict_detection() {
     assert_eq!(conflicts[0]["reason"], "server_newer");
 }
 
+/// Sync: a database's conflict policy is set over HTTP, and conflicts it
+/// resolves are logged, listed and dismissed.
+#[cfg(feature = "sync")]
+#[tokio::test]
+async fn sync_conflict_policy_and_log() {
+    let base = spawn_server_from_state(sync_state()).await;
+    let client = Client::new();
+
+    let resp = client
+        .put(format!("{base}/db/default/sync/policy"))
+        .json(&json!({ "policy": "field_merge", "fields": { "name": "field_merge" } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+    let policy: Value = client
+        .put(format!("{base}/db/default/sync/policy"))
+        .json(&json!({ "policy": "server_wins", "log_conflicts": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(policy["policy"], "server_wins");
+    let policy: Value = client
+        .get(format!("{base}/db/default/sync/policy"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(policy["log_conflicts"], true);
+
+    let push = |client_id: &str, last_seen_epoch: u64, change: Value| {
+        let body = json!({
+            "client_id": client_id,
+            "last_seen_epoch": last_seen_epoch,
+            "changes": [change],
+        });
+        let request = client.post(format!("{base}/db/default/sync")).json(&body);
+        async move { request.send().await.unwrap().json::<Value>().await.unwrap() }
+    };
+    let rename = |id: &Value, name: &str| {
+        json!({
+            "kind": "update",
+            "entity_type": "node",
+            "id": id,
+            "after": { "name": { "String": name } },
+            "timestamp": u64::MAX,
+        })
+    };
+
+    let created = push(
+        "phone",
+        0,
+        json!({
+            "kind": "create",
+            "entity_type": "node",
+            "labels": ["Person"],
+            "after": { "name": { "String": "Alix" } },
+        }),
+    )
+    .await;
+    let id = created["id_mappings"][0]["server_id"].clone();
+    let seen = created["server_epoch"].as_u64().unwrap();
+    let resp = push("laptop", seen, rename(&id, "Gus")).await;
+    assert_eq!(resp["applied"], 1);
+
+    // The phone has not seen the laptop's rename.
+    let resp = push("phone", seen, rename(&id, "Vincent")).await;
+    assert_eq!(resp["skipped"], 1);
+    let conflict = &resp["conflicts"][0];
+    assert_eq!(conflict["reason"], "server_wins");
+    assert_eq!(conflict["policy"], "server_wins");
+    assert_eq!(conflict["server_value"]["name"]["String"], "Gus");
+    assert!(conflict["server_epoch"].as_u64().unwrap() > seen);
+
+    let log: Value = client
+        .get(format!("{base}/db/default/sync/conflicts?client_id=phone"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let logged = log["conflicts"].as_array().unwrap();
+    assert_eq!(logged.len(), 1);
+    assert_eq!(logged[0]["change"]["after"]["name"]["String"], "Vincent");
+    assert_eq!(
+        logged[0]["conflict"]["server_value"]["name"]["String"],
+        "Gus"
+    );
+
+    let conflict_id = logged[0]["id"].as_u64().unwrap();
+    let resp = client
+        .delete(format!("{base}/db/default/sync/conflicts/{conflict_id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 204);
+    let resp = client
+        .delete(format!("{base}/db/default/sync/conflicts/{conflict_id}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+    let log: Value = client
+        .get(format!("{base}/db/default/sync/conflicts"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(log["conflicts"].as_array().unwrap().is_empty());
+    assert_eq!(log["last_id"], conflict_id);
+}
+
 /// Sync: the `limit` query parameter truncates the changefeed response.
 ///
 /// Seeds 7 nodes, then verifies that `limit=5` returns exactly 5 events while
@@ -4824,6 +5087,62 @@ async fn sync_limit_truncation() {
     assert!(caught_up["changes"].as_array().unwrap().is_empty());
 }
 
+/// Sync: filter parameters narrow the changefeed server-side.
+///
+/// Inserts two Person nodes and a City, then verifies `labels`, `predicate`
+/// and `properties` filters, and that an invalid predicate is a 400.
+#[cfg(feature = "sync")]
+#[tokio::test]
+async fn sync_changes_filters() {
+    let base = spawn_server_from_state(sync_state()).await;
+    let client = Client::new();
+
+    let resp = client
+        .post(format!("{base}/query"))
+        .json(
+            &json!({"query": "INSERT (:Person {name: 'Alix', age: 34}), \
+            (:Person {name: 'Gus'}), (:City {name: 'Amsterdam'})"}),
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let changes = |query: &str| {
+        let request = client.get(format!("{base}/db/default/changes?since=0&{query}"));
+        async move { request.send().await.unwrap() }
+    };
+
+    // INSERT records a create per node and an update per property.
+    let people: Value = changes("labels=Person").await.json().await.unwrap();
+    let people = people["changes"].as_array().unwrap();
+    assert_eq!(people.len(), 5);
+    assert!(
+        people
+            .iter()
+            .all(|e| e["after"].get("name") != Some(&json!({"String": "Amsterdam"})))
+    );
+
+    let named_a: Value = changes("predicate=after.name%20STARTS%20WITH%20'A'")
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(named_a["changes"].as_array().unwrap().len(), 2);
+
+    let aged: Value = changes("labels=Person,City&properties=age")
+        .await
+        .json()
+        .await
+        .unwrap();
+    let aged = aged["changes"].as_array().unwrap();
+    assert_eq!(aged.len(), 1);
+    assert_eq!(aged[0]["after"]["age"], json!({"Int64": 34}));
+
+    let invalid = changes("predicate=after.age%20%3E").await;
+    assert_eq!(invalid.status(), 400);
+}
+
 /// Sync: missing required fields produce descriptive conflict reasons.
 ///
 /// Verifies the three structural validation errors: `update_missing_id`,
@@ -5230,8 +5549,16 @@ async fn spawn_server_with_backup() -> (String, TempDir, TempDir) {
         token_store_path: None,
         #[cfg(feature = "replication")]
         replication_mode: grafeo_service::replication::ReplicationMode::Standalone,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
         backup_dir: Some(backup_dir.path().to_str().unwrap().to_string()),
         backup_retention: None,
+        backup_target: None,
+        backup_keys: None,
     };
     let service = grafeo_service::ServiceState::new(&config);
     let state = grafeo_server::AppState::new(
@@ -5305,6 +5632,70 @@ async fn backup_list_all() {
     assert_eq!(backups.len(), 1);
 }
 
+#[tokio::test]
+async fn backup_schedule_lifecycle() {
+    let (base, _data, dir) = spawn_server_with_backup().await;
+    let client = Client::new();
+
+    let resp = client
+        .get(format!("{base}/admin/default/backup/schedule"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+
+    // Too short an interval is rejected
+    let resp = client
+        .put(format!("{base}/admin/default/backup/schedule"))
+        .json(&json!({"full_interval_secs": 5}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+
+    let resp = client
+        .put(format!("{base}/admin/default/backup/schedule"))
+        .json(&json!({
+            "full_interval_secs": 21600,
+            "incremental_interval_secs": 900,
+            "keep": 4,
+            "max_age_secs": 604_800
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["database"], "default");
+    assert_eq!(body["schedule"]["keep"], 4);
+    assert!(body["next_full_at"].is_string());
+    assert!(body["next_incremental_at"].is_string());
+    assert!(dir.path().join("schedules.json").exists());
+
+    let resp = client
+        .get(format!("{base}/backups/schedules"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let schedules: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(schedules.len(), 1);
+
+    let resp = client
+        .delete(format!("{base}/admin/default/backup/schedule"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+
+    let resp = client
+        .get(format!("{base}/admin/default/backup/schedule"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
 #[tokio::test]
 async fn backup_not_found_database() {
     let (base, _data, _dir) = spawn_server_with_backup().await;
@@ -5481,8 +5872,16 @@ async fn spawn_server_persistent_backup() -> (String, TempDir, TempDir) {
         token_store_path: None,
         #[cfg(feature = "replication")]
         replication_mode: grafeo_service::replication::ReplicationMode::Standalone,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
         backup_dir: Some(backup_dir.path().to_str().unwrap().to_string()),
         backup_retention: None,
+        backup_target: None,
+        backup_keys: None,
     };
     let service = grafeo_service::ServiceState::new(&config);
     let state = grafeo_server::AppState::new(
@@ -5515,8 +5914,16 @@ async fn spawn_server_persistent_backup_with_retention(keep: usize) -> (String,
         token_store_path: None,
         #[cfg(feature = "replication")]
         replication_mode: grafeo_service::replication::ReplicationMode::Standalone,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
         backup_dir: Some(backup_dir.path().to_str().unwrap().to_string()),
         backup_retention: Some(keep),
+        backup_target: None,
+        backup_keys: None,
     };
     let service = grafeo_service::ServiceState::new(&config);
     let state = grafeo_server::AppState::new(
@@ -5528,10 +5935,105 @@ async fn spawn_server_persistent_backup_with_retention(keep: usize) -> (String,
     (base, data_dir, backup_dir)
 }
 
-/// Helper: create N nodes on a database via GQL.
-async fn seed_nodes(client: &Client, base: &str, db: &str, count: usize) {
-    for i in 0..count {
-        client
+/// Same as above but keeping backups at a `file://` target, so the backup
+/// dir is only a cache.
+async fn spawn_server_persistent_backup_with_target() -> (String, TempDir, TempDir, TempDir) {
+    let data_dir = TempDir::new().unwrap();
+    let backup_dir = TempDir::new().unwrap();
+    let target_dir = TempDir::new().unwrap();
+    let config = grafeo_service::ServiceConfig {
+        data_dir: Some(data_dir.path().to_str().unwrap().to_string()),
+        read_only: false,
+        session_ttl: 300,
+        query_timeout: 30,
+        rate_limit: 0,
+        rate_limit_window: 60,
+        #[cfg(feature = "auth")]
+        auth_token: None,
+        #[cfg(feature = "auth")]
+        auth_user: None,
+        #[cfg(feature = "auth")]
+        auth_password: None,
+        #[cfg(feature = "auth")]
+        token_store_path: None,
+        #[cfg(feature = "replication")]
+        replication_mode: grafeo_service::replication::ReplicationMode::Standalone,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
+        backup_dir: Some(backup_dir.path().to_str().unwrap().to_string()),
+        backup_retention: None,
+        backup_target: Some(grafeo_service::backup_target::BackupTargetConfig::Local(
+            target_dir.path().to_path_buf(),
+        )),
+        backup_keys: None,
+    };
+    let service = grafeo_service::ServiceState::new(&config);
+    let state = grafeo_server::AppState::new(
+        service,
+        vec![],
+        grafeo_service::types::EnabledFeatures::default(),
+    );
+    let base = spawn_server_from_state(state).await;
+    (base, data_dir, backup_dir, target_dir)
+}
+
+/// Same as [`spawn_server_persistent_backup`] but encrypting backups with
+/// the key `k1`.
+#[cfg(feature = "backup-encryption")]
+async fn spawn_server_persistent_backup_encrypted() -> (String, TempDir, TempDir) {
+    let data_dir = TempDir::new().unwrap();
+    let backup_dir = TempDir::new().unwrap();
+    let config = grafeo_service::ServiceConfig {
+        data_dir: Some(data_dir.path().to_str().unwrap().to_string()),
+        read_only: false,
+        session_ttl: 300,
+        query_timeout: 30,
+        rate_limit: 0,
+        rate_limit_window: 60,
+        #[cfg(feature = "auth")]
+        auth_token: None,
+        #[cfg(feature = "auth")]
+        auth_user: None,
+        #[cfg(feature = "auth")]
+        auth_password: None,
+        #[cfg(feature = "auth")]
+        token_store_path: None,
+        #[cfg(feature = "replication")]
+        replication_mode: grafeo_service::replication::ReplicationMode::Standalone,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
+        backup_dir: Some(backup_dir.path().to_str().unwrap().to_string()),
+        backup_retention: None,
+        backup_target: None,
+        backup_keys: Some(
+            grafeo_service::backup_crypto::BackupKeys::parse(
+                "k1:AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI=",
+            )
+            .unwrap(),
+        ),
+    };
+    let service = grafeo_service::ServiceState::new(&config);
+    let state = grafeo_server::AppState::new(
+        service,
+        vec![],
+        grafeo_service::types::EnabledFeatures::default(),
+    );
+    let base = spawn_server_from_state(state).await;
+    (base, data_dir, backup_dir)
+}
+
+/// Helper: create N nodes on a database via GQL.
+async fn seed_nodes(client: &Client, base: &str, db: &str, count: usize) {
+    for i in 0..count {
+        client
             .post(format!("{base}/query"))
             .json(&json!({
                 "query": format!("CREATE (:Item {{idx: {i}}})"),
@@ -5556,6 +6058,233 @@ async fn db_node_count(client: &Client, base: &str, db: &str) -> u64 {
     resp["node_count"].as_u64().unwrap()
 }
 
+#[tokio::test]
+async fn backup_target_restores_after_local_loss() {
+    let (base, _data, backup_dir, target_dir) = spawn_server_persistent_backup_with_target().await;
+    let client = Client::new();
+
+    seed_nodes(&client, &base, "default", 3).await;
+    let resp: Value = client
+        .post(format!("{base}/admin/default/backup"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let filename = resp["filename"].as_str().unwrap().to_string();
+    assert!(target_dir.path().join("default").join(&filename).exists());
+
+    // Wipe the local cache: the target still lists and restores the backup
+    std::fs::remove_dir_all(backup_dir.path().join("default")).unwrap();
+    let list: Value = client
+        .get(format!("{base}/admin/default/backups"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["filename"], filename.as_str());
+
+    seed_nodes(&client, &base, "default", 2).await;
+    let resp = client
+        .post(format!("{base}/admin/default/restore"))
+        .json(&json!({ "backup": filename }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(db_node_count(&client, &base, "default").await, 3);
+
+    let resp = client
+        .delete(format!("{base}/admin/default/backups/{filename}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert!(!target_dir.path().join("default").join(&filename).exists());
+}
+
+#[tokio::test]
+async fn backup_verify_reports_in_listing() {
+    let (base, _data, backup_dir) = spawn_server_persistent_backup().await;
+    let client = Client::new();
+
+    seed_nodes(&client, &base, "default", 3).await;
+    let resp: Value = client
+        .post(format!("{base}/admin/default/backup"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let filename = resp["filename"].as_str().unwrap().to_string();
+
+    let resp = client
+        .post(format!("{base}/admin/default/backups/{filename}/verify"))
+        .json(&json!({ "open": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["ok"], true);
+    assert_eq!(body["checksum_ok"], true);
+    assert_eq!(body["node_count"], 3);
+
+    let list: Value = client
+        .get(format!("{base}/admin/default/backups"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list[0]["verification"]["ok"], true);
+
+    // Corruption shows up in the bulk run
+    let path = backup_dir.path().join("default").join(&filename);
+    let mut data = std::fs::read(&path).unwrap();
+    data[0] ^= 0xff;
+    std::fs::write(&path, data).unwrap();
+    let resp = client
+        .post(format!("{base}/backups/verify"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["ok"], false);
+    assert_eq!(body[0]["checksum_ok"], false);
+
+    let resp = client
+        .post(format!(
+            "{base}/admin/default/backups/nonexistent.grafeo/verify"
+        ))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn restore_as_new_database() {
+    let (base, _data, _backup) = spawn_server_persistent_backup().await;
+    let client = Client::new();
+
+    seed_nodes(&client, &base, "default", 4).await;
+    let resp: Value = client
+        .post(format!("{base}/admin/default/backup"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let filename = resp["filename"].as_str().unwrap().to_string();
+    seed_nodes(&client, &base, "default", 2).await;
+
+    let resp = client
+        .post(format!("{base}/admin/default/restore/as"))
+        .json(&json!({ "name": "snapshot", "backup": filename }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["name"], "snapshot");
+    assert_eq!(body["node_count"], 4);
+    assert_eq!(body["persistent"], false);
+
+    assert_eq!(db_node_count(&client, &base, "snapshot").await, 4);
+    assert_eq!(db_node_count(&client, &base, "default").await, 6);
+
+    let resp = client
+        .post(format!("{base}/admin/default/restore/as"))
+        .json(&json!({ "name": "snapshot", "backup": filename }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 409);
+    let resp = client
+        .post(format!("{base}/admin/default/restore/as"))
+        .json(&json!({ "name": "other" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 400);
+}
+
+#[cfg(feature = "backup-encryption")]
+#[tokio::test]
+async fn encrypted_backup_downloads_and_restores_plaintext() {
+    let (base, _data, backup_dir) = spawn_server_persistent_backup_encrypted().await;
+    let client = Client::new();
+
+    seed_nodes(&client, &base, "default", 3).await;
+    let resp: Value = client
+        .post(format!("{base}/admin/default/backup"))
+        .json(&json!({ "label": "secret" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let filename = resp["filename"].as_str().unwrap().to_string();
+    assert_eq!(resp["encryption_key_id"], "k1");
+
+    // Sealed on disk, plaintext over the API
+    let on_disk = std::fs::read(backup_dir.path().join("default").join(&filename)).unwrap();
+    assert!(on_disk.starts_with(b"GENC"));
+    let downloaded = client
+        .get(format!("{base}/admin/default/backups/download/{filename}"))
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert!(!downloaded.starts_with(b"GENC"));
+    assert_eq!(downloaded.len() as u64, resp["size_bytes"]);
+
+    let list: Value = client
+        .get(format!("{base}/admin/default/backups"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list[0]["encryption_key_id"], "k1");
+    assert_eq!(list[0]["label"], "secret");
+
+    seed_nodes(&client, &base, "default", 2).await;
+    let resp = client
+        .post(format!("{base}/admin/default/restore"))
+        .json(&json!({ "backup": filename }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(db_node_count(&client, &base, "default").await, 3);
+
+    // Everything is already under the active key
+    let rekeyed: Value = client
+        .post(format!("{base}/backups/rekey"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(rekeyed.as_array().unwrap().len(), 0);
+}
+
 #[tokio::test]
 async fn restore_data_rollback() {
     let (base, _data, _backup) = spawn_server_persistent_backup().await;
@@ -6259,6 +6988,178 @@ async fn gwp_auth_read_only_token_cannot_write() {
     assert!(found_error, "read-only token should not allow writes");
 }
 
+/// Runs a statement over GWP, returning its rows and GQLSTATUS code.
+#[cfg(all(feature = "gwp", feature = "auth"))]
+async fn gwp_execute_raw(
+    gql_client: &mut gwp::proto::gql_service_client::GqlServiceClient<tonic::transport::Channel>,
+    session_id: &str,
+    statement: &str,
+    parameters: std::collections::HashMap<String, gwp::proto::Value>,
+) -> (Vec<Vec<gwp::types::Value>>, String) {
+    use futures_util::StreamExt;
+
+    let mut stream = gql_client
+        .execute(gwp::proto::ExecuteRequest {
+            session_id: session_id.to_string(),
+            statement: statement.to_string(),
+            parameters,
+            transaction_id: None,
+        })
+        .await
+        .expect("gRPC call should succeed, error is in stream")
+        .into_inner();
+
+    let mut rows = Vec::new();
+    let mut code = String::new();
+    while let Some(msg) = stream.next().await {
+        match msg.unwrap().frame {
+            Some(gwp::proto::execute_response::Frame::RowBatch(batch)) => rows.extend(
+                batch
+                    .rows
+                    .into_iter()
+                    .map(|r| r.values.into_iter().map(gwp::types::Value::from).collect()),
+            ),
+            Some(gwp::proto::execute_response::Frame::Summary(summary)) => {
+                code = summary.status.map(|s| s.code).unwrap_or_default();
+            }
+            _ => {}
+        }
+    }
+    (rows, code)
+}
+
+#[cfg(all(feature = "gwp", feature = "auth"))]
+#[tokio::test]
+async fn gwp_admin_procedures_list_and_kill_queries() {
+    use grafeo_service::auth::{TokenRecord, TokenScope};
+    use grafeo_service::token_service::hash_token;
+    use grafeo_service::token_store::TokenStore;
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let store = std::sync::Arc::new(TokenStore::load(tmp_dir.path().join("tokens.json")).unwrap());
+    let rw_token = "read-write-gwp-token";
+    store
+        .insert(TokenRecord {
+            id: "tok-rw".to_string(),
+            name: "rw-client".to_string(),
+            token_hash: hash_token(rw_token),
+            scope: TokenScope {
+                role: grafeo_service::auth::Role::ReadWrite,
+                databases: vec![],
+            },
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            expires_at: None,
+        })
+        .unwrap();
+    let provider = grafeo_service::auth::AuthProvider::with_token_store(
+        Some("admin-token".to_string()),
+        None,
+        None,
+        store,
+    )
+    .unwrap();
+    let service = grafeo_service::ServiceState::new_in_memory_with_auth_provider(300, provider);
+    let running = service.running_queries().register(
+        "default",
+        None,
+        "MATCH (n) RETURN n",
+        Some("someone"),
+        grafeo_service::types::QueryTransport::Http,
+    );
+
+    let gwp_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let gwp_addr: SocketAddr = gwp_listener.local_addr().unwrap();
+    drop(gwp_listener);
+    let auth_provider = service.auth().cloned();
+    let backend = grafeo_gwp::GrafeoBackend::new(service.clone());
+    let options = grafeo_gwp::GwpOptions {
+        auth_provider,
+        ..Default::default()
+    };
+    tokio::spawn(async move {
+        grafeo_gwp::serve(backend, gwp_addr, options).await.unwrap();
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let channel = tonic::transport::Channel::from_shared(format!("http://{gwp_addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut session_client =
+        gwp::proto::session_service_client::SessionServiceClient::new(channel.clone());
+    let mut gql_client = gwp::proto::gql_service_client::GqlServiceClient::new(channel);
+    let mut handshake = async |token: &str| {
+        session_client
+            .handshake(gwp::proto::HandshakeRequest {
+                protocol_version: 1,
+                credentials: Some(gwp::proto::AuthCredentials {
+                    method: Some(gwp::proto::auth_credentials::Method::BearerToken(
+                        token.to_string(),
+                    )),
+                }),
+                client_info: std::collections::HashMap::new(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .session_id
+    };
+    let rw_session = handshake(rw_token).await;
+    let admin_session = handshake("admin-token").await;
+    let id_param = std::collections::HashMap::from([(
+        "id".to_string(),
+        gwp::proto::Value::from(gwp::types::Value::String(running.id().to_string())),
+    )]);
+
+    // Non-admin tokens are refused.
+    let (_, code) = gwp_execute_raw(
+        &mut gql_client,
+        &rw_session,
+        "CALL grafeo.admin.list_queries()",
+        std::collections::HashMap::new(),
+    )
+    .await;
+    assert_eq!(code, "42000");
+    let (_, code) = gwp_execute_raw(
+        &mut gql_client,
+        &rw_session,
+        "CALL grafeo.admin.kill_query($id)",
+        id_param.clone(),
+    )
+    .await;
+    assert_eq!(code, "42000");
+    assert!(!running.cancel_token().is_cancelled());
+
+    // Admin lists the query, then kills it.
+    let (rows, code) = gwp_execute_raw(
+        &mut gql_client,
+        &admin_session,
+        "CALL grafeo.admin.list_queries()",
+        std::collections::HashMap::new(),
+    )
+    .await;
+    assert_eq!(code, "00000");
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        rows[0][0],
+        gwp::types::Value::String(running.id().to_string())
+    );
+    assert_eq!(rows[0][4], gwp::types::Value::String("someone".to_string()));
+    assert_eq!(rows[0][5], gwp::types::Value::String("http".to_string()));
+
+    let (rows, code) = gwp_execute_raw(
+        &mut gql_client,
+        &admin_session,
+        "CALL grafeo.admin.kill_query($id)",
+        id_param,
+    )
+    .await;
+    assert_eq!(code, "00000");
+    assert_eq!(rows, [[gwp::types::Value::Boolean(true)]]);
+    assert!(running.cancel_token().is_cancelled());
+}
+
 // ---------------------------------------------------------------------------
 // BoltR Identity-Aware Authentication
 // ---------------------------------------------------------------------------
@@ -6525,6 +7426,87 @@ async fn auth_scoped_token_filters_database_list() {
     assert_eq!(dbs[0]["name"], "db1");
 }
 
+/// Changefeed access check: a scoped token cannot read another database's changes.
+#[cfg(all(feature = "auth", feature = "sync"))]
+#[tokio::test]
+async fn auth_scoped_token_denied_changes_of_other_database() {
+    use grafeo_service::auth::TokenScope;
+
+    let scope = TokenScope {
+        role: grafeo_service::auth::Role::ReadOnly,
+        databases: vec!["db1".to_string()],
+    };
+    let (base, _admin_token, tokens) =
+        spawn_server_with_token_store("admin-tok", vec![("scoped-tok", "scoped-svc", scope)]).await;
+    let scoped_token = &tokens[0].0;
+
+    let resp = Client::new()
+        .get(format!("{base}/db/default/changes?since=0"))
+        .header("Authorization", format!("Bearer {scoped_token}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 403);
+}
+
+/// Replication snapshot access check: a whole-database export needs admin
+/// and the token's database scope.
+#[cfg(all(feature = "auth", feature = "replication"))]
+#[tokio::test]
+async fn auth_scoped_token_denied_replication_snapshot() {
+    use grafeo_service::auth::TokenScope;
+
+    let scope = TokenScope {
+        role: grafeo_service::auth::Role::ReadWrite,
+        databases: vec!["db1".to_string()],
+    };
+    let (base, _admin_token, tokens) =
+        spawn_server_with_token_store("admin-tok", vec![("scoped-tok", "scoped-svc", scope)]).await;
+    let scoped_token = &tokens[0].0;
+    let client = Client::new();
+
+    for db in ["default", "db1"] {
+        let resp = client
+            .get(format!("{base}/db/{db}/replication/snapshot"))
+            .header("Authorization", format!("Bearer {scoped_token}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 403, "snapshot of '{db}'");
+    }
+}
+
+/// Replication catalog access check: listing database settings needs admin.
+#[cfg(all(feature = "auth", feature = "replication"))]
+#[tokio::test]
+async fn auth_non_admin_token_denied_replication_catalog() {
+    use grafeo_service::auth::TokenScope;
+
+    let scope = TokenScope {
+        role: grafeo_service::auth::Role::ReadWrite,
+        databases: vec![],
+    };
+    let (base, admin_token, tokens) =
+        spawn_server_with_token_store("admin-tok", vec![("rw-tok", "rw-svc", scope)]).await;
+    let client = Client::new();
+
+    let resp = client
+        .get(format!("{base}/admin/replication/catalog"))
+        .header("Authorization", format!("Bearer {}", tokens[0].0))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 403);
+
+    let resp = client
+        .get(format!("{base}/admin/replication/catalog"))
+        .header("Authorization", format!("Bearer {admin_token}"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+}
+
 /// Database access check: scoped token cannot create a database outside its scope.
 #[cfg(feature = "auth")]
 #[tokio::test]
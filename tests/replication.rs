@@ -20,7 +20,23 @@ use tokio::net::TcpListener;
 
 /// Boots a primary in-memory server on an ephemeral port.
 async fn spawn_primary() -> String {
-    let config = grafeo_service::ServiceConfig {
+    spawn_primary_with(primary_config()).await
+}
+
+/// Boots a primary from an adjusted primary configuration.
+async fn spawn_primary_with(config: grafeo_service::ServiceConfig) -> String {
+    let service = grafeo_service::ServiceState::new(&config);
+    let state = grafeo_server::AppState::new(
+        service,
+        vec![],
+        grafeo_service::types::EnabledFeatures::default(),
+    );
+    spawn_server(state).await
+}
+
+/// Service configuration for an in-memory primary.
+fn primary_config() -> grafeo_service::ServiceConfig {
+    grafeo_service::ServiceConfig {
         data_dir: None,
         read_only: false,
         session_ttl: 300,
@@ -37,10 +53,44 @@ async fn spawn_primary() -> String {
         token_store_path: None,
         #[cfg(feature = "replication")]
         replication_mode: grafeo_service::replication::ReplicationMode::Primary,
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
         backup_dir: None,
         backup_retention: None,
-    };
+        backup_target: None,
+        backup_keys: None,
+    }
+}
+
+/// Boots a replica in-memory server on an ephemeral port.
+/// The `primary_url` is used for the replica guard middleware (write rejection),
+/// but the background replication task is not started.
+async fn spawn_replica(primary_url: &str) -> String {
+    let service = replica_service(primary_url);
+    let state = grafeo_server::AppState::new(
+        service,
+        vec![],
+        grafeo_service::types::EnabledFeatures::default(),
+    );
+    spawn_server(state).await
+}
+
+/// Boots a replica that follows `primary_url` with the background
+/// replication task, as the server binary does.
+#[cfg(feature = "replication")]
+async fn spawn_following_replica(primary_url: &str) -> String {
+    spawn_following_replica_with(replica_config(primary_url)).await
+}
+
+/// Boots a following replica from an adjusted replica configuration.
+#[cfg(feature = "replication")]
+async fn spawn_following_replica_with(config: grafeo_service::ServiceConfig) -> String {
     let service = grafeo_service::ServiceState::new(&config);
+    grafeo_http::replication_task::start(service.clone());
     let state = grafeo_server::AppState::new(
         service,
         vec![],
@@ -49,11 +99,14 @@ async fn spawn_primary() -> String {
     spawn_server(state).await
 }
 
-/// Boots a replica in-memory server on an ephemeral port.
-/// The `primary_url` is used for the replica guard middleware (write rejection),
-/// but we don't start the background replication task in tests.
-async fn spawn_replica(primary_url: &str) -> String {
-    let config = grafeo_service::ServiceConfig {
+fn replica_service(primary_url: &str) -> grafeo_service::ServiceState {
+    grafeo_service::ServiceState::new(&replica_config(primary_url))
+}
+
+/// Service configuration for a replica of `primary_url`.
+#[cfg_attr(not(feature = "replication"), allow(unused_variables))]
+fn replica_config(primary_url: &str) -> grafeo_service::ServiceConfig {
+    grafeo_service::ServiceConfig {
         data_dir: None,
         read_only: false,
         session_ttl: 300,
@@ -72,16 +125,17 @@ async fn spawn_replica(primary_url: &str) -> String {
         replication_mode: grafeo_service::replication::ReplicationMode::Replica {
             primary_url: primary_url.to_string(),
         },
+        #[cfg(feature = "replication")]
+        replica_max_lag: 0,
+        #[cfg(feature = "replication")]
+        replica_forward_writes: false,
+        #[cfg(feature = "replication")]
+        replica_token: None,
         backup_dir: None,
         backup_retention: None,
-    };
-    let service = grafeo_service::ServiceState::new(&config);
-    let state = grafeo_server::AppState::new(
-        service,
-        vec![],
-        grafeo_service::types::EnabledFeatures::default(),
-    );
-    spawn_server(state).await
+        backup_target: None,
+        backup_keys: None,
+    }
 }
 
 async fn spawn_server(state: grafeo_server::AppState) -> String {
@@ -382,3 +436,674 @@ async fn concurrent_writes_all_replicate() {
         "Replica should match primary after replication"
     );
 }
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn background_task_follows_primary() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+
+    // History written before the replica starts is caught up by polling.
+    for i in 0..3 {
+        query(&client, &primary, &format!("INSERT (:Early {{seq: {i}}})")).await;
+    }
+    let replica = spawn_following_replica(&primary).await;
+    wait_for_count(&client, &replica, 3).await;
+
+    // Later writes arrive over the push stream.
+    for i in 0..4 {
+        query(&client, &primary, &format!("INSERT (:Late {{seq: {i}}})")).await;
+    }
+    wait_for_count(&client, &replica, 7).await;
+}
+
+#[cfg(all(feature = "replication", feature = "auth"))]
+#[tokio::test]
+async fn replica_authenticates_to_primary() {
+    let client = Client::new();
+    let primary = spawn_primary_with(grafeo_service::ServiceConfig {
+        auth_token: Some("primary-secret".to_string()),
+        ..primary_config()
+    })
+    .await;
+    let resp = client
+        .post(format!("{primary}/query"))
+        .bearer_auth("primary-secret")
+        .json(&json!({"query": "INSERT (:Person {name: 'Alix'})"}))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    // Without the token, the primary refuses the replica.
+    let anonymous = spawn_following_replica(&primary).await;
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert_eq!(node_count(&client, &anonymous).await, 0);
+
+    let replica = spawn_following_replica_with(grafeo_service::ServiceConfig {
+        replica_token: Some("primary-secret".to_string()),
+        ..replica_config(&primary)
+    })
+    .await;
+    wait_for_count(&client, &replica, 1).await;
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn catch_up_applies_transactions_larger_than_a_batch() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    query(&client, &primary, "INSERT (:Seed)").await;
+    let seeded: Value = client
+        .get(format!("{primary}/db/default/changes?since=0"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let seed_epoch = seeded["server_epoch"].as_u64().unwrap();
+
+    // One transaction of 600 creates, more than a catch-up batch of 500.
+    let begin: Value = client
+        .post(format!("{primary}/tx/begin"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let session_id = begin["session_id"].as_str().unwrap().to_string();
+    for i in 0..600 {
+        let resp = client
+            .post(format!("{primary}/tx/query"))
+            .header("X-Session-Id", &session_id)
+            .json(&json!({"query": format!("INSERT (:Bulk {{seq: {i}}})")}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+    let resp = client
+        .post(format!("{primary}/tx/commit"))
+        .header("X-Session-Id", &session_id)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(node_count(&client, &primary).await, 601);
+
+    let pulled: Value = client
+        .get(format!(
+            "{primary}/db/default/changes?since={}&limit=500&complete=true",
+            seed_epoch + 1
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pulled["complete"], true);
+    assert!(pulled["changes"].as_array().unwrap().len() >= 600);
+
+    // A replica that already holds the seed epoch catches up by polling
+    // rather than from a snapshot.
+    let config = replica_config(&primary);
+    let service = grafeo_service::ServiceState::new(&config);
+    service
+        .replication_state()
+        .advance_epoch("default", seed_epoch);
+    grafeo_http::replication_task::start(service.clone());
+    let replica = spawn_server(grafeo_server::AppState::new(
+        service,
+        vec![],
+        grafeo_service::types::EnabledFeatures::default(),
+    ))
+    .await;
+    wait_for_count(&client, &replica, 600).await;
+    let resp = query(&client, &replica, "MATCH (n:Bulk) RETURN count(n)").await;
+    assert_eq!(resp["rows"][0][0], 600);
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn replica_keeps_primary_ids() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let replica = spawn_following_replica(&primary).await;
+
+    // A rolled-back transaction leaves a gap in the primary's node IDs.
+    let begin: Value = client
+        .post(format!("{primary}/tx/begin"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let session_id = begin["session_id"].as_str().unwrap().to_string();
+    client
+        .post(format!("{primary}/tx/query"))
+        .header("X-Session-Id", &session_id)
+        .json(&json!({"query": "INSERT (:Gone)"}))
+        .send()
+        .await
+        .unwrap();
+    client
+        .post(format!("{primary}/tx/rollback"))
+        .header("X-Session-Id", &session_id)
+        .send()
+        .await
+        .unwrap();
+
+    query(
+        &client,
+        &primary,
+        "INSERT (:Person {name: 'Alix'})-[:KNOWS]->(:Person {name: 'Gus'})",
+    )
+    .await;
+    wait_for_count(&client, &replica, 2).await;
+
+    let ids = "MATCH (a:Person)-[e:KNOWS]->(b:Person) RETURN id(a), id(e), id(b)";
+    let expected = query(&client, &primary, ids).await["rows"].clone();
+    assert_eq!(query(&client, &replica, ids).await["rows"], expected);
+
+    // A write addressed by ID on the primary reaches the same node.
+    let alix = expected[0][0].as_i64().unwrap();
+    query(
+        &client,
+        &primary,
+        &format!("MATCH (n) WHERE id(n) = {alix} SET n.city = 'Paris'"),
+    )
+    .await;
+    let city = format!("MATCH (n) WHERE id(n) = {alix} RETURN n.city");
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+    while query(&client, &replica, &city).await["rows"][0][0] != "Paris" {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "update not replicated"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn stream_sends_history_as_batches() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    query(&client, &primary, "INSERT (:Streamed)").await;
+
+    let resp = client
+        .get(format!("{primary}/db/default/replication/stream?since=0"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let mut resp = resp;
+    let mut body = String::new();
+    while !body.contains("\n\n") {
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), resp.chunk())
+            .await
+            .expect("stream should send history promptly")
+            .unwrap()
+            .expect("stream ended early");
+        body.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    assert!(body.starts_with("event: batch\n"), "got: {body}");
+    let data = body.lines().find_map(|l| l.strip_prefix("data: ")).unwrap();
+    let batch: Value = serde_json::from_str(data).unwrap();
+    assert_eq!(batch["since"], 0);
+    assert!(!batch["changes"].as_array().unwrap().is_empty());
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn stream_unknown_database_is_404() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let resp = client
+        .get(format!("{primary}/db/nope/replication/stream?since=0"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn snapshot_carries_its_epoch() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    query(&client, &primary, "INSERT (:Snap {v: 1})").await;
+
+    let resp = client
+        .get(format!("{primary}/db/default/replication/snapshot"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let epoch: u64 = resp.headers()["x-grafeo-snapshot-epoch"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(epoch > 0);
+    assert!(!resp.bytes().await.unwrap().is_empty());
+
+    let resp = client
+        .get(format!("{primary}/db/nope/replication/snapshot"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn bootstrapped_replica_resumes_after_snapshot_epoch() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    for i in 0..5 {
+        query(&client, &primary, &format!("INSERT (:Before {{seq: {i}}})")).await;
+    }
+
+    let replica = spawn_following_replica(&primary).await;
+    wait_for_count(&client, &replica, 5).await;
+
+    // Changes after the snapshot are applied exactly once on top of it.
+    query(&client, &primary, "INSERT (:After)").await;
+    wait_for_count(&client, &replica, 6).await;
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    assert_eq!(node_count(&client, &replica).await, 6);
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn replica_mirrors_created_and_dropped_databases() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let replica = spawn_following_replica(&primary).await;
+
+    let resp = client
+        .post(format!("{primary}/db"))
+        .json(&json!({"name": "late", "database_type": "Lpg"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    client
+        .post(format!("{primary}/query"))
+        .json(&json!({"query": "INSERT (:Late)", "database": "late"}))
+        .send()
+        .await
+        .unwrap();
+
+    let catalog: Value = client
+        .get(format!("{primary}/admin/replication/catalog"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(catalog[1]["name"], "late");
+    assert_eq!(catalog[1]["database_type"], "Lpg");
+
+    // The new database is created on the replica and its data follows.
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        let resp: Value = client
+            .post(format!("{replica}/query"))
+            .json(&json!({"query": "MATCH (n) RETURN count(n)", "database": "late"}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if resp["rows"][0][0].as_i64() == Some(1) {
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "database 'late' never replicated: {resp}"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    // Dropping it on the primary drops it on the replica.
+    client
+        .delete(format!("{primary}/db/late"))
+        .send()
+        .await
+        .unwrap();
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        let status = client
+            .get(format!("{replica}/db/late"))
+            .send()
+            .await
+            .unwrap()
+            .status();
+        if status.as_u16() == 404 {
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "database 'late' was never dropped on the replica"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn replica_exports_lag_metrics() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    query(&client, &primary, "INSERT (:Tracked)").await;
+    let replica = spawn_following_replica(&primary).await;
+    wait_for_count(&client, &replica, 1).await;
+
+    let status: Value = client
+        .get(format!("{replica}/admin/replication"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let db = &status["databases"]["default"];
+    assert!(db["primary_epoch"].as_u64().unwrap() > 0);
+    assert!(db["last_applied_at"].as_u64().is_some());
+
+    let metrics = client
+        .get(format!("{replica}/metrics"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains("grafeo_replication_lag_seconds{database=\"default\"}"));
+    assert!(metrics.contains("grafeo_replication_primary_epoch{database=\"default\"}"));
+
+    // The primary itself exports no replication gauges.
+    let metrics = client
+        .get(format!("{primary}/metrics"))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(!metrics.contains("grafeo_replication_"));
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn lagging_replica_fails_readiness() {
+    let client = Client::new();
+
+    // A caught-up replica is ready.
+    let primary = spawn_primary().await;
+    let mut config = replica_config(&primary);
+    config.replica_max_lag = 2;
+    let replica = spawn_following_replica_with(config).await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    let resp = client.get(format!("{replica}/ready")).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // A replica that cannot reach its primary falls behind.
+    let mut config = replica_config("http://127.0.0.1:9");
+    config.replica_max_lag = 1;
+    let replica = spawn_following_replica_with(config).await;
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        let resp = client.get(format!("{replica}/ready")).send().await.unwrap();
+        if resp.status().as_u16() == 503 {
+            let body: Value = resp.json().await.unwrap();
+            assert_eq!(body["reason"], "replica lag exceeds threshold");
+            assert_eq!(body["lagging"][0], "default");
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "replica never reported itself not ready"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    }
+}
+
+#[cfg(feature = "replication")]
+async fn spawn_forwarding_replica(primary_url: &str) -> String {
+    let mut config = replica_config(primary_url);
+    config.replica_forward_writes = true;
+    spawn_following_replica_with(config).await
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn forwarded_write_is_visible_on_the_replica() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let replica = spawn_forwarding_replica(&primary).await;
+
+    let resp = client
+        .post(format!("{replica}/query"))
+        .json(&json!({"query": "INSERT (:Forwarded {name: 'a'}) RETURN 1 AS ok"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(resp.headers().contains_key("x-grafeo-epoch"));
+
+    // Read-your-writes: the replica has applied the write before answering.
+    assert_eq!(node_count(&client, &replica).await, 1);
+    assert_eq!(node_count(&client, &primary).await, 1);
+
+    // Reads are still answered locally.
+    let resp = client
+        .post(format!("{replica}/query"))
+        .json(&json!({"query": "MATCH (n:Forwarded) RETURN n.name AS name"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(!resp.headers().contains_key("x-grafeo-epoch"));
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn forwarded_transaction_commits_on_the_primary() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let replica = spawn_forwarding_replica(&primary).await;
+
+    let begin: Value = client
+        .post(format!("{replica}/tx/begin"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let session_id = begin["session_id"].as_str().unwrap().to_string();
+
+    for i in 0..2 {
+        let resp = client
+            .post(format!("{replica}/tx/query"))
+            .header("X-Session-Id", &session_id)
+            .json(&json!({"query": format!("INSERT (:InTx {{seq: {i}}})")}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    let resp = client
+        .post(format!("{replica}/tx/commit"))
+        .header("X-Session-Id", &session_id)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(node_count(&client, &replica).await, 2);
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn forwarding_to_unreachable_primary_is_503() {
+    let client = Client::new();
+    let replica = spawn_forwarding_replica("http://127.0.0.1:9").await;
+    let resp = client
+        .post(format!("{replica}/batch"))
+        .json(&json!({"queries": [{"query": "INSERT (:Lost)"}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "unavailable");
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn promoted_replica_accepts_writes_and_fences_the_old_primary() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    query(&client, &primary, "INSERT (:Before)").await;
+    let replica = spawn_following_replica(&primary).await;
+    wait_for_count(&client, &replica, 1).await;
+
+    let resp = client
+        .post(format!("{replica}/admin/replication/promote"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let status: Value = resp.json().await.unwrap();
+    assert_eq!(status["mode"], "primary");
+    assert_eq!(status["term"], 1);
+
+    // The new primary accepts writes.
+    let resp = client
+        .post(format!("{replica}/batch"))
+        .json(&json!({"queries": [{"query": "INSERT (:After)"}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(node_count(&client, &replica).await, 2);
+
+    // The old primary learns of the new term and rejects writes.
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        let status: Value = client
+            .get(format!("{primary}/admin/replication"))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if status["fenced"] == true {
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "old primary not fenced"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let resp = client
+        .post(format!("{primary}/batch"))
+        .json(&json!({"queries": [{"query": "INSERT (:Split)"}]}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "fenced");
+
+    // Promoting a primary is a conflict.
+    let resp = client
+        .post(format!("{replica}/admin/replication/promote"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn demoted_primary_follows_the_new_primary() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let replica = spawn_following_replica(&primary).await;
+    client
+        .post(format!("{replica}/admin/replication/promote"))
+        .send()
+        .await
+        .unwrap();
+    query(&client, &replica, "INSERT (:OnNewPrimary)").await;
+
+    let resp = client
+        .post(format!("{primary}/admin/replication/follow"))
+        .json(&json!({"primary_url": replica}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let status: Value = resp.json().await.unwrap();
+    assert_eq!(status["mode"], "replica");
+    assert_eq!(status["fenced"], false);
+
+    wait_for_count(&client, &primary, 1).await;
+    query(&client, &replica, "INSERT (:Later)").await;
+    wait_for_count(&client, &primary, 2).await;
+}
+
+#[cfg(feature = "replication")]
+#[tokio::test]
+async fn follow_rejects_a_node_that_is_not_a_primary() {
+    let client = Client::new();
+    let primary = spawn_primary().await;
+    let replica = spawn_following_replica(&primary).await;
+
+    let resp = client
+        .post(format!("{primary}/admin/replication/follow"))
+        .json(&json!({"primary_url": replica}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let resp = client
+        .post(format!("{primary}/admin/replication/follow"))
+        .json(&json!({"primary_url": "http://127.0.0.1:9"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 503);
+}
+
+/// Polls the replica until it reports `expected` nodes, failing after 10s.
+#[cfg(feature = "replication")]
+async fn wait_for_count(client: &Client, base: &str, expected: i64) {
+    let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+    loop {
+        let count = node_count(client, base).await;
+        if count == expected {
+            return;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "replica has {count} nodes, expected {expected}"
+        );
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+}